@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tauri::State;
 
-use crate::{paths, space::SpaceState, utils};
+use crate::{crypto, index, paths, space::SpaceState, utils};
 
 const DEFAULT_FILE_LIST_LIMIT: usize = 20_000;
 const DEFAULT_CHAR_BUDGET: usize = 12_000;
@@ -64,6 +64,24 @@ pub struct AiContextBuildResponse {
     pub resolved_paths: Vec<String>,
 }
 
+/// `path`, or where its note was moved to if it no longer exists, so
+/// attachments saved with a chat keep pointing at the same note.
+fn follow_moved_note(root: &Path, path: String) -> String {
+    if path.is_empty() || root.join(&path).exists() {
+        return path;
+    }
+    index::current_note_path(root, &path)
+        .ok()
+        .flatten()
+        .unwrap_or(path)
+}
+
+/// A file's text for the context; encrypted notes are left out while the
+/// space is locked.
+fn read_context_text(root: &Path, abs: &Path) -> Option<String> {
+    crypto::open_text(root, &std::fs::read(abs).ok()?).ok()
+}
+
 fn normalize_rel(raw: &str) -> Option<String> {
     let mut parts: Vec<&str> = Vec::new();
     let normalized = raw.trim().replace('\\', "/");
@@ -264,9 +282,9 @@ pub async fn ai_context_build(
                         continue;
                     }
                     let abs = paths::join_under(&root, &PathBuf::from(&rel))?;
-                    let text = match std::fs::read_to_string(&abs) {
-                        Ok(v) => v,
-                        Err(_) => continue,
+                    let text = match read_context_text(&root, &abs) {
+                        Some(v) => v,
+                        None => continue,
                     };
                     seen.insert(rel.clone());
                     resolved_paths.push(rel.clone());
@@ -282,11 +300,12 @@ pub async fn ai_context_build(
                 continue;
             }
 
+            let path = follow_moved_note(&root, path);
             if path.is_empty() || seen.contains(&path) {
                 continue;
             }
             let abs = paths::join_under(&root, &PathBuf::from(&path))?;
-            if let Ok(text) = std::fs::read_to_string(&abs) {
+            if let Some(text) = read_context_text(&root, &abs) {
                 seen.insert(path.clone());
                 resolved_paths.push(path.clone());
                 push_manifest_item(
@@ -334,8 +353,11 @@ pub async fn ai_context_resolve_paths(
                         out.push(rel);
                     }
                 }
-            } else if !path.is_empty() && seen.insert(path.clone()) {
-                out.push(path);
+            } else {
+                let path = follow_moved_note(&root, path);
+                if !path.is_empty() && seen.insert(path.clone()) {
+                    out.push(path);
+                }
             }
         }
         Ok(out)
@@ -11,7 +11,8 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_json::json;
 
-use crate::{index::open_db, io_atomic, paths, utils};
+use crate::history::{self, VersionSource};
+use crate::{crypto, index::open_db, io_atomic, paths, utils};
 
 const MAX_READ_BYTES: u64 = 512 * 1024;
 const MAX_READ_CHARS: usize = 12_000;
@@ -55,6 +56,21 @@ fn is_utf8_text(path: &Path) -> bool {
     std::str::from_utf8(&bytes).is_ok()
 }
 
+/// Reads a text file, decrypting encrypted notes. Those stay unreadable
+/// while the space is locked.
+fn read_text(root: &Path, path: &Path) -> Result<String, ToolError> {
+    let bytes = fs::read(path)?;
+    crypto::open_text(root, &bytes).map_err(ToolError)
+}
+
+/// Keeps what an AI tool wrote, and what it replaced, in version history.
+fn record_tool_write(root: &Path, rel: &str, previous: Option<Vec<u8>>, next: &[u8]) {
+    let Ok(rel) = normalize_rel_path(rel) else {
+        return;
+    };
+    let _ = history::record_write(root, &rel, previous.as_deref(), next, VersionSource::Ai);
+}
+
 fn safe_join(root: &Path, rel: &str) -> Result<PathBuf, ToolError> {
     let normalized = normalize_rel_path(rel)?;
     let rel = PathBuf::from(normalized);
@@ -294,7 +310,7 @@ impl Tool for SearchTool {
                         continue;
                     }
                 }
-                let text = match fs::read_to_string(entry.path()) {
+                let text = match read_text(&self.root, &entry.path()) {
                     Ok(v) => v,
                     Err(_) => continue,
                 };
@@ -379,7 +395,7 @@ impl Tool for ReadFileTool {
         if meta.len() > MAX_READ_BYTES || !is_utf8_text(&abs) {
             return Ok(err_payload("binary or oversized file blocked"));
         }
-        let text = fs::read_to_string(&abs)?;
+        let text = read_text(&self.root, &abs)?;
         let start = args.offset.unwrap_or(0).min(text.len());
         let mut end = text.len();
         if let Some(len) = args.length {
@@ -473,8 +489,17 @@ impl Tool for WriteFileTool {
         if exists && mode == "create_only" {
             return Ok(err_payload("file already exists"));
         }
-        io_atomic::write_atomic(&abs, args.content.as_bytes())
-            .map_err(|e| ToolError(e.to_string()))?;
+        let previous = fs::read(&abs).ok();
+        let bytes = crypto::seal_text(
+            &self.root,
+            &normalize_rel_path(&args.path)?,
+            previous.as_deref(),
+            false,
+            &args.content,
+        )
+        .map_err(ToolError)?;
+        io_atomic::write_atomic(&abs, &bytes).map_err(|e| ToolError(e.to_string()))?;
+        record_tool_write(&self.root, &args.path, previous, &bytes);
         Ok(ok(
             json!({"path": args.path, "bytes_written": args.content.len()}),
         ))
@@ -505,7 +530,8 @@ impl Tool for ApplyPatchTool {
     }
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
         let abs = safe_join(&self.root, &args.path)?;
-        let text = fs::read_to_string(&abs).map_err(|e| ToolError(e.to_string()))?;
+        let previous = fs::read(&abs)?;
+        let text = crypto::open_text(&self.root, &previous).map_err(ToolError)?;
         let updated = if args.all.unwrap_or(false) {
             text.replace(&args.find, &args.replace)
         } else {
@@ -514,7 +540,16 @@ impl Tool for ApplyPatchTool {
         if updated == text {
             return Ok(err_payload("no matching text for patch"));
         }
-        io_atomic::write_atomic(&abs, updated.as_bytes()).map_err(|e| ToolError(e.to_string()))?;
+        let bytes = crypto::seal_text(
+            &self.root,
+            &normalize_rel_path(&args.path)?,
+            Some(&previous),
+            false,
+            &updated,
+        )
+        .map_err(ToolError)?;
+        io_atomic::write_atomic(&abs, &bytes).map_err(|e| ToolError(e.to_string()))?;
+        record_tool_write(&self.root, &args.path, Some(previous), &bytes);
         Ok(ok(json!({"path": args.path, "patched": true})))
     }
 }
@@ -0,0 +1,129 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::space::SpaceState;
+use crate::space_fs::helpers::deny_hidden_rel_path;
+use crate::utils;
+
+use super::files;
+use super::keyring;
+use super::settings::read_crypto_settings;
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CryptoStatus {
+    /// Whether a passphrase has been set for this space.
+    pub configured: bool,
+    pub unlocked: bool,
+    /// Folders whose notes are stored encrypted.
+    pub folders: Vec<String>,
+}
+
+fn status(root: &Path) -> CryptoStatus {
+    let settings = read_crypto_settings(root);
+    CryptoStatus {
+        configured: settings.verifier.is_some(),
+        unlocked: keyring::is_unlocked(root),
+        folders: settings.folders,
+    }
+}
+
+fn checked_rel_path(path: &str) -> Result<String, String> {
+    let rel = PathBuf::from(path);
+    deny_hidden_rel_path(&rel)?;
+    let rel_path = utils::to_slash(&rel).trim_matches('/').to_string();
+    if rel_path.is_empty() {
+        return Err("choose a note or folder to encrypt".to_string());
+    }
+    Ok(rel_path)
+}
+
+#[tauri::command]
+pub async fn crypto_status(state: State<'_, SpaceState>) -> Result<CryptoStatus, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || Ok(status(&root)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Unlocks encrypted notes until `crypto_lock` or the app quits. The first
+/// unlock of a space sets its passphrase.
+#[tauri::command]
+pub async fn crypto_unlock(
+    state: State<'_, SpaceState>,
+    passphrase: String,
+) -> Result<CryptoStatus, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        files::unlock(&root, &passphrase)?;
+        Ok(status(&root))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn crypto_lock(state: State<'_, SpaceState>) -> Result<CryptoStatus, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        files::lock(&root)?;
+        Ok(status(&root))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Re-encrypts every encrypted note; returns how many were rewritten.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn crypto_change_passphrase(
+    state: State<'_, SpaceState>,
+    old_passphrase: String,
+    new_passphrase: String,
+) -> Result<usize, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        files::change_passphrase(
+            &root,
+            &recent_local_changes,
+            &old_passphrase,
+            &new_passphrase,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Encrypts a note or a whole folder; returns how many notes were encrypted.
+#[tauri::command]
+pub async fn crypto_encrypt_path(
+    state: State<'_, SpaceState>,
+    path: String,
+) -> Result<usize, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        let rel_path = checked_rel_path(&path)?;
+        files::encrypt_path(&root, &recent_local_changes, &rel_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Decrypts a note or a whole folder; returns how many notes were decrypted.
+#[tauri::command]
+pub async fn crypto_decrypt_path(
+    state: State<'_, SpaceState>,
+    path: String,
+) -> Result<usize, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        let rel_path = checked_rel_path(&path)?;
+        files::decrypt_path(&root, &recent_local_changes, &rel_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
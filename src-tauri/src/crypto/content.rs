@@ -0,0 +1,54 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+use super::envelope::{self, Envelope};
+use super::keyring;
+use super::settings::read_crypto_settings;
+
+pub const LOCKED_ERROR: &str = "note is encrypted; unlock the space to open it";
+
+/// Whether `rel_path` is stored encrypted: it already is on disk
+/// (`existing`), or it lives in an encrypted folder.
+pub fn should_encrypt(root: &Path, rel_path: &str, existing: Option<&[u8]>) -> bool {
+    existing.is_some_and(envelope::is_encrypted)
+        || read_crypto_settings(root).folder_of(rel_path).is_some()
+}
+
+/// The text of a file as read from disk, decrypted when it is encrypted.
+/// Fails with [`LOCKED_ERROR`] while the space is locked.
+pub fn open_text(root: &Path, bytes: &[u8]) -> Result<String, String> {
+    let plaintext = if envelope::is_encrypted(bytes) {
+        let envelope = Envelope::parse(bytes)?;
+        let key = keyring::key_for(root, envelope.spec())?.ok_or(LOCKED_ERROR)?;
+        envelope.open(&key)?
+    } else {
+        bytes.to_vec()
+    };
+    String::from_utf8(plaintext).map_err(|_| "file is not valid UTF-8".to_string())
+}
+
+/// The bytes to write for `text`: encrypted when [`should_encrypt`] says
+/// so or when `text` was decrypted from an encrypted source (a history
+/// version, a commit, an undo), which needs the space unlocked.
+pub fn seal_text(
+    root: &Path,
+    rel_path: &str,
+    existing: Option<&[u8]>,
+    source_encrypted: bool,
+    text: &str,
+) -> Result<Vec<u8>, String> {
+    if !source_encrypted && !should_encrypt(root, rel_path, existing) {
+        return Ok(text.as_bytes().to_vec());
+    }
+    let key = keyring::current_key(root).ok_or(LOCKED_ERROR)?;
+    Ok(envelope::seal(&key, text.as_bytes())?.into_bytes())
+}
+
+/// What the index may see of `markdown`: the plaintext of an encrypted
+/// note while the space is unlocked, and nothing while it is locked.
+pub fn indexable_text<'a>(root: &Path, markdown: &'a str) -> Cow<'a, str> {
+    if !envelope::is_encrypted(markdown.as_bytes()) {
+        return Cow::Borrowed(markdown);
+    }
+    Cow::Owned(open_text(root, markdown.as_bytes()).unwrap_or_default())
+}
@@ -0,0 +1,202 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+/// Encrypted files start with this line. Being an HTML comment, the file
+/// still renders as an (empty) note in other markdown tools.
+const HEADER: &str = "<!-- glyph-encrypted v1\n";
+const FOOTER: &str = "\n-->\n";
+const SALT_LEN: usize = 16;
+/// Upper bounds on the costs a file header may ask for, so a crafted file
+/// cannot make unlocking exhaust memory or hang.
+const MAX_MEMORY_KIB: u32 = 256 * 1024;
+const MAX_TIME_COST: u32 = 10;
+const MAX_LANES: u32 = 4;
+
+/// Argon2id costs used to derive a key from the passphrase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory in KiB.
+    pub m: u32,
+    pub t: u32,
+    pub p: u32,
+}
+
+impl KdfParams {
+    #[cfg(not(test))]
+    pub const DEFAULT: Self = Self {
+        m: 64 * 1024,
+        t: 3,
+        p: 1,
+    };
+    /// Tests derive many keys; keep them cheap.
+    #[cfg(test)]
+    pub const DEFAULT: Self = Self { m: 64, t: 1, p: 1 };
+
+    /// Whether a header's costs are within bounds: no cheaper than the
+    /// defaults, which would make the passphrase easier to guess, and no
+    /// dearer than the caps.
+    fn is_supported(&self) -> bool {
+        (Self::DEFAULT.m..=MAX_MEMORY_KIB).contains(&self.m)
+            && (Self::DEFAULT.t..=MAX_TIME_COST).contains(&self.t)
+            && (Self::DEFAULT.p..=MAX_LANES).contains(&self.p)
+    }
+}
+
+/// Everything besides the passphrase needed to derive a key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySpec {
+    pub salt: Vec<u8>,
+    pub params: KdfParams,
+}
+
+impl KeySpec {
+    /// A fresh random salt with the default costs.
+    pub fn generate() -> Self {
+        let mut salt = vec![0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            salt,
+            params: KdfParams::DEFAULT,
+        }
+    }
+}
+
+/// A key derived from the passphrase. Its bytes are wiped when dropped.
+#[derive(Clone)]
+pub struct DerivedKey {
+    pub spec: KeySpec,
+    key: [u8; 32],
+}
+
+impl Drop for DerivedKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+pub fn derive_key(passphrase: &str, spec: &KeySpec) -> Result<DerivedKey, String> {
+    let params = Params::new(spec.params.m, spec.params.t, spec.params.p, Some(32))
+        .map_err(|e| e.to_string())?;
+    let mut derived = DerivedKey {
+        spec: spec.clone(),
+        key: [0u8; 32],
+    };
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &spec.salt, &mut derived.key)
+        .map_err(|e| e.to_string())?;
+    Ok(derived)
+}
+
+#[derive(Serialize, Deserialize)]
+struct EnvelopeBody {
+    kdf: String,
+    #[serde(flatten)]
+    params: KdfParams,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+/// A parsed encrypted file.
+pub struct Envelope {
+    spec: KeySpec,
+    nonce: Vec<u8>,
+    data: Vec<u8>,
+}
+
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(HEADER.as_bytes())
+}
+
+/// Encrypts `plaintext` with XChaCha20-Poly1305 under a random nonce.
+pub fn seal(key: &DerivedKey, plaintext: &[u8]) -> Result<String, String> {
+    let cipher = XChaCha20Poly1305::new(&key.key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let data = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "encryption failed".to_string())?;
+    let body = EnvelopeBody {
+        kdf: "argon2id".to_string(),
+        params: key.spec.params,
+        salt: STANDARD.encode(&key.spec.salt),
+        nonce: STANDARD.encode(nonce),
+        data: STANDARD.encode(data),
+    };
+    let json = serde_json::to_string(&body).map_err(|e| e.to_string())?;
+    Ok(format!("{HEADER}{json}{FOOTER}"))
+}
+
+impl Envelope {
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let invalid = || "invalid encrypted file".to_string();
+        let text = std::str::from_utf8(bytes).map_err(|_| invalid())?;
+        let json = text
+            .strip_prefix(HEADER)
+            .and_then(|rest| rest.trim_end().strip_suffix("-->"))
+            .ok_or_else(invalid)?;
+        let body: EnvelopeBody = serde_json::from_str(json.trim()).map_err(|_| invalid())?;
+        if body.kdf != "argon2id" {
+            return Err(format!("unsupported key derivation '{}'", body.kdf));
+        }
+        if !body.params.is_supported() {
+            return Err("unsupported key derivation costs".to_string());
+        }
+        let decode = |value: &str| STANDARD.decode(value).map_err(|_| invalid());
+        let nonce = decode(&body.nonce)?;
+        if nonce.len() != 24 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            spec: KeySpec {
+                salt: decode(&body.salt)?,
+                params: body.params,
+            },
+            nonce,
+            data: decode(&body.data)?,
+        })
+    }
+
+    pub fn spec(&self) -> &KeySpec {
+        &self.spec
+    }
+
+    /// Fails when `key` is wrong or the file was tampered with.
+    pub fn open(&self, key: &DerivedKey) -> Result<Vec<u8>, String> {
+        XChaCha20Poly1305::new(&key.key.into())
+            .decrypt(XNonce::from_slice(&self.nonce), self.data.as_slice())
+            .map_err(|_| "could not decrypt: wrong passphrase or damaged file".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_rejects_the_wrong_key() {
+        let spec = KeySpec::generate();
+        let key = derive_key("correct horse", &spec).unwrap();
+        let sealed = seal(&key, "# Secret\n\nbody".as_bytes()).unwrap();
+        assert!(is_encrypted(sealed.as_bytes()));
+        assert!(!sealed.contains("Secret"));
+
+        let envelope = Envelope::parse(sealed.as_bytes()).unwrap();
+        assert_eq!(envelope.spec(), &spec);
+        assert_eq!(envelope.open(&key).unwrap(), b"# Secret\n\nbody");
+
+        let wrong = derive_key("battery staple", &spec).unwrap();
+        assert!(envelope.open(&wrong).is_err());
+
+        let mut tampered = sealed.clone().into_bytes();
+        let at = tampered.len() - 10;
+        tampered[at] = if tampered[at] == b'A' { b'B' } else { b'A' };
+        let tampered = Envelope::parse(&tampered).unwrap();
+        assert!(tampered.open(&key).is_err());
+    }
+}
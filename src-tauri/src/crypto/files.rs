@@ -0,0 +1,414 @@
+use std::path::{Path, PathBuf};
+
+use crate::history;
+use crate::space::state::{mark_recent_local_change, RecentLocalChanges};
+use crate::{index, io_atomic, paths, utils};
+
+use super::content::open_text;
+use super::envelope::{self, derive_key, DerivedKey, Envelope, KeySpec};
+use super::keyring;
+use super::settings::{read_crypto_settings, write_crypto_settings};
+
+/// Encrypted into the settings to check the passphrase on unlock.
+const VERIFIER_TEXT: &str = "glyph passphrase check";
+const UNLOCK_FIRST: &str = "unlock the space first";
+
+struct EncryptedNote {
+    rel_path: String,
+    abs: PathBuf,
+    bytes: Vec<u8>,
+}
+
+/// Notes at `rel_path` or under it when it is a folder.
+fn notes_under(root: &Path, rel_path: &str) -> Result<Vec<(String, PathBuf)>, String> {
+    let prefix = format!("{rel_path}/");
+    Ok(index::collect_markdown_files(root)?
+        .into_iter()
+        .filter(|(rel, _)| rel_path.is_empty() || rel == rel_path || rel.starts_with(&prefix))
+        .collect())
+}
+
+fn encrypted_notes(root: &Path, rel_path: &str) -> Result<Vec<EncryptedNote>, String> {
+    Ok(notes_under(root, rel_path)?
+        .into_iter()
+        .filter_map(|(rel_path, abs)| {
+            let bytes = std::fs::read(&abs).ok()?;
+            envelope::is_encrypted(&bytes).then_some(EncryptedNote {
+                rel_path,
+                abs,
+                bytes,
+            })
+        })
+        .collect())
+}
+
+fn write_note(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    rel_path: &str,
+    abs: &Path,
+    bytes: &[u8],
+) -> Result<(), String> {
+    mark_recent_local_change(recent_local_changes, rel_path);
+    io_atomic::write_atomic(abs, bytes).map_err(|e| e.to_string())?;
+    if let Ok(markdown) = std::str::from_utf8(bytes) {
+        let _ = index::index_note(root, rel_path, markdown);
+    }
+    Ok(())
+}
+
+fn check_passphrase(passphrase: &str, sealed: &[u8]) -> Result<DerivedKey, String> {
+    let envelope = Envelope::parse(sealed)?;
+    let key = derive_key(passphrase, envelope.spec())?;
+    envelope
+        .open(&key)
+        .map_err(|_| "wrong passphrase".to_string())?;
+    Ok(key)
+}
+
+/// Re-indexes every encrypted note, which adds their bodies to the index
+/// while the space is unlocked and purges them while it is locked.
+pub fn reindex_encrypted(root: &Path) -> Result<(), String> {
+    let notes = encrypted_notes(root, "")?
+        .into_iter()
+        .filter_map(|note| Some((note.rel_path, String::from_utf8(note.bytes).ok()?)))
+        .collect::<Vec<_>>();
+    index::index_notes(root, &notes)
+}
+
+/// Locks the space and drops the bodies of encrypted notes from the index.
+/// Runs when a space opens, in case the app last quit while it was
+/// unlocked, and when it closes.
+pub fn purge_decrypted(root: &Path) -> Result<(), String> {
+    keyring::lock(root);
+    if read_crypto_settings(root).verifier.is_none() {
+        // Never unlocked here, so nothing was decrypted into the index.
+        return Ok(());
+    }
+    reindex_encrypted(root)
+}
+
+/// Keys for the notes encrypted under salts other than `current`'s, derived
+/// up front so the passphrase need not be kept. Notes the passphrase does
+/// not open are skipped.
+fn other_keys(
+    passphrase: &str,
+    current: &DerivedKey,
+    notes: &[EncryptedNote],
+) -> Result<Vec<DerivedKey>, String> {
+    let mut keys: Vec<DerivedKey> = Vec::new();
+    for note in notes {
+        if keys.len() >= keyring::MAX_DERIVED_KEYS {
+            break;
+        }
+        let Ok(envelope) = Envelope::parse(&note.bytes) else {
+            continue;
+        };
+        let spec = envelope.spec();
+        if &current.spec == spec || keys.iter().any(|key| &key.spec == spec) {
+            continue;
+        }
+        let key = derive_key(passphrase, spec)?;
+        if envelope.open(&key).is_ok() {
+            keys.push(key);
+        }
+    }
+    Ok(keys)
+}
+
+/// Checks `passphrase` and unlocks the space. The first unlock sets the
+/// passphrase; it must still open notes encrypted elsewhere, e.g. synced
+/// from another device.
+pub fn unlock(root: &Path, passphrase: &str) -> Result<(), String> {
+    if passphrase.is_empty() {
+        return Err("passphrase is required".to_string());
+    }
+    let mut settings = read_crypto_settings(root);
+    let notes = encrypted_notes(root, "")?;
+    let key = match &settings.verifier {
+        Some(verifier) => check_passphrase(passphrase, verifier.as_bytes())?,
+        None => {
+            if let Some(note) = notes.first() {
+                check_passphrase(passphrase, &note.bytes)?;
+            }
+            let key = derive_key(passphrase, &KeySpec::generate())?;
+            settings.verifier = Some(envelope::seal(&key, VERIFIER_TEXT.as_bytes())?);
+            write_crypto_settings(root, &settings)?;
+            key
+        }
+    };
+    let others = other_keys(passphrase, &key, &notes)?;
+    keyring::unlock(root, key, others);
+    reindex_encrypted(root)
+}
+
+pub fn lock(root: &Path) -> Result<(), String> {
+    if keyring::lock(root) {
+        reindex_encrypted(root)?;
+    }
+    Ok(())
+}
+
+/// Encrypts the note at `rel_path`, or every note in the folder, which
+/// also keeps notes created there later encrypted. Plaintext versions in
+/// note history are dropped. Returns how many notes were encrypted.
+pub fn encrypt_path(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    rel_path: &str,
+) -> Result<usize, String> {
+    let key = keyring::current_key(root).ok_or(UNLOCK_FIRST)?;
+    let abs = paths::join_under(root, Path::new(rel_path))?;
+    if abs.is_dir() {
+        let mut settings = read_crypto_settings(root);
+        let listed = settings.folders.iter().any(|folder| folder == rel_path);
+        if !listed && settings.folder_of(rel_path).is_none() {
+            settings.folders.push(rel_path.to_string());
+            settings.folders.sort();
+            write_crypto_settings(root, &settings)?;
+        }
+    } else if !utils::is_markdown_path(&abs) {
+        return Err("only notes can be encrypted".to_string());
+    }
+
+    let mut count = 0;
+    for (rel, abs) in notes_under(root, rel_path)? {
+        let bytes = std::fs::read(&abs).map_err(|e| e.to_string())?;
+        if envelope::is_encrypted(&bytes) {
+            continue;
+        }
+        let sealed = envelope::seal(&key, &bytes)?;
+        mark_recent_local_change(recent_local_changes, &rel);
+        io_atomic::write_atomic(&abs, sealed.as_bytes()).map_err(|e| e.to_string())?;
+        history::forget_versions(root, &rel)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Stores the note at `rel_path`, or every note in the folder, as
+/// plaintext again. Returns how many notes were decrypted.
+pub fn decrypt_path(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    rel_path: &str,
+) -> Result<usize, String> {
+    if !keyring::is_unlocked(root) {
+        return Err(UNLOCK_FIRST.to_string());
+    }
+    let abs = paths::join_under(root, Path::new(rel_path))?;
+    let mut settings = read_crypto_settings(root);
+    if abs.is_dir() {
+        let nested = format!("{rel_path}/");
+        settings
+            .folders
+            .retain(|folder| folder != rel_path && !folder.starts_with(&nested));
+        write_crypto_settings(root, &settings)?;
+    }
+    if let Some(folder) = settings.folder_of(rel_path) {
+        return Err(format!(
+            "{rel_path} is in the encrypted folder {folder}; decrypt the folder instead"
+        ));
+    }
+
+    let notes = encrypted_notes(root, rel_path)?;
+    for note in &notes {
+        let text = open_text(root, &note.bytes)?;
+        write_note(
+            root,
+            recent_local_changes,
+            &note.rel_path,
+            &note.abs,
+            text.as_bytes(),
+        )?;
+    }
+    Ok(notes.len())
+}
+
+/// Re-encrypts every encrypted note under `new_passphrase`. All notes are
+/// decrypted before any is rewritten, so a note the old passphrase cannot
+/// open leaves everything untouched. Older versions in note history stay
+/// encrypted under the old passphrase.
+pub fn change_passphrase(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    old_passphrase: &str,
+    new_passphrase: &str,
+) -> Result<usize, String> {
+    if new_passphrase.is_empty() {
+        return Err("passphrase is required".to_string());
+    }
+    let mut settings = read_crypto_settings(root);
+    let verifier = settings.verifier.as_deref().ok_or("no passphrase is set")?;
+    check_passphrase(old_passphrase, verifier.as_bytes())?;
+
+    let mut old_keys: Vec<DerivedKey> = Vec::new();
+    let mut plaintexts = Vec::new();
+    for note in encrypted_notes(root, "")? {
+        let envelope = Envelope::parse(&note.bytes)?;
+        let key = match old_keys.iter().find(|key| &key.spec == envelope.spec()) {
+            Some(key) => key.clone(),
+            None => {
+                let key = derive_key(old_passphrase, envelope.spec())?;
+                old_keys.push(key.clone());
+                key
+            }
+        };
+        let plaintext = envelope
+            .open(&key)
+            .map_err(|e| format!("{}: {e}", note.rel_path))?;
+        plaintexts.push((note, plaintext));
+    }
+
+    let key = derive_key(new_passphrase, &KeySpec::generate())?;
+    for (note, plaintext) in &plaintexts {
+        let sealed = envelope::seal(&key, plaintext)?;
+        mark_recent_local_change(recent_local_changes, &note.rel_path);
+        io_atomic::write_atomic(&note.abs, sealed.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    settings.verifier = Some(envelope::seal(&key, VERIFIER_TEXT.as_bytes())?);
+    write_crypto_settings(root, &settings)?;
+    keyring::unlock(root, key, Vec::new());
+    Ok(plaintexts.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::content::{seal_text, LOCKED_ERROR};
+
+    fn fts_body(root: &Path, rel_path: &str) -> String {
+        index::open_db(root)
+            .unwrap()
+            .query_row(
+                "SELECT body FROM notes_fts WHERE id = ?",
+                [rel_path],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn encrypts_folders_and_keeps_locked_bodies_out_of_the_index() {
+        let root = std::env::temp_dir().join(format!("glyph-crypto-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("private")).unwrap();
+        let recent = RecentLocalChanges::default();
+        let note = "private/plan.md";
+        let markdown = "# Plan\n\nsecret rendezvous\n";
+        std::fs::write(root.join(note), markdown).unwrap();
+        index::index_note(&root, note, markdown).unwrap();
+
+        assert_eq!(
+            encrypt_path(&root, &recent, "private"),
+            Err(UNLOCK_FIRST.to_string())
+        );
+        unlock(&root, "hunter2").unwrap();
+        assert_eq!(encrypt_path(&root, &recent, "private").unwrap(), 1);
+        let bytes = std::fs::read(root.join(note)).unwrap();
+        assert!(envelope::is_encrypted(&bytes));
+        assert!(!String::from_utf8_lossy(&bytes).contains("secret"));
+        assert_eq!(open_text(&root, &bytes).unwrap(), markdown);
+        assert!(fts_body(&root, note).contains("secret"));
+
+        // Notes created in the folder later are encrypted too.
+        let sealed = seal_text(&root, "private/new.md", None, false, "fresh").unwrap();
+        assert!(envelope::is_encrypted(&sealed));
+        assert_eq!(
+            seal_text(&root, "public.md", None, false, "open").unwrap(),
+            b"open"
+        );
+
+        lock(&root).unwrap();
+        assert_eq!(open_text(&root, &bytes), Err(LOCKED_ERROR.to_string()));
+        assert!(!fts_body(&root, note).contains("secret"));
+        assert_eq!(unlock(&root, "wrong"), Err("wrong passphrase".to_string()));
+
+        unlock(&root, "hunter2").unwrap();
+        assert!(fts_body(&root, note).contains("secret"));
+        assert_eq!(
+            change_passphrase(&root, &recent, "hunter2", "correct horse").unwrap(),
+            1
+        );
+        lock(&root).unwrap();
+        assert!(unlock(&root, "hunter2").is_err());
+        unlock(&root, "correct horse").unwrap();
+
+        assert!(decrypt_path(&root, &recent, note).is_err());
+        assert_eq!(decrypt_path(&root, &recent, "private").unwrap(), 1);
+        assert_eq!(std::fs::read_to_string(root.join(note)).unwrap(), markdown);
+        assert!(read_crypto_settings(&root).folders.is_empty());
+        lock(&root).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn purges_decrypted_bodies_left_by_an_unlocked_session() {
+        let root = std::env::temp_dir().join(format!("glyph-crypto-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let recent = RecentLocalChanges::default();
+        let note = "diary.md";
+        std::fs::write(root.join(note), "dear diary\n").unwrap();
+        unlock(&root, "hunter2").unwrap();
+        encrypt_path(&root, &recent, note).unwrap();
+        let sealed = std::fs::read_to_string(root.join(note)).unwrap();
+        index::index_note(&root, note, &sealed).unwrap();
+        assert!(fts_body(&root, note).contains("dear diary"));
+
+        // The app quit while unlocked: the keys are gone, the index is not.
+        keyring::lock(&root);
+        assert!(fts_body(&root, note).contains("dear diary"));
+
+        // Reopening the space purges it.
+        purge_decrypted(&root).unwrap();
+        assert!(!fts_body(&root, note).contains("dear diary"));
+        assert!(!keyring::is_unlocked(&root));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn opens_notes_encrypted_under_other_salts_without_keeping_the_passphrase() {
+        let root = std::env::temp_dir().join(format!("glyph-crypto-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        unlock(&root, "hunter2").unwrap();
+        lock(&root).unwrap();
+
+        // Encrypted on another device, under its own salt.
+        let elsewhere = derive_key("hunter2", &KeySpec::generate()).unwrap();
+        let synced = envelope::seal(&elsewhere, b"from the laptop").unwrap();
+        std::fs::write(root.join("synced.md"), &synced).unwrap();
+        unlock(&root, "hunter2").unwrap();
+        assert_eq!(
+            open_text(&root, synced.as_bytes()).unwrap(),
+            "from the laptop"
+        );
+
+        // A note arriving after unlock needs the passphrase again.
+        let later = derive_key("hunter2", &KeySpec::generate()).unwrap();
+        let later = envelope::seal(&later, b"later").unwrap();
+        assert_eq!(
+            open_text(&root, later.as_bytes()),
+            Err(keyring::UNKNOWN_KEY_ERROR.to_string())
+        );
+        lock(&root).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn rejects_key_derivation_costs_outside_bounds() {
+        let key = derive_key("hunter2", &KeySpec::generate()).unwrap();
+        let sealed = envelope::seal(&key, b"text").unwrap();
+        assert!(Envelope::parse(sealed.as_bytes()).is_ok());
+        for (from, to) in [
+            (r#""m":64,"#, r#""m":4294967295,"#),
+            (r#""t":1,"#, r#""t":4294967295,"#),
+            (r#""p":1,"#, r#""p":0,"#),
+        ] {
+            assert!(sealed.contains(from));
+            let crafted = sealed.replace(from, to);
+            assert_eq!(
+                Envelope::parse(crafted.as_bytes()).err().as_deref(),
+                Some("unsupported key derivation costs")
+            );
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use super::envelope::{DerivedKey, KeySpec};
+
+/// Keys kept per space besides the current one.
+pub const MAX_DERIVED_KEYS: usize = 16;
+
+pub const UNKNOWN_KEY_ERROR: &str =
+    "note was encrypted with a key this session does not hold; lock and unlock the space to open it";
+
+/// An unlocked space keeps only keys derived from the passphrase, never the
+/// passphrase itself: the current key first, then the keys of notes
+/// encrypted under another salt (e.g. synced from a second device). Keys
+/// wipe their bytes when dropped.
+struct Unlocked {
+    keys: Vec<DerivedKey>,
+}
+
+fn keyring() -> &'static Mutex<HashMap<PathBuf, Unlocked>> {
+    static KEYRING: OnceLock<Mutex<HashMap<PathBuf, Unlocked>>> = OnceLock::new();
+    KEYRING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn with_keyring<T>(f: impl FnOnce(&mut HashMap<PathBuf, Unlocked>) -> T) -> T {
+    let mut guard = keyring()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut guard)
+}
+
+/// Unlocks `root` with `current`, the key new encryptions use, and `others`
+/// for opening notes encrypted under other salts.
+pub fn unlock(root: &Path, current: DerivedKey, others: Vec<DerivedKey>) {
+    let mut keys = vec![current];
+    for key in others {
+        if keys.len() > MAX_DERIVED_KEYS {
+            break;
+        }
+        if keys.iter().all(|known| known.spec != key.spec) {
+            keys.push(key);
+        }
+    }
+    with_keyring(|unlocked| {
+        unlocked.insert(root.to_path_buf(), Unlocked { keys });
+    });
+}
+
+/// Forgets the keys of `root`. Returns whether it was unlocked.
+pub fn lock(root: &Path) -> bool {
+    with_keyring(|keys| keys.remove(root).is_some())
+}
+
+pub fn is_unlocked(root: &Path) -> bool {
+    with_keyring(|keys| keys.contains_key(root))
+}
+
+pub fn current_key(root: &Path) -> Option<DerivedKey> {
+    with_keyring(|keys| keys.get(root).map(|unlocked| unlocked.keys[0].clone()))
+}
+
+/// The key for `spec`. `None` while locked; an error when the space is
+/// unlocked but no key was derived for `spec`.
+pub fn key_for(root: &Path, spec: &KeySpec) -> Result<Option<DerivedKey>, String> {
+    with_keyring(|keys| {
+        let Some(unlocked) = keys.get(root) else {
+            return Ok(None);
+        };
+        unlocked
+            .keys
+            .iter()
+            .find(|key| &key.spec == spec)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| UNKNOWN_KEY_ERROR.to_string())
+    })
+}
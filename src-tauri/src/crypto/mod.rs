@@ -0,0 +1,12 @@
+pub mod commands;
+mod content;
+mod envelope;
+mod files;
+mod keyring;
+mod settings;
+
+pub(crate) use content::{indexable_text, open_text, seal_text};
+pub(crate) use envelope::is_encrypted;
+pub(crate) use files::purge_decrypted;
+#[cfg(test)]
+pub(crate) use files::{encrypt_path, unlock};
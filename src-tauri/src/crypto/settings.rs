@@ -0,0 +1,49 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{glyph_paths, io_atomic};
+
+const CRYPTO_SETTINGS_FILE: &str = "crypto.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CryptoSettings {
+    /// A known text encrypted with the passphrase, used to check it on
+    /// unlock. `None` until a passphrase is set.
+    #[serde(default)]
+    pub verifier: Option<String>,
+    /// Folders (relative, slash-separated) whose notes are always stored
+    /// encrypted, including notes created there later.
+    #[serde(default)]
+    pub folders: Vec<String>,
+}
+
+impl CryptoSettings {
+    /// The encrypted folder containing `rel_path`, if any.
+    pub fn folder_of(&self, rel_path: &str) -> Option<&str> {
+        self.folders
+            .iter()
+            .map(String::as_str)
+            .find(|folder| rel_path.starts_with(&format!("{folder}/")))
+    }
+}
+
+fn crypto_settings_path(space_root: &Path) -> Result<PathBuf, String> {
+    Ok(glyph_paths::glyph_dir(space_root)?.join(CRYPTO_SETTINGS_FILE))
+}
+
+pub fn read_crypto_settings(space_root: &Path) -> CryptoSettings {
+    let Ok(path) = crypto_settings_path(space_root) else {
+        return CryptoSettings::default();
+    };
+    let bytes = std::fs::read(path).unwrap_or_default();
+    serde_json::from_slice(&bytes).unwrap_or_default()
+}
+
+pub fn write_crypto_settings(space_root: &Path, settings: &CryptoSettings) -> Result<(), String> {
+    glyph_paths::ensure_glyph_dir(space_root)?;
+    let path = crypto_settings_path(space_root)?;
+    let bytes = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+    io_atomic::write_atomic(&path, &bytes).map_err(|e| e.to_string())
+}
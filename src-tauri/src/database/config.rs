@@ -2,16 +2,18 @@ use serde::Deserialize;
 use serde_yaml::{Mapping, Value};
 
 use crate::notes::frontmatter::{
-    normalize_frontmatter_mapping, parse_frontmatter_mapping, render_frontmatter_mapping_yaml,
-    split_frontmatter,
+    normalize_frontmatter_mapping, parse_frontmatter_mapping, split_frontmatter,
 };
+use crate::notes::frontmatter_edit::render_frontmatter_lossless;
 
 use super::types::DatabaseConfig;
 #[cfg(test)]
 use super::types::{DatabaseColumn, DatabaseNewNoteConfig, DatabaseSource, DatabaseViewState};
+use super::views::{select_view, sync_active_view};
 
 const DATABASE_KIND: &str = "database";
-const DATABASE_VERSION: i64 = 1;
+/// Version 2 added named views; version 1 configs are upgraded on parse.
+const DATABASE_VERSION: i64 = 2;
 
 fn key(name: &str) -> Value {
     Value::String(name.to_string())
@@ -39,6 +41,9 @@ fn default_columns() -> Vec<DatabaseColumn> {
             visible: true,
             property_key: None,
             property_kind: None,
+            formula: None,
+            relation: None,
+            rollup: None,
         },
         DatabaseColumn {
             id: "tags".to_string(),
@@ -49,6 +54,9 @@ fn default_columns() -> Vec<DatabaseColumn> {
             visible: true,
             property_key: None,
             property_kind: None,
+            formula: None,
+            relation: None,
+            rollup: None,
         },
         DatabaseColumn {
             id: "updated".to_string(),
@@ -59,6 +67,9 @@ fn default_columns() -> Vec<DatabaseColumn> {
             visible: true,
             property_key: None,
             property_kind: None,
+            formula: None,
+            relation: None,
+            rollup: None,
         },
     ]
 }
@@ -70,18 +81,28 @@ pub fn starter_database_config(default_dir: &str) -> DatabaseConfig {
             kind: "folder".to_string(),
             value: default_dir.trim_matches('/').to_string(),
             recursive: true,
+            property: None,
+            folder: None,
+            operator: None,
+            sources: Vec::new(),
         },
         new_note: DatabaseNewNoteConfig {
             folder: default_dir.trim_matches('/').to_string(),
             title_prefix: "Untitled".to_string(),
+            template: None,
         },
         view: DatabaseViewState {
             layout: "table".to_string(),
             board_group_by: None,
+            calendar_date_column: None,
+            calendar_range: None,
         },
         columns: default_columns(),
         sorts: Vec::new(),
         filters: Vec::new(),
+        properties: Vec::new(),
+        views: Vec::new(),
+        active_view: None,
     }
 }
 
@@ -124,9 +145,11 @@ pub fn parse_database_config(markdown: &str) -> Result<DatabaseConfig, String> {
     if glyph.kind.as_deref() != Some(DATABASE_KIND) {
         return Err("note is not a database note".to_string());
     }
-    glyph
+    let mut config = glyph
         .database
-        .ok_or_else(|| "database note is missing glyph.database config".to_string())
+        .ok_or_else(|| "database note is missing glyph.database config".to_string())?;
+    select_view(&mut config, None)?;
+    Ok(config)
 }
 
 pub fn render_database_markdown(
@@ -142,13 +165,15 @@ pub fn render_database_markdown(
         key("version"),
         Value::Number(serde_yaml::Number::from(DATABASE_VERSION)),
     );
+    let mut config = config.clone();
+    sync_active_view(&mut config);
     glyph.insert(
         key("database"),
-        serde_yaml::to_value(config).map_err(|e| e.to_string())?,
+        serde_yaml::to_value(&config).map_err(|e| e.to_string())?,
     );
 
     let normalized = normalize_frontmatter_mapping(mapping, path, None, None);
-    let rendered_yaml = render_frontmatter_mapping_yaml(&normalized)?;
+    let rendered_yaml = render_frontmatter_lossless(yaml, &normalized)?;
     Ok(format!(
         "---\n{rendered_yaml}---\n\n{}",
         body.trim_start_matches('\n')
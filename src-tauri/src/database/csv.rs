@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+
+use serde_yaml::Value;
+
+use crate::notes::properties::{infer_text_kind, text_to_yaml_value};
+
+use super::types::DatabaseColumn;
+
+const BOM: char = '\u{feff}';
+
+/// Parses RFC 4180 CSV: quoted fields may hold commas, doubled quotes and
+/// line breaks. A leading UTF-8 BOM and blank lines are skipped.
+pub(super) fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let text = text.strip_prefix(BOM).unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    let mut end_record = |record: &mut Vec<String>, field: &mut String| {
+        record.push(std::mem::take(field));
+        let done = std::mem::take(record);
+        if !(done.len() == 1 && done[0].is_empty()) {
+            records.push(done);
+        }
+    };
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                end_record(&mut record, &mut field);
+            }
+            '\n' => end_record(&mut record, &mut field),
+            _ => field.push(ch),
+        }
+    }
+    if in_quotes {
+        return Err("CSV has an unterminated quoted field".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        end_record(&mut record, &mut field);
+    }
+    Ok(records)
+}
+
+fn write_field(out: &mut String, field: &str) {
+    let needs_quotes = field.contains([',', '"', '\r', '\n'])
+        || field.starts_with(char::is_whitespace)
+        || field.ends_with(char::is_whitespace);
+    if needs_quotes {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(field);
+    }
+}
+
+/// Writes records as CSV with CRLF line endings, optionally behind a BOM so
+/// spreadsheet apps detect UTF-8.
+pub(super) fn write_csv(records: &[Vec<String>], bom: bool) -> String {
+    let mut out = String::new();
+    if bom {
+        out.push(BOM);
+    }
+    for record in records {
+        for (index, field) in record.iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+            write_field(&mut out, field);
+        }
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// A CSV column that becomes a note property.
+#[derive(Debug)]
+pub(super) struct CsvProperty {
+    pub index: usize,
+    pub key: String,
+    pub label: String,
+    pub kind: &'static str,
+}
+
+/// How the columns of a CSV map onto notes: one column names the note, a
+/// `tags` column fills the tags and the rest become typed properties.
+#[derive(Debug)]
+pub(super) struct CsvImportPlan {
+    pub title_index: usize,
+    pub tags_index: Option<usize>,
+    pub properties: Vec<CsvProperty>,
+}
+
+/// The common kind of a column's non-empty values, or `text` when they
+/// disagree.
+fn column_kind<'a>(values: impl Iterator<Item = &'a str>) -> &'static str {
+    let mut kind = None;
+    for value in values.filter(|value| !value.trim().is_empty()) {
+        let next = infer_text_kind(value);
+        match kind {
+            None => kind = Some(next),
+            Some(current) if current == next => {}
+            Some(_) => return "text",
+        }
+    }
+    kind.unwrap_or("text")
+}
+
+pub(super) fn plan_import(
+    records: &[Vec<String>],
+    title_column: Option<&str>,
+) -> Result<CsvImportPlan, String> {
+    let header = records
+        .first()
+        .ok_or_else(|| "CSV file is empty".to_string())?;
+    let find = |name: &str| {
+        header
+            .iter()
+            .position(|label| label.trim().eq_ignore_ascii_case(name))
+    };
+    let title_index = match title_column.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => find(name).ok_or_else(|| format!("CSV has no column '{name}'"))?,
+        None => find("title").or_else(|| find("name")).unwrap_or(0),
+    };
+    let tags_index = find("tags").filter(|index| *index != title_index);
+
+    let mut seen = HashSet::new();
+    let mut properties = Vec::new();
+    for (index, label) in header.iter().enumerate() {
+        if index == title_index || Some(index) == tags_index {
+            continue;
+        }
+        let label = label.trim();
+        let base = if label.is_empty() {
+            format!("column_{}", index + 1)
+        } else {
+            label.to_string()
+        };
+        let mut key = base.clone();
+        let mut suffix = 2;
+        while !seen.insert(key.to_lowercase()) {
+            key = format!("{base} {suffix}");
+            suffix += 1;
+        }
+        let kind = column_kind(
+            records[1..]
+                .iter()
+                .filter_map(|record| record.get(index).map(String::as_str)),
+        );
+        properties.push(CsvProperty {
+            index,
+            label: if label.is_empty() {
+                key.clone()
+            } else {
+                label.to_string()
+            },
+            key,
+            kind,
+        });
+    }
+    Ok(CsvImportPlan {
+        title_index,
+        tags_index,
+        properties,
+    })
+}
+
+impl CsvProperty {
+    /// The property value of a cell, `None` when it is blank. Text keeps the
+    /// surrounding whitespace that export quotes; other kinds are parsed from
+    /// the trimmed cell.
+    pub(super) fn value(&self, raw: &str) -> Result<Option<Value>, String> {
+        if raw.trim().is_empty() {
+            return Ok(None);
+        }
+        if self.kind == "text" {
+            return Ok(Some(Value::String(raw.to_string())));
+        }
+        text_to_yaml_value(&self.key, self.kind, raw).map(Some)
+    }
+
+    pub(super) fn column(&self) -> DatabaseColumn {
+        DatabaseColumn {
+            id: format!("property:{}", self.key),
+            column_type: "property".to_string(),
+            label: self.label.clone(),
+            icon: None,
+            width: Some(180),
+            visible: true,
+            property_key: Some(self.key.clone()),
+            property_kind: Some(self.kind.to_string()),
+            formula: None,
+            relation: None,
+            rollup: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_quotes_newlines_and_bom() {
+        let records = vec![
+            vec![
+                "Title".to_string(),
+                "Notes".to_string(),
+                "Score".to_string(),
+            ],
+            vec![
+                "Say \"hi\", please".to_string(),
+                "line one\nline two\r\nline three".to_string(),
+                " 7 ".to_string(),
+            ],
+            vec!["Café ☕".to_string(), String::new(), "3".to_string()],
+        ];
+        let written = write_csv(&records, true);
+        assert!(written.starts_with('\u{feff}'));
+        assert_eq!(parse_csv(&written).unwrap(), records);
+        assert_eq!(parse_csv(&write_csv(&records, false)).unwrap(), records);
+        assert!(parse_csv("a,\"b\nc").is_err());
+    }
+
+    #[test]
+    fn plans_typed_properties_from_columns() {
+        let records = parse_csv(
+            "\u{feff}Score,Name,Done,Due,Zip,Tags,,score\n1.5,Alpha,true,2024-05-01,02139,a;b,x,1\n2,Beta,false,,10001,,y,2\n",
+        )
+        .unwrap();
+        let plan = plan_import(&records, None).unwrap();
+        assert_eq!(plan.title_index, 1);
+        assert_eq!(plan.tags_index, Some(5));
+        let kinds = plan
+            .properties
+            .iter()
+            .map(|property| (property.key.as_str(), property.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ("Score", "number"),
+                ("Done", "checkbox"),
+                ("Due", "date"),
+                ("Zip", "text"),
+                ("column_7", "text"),
+                ("score 2", "number"),
+            ]
+        );
+        assert!(plan_import(&records, Some("missing")).is_err());
+
+        let values = ["1.5", "true", " 2024-05-01 ", "  02139 ", "x", " 1 "]
+            .iter()
+            .zip(&plan.properties)
+            .map(|(raw, property)| property.value(raw).unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(values[0], serde_yaml::from_str::<Value>("1.5").unwrap());
+        assert_eq!(values[1], Value::Bool(true));
+        assert_eq!(values[3], Value::String("  02139 ".to_string()));
+        assert_eq!(values[5], serde_yaml::from_str::<Value>("1").unwrap());
+        assert_eq!(plan.properties[0].value("  ").unwrap(), None);
+    }
+}
@@ -0,0 +1,515 @@
+use std::cmp::Ordering;
+
+use rusqlite::types::Value;
+
+use crate::dates::{date_from_iso, parse_natural_date, DateContext};
+
+use super::formula::FormulaValue;
+use super::types::{DatabaseColumn, DatabaseFilter, DatabaseSort};
+
+/// Quotes `raw` as an SQL string literal. Used for property keys that are
+/// repeated inside sort expressions, where positional parameters would have
+/// to be re-bound for every keyset comparison.
+fn sql_literal(raw: &str) -> String {
+    format!("'{}'", raw.replace('\'', "''"))
+}
+
+fn like_pattern(raw: &str) -> String {
+    let escaped = raw
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+fn normalize_tag_text(raw: &str) -> String {
+    raw.trim().trim_start_matches('#').to_lowercase()
+}
+
+fn property_column(key: &str, column: &str) -> String {
+    format!(
+        "(SELECT np.{column} FROM note_properties np WHERE np.note_id = n.id AND np.key = {})",
+        sql_literal(key)
+    )
+}
+
+fn property_key(column: &DatabaseColumn) -> Option<&str> {
+    column
+        .property_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// The text a column holds for the row `n`, or `None` for unknown columns.
+fn text_expr(column: &DatabaseColumn) -> Option<String> {
+    match column.column_type.as_str() {
+        "title" => Some("n.title".to_string()),
+        "path" => Some("n.id".to_string()),
+        "created" => Some("n.created".to_string()),
+        "updated" => Some("n.updated".to_string()),
+        "tags" => Some(
+            "(SELECT group_concat(tag, ', ') FROM (SELECT t.tag FROM tags t WHERE t.note_id = n.id ORDER BY t.tag))"
+                .to_string(),
+        ),
+        "property" => property_key(column).map(|key| property_column(key, "value_text")),
+        _ => None,
+    }
+}
+
+fn is_list_column(column: &DatabaseColumn) -> bool {
+    column.column_type == "tags" || matches!(column.property_kind.as_deref(), Some("list" | "tags"))
+}
+
+/// SQL over the individual values of a list-like column (tags or list
+/// properties), selecting one `value` per item.
+fn list_items_sql(column: &DatabaseColumn) -> Option<String> {
+    match column.column_type.as_str() {
+        "tags" => Some("SELECT t.tag AS value FROM tags t WHERE t.note_id = n.id".to_string()),
+        "property" => property_key(column).map(|key| {
+            format!(
+                "SELECT j.value AS value FROM note_properties np, json_each(np.value_json) j WHERE np.note_id = n.id AND np.key = {}",
+                sql_literal(key)
+            )
+        }),
+        _ => None,
+    }
+}
+
+fn filter_text(filter: &DatabaseFilter) -> Option<String> {
+    filter
+        .value_text
+        .as_deref()
+        .or(filter.value_list.first().map(String::as_str))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn equals_any(
+    column: &DatabaseColumn,
+    text: &str,
+    values: &[String],
+    params: &mut Vec<Value>,
+) -> String {
+    let placeholders = std::iter::repeat_n("?", values.len())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut clause = format!("lower(COALESCE({text}, '')) IN ({placeholders})");
+    params.extend(values.iter().cloned().map(Value::from));
+    if let Some(items) = list_items_sql(column) {
+        clause = format!(
+            "({clause} OR EXISTS (SELECT 1 FROM ({items}) WHERE lower(value) IN ({placeholders})))"
+        );
+        params.extend(values.iter().cloned().map(Value::from));
+    }
+    clause
+}
+
+fn date_clause(
+    text: &str,
+    comparison: &str,
+    filter: &DatabaseFilter,
+    ctx: &DateContext,
+    params: &mut Vec<Value>,
+) -> Option<String> {
+    let date = parse_natural_date(&filter_text(filter)?, ctx).ok()?;
+    params.push(Value::from(date));
+    Some(format!(
+        "(COALESCE({text}, '') != '' AND substr({text}, 1, 10) {comparison} ?)"
+    ))
+}
+
+/// Compiles one filter into a condition on `notes n`. Filters on unknown
+/// columns, with unknown operators or without a usable value match every
+/// row, the same way the table view treats a half-edited filter.
+pub fn filter_sql(
+    columns: &[DatabaseColumn],
+    filter: &DatabaseFilter,
+    ctx: &DateContext,
+    params: &mut Vec<Value>,
+) -> Option<String> {
+    let column = columns
+        .iter()
+        .find(|column| column.id == filter.column_id)?;
+    let text = text_expr(column)?;
+    match filter.operator.as_str() {
+        "contains" => {
+            let needle = filter_text(filter)?;
+            params.push(Value::from(like_pattern(&needle)));
+            Some(format!("lower(COALESCE({text}, '')) LIKE ? ESCAPE '\\'"))
+        }
+        "equals" => {
+            let value = filter_text(filter)?;
+            Some(equals_any(column, &text, &[value], params))
+        }
+        "in_list" => {
+            let values = filter
+                .value_list
+                .iter()
+                .map(|value| value.trim().to_lowercase())
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<_>>();
+            if values.is_empty() {
+                return None;
+            }
+            Some(equals_any(column, &text, &values, params))
+        }
+        "is_empty" => Some(format!("COALESCE({text}, '') = ''")),
+        "is_not_empty" => Some(format!("COALESCE({text}, '') != ''")),
+        "is_true" | "is_false" => {
+            let key = property_key(column)?;
+            let expected = if filter.operator == "is_true" {
+                "true"
+            } else {
+                "false"
+            };
+            Some(format!(
+                "({} = 'checkbox' AND {} = '{expected}')",
+                property_column(key, "value_type"),
+                property_column(key, "value_json"),
+            ))
+        }
+        "tags_contains" => {
+            let tag = normalize_tag_text(&filter_text(filter)?);
+            let items = list_items_sql(column)?;
+            params.push(Value::from(tag));
+            Some(format!(
+                "EXISTS (SELECT 1 FROM ({items}) WHERE lower(ltrim(value, '#')) = ?)"
+            ))
+        }
+        "before" => date_clause(&text, "<", filter, ctx, params),
+        "after" => date_clause(&text, ">", filter, ctx, params),
+        "on_or_before" => date_clause(&text, "<=", filter, ctx, params),
+        "on_or_after" => date_clause(&text, ">=", filter, ctx, params),
+        _ => None,
+    }
+}
+
+fn date_matches(
+    value: &FormulaValue,
+    filter: &DatabaseFilter,
+    ctx: &DateContext,
+    accept: fn(Ordering) -> bool,
+) -> bool {
+    let Some(bound) = filter_text(filter)
+        .and_then(|raw| parse_natural_date(&raw, ctx).ok())
+        .and_then(|iso| date_from_iso(&iso))
+    else {
+        return true;
+    };
+    matches!(value.as_date(), Ok(Some(date)) if accept(date.cmp(&bound)))
+}
+
+/// The in-memory counterpart of [`filter_sql`] for computed (formula)
+/// values. Error cells never match.
+pub fn value_matches_filter(
+    value: &Result<FormulaValue, String>,
+    filter: &DatabaseFilter,
+    ctx: &DateContext,
+) -> bool {
+    let Ok(value) = value else {
+        return false;
+    };
+    let items = match value {
+        FormulaValue::List(items) => items.iter().map(|item| item.to_lowercase()).collect(),
+        other => vec![other.as_text().to_lowercase()],
+    };
+    match filter.operator.as_str() {
+        "contains" => {
+            filter_text(filter).is_none_or(|needle| items.iter().any(|item| item.contains(&needle)))
+        }
+        "equals" => filter_text(filter).is_none_or(|needle| items.contains(&needle)),
+        "in_list" => {
+            let values = filter
+                .value_list
+                .iter()
+                .map(|value| value.trim().to_lowercase())
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<_>>();
+            values.is_empty() || values.iter().any(|value| items.contains(value))
+        }
+        "is_empty" => value.is_empty(),
+        "is_not_empty" => !value.is_empty(),
+        "is_true" => *value == FormulaValue::Bool(true),
+        "is_false" => *value == FormulaValue::Bool(false),
+        "tags_contains" => filter_text(filter).is_none_or(|tag| {
+            let tag = normalize_tag_text(&tag);
+            items.iter().any(|item| normalize_tag_text(item) == tag)
+        }),
+        "before" => date_matches(value, filter, ctx, Ordering::is_lt),
+        "after" => date_matches(value, filter, ctx, Ordering::is_gt),
+        "on_or_before" => date_matches(value, filter, ctx, Ordering::is_le),
+        "on_or_after" => date_matches(value, filter, ctx, Ordering::is_ge),
+        _ => true,
+    }
+}
+
+/// Orders two computed values for one sort: blanks and errors last in
+/// either direction, everything else by [`FormulaValue::compare`].
+pub fn compare_values(
+    a: &Result<FormulaValue, String>,
+    b: &Result<FormulaValue, String>,
+    descending: bool,
+) -> Ordering {
+    let blank =
+        |value: &Result<FormulaValue, String>| value.as_ref().map_or(true, FormulaValue::is_empty);
+    match (a, b) {
+        (Ok(a), Ok(b)) if !a.is_empty() && !b.is_empty() => {
+            let ordering = a.compare(b);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+        _ => blank(a).cmp(&blank(b)),
+    }
+}
+
+/// One `ORDER BY` key. Keys never evaluate to NULL so they can be compared
+/// against cursor values with plain `=`/`<`/`>`.
+pub struct SortKey {
+    pub expr: String,
+    pub descending: bool,
+}
+
+/// Expands the configured sorts into ordering keys. Each sort becomes an
+/// "is empty" key (so blank cells sort last in either direction) followed by
+/// the value itself; `n.id` is always appended as a final tiebreaker.
+pub fn sort_keys(columns: &[DatabaseColumn], sorts: &[DatabaseSort]) -> Vec<SortKey> {
+    let mut keys = Vec::new();
+    for sort in sorts {
+        let Some(column) = columns.iter().find(|column| column.id == sort.column_id) else {
+            continue;
+        };
+        let Some(text) = text_expr(column) else {
+            continue;
+        };
+        let value = match column.property_kind.as_deref() {
+            Some("number") if column.column_type == "property" => {
+                format!("COALESCE(CAST(NULLIF({text}, '') AS REAL), 0)")
+            }
+            _ if is_list_column(column) || column.column_type == "title" => {
+                format!("lower(COALESCE({text}, ''))")
+            }
+            _ => format!("COALESCE({text}, '')"),
+        };
+        keys.push(SortKey {
+            expr: format!("(COALESCE({text}, '') = '')"),
+            descending: false,
+        });
+        keys.push(SortKey {
+            expr: value,
+            descending: sort.direction.eq_ignore_ascii_case("desc"),
+        });
+    }
+    if keys.is_empty() {
+        keys.push(SortKey {
+            expr: "n.updated".to_string(),
+            descending: true,
+        });
+    }
+    keys.push(SortKey {
+        expr: "n.id".to_string(),
+        descending: false,
+    });
+    keys
+}
+
+pub fn order_by_sql(keys: &[SortKey]) -> String {
+    keys.iter()
+        .map(|key| {
+            format!(
+                "{} {}",
+                key.expr,
+                if key.descending { "DESC" } else { "ASC" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The condition selecting rows strictly after `after` (the previous page's
+/// last sort values) in the order described by `keys`.
+pub fn keyset_sql(keys: &[SortKey], after: &[Value], params: &mut Vec<Value>) -> String {
+    let mut branches = Vec::new();
+    for (index, key) in keys.iter().enumerate() {
+        let mut parts = Vec::new();
+        for (equal_key, value) in keys[..index].iter().zip(after) {
+            parts.push(format!("{} = ?", equal_key.expr));
+            params.push(value.clone());
+        }
+        parts.push(format!(
+            "{} {} ?",
+            key.expr,
+            if key.descending { "<" } else { ">" }
+        ));
+        params.push(after[index].clone());
+        branches.push(format!("({})", parts.join(" AND ")));
+    }
+    format!("({})", branches.join(" OR "))
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::{types::Value, Connection};
+
+    use super::{filter_sql, keyset_sql, order_by_sql, sort_keys};
+    use crate::database::types::{DatabaseColumn, DatabaseFilter, DatabaseSort};
+    use crate::dates::DateContext;
+
+    fn column(id: &str, column_type: &str, property_kind: Option<&str>) -> DatabaseColumn {
+        DatabaseColumn {
+            id: id.to_string(),
+            column_type: column_type.to_string(),
+            label: id.to_string(),
+            icon: None,
+            width: None,
+            visible: true,
+            property_key: (column_type == "property").then(|| id.to_string()),
+            property_kind: property_kind.map(str::to_string),
+            formula: None,
+            relation: None,
+            rollup: None,
+        }
+    }
+
+    fn filter(column_id: &str, operator: &str, value: &str) -> DatabaseFilter {
+        DatabaseFilter {
+            column_id: column_id.to_string(),
+            operator: operator.to_string(),
+            value_text: (!value.is_empty()).then(|| value.to_string()),
+            value_bool: None,
+            value_list: Vec::new(),
+        }
+    }
+
+    fn seeded() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id TEXT, title TEXT, created TEXT, updated TEXT);
+             CREATE TABLE tags (note_id TEXT, tag TEXT);
+             CREATE TABLE note_properties (note_id TEXT, key TEXT, value_type TEXT, value_text TEXT, value_json TEXT);
+             INSERT INTO notes VALUES ('a.md', 'Alpha', '2025-01-01', '2025-03-01'),
+                                      ('b.md', 'Beta', '2025-01-02', '2025-03-02'),
+                                      ('c.md', 'Gamma', '2025-01-03', '2025-03-03');
+             INSERT INTO tags VALUES ('a.md', '#work'), ('c.md', '#home');
+             INSERT INTO note_properties VALUES
+               ('a.md', 'due', 'text', '2025-03-10', '\"2025-03-10\"'),
+               ('b.md', 'due', 'text', '2025-02-01', '\"2025-02-01\"'),
+               ('a.md', 'done', 'checkbox', 'true', 'true'),
+               ('b.md', 'done', 'checkbox', 'false', 'false'),
+               ('a.md', 'points', 'number', '10', '10'),
+               ('b.md', 'points', 'number', '9', '9'),
+               ('c.md', 'labels', 'list', 'x, y', '[\"x\",\"y\"]');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn matching(conn: &Connection, filters: &[DatabaseFilter]) -> Vec<String> {
+        let columns = vec![
+            column("title", "title", None),
+            column("tags", "tags", None),
+            column("due", "property", Some("date")),
+            column("done", "property", Some("checkbox")),
+            column("labels", "property", Some("list")),
+        ];
+        let ctx = DateContext::new(Some("2025-03-05"), None).unwrap();
+        let mut params = Vec::new();
+        let mut clauses = vec!["1 = 1".to_string()];
+        for filter in filters {
+            clauses.extend(filter_sql(&columns, filter, &ctx, &mut params));
+        }
+        let sql = format!(
+            "SELECT n.id FROM notes n WHERE {} ORDER BY n.id",
+            clauses.join(" AND ")
+        );
+        let mut stmt = conn.prepare(&sql).unwrap();
+        stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<String>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn compiles_filters_to_sql() {
+        let conn = seeded();
+        assert_eq!(
+            matching(&conn, &[filter("title", "contains", "ET")]),
+            ["b.md"]
+        );
+        assert_eq!(
+            matching(&conn, &[filter("labels", "equals", "Y")]),
+            ["c.md"]
+        );
+        assert_eq!(
+            matching(&conn, &[filter("tags", "tags_contains", "work")]),
+            ["a.md"]
+        );
+        assert_eq!(
+            matching(&conn, &[filter("due", "before", "today")]),
+            ["b.md"]
+        );
+        assert_eq!(
+            matching(&conn, &[filter("due", "on_or_after", "today")]),
+            ["a.md"]
+        );
+        assert_eq!(matching(&conn, &[filter("due", "is_empty", "")]), ["c.md"]);
+        assert_eq!(matching(&conn, &[filter("done", "is_false", "")]), ["b.md"]);
+        assert_eq!(
+            matching(&conn, &[filter("title", "contains", "")]),
+            ["a.md", "b.md", "c.md"]
+        );
+        let mut in_list = filter("title", "in_list", "");
+        in_list.value_list = vec!["alpha".to_string(), "GAMMA".to_string()];
+        assert_eq!(matching(&conn, &[in_list]), ["a.md", "c.md"]);
+    }
+
+    #[test]
+    fn pages_through_multi_key_sorts_with_keysets() {
+        let conn = seeded();
+        let columns = vec![column("points", "property", Some("number"))];
+        let keys = sort_keys(
+            &columns,
+            &[DatabaseSort {
+                column_id: "points".to_string(),
+                direction: "desc".to_string(),
+            }],
+        );
+        let key_columns = keys
+            .iter()
+            .map(|key| key.expr.clone())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut after: Option<Vec<Value>> = None;
+        let mut seen = Vec::new();
+        loop {
+            let mut params = Vec::new();
+            let condition = match &after {
+                Some(values) => keyset_sql(&keys, values, &mut params),
+                None => "1 = 1".to_string(),
+            };
+            let sql = format!(
+                "SELECT n.id, {key_columns} FROM notes n WHERE {condition} ORDER BY {} LIMIT 1",
+                order_by_sql(&keys)
+            );
+            let mut stmt = conn.prepare(&sql).unwrap();
+            let row = stmt
+                .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                    let values = (1..=keys.len())
+                        .map(|index| row.get::<_, Value>(index))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok((row.get::<_, String>(0)?, values))
+                })
+                .unwrap()
+                .next();
+            let Some(row) = row else { break };
+            let (id, values) = row.unwrap();
+            seen.push(id);
+            after = Some(values);
+        }
+        assert_eq!(seen, ["a.md", "b.md", "c.md"]);
+    }
+}
@@ -0,0 +1,976 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use time::Date;
+
+use crate::dates::{self, date_from_iso, date_to_iso, DateContext};
+
+use super::types::{DatabaseCellValue, DatabaseColumn, DatabaseRow};
+
+/// A typed formula result. Evaluation errors are carried separately as
+/// `Err(String)` so one bad cell does not fail the whole load.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FormulaValue {
+    Empty,
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Date(Date),
+    List(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Literal(FormulaValue),
+    Ref(String),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+const OPERATORS: [&str; 18] = [
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "(", ")", ",", "=",
+];
+
+/// Bounds on formula size, so evaluating (and dropping) the expression
+/// tree cannot overflow the stack.
+const MAX_TOKENS: usize = 2_000;
+const MAX_NESTING: usize = 64;
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()))
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let literal = chars[start..i].iter().collect::<String>();
+            let number = literal
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{literal}'"))?;
+            tokens.push(Token::Number(number));
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err("unterminated string".to_string()),
+                    Some('\\') if i + 1 < chars.len() => {
+                        text.push(chars[i + 1]);
+                        i += 2;
+                    }
+                    Some(&q) if q == c => {
+                        i += 1;
+                        break;
+                    }
+                    Some(&other) => {
+                        text.push(other);
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push(Token::Text(text));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest = chars[i..].iter().take(2).collect::<String>();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| format!("unexpected character '{c}'"))?;
+            // A lone `=` reads as equality, as it does in spreadsheet formulas.
+            tokens.push(Token::Op(if *op == "=" { "==" } else { op }));
+            i += op.len();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        let op = match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => *op,
+            Some(Token::Ident(word)) => match word.to_lowercase().as_str() {
+                "and" if ops.contains(&"&&") => "&&",
+                "or" if ops.contains(&"||") => "||",
+                "not" if ops.contains(&"!") => "!",
+                _ => return None,
+            },
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(op)
+    }
+
+    fn expect_op(&mut self, op: &'static str) -> Result<(), String> {
+        self.eat_op(&[op])
+            .map(|_| ())
+            .ok_or_else(|| format!("expected '{op}'"))
+    }
+
+    fn binary_level(
+        &mut self,
+        ops: &[&'static str],
+        next: fn(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        let mut left = next(self)?;
+        while let Some(op) = self.eat_op(ops) {
+            let right = next(self)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["||"], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["&&"], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["==", "!=", "<=", ">=", "<", ">"], Self::additive)
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["+", "-"], Self::multiplicative)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["*", "/", "%"], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.depth >= MAX_NESTING {
+            return Err("formula is nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let expr = match self.eat_op(&["-", "!"]) {
+            Some(op) => self.unary().map(|inner| Expr::Unary(op, Box::new(inner))),
+            None => self.primary(),
+        };
+        self.depth -= 1;
+        expr
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| "unexpected end of formula".to_string())?;
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Literal(FormulaValue::Number(n))),
+            Token::Text(text) => Ok(Expr::Literal(FormulaValue::Text(text))),
+            Token::Op("(") => {
+                let inner = self.or()?;
+                self.expect_op(")")?;
+                Ok(inner)
+            }
+            Token::Ident(name) => {
+                if self.eat_op(&["("]).is_none() {
+                    return Ok(match name.to_lowercase().as_str() {
+                        "true" => Expr::Literal(FormulaValue::Bool(true)),
+                        "false" => Expr::Literal(FormulaValue::Bool(false)),
+                        "null" | "empty" => Expr::Literal(FormulaValue::Empty),
+                        _ => Expr::Ref(name),
+                    });
+                }
+                let mut args = Vec::new();
+                if self.eat_op(&[")"]).is_none() {
+                    loop {
+                        args.push(self.or()?);
+                        if self.eat_op(&[")"]).is_some() {
+                            break;
+                        }
+                        self.expect_op(",")?;
+                    }
+                }
+                Ok(Expr::Call(name.to_lowercase(), args))
+            }
+            Token::Op(op) => Err(format!("unexpected '{op}'")),
+        }
+    }
+}
+
+/// Parses a formula such as `if(done, "✓", days_between(due, now()) + " days")`.
+pub fn parse_formula(source: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        depth: 0,
+    };
+    if parser.tokens.is_empty() {
+        return Err("formula is empty".to_string());
+    }
+    if parser.tokens.len() > MAX_TOKENS {
+        return Err("formula is too long".to_string());
+    }
+    let expr = parser.or()?;
+    if parser.pos < parser.tokens.len() {
+        return Err("unexpected input after formula".to_string());
+    }
+    Ok(expr)
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        (n as i64).to_string()
+    } else {
+        let text = format!("{n:.6}");
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+impl FormulaValue {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Empty => true,
+            Self::Text(text) => text.is_empty(),
+            Self::List(items) => items.is_empty(),
+            _ => false,
+        }
+    }
+
+    pub fn as_text(&self) -> String {
+        match self {
+            Self::Empty => String::new(),
+            Self::Number(n) => format_number(*n),
+            Self::Text(text) => text.clone(),
+            Self::Bool(b) => b.to_string(),
+            Self::Date(date) => date_to_iso(*date),
+            Self::List(items) => items.join(", "),
+        }
+    }
+
+    pub fn as_number(&self) -> Result<f64, String> {
+        match self {
+            Self::Empty => Ok(0.0),
+            Self::Number(n) => Ok(*n),
+            Self::Bool(b) => Ok(if *b { 1.0 } else { 0.0 }),
+            Self::Text(text) => text
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("'{text}' is not a number")),
+            Self::Date(_) | Self::List(_) => Err("expected a number".to_string()),
+        }
+    }
+
+    pub fn truthy(&self) -> bool {
+        match self {
+            Self::Bool(b) => *b,
+            Self::Number(n) => *n != 0.0,
+            other => !other.is_empty(),
+        }
+    }
+
+    /// `None` for empty values so date functions can pass blanks through.
+    pub fn as_date(&self) -> Result<Option<Date>, String> {
+        match self {
+            Self::Empty => Ok(None),
+            Self::Date(date) => Ok(Some(*date)),
+            Self::Text(text) if text.trim().is_empty() => Ok(None),
+            Self::Text(text) => date_from_iso(text.trim().get(..10).unwrap_or(text.trim()))
+                .map(Some)
+                .ok_or_else(|| format!("'{text}' is not a date")),
+            _ => Err("expected a date".to_string()),
+        }
+    }
+
+    fn items(&self) -> Vec<String> {
+        match self {
+            Self::List(items) => items.clone(),
+            Self::Empty => Vec::new(),
+            other => vec![other.as_text()],
+        }
+    }
+
+    /// Orders two values: numbers and dates by value, booleans false-first
+    /// and everything else as case-insensitive text.
+    pub fn compare(&self, other: &Self) -> Ordering {
+        if matches!(self, Self::Date(_)) || matches!(other, Self::Date(_)) {
+            if let (Ok(Some(a)), Ok(Some(b))) = (self.as_date(), other.as_date()) {
+                return a.cmp(&b);
+            }
+        }
+        match (self, other) {
+            (Self::Date(a), Self::Date(b)) => a.cmp(b),
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            _ => match (self.as_number(), other.as_number()) {
+                (Ok(a), Ok(b)) if !matches!((self, other), (Self::Empty, Self::Empty)) => {
+                    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+                }
+                _ => self
+                    .as_text()
+                    .to_lowercase()
+                    .cmp(&other.as_text().to_lowercase()),
+            },
+        }
+    }
+
+    fn equals(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Empty, other) | (other, Self::Empty) => other.is_empty(),
+            (Self::Number(a), Self::Number(b)) => a == b,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            _ => self.as_text() == other.as_text(),
+        }
+    }
+
+    pub fn into_cell(self) -> DatabaseCellValue {
+        let (kind, value_text, value_bool, value_list) = match self {
+            Self::Empty => ("empty", None, None, Vec::new()),
+            Self::Number(n) => ("number", Some(format_number(n)), None, Vec::new()),
+            Self::Text(text) => ("text", Some(text), None, Vec::new()),
+            Self::Bool(b) => ("checkbox", None, Some(b), Vec::new()),
+            Self::Date(date) => ("date", Some(date_to_iso(date)), None, Vec::new()),
+            Self::List(items) => ("list", None, None, items),
+        };
+        DatabaseCellValue {
+            kind: kind.to_string(),
+            value_text,
+            value_bool,
+            value_list,
+        }
+    }
+}
+
+pub(super) fn error_cell(message: String) -> DatabaseCellValue {
+    DatabaseCellValue {
+        kind: "error".to_string(),
+        value_text: Some(message),
+        value_bool: None,
+        value_list: Vec::new(),
+    }
+}
+
+fn add_days(date: Date, days: f64) -> Result<FormulaValue, String> {
+    if !days.is_finite() {
+        return Err("date out of range".to_string());
+    }
+    dates::add_days(date, days.trunc() as i64).map(FormulaValue::Date)
+}
+
+pub(super) fn cell_value(cell: &DatabaseCellValue, kind_hint: Option<&str>) -> FormulaValue {
+    match cell.kind.as_str() {
+        "checkbox" => cell
+            .value_bool
+            .map(FormulaValue::Bool)
+            .unwrap_or(FormulaValue::Empty),
+        "list" | "tags" => FormulaValue::List(cell.value_list.clone()),
+        kind => {
+            let text = cell.value_text.clone().unwrap_or_default();
+            let trimmed = text.trim();
+            if trimmed.is_empty() {
+                return FormulaValue::Empty;
+            }
+            if kind == "number" || kind_hint == Some("number") {
+                if let Ok(n) = trimmed.parse::<f64>() {
+                    return FormulaValue::Number(n);
+                }
+            }
+            if kind_hint == Some("date") {
+                if let Some(date) = date_from_iso(trimmed.get(..10).unwrap_or(trimmed)) {
+                    return FormulaValue::Date(date);
+                }
+            }
+            FormulaValue::Text(text)
+        }
+    }
+}
+
+fn timestamp_value(raw: &str) -> FormulaValue {
+    raw.get(..10)
+        .and_then(date_from_iso)
+        .map(FormulaValue::Date)
+        .unwrap_or_else(|| FormulaValue::Text(raw.to_string()))
+}
+
+/// The parsed formulas of a database's formula columns, keyed by column id.
+pub struct FormulaSet<'a> {
+    columns: &'a [DatabaseColumn],
+    formulas: HashMap<&'a str, Result<Expr, String>>,
+    ctx: &'a DateContext,
+}
+
+impl<'a> FormulaSet<'a> {
+    pub fn new(columns: &'a [DatabaseColumn], ctx: &'a DateContext) -> Self {
+        let formulas = columns
+            .iter()
+            .filter(|column| column.column_type == "formula")
+            .map(|column| {
+                let source = column.formula.as_deref().unwrap_or_default();
+                (column.id.as_str(), parse_formula(source))
+            })
+            .collect();
+        Self {
+            columns,
+            formulas,
+            ctx,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.formulas.is_empty()
+    }
+
+    /// Whether `column_id` names a formula, relation or rollup column, whose
+    /// values only exist once a row is loaded.
+    pub fn is_computed_column(&self, column_id: &str) -> bool {
+        self.columns.iter().any(|column| {
+            column.id == column_id
+                && matches!(
+                    column.column_type.as_str(),
+                    "formula" | "relation" | "rollup"
+                )
+        })
+    }
+
+    /// Fills `row.computed` with one typed (or error) cell per formula column.
+    pub fn evaluate_row(&self, row: &mut DatabaseRow) {
+        let mut results = Vec::with_capacity(self.formulas.len());
+        for id in self.formulas.keys() {
+            let cell = match self.formula_value(row, id, &mut Vec::new()) {
+                Ok(value) => value.into_cell(),
+                Err(message) => error_cell(message),
+            };
+            results.push((id.to_string(), cell));
+        }
+        row.computed.extend(results);
+    }
+
+    /// The value of any column for `row`, evaluating formula columns.
+    pub fn column_value(
+        &self,
+        row: &DatabaseRow,
+        column: &DatabaseColumn,
+    ) -> Result<FormulaValue, String> {
+        self.column_value_inner(row, column, &mut Vec::new())
+    }
+
+    fn formula_value(
+        &self,
+        row: &DatabaseRow,
+        id: &str,
+        visiting: &mut Vec<String>,
+    ) -> Result<FormulaValue, String> {
+        if visiting.iter().any(|seen| seen == id) {
+            return Err(format!("circular reference to '{id}'"));
+        }
+        let expr = match self.formulas.get(id) {
+            Some(Ok(expr)) => expr,
+            Some(Err(message)) => return Err(message.clone()),
+            None => return Err(format!("unknown formula column '{id}'")),
+        };
+        visiting.push(id.to_string());
+        let value = self.eval(row, expr, visiting);
+        visiting.pop();
+        value
+    }
+
+    fn column_value_inner(
+        &self,
+        row: &DatabaseRow,
+        column: &DatabaseColumn,
+        visiting: &mut Vec<String>,
+    ) -> Result<FormulaValue, String> {
+        Ok(match column.column_type.as_str() {
+            "title" => FormulaValue::Text(row.title.clone()),
+            "path" => FormulaValue::Text(row.note_path.clone()),
+            "created" => timestamp_value(&row.created),
+            "updated" => timestamp_value(&row.updated),
+            "tags" => FormulaValue::List(row.tags.clone()),
+            "formula" => return self.formula_value(row, &column.id, visiting),
+            "relation" => FormulaValue::List(
+                row.relations
+                    .get(&column.id)
+                    .map(|targets| targets.iter().map(|target| target.title.clone()).collect())
+                    .unwrap_or_default(),
+            ),
+            "rollup" => match row.computed.get(&column.id) {
+                Some(cell) if cell.kind == "error" => {
+                    return Err(cell.value_text.clone().unwrap_or_default())
+                }
+                Some(cell) => cell_value(cell, Some(cell.kind.as_str())),
+                None => FormulaValue::Empty,
+            },
+            _ => column
+                .property_key
+                .as_deref()
+                .and_then(|key| row.properties.get(key))
+                .map(|cell| cell_value(cell, column.property_kind.as_deref()))
+                .unwrap_or(FormulaValue::Empty),
+        })
+    }
+
+    /// Column ids win over built-in fields, which win over raw property keys.
+    fn resolve(
+        &self,
+        row: &DatabaseRow,
+        name: &str,
+        visiting: &mut Vec<String>,
+    ) -> Result<FormulaValue, String> {
+        if let Some(column) = self.columns.iter().find(|column| column.id == name) {
+            return self.column_value_inner(row, column, visiting);
+        }
+        Ok(match name {
+            "title" => FormulaValue::Text(row.title.clone()),
+            "path" => FormulaValue::Text(row.note_path.clone()),
+            "created" => timestamp_value(&row.created),
+            "updated" => timestamp_value(&row.updated),
+            "tags" => FormulaValue::List(row.tags.clone()),
+            key => row
+                .properties
+                .get(key)
+                .map(|cell| cell_value(cell, None))
+                .unwrap_or(FormulaValue::Empty),
+        })
+    }
+
+    fn eval(
+        &self,
+        row: &DatabaseRow,
+        expr: &Expr,
+        visiting: &mut Vec<String>,
+    ) -> Result<FormulaValue, String> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Ref(name) => self.resolve(row, name, visiting),
+            Expr::Unary(op, inner) => {
+                let value = self.eval(row, inner, visiting)?;
+                match *op {
+                    "-" => Ok(FormulaValue::Number(-value.as_number()?)),
+                    _ => Ok(FormulaValue::Bool(!value.truthy())),
+                }
+            }
+            Expr::Binary("&&", left, right) => Ok(FormulaValue::Bool(
+                self.eval(row, left, visiting)?.truthy()
+                    && self.eval(row, right, visiting)?.truthy(),
+            )),
+            Expr::Binary("||", left, right) => Ok(FormulaValue::Bool(
+                self.eval(row, left, visiting)?.truthy()
+                    || self.eval(row, right, visiting)?.truthy(),
+            )),
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(row, left, visiting)?;
+                let right = self.eval(row, right, visiting)?;
+                binary(op, left, right)
+            }
+            Expr::Call(name, args) if name == "if" => {
+                if !(2..=3).contains(&args.len()) {
+                    return Err("if() takes a condition and one or two values".to_string());
+                }
+                if self.eval(row, &args[0], visiting)?.truthy() {
+                    self.eval(row, &args[1], visiting)
+                } else if let Some(otherwise) = args.get(2) {
+                    self.eval(row, otherwise, visiting)
+                } else {
+                    Ok(FormulaValue::Empty)
+                }
+            }
+            Expr::Call(name, args) if name == "prop" => {
+                let [key] = args.as_slice() else {
+                    return Err("prop() takes one property name".to_string());
+                };
+                let key = self.eval(row, key, visiting)?.as_text();
+                self.resolve(row, &key, visiting)
+            }
+            Expr::Call(name, args) => {
+                let values = args
+                    .iter()
+                    .map(|arg| self.eval(row, arg, visiting))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call(name, values, self.ctx)
+            }
+        }
+    }
+}
+
+fn binary(op: &str, left: FormulaValue, right: FormulaValue) -> Result<FormulaValue, String> {
+    use FormulaValue::*;
+    Ok(match (op, &left, &right) {
+        ("==", _, _) => Bool(left.equals(&right)),
+        ("!=", _, _) => Bool(!left.equals(&right)),
+        ("<", _, _) => Bool(left.compare(&right) == Ordering::Less),
+        ("<=", _, _) => Bool(left.compare(&right) != Ordering::Greater),
+        (">", _, _) => Bool(left.compare(&right) == Ordering::Greater),
+        (">=", _, _) => Bool(left.compare(&right) != Ordering::Less),
+        ("+", Date(date), other) | ("+", other, Date(date)) if !matches!(other, Text(_)) => {
+            add_days(*date, other.as_number()?)?
+        }
+        ("-", Date(a), Date(b)) => Number((*a - *b).whole_days() as f64),
+        ("-", Date(date), other) => add_days(*date, -other.as_number()?)?,
+        ("+", Text(_), _) | ("+", _, Text(_)) => Text(left.as_text() + &right.as_text()),
+        ("+", _, _) => Number(left.as_number()? + right.as_number()?),
+        ("-", _, _) => Number(left.as_number()? - right.as_number()?),
+        ("*", _, _) => Number(left.as_number()? * right.as_number()?),
+        ("/" | "%", _, _) => {
+            let divisor = right.as_number()?;
+            if divisor == 0.0 {
+                return Err("division by zero".to_string());
+            }
+            let dividend = left.as_number()?;
+            Number(if op == "/" {
+                dividend / divisor
+            } else {
+                dividend % divisor
+            })
+        }
+        _ => return Err(format!("unsupported operator '{op}'")),
+    })
+}
+
+fn arity(name: &str, args: &[FormulaValue], min: usize, max: usize) -> Result<(), String> {
+    if args.len() < min || args.len() > max {
+        return Err(if min == max {
+            format!("{name}() takes {min} argument(s)")
+        } else {
+            format!("{name}() takes {min} to {max} arguments")
+        });
+    }
+    Ok(())
+}
+
+fn call(name: &str, args: Vec<FormulaValue>, ctx: &DateContext) -> Result<FormulaValue, String> {
+    use FormulaValue::*;
+    let text = |index: usize| {
+        args.get(index)
+            .map(FormulaValue::as_text)
+            .unwrap_or_default()
+    };
+    let number = |index: usize| args.get(index).map_or(Ok(0.0), FormulaValue::as_number);
+    let fixed = |count: usize| arity(name, &args, count, count);
+    Ok(match name {
+        "not" => {
+            fixed(1)?;
+            Bool(!args[0].truthy())
+        }
+        "empty" => {
+            fixed(1)?;
+            Bool(args[0].is_empty())
+        }
+        "coalesce" => args
+            .iter()
+            .find(|value| !value.is_empty())
+            .cloned()
+            .unwrap_or(Empty),
+        "length" | "len" => {
+            fixed(1)?;
+            Number(match &args[0] {
+                List(items) => items.len(),
+                other => other.as_text().chars().count(),
+            } as f64)
+        }
+        "lower" => {
+            fixed(1)?;
+            Text(text(0).to_lowercase())
+        }
+        "upper" => {
+            fixed(1)?;
+            Text(text(0).to_uppercase())
+        }
+        "trim" => {
+            fixed(1)?;
+            Text(text(0).trim().to_string())
+        }
+        "text" | "format" => {
+            fixed(1)?;
+            Text(text(0))
+        }
+        "concat" => Text(args.iter().map(FormulaValue::as_text).collect()),
+        "join" => {
+            arity(name, &args, 1, 2)?;
+            let separator = if args.len() == 2 {
+                text(1)
+            } else {
+                ", ".to_string()
+            };
+            Text(args[0].items().join(&separator))
+        }
+        "contains" => {
+            fixed(2)?;
+            Bool(match &args[0] {
+                List(items) => items.contains(&text(1)),
+                other => other.as_text().contains(&text(1)),
+            })
+        }
+        "starts_with" => {
+            fixed(2)?;
+            Bool(text(0).starts_with(&text(1)))
+        }
+        "ends_with" => {
+            fixed(2)?;
+            Bool(text(0).ends_with(&text(1)))
+        }
+        "replace" => {
+            fixed(3)?;
+            Text(text(0).replace(&text(1), &text(2)))
+        }
+        "slice" => {
+            arity(name, &args, 2, 3)?;
+            let chars = text(0).chars().collect::<Vec<_>>();
+            let start = (number(1)?.max(0.0) as usize).min(chars.len());
+            let end = if args.len() == 3 {
+                (number(2)?.max(0.0) as usize).clamp(start, chars.len())
+            } else {
+                chars.len()
+            };
+            Text(chars[start..end].iter().collect())
+        }
+        "number" => {
+            fixed(1)?;
+            if args[0].is_empty() {
+                Empty
+            } else {
+                Number(number(0)?)
+            }
+        }
+        "round" => {
+            arity(name, &args, 1, 2)?;
+            let factor = 10f64.powi(number(1)?.clamp(0.0, 10.0) as i32);
+            Number((number(0)? * factor).round() / factor)
+        }
+        "floor" => {
+            fixed(1)?;
+            Number(number(0)?.floor())
+        }
+        "ceil" => {
+            fixed(1)?;
+            Number(number(0)?.ceil())
+        }
+        "abs" => {
+            fixed(1)?;
+            Number(number(0)?.abs())
+        }
+        "pow" => {
+            fixed(2)?;
+            Number(number(0)?.powf(number(1)?))
+        }
+        "min" | "max" => {
+            let mut best: Option<f64> = None;
+            for value in args.iter().flat_map(|value| match value {
+                List(items) => items.iter().cloned().map(Text).collect(),
+                other => vec![other.clone()],
+            }) {
+                if value.is_empty() {
+                    continue;
+                }
+                let n = value.as_number()?;
+                best = Some(match best {
+                    Some(current) if name == "min" => current.min(n),
+                    Some(current) => current.max(n),
+                    None => n,
+                });
+            }
+            best.map(Number).unwrap_or(Empty)
+        }
+        "now" | "today" => {
+            fixed(0)?;
+            Date(ctx.today)
+        }
+        "date" => {
+            fixed(1)?;
+            args[0].as_date()?.map(Date).unwrap_or(Empty)
+        }
+        "days_between" => {
+            fixed(2)?;
+            match (args[0].as_date()?, args[1].as_date()?) {
+                (Some(a), Some(b)) => Number((a - b).whole_days() as f64),
+                _ => Empty,
+            }
+        }
+        "add_days" => {
+            fixed(2)?;
+            match args[0].as_date()? {
+                Some(date) => add_days(date, number(1)?)?,
+                None => Empty,
+            }
+        }
+        "year" | "month" | "day" | "weekday" => {
+            fixed(1)?;
+            match args[0].as_date()? {
+                Some(date) => Number(match name {
+                    "year" => date.year() as f64,
+                    "month" => u8::from(date.month()) as f64,
+                    "day" => date.day() as f64,
+                    _ => date.weekday().number_from_monday() as f64,
+                }),
+                None => Empty,
+            }
+        }
+        other => return Err(format!("unknown function '{other}'")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{parse_formula, FormulaSet};
+    use crate::database::types::{DatabaseCellValue, DatabaseColumn, DatabaseRow};
+    use crate::dates::DateContext;
+
+    fn formula_column(id: &str, formula: &str) -> DatabaseColumn {
+        DatabaseColumn {
+            id: id.to_string(),
+            column_type: "formula".to_string(),
+            label: id.to_string(),
+            icon: None,
+            width: None,
+            visible: true,
+            property_key: None,
+            property_kind: None,
+            formula: Some(formula.to_string()),
+            relation: None,
+            rollup: None,
+        }
+    }
+
+    fn row() -> DatabaseRow {
+        let mut properties = BTreeMap::new();
+        let text = |value: &str, kind: &str| DatabaseCellValue {
+            kind: kind.to_string(),
+            value_text: Some(value.to_string()),
+            value_bool: None,
+            value_list: Vec::new(),
+        };
+        properties.insert("due".to_string(), text("2025-03-12", "text"));
+        properties.insert("points".to_string(), text("8", "number"));
+        properties.insert(
+            "done".to_string(),
+            DatabaseCellValue {
+                kind: "checkbox".to_string(),
+                value_text: None,
+                value_bool: Some(false),
+                value_list: Vec::new(),
+            },
+        );
+        DatabaseRow {
+            note_path: "Tasks/Launch.md".to_string(),
+            title: "Launch".to_string(),
+            created: "2025-03-01T09:00:00Z".to_string(),
+            updated: "2025-03-04T09:00:00Z".to_string(),
+            preview: String::new(),
+            tags: vec!["#work".to_string()],
+            properties,
+            computed: BTreeMap::new(),
+            relations: BTreeMap::new(),
+            cover: None,
+        }
+    }
+
+    fn evaluate(columns: &[DatabaseColumn]) -> BTreeMap<String, DatabaseCellValue> {
+        let ctx = DateContext::new(Some("2025-03-05"), None).unwrap();
+        let set = FormulaSet::new(columns, &ctx);
+        let mut row = row();
+        set.evaluate_row(&mut row);
+        row.computed
+    }
+
+    fn text_of(cells: &BTreeMap<String, DatabaseCellValue>, id: &str) -> (String, String) {
+        let cell = &cells[id];
+        (
+            cell.kind.clone(),
+            cell.value_text
+                .clone()
+                .or(cell.value_bool.map(|b| b.to_string()))
+                .unwrap_or_default(),
+        )
+    }
+
+    #[test]
+    fn evaluates_arithmetic_strings_dates_and_conditionals() {
+        let cells = evaluate(&[
+            formula_column("left", "days_between(due, now())"),
+            formula_column("score", "points * 2 + 1.5"),
+            formula_column("label", "upper(slice(title, 0, 3)) + \"-\" + left"),
+            formula_column("state", "if(done or left < 0, \"closed\", \"open\")"),
+            formula_column("deadline", "add_days(created, 30)"),
+            formula_column("late", "due < today()"),
+        ]);
+        assert_eq!(text_of(&cells, "left"), ("number".into(), "7".into()));
+        assert_eq!(text_of(&cells, "score"), ("number".into(), "17.5".into()));
+        assert_eq!(text_of(&cells, "label"), ("text".into(), "LAU-7".into()));
+        assert_eq!(text_of(&cells, "state"), ("text".into(), "open".into()));
+        assert_eq!(
+            text_of(&cells, "deadline"),
+            ("date".into(), "2025-03-31".into())
+        );
+        assert_eq!(text_of(&cells, "late"), ("checkbox".into(), "false".into()));
+    }
+
+    #[test]
+    fn reports_errors_per_cell() {
+        let cells = evaluate(&[
+            formula_column("ok", "length(tags)"),
+            formula_column("zero", "points / 0"),
+            formula_column("loop_a", "loop_b + 1"),
+            formula_column("loop_b", "loop_a + 1"),
+            formula_column("bad", "frobnicate(1)"),
+        ]);
+        assert_eq!(text_of(&cells, "ok"), ("number".into(), "1".into()));
+        assert_eq!(
+            text_of(&cells, "zero"),
+            ("error".into(), "division by zero".into())
+        );
+        assert_eq!(cells["loop_a"].kind, "error");
+        assert_eq!(cells["bad"].kind, "error");
+        assert!(parse_formula("1 +").is_err());
+        assert!(parse_formula("\"open").is_err());
+    }
+
+    #[test]
+    fn rejects_huge_day_counts_and_deep_nesting() {
+        let cells = evaluate(&[
+            formula_column("far", "add_days(due, 1000000000000000)"),
+            formula_column("plus", "add_days(due, 0) + 1000000000000000"),
+            formula_column("huge", "add_days(due, 0) - 1 / 0.000000000001"),
+        ]);
+        for id in ["far", "plus", "huge"] {
+            assert_eq!(
+                text_of(&cells, id),
+                ("error".into(), "date out of range".into())
+            );
+        }
+        let nested = format!("{}1{}", "(".repeat(500), ")".repeat(500));
+        assert_eq!(
+            parse_formula(&nested).unwrap_err(),
+            "formula is nested too deeply"
+        );
+        assert!(parse_formula(&format!("{}1", "-".repeat(500))).is_err());
+        assert!(parse_formula(&vec!["1"; 5_000].join(" + ")).is_err());
+        assert!(parse_formula(&format!("{}1{}", "(".repeat(20), ")".repeat(20))).is_ok());
+    }
+}
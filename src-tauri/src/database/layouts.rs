@@ -0,0 +1,463 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use time::Duration;
+
+use crate::dates::{date_from_iso, date_to_iso, DateContext};
+use crate::index::links::normalize_rel_path;
+use crate::notes::attachments::is_image_ext;
+use crate::notes::frontmatter::split_frontmatter;
+use crate::paths;
+
+use super::formula::{FormulaSet, FormulaValue};
+use super::types::{
+    DatabaseCalendarDay, DatabaseCalendarResult, DatabaseColumn, DatabaseConfig, DatabaseGroup,
+    DatabaseGroupAggregate, DatabaseGroupResult, DatabaseRow,
+};
+
+fn find_column<'a>(
+    config: &'a DatabaseConfig,
+    column_id: &str,
+) -> Result<&'a DatabaseColumn, String> {
+    config
+        .columns
+        .iter()
+        .find(|column| column.id == column_id)
+        .ok_or_else(|| format!("unknown database column '{column_id}'"))
+}
+
+/// Number properties, formulas and rollups; only those that produce numbers
+/// end up with aggregates.
+fn is_aggregate_column(column: &DatabaseColumn) -> bool {
+    match column.column_type.as_str() {
+        "property" => column.property_kind.as_deref() == Some("number"),
+        "formula" | "rollup" => true,
+        _ => false,
+    }
+}
+
+/// Groups `rows` by the value of `group_by`. List values put a row in one
+/// group per item; rows without a value share the empty-key group, which
+/// sorts last.
+pub(super) fn group_rows(
+    config: &DatabaseConfig,
+    rows: &[DatabaseRow],
+    group_by: &str,
+    ctx: &DateContext,
+    truncated: bool,
+) -> Result<DatabaseGroupResult, String> {
+    let column = find_column(config, group_by)?;
+    let formulas = FormulaSet::new(&config.columns, ctx);
+    let aggregate_columns = config
+        .columns
+        .iter()
+        .filter(|column| is_aggregate_column(column))
+        .collect::<Vec<_>>();
+
+    let mut groups = Vec::<(FormulaValue, DatabaseGroup, Vec<(f64, u32)>)>::new();
+    for row in rows {
+        let value = formulas
+            .column_value(row, column)
+            .unwrap_or(FormulaValue::Empty);
+        let mut values = match value {
+            FormulaValue::List(items) => items
+                .into_iter()
+                .filter(|item| !item.trim().is_empty())
+                .map(FormulaValue::Text)
+                .collect::<Vec<_>>(),
+            value if value.is_empty() => Vec::new(),
+            value => vec![value],
+        };
+        if values.is_empty() {
+            values.push(FormulaValue::Empty);
+        }
+        let numbers = aggregate_columns
+            .iter()
+            .map(|column| match formulas.column_value(row, column) {
+                Ok(FormulaValue::Number(n)) => Some(n),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut seen = Vec::<String>::new();
+        for value in values {
+            let key = value.as_text();
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key.clone());
+            let index = match groups.iter().position(|(_, group, _)| group.key == key) {
+                Some(index) => index,
+                None => {
+                    let label = if key.is_empty() {
+                        format!("No {}", column.label)
+                    } else {
+                        key.clone()
+                    };
+                    groups.push((
+                        value,
+                        DatabaseGroup {
+                            key,
+                            label,
+                            note_paths: Vec::new(),
+                            count: 0,
+                            aggregates: Vec::new(),
+                        },
+                        vec![(0.0, 0); aggregate_columns.len()],
+                    ));
+                    groups.len() - 1
+                }
+            };
+            let (_, group, totals) = &mut groups[index];
+            group.note_paths.push(row.note_path.clone());
+            group.count += 1;
+            for (total, number) in totals.iter_mut().zip(&numbers) {
+                if let Some(n) = number {
+                    total.0 += n;
+                    total.1 += 1;
+                }
+            }
+        }
+    }
+
+    let numeric = (0..aggregate_columns.len())
+        .map(|index| groups.iter().any(|(_, _, totals)| totals[index].1 > 0))
+        .collect::<Vec<_>>();
+    groups.sort_by(|(a, ..), (b, ..)| match (a.is_empty(), b.is_empty()) {
+        (true, false) => std::cmp::Ordering::Greater,
+        (false, true) => std::cmp::Ordering::Less,
+        _ => a.compare(b),
+    });
+    let groups = groups
+        .into_iter()
+        .map(|(_, mut group, totals)| {
+            group.aggregates = aggregate_columns
+                .iter()
+                .zip(totals)
+                .zip(&numeric)
+                .filter(|(_, numeric)| **numeric)
+                .map(|((column, (sum, count)), _)| DatabaseGroupAggregate {
+                    column_id: column.id.clone(),
+                    sum,
+                    average: (count > 0).then(|| sum / count as f64),
+                    count,
+                })
+                .collect();
+            group
+        })
+        .collect();
+    Ok(DatabaseGroupResult {
+        group_by: group_by.to_string(),
+        groups,
+        total_count: rows.len() as u32,
+        truncated,
+    })
+}
+
+/// Buckets `rows` by the date in `date_column` over the `month` or `week`
+/// containing `anchor`.
+pub(super) fn calendar_rows(
+    config: &DatabaseConfig,
+    rows: &[DatabaseRow],
+    date_column: &str,
+    range: &str,
+    anchor: &str,
+    ctx: &DateContext,
+    truncated: bool,
+) -> Result<DatabaseCalendarResult, String> {
+    let column = find_column(config, date_column)?;
+    let anchor = date_from_iso(anchor).ok_or_else(|| format!("invalid date '{anchor}'"))?;
+    let (start, end) = match range {
+        "month" => {
+            let start = anchor.replace_day(1).map_err(|e| e.to_string())?;
+            let days = anchor.month().length(anchor.year());
+            (start, start + Duration::days(i64::from(days) - 1))
+        }
+        "week" => {
+            let start = ctx.start_of_week(anchor);
+            (start, start + Duration::days(6))
+        }
+        other => return Err(format!("unsupported calendar range '{other}'")),
+    };
+
+    let formulas = FormulaSet::new(&config.columns, ctx);
+    let mut days = BTreeMap::new();
+    let mut day = start;
+    while day <= end {
+        days.insert(day, Vec::new());
+        day += Duration::days(1);
+    }
+    let mut undated_count = 0;
+    for row in rows {
+        let date = formulas
+            .column_value(row, column)
+            .ok()
+            .and_then(|value| value.as_date().ok().flatten());
+        match date {
+            Some(date) => {
+                if let Some(bucket) = days.get_mut(&date) {
+                    bucket.push(row.note_path.clone());
+                }
+            }
+            None => undated_count += 1,
+        }
+    }
+
+    Ok(DatabaseCalendarResult {
+        date_column: date_column.to_string(),
+        range: range.to_string(),
+        start: date_to_iso(start),
+        end: date_to_iso(end),
+        days: days
+            .into_iter()
+            .map(|(date, note_paths)| DatabaseCalendarDay {
+                date: date_to_iso(date),
+                note_paths,
+            })
+            .collect(),
+        undated_count,
+        truncated,
+    })
+}
+
+fn is_image_target(target: &str) -> bool {
+    Path::new(target)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| is_image_ext(&ext.to_lowercase()))
+}
+
+/// Resolves an image reference in `note_path` to a space-relative path, or
+/// keeps remote URLs as they are.
+fn resolve_image(root: &Path, note_path: &str, raw: &str, embed: bool) -> Option<String> {
+    let mut target = raw.trim().trim_start_matches('<');
+    if !embed {
+        target = target.split_whitespace().next().unwrap_or_default();
+    }
+    let target = target.trim_end_matches('>');
+    let target = target
+        .split(['|', '#', '?'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if !is_image_target(target) {
+        return None;
+    }
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return Some(target.to_string());
+    }
+    let note_dir = Path::new(note_path)
+        .parent()
+        .map(|dir| dir.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_default();
+    let relative = |dir: &str| {
+        if dir.is_empty() {
+            normalize_rel_path(target)
+        } else {
+            normalize_rel_path(&format!("{dir}/{target}"))
+        }
+    };
+    if let Some(absolute) = target.strip_prefix('/') {
+        return normalize_rel_path(absolute);
+    }
+    if embed && !target.contains('/') {
+        // Wikilink embeds name a file; look beside the note, then in assets.
+        let exists =
+            |rel: &String| paths::join_under(root, Path::new(rel)).is_ok_and(|abs| abs.is_file());
+        return relative(&note_dir)
+            .filter(exists)
+            .or_else(|| relative("assets").filter(exists))
+            .or_else(|| relative(&note_dir));
+    }
+    if embed {
+        return normalize_rel_path(target);
+    }
+    relative(&note_dir)
+}
+
+/// The first image in a note's body: `![alt](path)` or `![[file.png]]`.
+fn first_image(root: &Path, note_path: &str, markdown: &str) -> Option<String> {
+    let (_, body) = split_frontmatter(markdown);
+    let mut rest = body;
+    while let Some(start) = rest.find("![") {
+        let after = &rest[start + 2..];
+        let found = if let Some(embed) = after.strip_prefix('[') {
+            embed
+                .find("]]")
+                .and_then(|end| resolve_image(root, note_path, &embed[..end], true))
+        } else {
+            after.find("](").and_then(|open| {
+                let target = &after[open + 2..];
+                target
+                    .find(')')
+                    .and_then(|close| resolve_image(root, note_path, &target[..close], false))
+            })
+        };
+        if found.is_some() {
+            return found;
+        }
+        rest = after;
+    }
+    None
+}
+
+/// Sets each row's `cover` to the first image in its note.
+pub(super) fn fill_covers(root: &Path, rows: &mut [DatabaseRow]) {
+    for row in rows {
+        let markdown = paths::join_under(root, Path::new(&row.note_path))
+            .and_then(|abs| std::fs::read_to_string(abs).map_err(|e| e.to_string()));
+        if let Ok(markdown) = markdown {
+            row.cover = first_image(root, &row.note_path, &markdown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::config::starter_database_config;
+    use crate::database::types::DatabaseCellValue;
+
+    fn number_column(id: &str) -> DatabaseColumn {
+        DatabaseColumn {
+            id: format!("property:{id}"),
+            column_type: "property".to_string(),
+            label: id.to_string(),
+            icon: None,
+            width: None,
+            visible: true,
+            property_key: Some(id.to_string()),
+            property_kind: Some("number".to_string()),
+            formula: None,
+            relation: None,
+            rollup: None,
+        }
+    }
+
+    fn row(path: &str, props: &[(&str, &str, &str)]) -> DatabaseRow {
+        DatabaseRow {
+            note_path: path.to_string(),
+            title: path.to_string(),
+            created: String::new(),
+            updated: String::new(),
+            preview: String::new(),
+            tags: Vec::new(),
+            properties: props
+                .iter()
+                .map(|(key, kind, value)| {
+                    (
+                        key.to_string(),
+                        DatabaseCellValue {
+                            kind: kind.to_string(),
+                            value_text: Some(value.to_string()),
+                            value_bool: None,
+                            value_list: Vec::new(),
+                        },
+                    )
+                })
+                .collect(),
+            computed: BTreeMap::new(),
+            relations: BTreeMap::new(),
+            cover: None,
+        }
+    }
+
+    fn config() -> DatabaseConfig {
+        let mut config = starter_database_config("tasks");
+        let mut status = number_column("status");
+        status.property_kind = Some("text".to_string());
+        let mut due = number_column("due");
+        due.property_kind = Some("date".to_string());
+        config.columns = vec![status, number_column("points"), due];
+        config
+    }
+
+    #[test]
+    fn groups_rows_with_counts_and_aggregates() {
+        let rows = vec![
+            row(
+                "a.md",
+                &[("status", "text", "doing"), ("points", "number", "3")],
+            ),
+            row(
+                "b.md",
+                &[("status", "text", "done"), ("points", "number", "5")],
+            ),
+            row(
+                "c.md",
+                &[("status", "text", "doing"), ("points", "number", "4")],
+            ),
+            row("d.md", &[]),
+        ];
+        let ctx = DateContext::default();
+        let result = group_rows(&config(), &rows, "property:status", &ctx, false).unwrap();
+        let keys = result
+            .groups
+            .iter()
+            .map(|group| (group.key.as_str(), group.count))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![("doing", 2), ("done", 1), ("", 1)]);
+        assert_eq!(result.groups[2].label, "No status");
+        let doing = &result.groups[0].aggregates[0];
+        assert_eq!(doing.column_id, "property:points");
+        assert_eq!((doing.sum, doing.average, doing.count), (7.0, Some(3.5), 2));
+        assert_eq!(result.groups[2].aggregates[0].average, None);
+    }
+
+    #[test]
+    fn buckets_rows_by_date_over_a_week() {
+        let rows = vec![
+            row("a.md", &[("due", "date", "2024-05-15")]),
+            row("b.md", &[("due", "date", "2024-05-20T09:00")]),
+            row("c.md", &[("due", "date", "2024-06-01")]),
+            row("d.md", &[]),
+        ];
+        let ctx = DateContext::new(None, Some("monday")).unwrap();
+        let week = calendar_rows(
+            &config(),
+            &rows,
+            "property:due",
+            "week",
+            "2024-05-15",
+            &ctx,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            (week.start.as_str(), week.end.as_str()),
+            ("2024-05-13", "2024-05-19")
+        );
+        assert_eq!(week.days.len(), 7);
+        assert_eq!(week.days[2].note_paths, vec!["a.md"]);
+        assert_eq!(week.undated_count, 1);
+
+        let month = calendar_rows(
+            &config(),
+            &rows,
+            "property:due",
+            "month",
+            "2024-05-15",
+            &ctx,
+            false,
+        )
+        .unwrap();
+        assert_eq!(month.days.len(), 31);
+        assert_eq!(month.days[19].note_paths, vec!["b.md"]);
+    }
+
+    #[test]
+    fn finds_the_first_image_as_cover() {
+        let root = Path::new("/nonexistent");
+        let markdown =
+            "---\ncover: x\n---\n\nSee ![chart](../assets/chart.png \"Chart\") and ![[b.jpg]]";
+        assert_eq!(
+            first_image(root, "notes/a.md", markdown).as_deref(),
+            Some("assets/chart.png")
+        );
+        assert_eq!(
+            first_image(root, "notes/a.md", "![[b.JPG|200]] ![](c.png)").as_deref(),
+            Some("notes/b.JPG")
+        );
+        assert_eq!(first_image(root, "a.md", "![doc](file.pdf)"), None);
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use tauri::{Emitter, State};
+
+use crate::dates::DateContext;
+use crate::index::open_db;
+use crate::space::SpaceState;
+
+use super::config::parse_database_config;
+use super::formula::FormulaSet;
+use super::layouts::fill_covers;
+use super::query::{hydrate_rows_by_paths, matching_note_paths, read_database_markdown};
+use super::relations::resolve_relations;
+use super::types::{DatabaseRow, DatabaseRowsChangedEvent};
+
+const ROWS_CHANGED_EVENT: &str = "database:rows_changed";
+
+/// A database some view has open: how many views hold it, which notes it
+/// matched when last checked and the date context the view loaded it with.
+#[derive(Debug, Default)]
+pub(crate) struct OpenDatabase {
+    views: usize,
+    members: HashSet<String>,
+    ctx: DateContext,
+}
+
+pub(crate) type OpenDatabases = Arc<Mutex<HashMap<String, OpenDatabase>>>;
+
+fn all_members(
+    root: &Path,
+    database_path: &str,
+    ctx: &DateContext,
+) -> Result<HashSet<String>, String> {
+    let config = parse_database_config(&read_database_markdown(root, database_path)?)?;
+    let conn = open_db(root)?;
+    matching_note_paths(&conn, database_path, &config, None, ctx)
+}
+
+/// How one batch of changed notes affects a database's rows.
+#[derive(Debug)]
+struct MemberChanges {
+    members: HashSet<String>,
+    inserted: Vec<String>,
+    updated: Vec<String>,
+    removed: Vec<String>,
+}
+
+impl MemberChanges {
+    fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+fn diff_members(
+    root: &Path,
+    database_path: &str,
+    members: &HashSet<String>,
+    changed: &[(String, bool)],
+    ctx: &DateContext,
+) -> Result<MemberChanges, String> {
+    // An edited database note may have a new source or filters.
+    if changed
+        .iter()
+        .any(|(path, removed)| path == database_path && !removed)
+    {
+        let next = all_members(root, database_path, ctx)?;
+        let inserted = next.difference(members).cloned().collect();
+        let removed = members.difference(&next).cloned().collect();
+        let updated = changed
+            .iter()
+            .filter(|(path, _)| members.contains(path) && next.contains(path))
+            .map(|(path, _)| path.clone())
+            .collect();
+        return Ok(MemberChanges {
+            members: next,
+            inserted,
+            updated,
+            removed,
+        });
+    }
+
+    let present = changed
+        .iter()
+        .filter(|(_, removed)| !removed)
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    let config = parse_database_config(&read_database_markdown(root, database_path)?)?;
+    let conn = open_db(root)?;
+    let matching = matching_note_paths(&conn, database_path, &config, Some(&present), ctx)?;
+
+    let mut next = members.clone();
+    let (mut inserted, mut updated, mut removed) = (Vec::new(), Vec::new(), Vec::new());
+    for (path, _) in changed {
+        let was_member = members.contains(path);
+        match (was_member, matching.contains(path)) {
+            (false, true) => inserted.push(path.clone()),
+            (true, true) => updated.push(path.clone()),
+            (true, false) => removed.push(path.clone()),
+            (false, false) => continue,
+        }
+        if matching.contains(path) {
+            next.insert(path.clone());
+        } else {
+            next.remove(path);
+        }
+    }
+    Ok(MemberChanges {
+        members: next,
+        inserted,
+        updated,
+        removed,
+    })
+}
+
+/// Fills in what `load_database` adds to indexed rows: relations, rollups,
+/// formula values and, for galleries, covers.
+fn complete_rows(
+    root: &Path,
+    database_path: &str,
+    ctx: &DateContext,
+    rows: &mut [DatabaseRow],
+) -> Result<(), String> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+    let config = parse_database_config(&read_database_markdown(root, database_path)?)?;
+    let conn = open_db(root)?;
+    resolve_relations(root, &conn, &config, ctx, rows)?;
+    let formulas = FormulaSet::new(&config.columns, ctx);
+    for row in rows.iter_mut() {
+        formulas.evaluate_row(row);
+    }
+    if config.view.layout == "gallery" {
+        fill_covers(root, rows);
+    }
+    Ok(())
+}
+
+/// Emits `database:rows_changed` for every open database whose rows the
+/// batch of re-indexed (`false`) or removed (`true`) notes touched.
+pub(crate) fn emit_rows_changed(
+    app: &tauri::AppHandle,
+    root: &Path,
+    open_databases: &OpenDatabases,
+    changed: &[(String, bool)],
+) {
+    let snapshot = {
+        let guard = open_databases
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        guard
+            .iter()
+            .map(|(path, open)| (path.clone(), open.members.clone(), open.ctx))
+            .collect::<Vec<_>>()
+    };
+    for (database_path, members, ctx) in snapshot {
+        let Ok(changes) = diff_members(root, &database_path, &members, changed, &ctx) else {
+            continue;
+        };
+        if changes.is_empty() {
+            continue;
+        }
+        let Ok(conn) = open_db(root) else {
+            continue;
+        };
+        let (Ok(mut inserted), Ok(mut updated)) = (
+            hydrate_rows_by_paths(&conn, &changes.inserted),
+            hydrate_rows_by_paths(&conn, &changes.updated),
+        ) else {
+            continue;
+        };
+        if complete_rows(root, &database_path, &ctx, &mut inserted).is_err()
+            || complete_rows(root, &database_path, &ctx, &mut updated).is_err()
+        {
+            continue;
+        }
+        if let Some(open) = open_databases
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get_mut(&database_path)
+        {
+            open.members = changes.members;
+        }
+        let _ = app.emit(
+            ROWS_CHANGED_EVENT,
+            DatabaseRowsChangedEvent {
+                database_path,
+                inserted,
+                updated,
+                removed: changes.removed,
+            },
+        );
+    }
+}
+
+/// Starts pushing row changes for a database to the frontend, resolving
+/// relative dates with the view's `today` and `week_start`. Each call needs a
+/// matching `database_unwatch`.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_watch(
+    state: State<'_, SpaceState>,
+    path: String,
+    today: Option<String>,
+    week_start: Option<String>,
+) -> Result<(), String> {
+    let root = state.current_root()?;
+    let open_databases = state.open_databases();
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
+        let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+        let members = all_members(&root, &path, &ctx)?;
+        let mut guard = open_databases
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let open = guard.entry(path).or_default();
+        open.views += 1;
+        open.members = members;
+        open.ctx = ctx;
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn database_unwatch(state: State<'_, SpaceState>, path: String) {
+    let open_databases = state.open_databases();
+    let mut guard = open_databases
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(open) = guard.get_mut(&path) {
+        open.views = open.views.saturating_sub(1);
+        if open.views == 0 {
+            guard.remove(&path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{index_note, remove_note};
+
+    #[test]
+    fn classifies_changed_notes_against_database_members() {
+        let root = std::env::temp_dir().join(format!("glyph-live-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let database = "Projects.md";
+        let database_markdown = "---\ntitle: Projects\nglyph:\n  kind: database\n  version: 1\n  database:\n    source:\n      kind: property\n      property: type\n      value: project\n    columns:\n      - id: title\n        type: title\n        label: Title\n        visible: true\n      - id: shout\n        type: formula\n        label: Shout\n        visible: true\n        formula: upper(title)\n    new_note:\n      folder: projects\n---\n";
+        std::fs::write(root.join(database), database_markdown).unwrap();
+        index_note(&root, database, database_markdown).unwrap();
+        for (path, markdown) in [
+            ("a.md", "---\ntitle: A\ntype: project\n---\n"),
+            ("b.md", "---\ntitle: B\ntype: project\n---\n"),
+            ("c.md", "---\ntitle: C\n---\n"),
+        ] {
+            index_note(&root, path, markdown).unwrap();
+        }
+        let ctx = DateContext::default();
+        let members = all_members(&root, database, &ctx).unwrap();
+        assert_eq!(
+            members,
+            HashSet::from(["a.md".to_string(), "b.md".to_string()])
+        );
+
+        index_note(&root, "a.md", "---\ntitle: A2\ntype: project\n---\n").unwrap();
+        index_note(&root, "b.md", "---\ntitle: B\n---\n").unwrap();
+        index_note(&root, "c.md", "---\ntitle: C\ntype: project\n---\n").unwrap();
+        let changed = [
+            ("a.md".to_string(), false),
+            ("b.md".to_string(), false),
+            ("c.md".to_string(), false),
+        ];
+        let changes = diff_members(&root, database, &members, &changed, &ctx).unwrap();
+        assert_eq!(changes.inserted, vec!["c.md"]);
+        assert_eq!(changes.updated, vec!["a.md"]);
+        assert_eq!(changes.removed, vec!["b.md"]);
+
+        let conn = open_db(&root).unwrap();
+        let mut updated = hydrate_rows_by_paths(&conn, &changes.updated).unwrap();
+        complete_rows(&root, database, &ctx, &mut updated).unwrap();
+        assert_eq!(
+            updated[0].computed["shout"].value_text.as_deref(),
+            Some("A2")
+        );
+
+        remove_note(&root, "a.md").unwrap();
+        let changes = diff_members(
+            &root,
+            database,
+            &changes.members,
+            &[("a.md".to_string(), true)],
+            &ctx,
+        )
+        .unwrap();
+        assert!(changes.inserted.is_empty() && changes.updated.is_empty());
+        assert_eq!(changes.removed, vec!["a.md"]);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -1,4 +1,11 @@
 pub mod config;
+mod csv;
+mod filters;
+mod formula;
+mod layouts;
+pub mod live;
 pub mod mutations;
 mod query;
+mod relations;
 mod types;
+pub mod views;
@@ -1,32 +1,48 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 use serde_yaml::{Mapping, Number, Value};
 use tauri::State;
 
-use crate::index::{index_note, open_db};
+use crate::crypto;
+use crate::dates::{parse_natural_date, DateContext};
+use crate::index::{index_note, index_notes, open_db, remove_note};
 use crate::io_atomic;
 use crate::notes::frontmatter::{
-    normalize_frontmatter_mapping, now_rfc3339, parse_frontmatter_mapping,
-    render_frontmatter_mapping_yaml, split_frontmatter,
+    normalize_frontmatter_mapping, now_rfc3339, parse_frontmatter_mapping, split_frontmatter,
 };
+use crate::notes::frontmatter_edit::render_frontmatter_lossless;
+use crate::notes::properties::text_to_yaml_value;
+use crate::notes::property_schema::{
+    default_property_value, find_definition, missing_required_properties, read_property_schema,
+    validate_property_value, PropertyDefinition, PropertyError,
+};
+use crate::notes::templates::{render_template, TemplateVars};
 use crate::paths;
 use crate::space::state::{mark_recent_local_change, RecentLocalChanges};
 use crate::space::SpaceState;
 use crate::space_fs::helpers::deny_hidden_rel_path;
+use crate::space_fs::read_write::trash::move_path_to_trash;
 
 use super::config::{parse_database_config, render_database_markdown};
-use super::query::{hydrate_rows_by_paths, load_database, read_database_markdown};
+use super::csv::{parse_csv, plan_import, write_csv};
+use super::formula::FormulaSet;
+use super::layouts::{calendar_rows, group_rows};
+use super::query::{hydrate_rows_by_paths, load_all_rows, load_database, read_database_markdown};
 use super::types::{
-    DatabaseCellValue, DatabaseColumn, DatabaseConfig, DatabaseCreateRowResult, DatabaseRow,
+    DatabaseBulkResult, DatabaseCalendarResult, DatabaseCellValue, DatabaseColumn, DatabaseConfig,
+    DatabaseCreateRowResult, DatabaseCsvExportResult, DatabaseCsvImportResult, DatabaseGroupResult,
+    DatabaseNormalizeResult, DatabaseRow, DatabaseUndoMove, DatabaseUndoNote, DatabaseUndoPayload,
 };
 
 const MAX_ROW_CREATE_COLLISION_INDEX: usize = 1_000;
+const DEFAULT_ARCHIVE_FOLDER: &str = "Archive";
 
 fn key(name: &str) -> Value {
     Value::String(name.to_string())
 }
 
-fn slugify_title(title: &str) -> String {
+pub(crate) fn slugify_title(title: &str) -> String {
     let slug = title
         .trim()
         .chars()
@@ -77,7 +93,7 @@ fn yaml_value_from_cell(
                     .map_err(|_| "invalid number value".to_string())?;
                 Ok(Value::Number(Number::from(float)))
             }
-            "list" | "tags" => Ok(Value::Sequence(
+            "list" | "tags" | "multi_select" => Ok(Value::Sequence(
                 value
                     .value_list
                     .iter()
@@ -87,54 +103,114 @@ fn yaml_value_from_cell(
             "yaml" => Err("yaml columns are read-only".to_string()),
             _ => Ok(Value::String(value.value_text.clone().unwrap_or_default())),
         },
-        "path" | "created" | "updated" => {
+        "path" | "created" | "updated" | "formula" | "relation" | "rollup" => {
             Err(format!("{} columns are read-only", column.column_type))
         }
         other => Err(format!("unsupported column type '{other}'")),
     }
 }
 
-fn write_markdown_note(
+pub(crate) fn note_abs_path(root: &Path, rel_path: &str) -> Result<PathBuf, String> {
+    let rel = PathBuf::from(rel_path);
+    deny_hidden_rel_path(&rel)?;
+    paths::join_under(root, &rel)
+}
+
+pub(crate) fn read_note(root: &Path, rel_path: &str) -> Result<String, String> {
+    let bytes = std::fs::read(note_abs_path(root, rel_path)?).map_err(|e| e.to_string())?;
+    crypto::open_text(root, &bytes)
+}
+
+/// A note's markdown and the undo entry that puts it back as stored.
+fn read_note_for_undo(root: &Path, rel_path: &str) -> Result<(String, DatabaseUndoNote), String> {
+    let bytes = std::fs::read(note_abs_path(root, rel_path)?).map_err(|e| e.to_string())?;
+    let markdown = crypto::open_text(root, &bytes)?;
+    let encrypted = crypto::is_encrypted(&bytes);
+    let stored = if encrypted {
+        String::from_utf8(bytes).map_err(|e| e.to_string())?
+    } else {
+        markdown.clone()
+    };
+    Ok((
+        markdown,
+        DatabaseUndoNote {
+            note_path: rel_path.to_string(),
+            markdown: stored,
+            encrypted,
+        },
+    ))
+}
+
+/// Writes the note without indexing it, for callers that index a batch.
+/// `source_encrypted` keeps content that came from an encrypted note
+/// encrypted wherever it is written.
+fn write_note_file(
     root: &Path,
     recent_local_changes: &RecentLocalChanges,
     rel_path: &str,
     markdown: &str,
+    source_encrypted: bool,
 ) -> Result<(), String> {
-    let rel = PathBuf::from(rel_path);
-    deny_hidden_rel_path(&rel)?;
-    let abs = paths::join_under(root, &rel)?;
+    let abs = note_abs_path(root, rel_path)?;
+    let bytes = crypto::seal_text(
+        root,
+        rel_path,
+        std::fs::read(&abs).ok().as_deref(),
+        source_encrypted,
+        markdown,
+    )?;
     if let Some(parent) = abs.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
     mark_recent_local_change(recent_local_changes, rel_path);
-    io_atomic::write_atomic(&abs, markdown.as_bytes()).map_err(|e| e.to_string())?;
+    io_atomic::write_atomic(&abs, &bytes).map_err(|e| e.to_string())
+}
+
+pub(crate) fn write_markdown_note(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    rel_path: &str,
+    markdown: &str,
+) -> Result<(), String> {
+    write_note_file(root, recent_local_changes, rel_path, markdown, false)?;
     index_note(root, rel_path, markdown)?;
     Ok(())
 }
 
 fn note_exists(root: &Path, rel_path: &str) -> Result<bool, String> {
-    let rel = PathBuf::from(rel_path);
-    deny_hidden_rel_path(&rel)?;
-    let abs = paths::join_under(root, &rel)?;
-    Ok(abs.exists())
+    Ok(note_abs_path(root, rel_path)?.exists())
 }
 
 fn render_note_markdown(path: &str, markdown: &str, mapping: Mapping) -> Result<String, String> {
-    let (_yaml, body) = split_frontmatter(markdown);
+    let (yaml, body) = split_frontmatter(markdown);
     let normalized = normalize_frontmatter_mapping(mapping, path, None, None);
-    let rendered_yaml = render_frontmatter_mapping_yaml(&normalized)?;
+    let rendered_yaml = render_frontmatter_lossless(yaml, &normalized)?;
     Ok(format!(
         "---\n{rendered_yaml}---\n\n{}",
         body.trim_start_matches('\n')
     ))
 }
 
+/// Property definitions declared by the database at `database_path`, if any,
+/// and by the space.
+fn property_definitions(
+    root: &Path,
+    database_path: Option<&str>,
+) -> Result<(Vec<PropertyDefinition>, Vec<PropertyDefinition>), String> {
+    let scoped = match database_path {
+        Some(path) => parse_database_config(&read_database_markdown(root, path)?)?.properties,
+        None => Vec::new(),
+    };
+    Ok((scoped, read_property_schema(root).properties))
+}
+
 fn apply_cell_update_to_markdown(
     note_path: &str,
     markdown: &str,
     column: &DatabaseColumn,
     value: &DatabaseCellValue,
-) -> Result<String, String> {
+    definition: Option<&PropertyDefinition>,
+) -> Result<String, PropertyError> {
     let (yaml, _body) = split_frontmatter(markdown);
     let mut mapping = parse_frontmatter_mapping(yaml)?;
 
@@ -150,25 +226,163 @@ fn apply_cell_update_to_markdown(
                 .property_key
                 .clone()
                 .ok_or_else(|| "property column is missing property_key".to_string())?;
-            mapping.insert(key(&property_key), yaml_value_from_cell(column, value)?);
+            let mut next = yaml_value_from_cell(column, value)?;
+            if let Some(definition) = definition {
+                next = validate_property_value(definition, &next)
+                    .map_err(|error| error.at(note_path))?;
+            }
+            mapping.insert(key(&property_key), next);
         }
-        "path" | "created" | "updated" => {
-            return Err(format!("{} columns are read-only", column.column_type))
+        "path" | "created" | "updated" | "formula" | "relation" | "rollup" => {
+            return Err(format!("{} columns are read-only", column.column_type).into())
         }
-        other => return Err(format!("unsupported column type '{other}'")),
+        other => return Err(format!("unsupported column type '{other}'").into()),
     }
 
-    render_note_markdown(note_path, markdown, mapping)
+    Ok(render_note_markdown(note_path, markdown, mapping)?)
 }
 
-fn create_new_row_markdown(note_path: &str, title: &str) -> Result<String, String> {
+/// The definition governing a property column, if it has one.
+fn column_definition<'a>(
+    definitions: &'a (Vec<PropertyDefinition>, Vec<PropertyDefinition>),
+    column: &DatabaseColumn,
+) -> Option<&'a PropertyDefinition> {
+    let property_key = column.property_key.as_deref()?;
+    if column.column_type != "property" {
+        return None;
+    }
+    find_definition(&definitions.0, &definitions.1, property_key)
+}
+
+fn new_row_mapping(title: &str) -> Mapping {
     let now = now_rfc3339();
     let mut mapping = Mapping::new();
     mapping.insert(key("title"), Value::String(title.to_string()));
     mapping.insert(key("created"), Value::String(now.clone()));
     mapping.insert(key("updated"), Value::String(now));
     mapping.insert(key("tags"), Value::Sequence(Vec::new()));
-    render_note_markdown(note_path, "", mapping)
+    mapping
+}
+
+fn create_new_row_markdown(
+    note_path: &str,
+    title: &str,
+    defaults: &[(String, Value)],
+    template: Option<(Mapping, String)>,
+) -> Result<String, String> {
+    let mut mapping = new_row_mapping(title);
+    for (property_key, value) in defaults {
+        mapping.insert(key(property_key), value.clone());
+    }
+    let mut body = String::new();
+    if let Some((template_mapping, template_body)) = template {
+        mapping.extend(template_mapping);
+        body = template_body;
+    }
+    render_note_markdown(note_path, &body, mapping)
+}
+
+/// The database's row template rendered for a new row titled `title`.
+fn row_template(
+    root: &Path,
+    database_path: &str,
+    database_markdown: &str,
+    config: &DatabaseConfig,
+    title: &str,
+    ctx: &DateContext,
+) -> Result<Option<(Mapping, String)>, String> {
+    let Some(template_path) = config
+        .new_note
+        .template
+        .as_deref()
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+    else {
+        return Ok(None);
+    };
+    let markdown = read_note(root, template_path)
+        .map_err(|e| format!("could not read row template '{template_path}': {e}"))?;
+    let database = parse_frontmatter_mapping(split_frontmatter(database_markdown).0)?
+        .get(key("title"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| {
+            Path::new(database_path)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_default();
+    let vars = TemplateVars::new(ctx.today.midnight())
+        .with("title", title.to_string())
+        .with("database", database);
+    render_template(&markdown, &vars).map(Some)
+}
+
+/// Default values for new rows: every database definition with a default, and
+/// space definitions with a default for keys the database has a column for.
+fn row_defaults(
+    config: &DatabaseConfig,
+    space: &[PropertyDefinition],
+) -> Result<Vec<(String, Value)>, PropertyError> {
+    let columns = config
+        .columns
+        .iter()
+        .filter_map(|column| column.property_key.as_deref())
+        .collect::<Vec<_>>();
+    let mut defaults = Vec::<(String, Value)>::new();
+    let space = space.iter().filter(|definition| {
+        columns
+            .iter()
+            .any(|column| column.eq_ignore_ascii_case(definition.key.trim()))
+    });
+    for definition in config.properties.iter().chain(space) {
+        let property_key = definition.key.trim();
+        if defaults
+            .iter()
+            .any(|(existing, _)| existing.eq_ignore_ascii_case(property_key))
+        {
+            continue;
+        }
+        if let Some(value) = default_property_value(definition)? {
+            defaults.push((property_key.to_string(), value));
+        }
+    }
+    Ok(defaults)
+}
+
+/// The first free `<folder>/<slug>.md`, numbering the slug on collisions.
+fn available_row_path(root: &Path, folder: &str, title: &str) -> Result<String, String> {
+    let slug = slugify_title(title);
+    let mut candidate = if folder.is_empty() {
+        format!("{slug}.md")
+    } else {
+        format!("{folder}/{slug}.md")
+    };
+    let mut index = 2;
+    while note_exists(root, &candidate)? {
+        if index > MAX_ROW_CREATE_COLLISION_INDEX {
+            return Err(format!(
+                "reached note name collision limit while creating database row for slug '{slug}' in folder '{folder}' (last candidate: '{candidate}', next index: {index})"
+            ));
+        }
+        candidate = if folder.is_empty() {
+            format!("{slug} {index}.md")
+        } else {
+            format!("{folder}/{slug} {index}.md")
+        };
+        index += 1;
+    }
+    Ok(candidate)
+}
+
+fn default_row_title(config: &DatabaseConfig) -> String {
+    let prefix = config.new_note.title_prefix.trim();
+    if prefix.is_empty() {
+        "Untitled".to_string()
+    } else {
+        prefix.to_string()
+    }
 }
 
 fn row_by_path(root: &Path, note_path: &str) -> Result<DatabaseRow, String> {
@@ -206,11 +420,92 @@ pub async fn database_load(
     state: State<'_, SpaceState>,
     path: String,
     limit: Option<u32>,
+    cursor: Option<String>,
+    view_id: Option<String>,
+    today: Option<String>,
+    week_start: Option<String>,
 ) -> Result<super::types::DatabaseLoadResult, String> {
     let root = state.current_root()?;
-    tauri::async_runtime::spawn_blocking(move || load_database(&root, &path, limit))
-        .await
-        .map_err(|e| e.to_string())?
+    tauri::async_runtime::spawn_blocking(move || {
+        let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+        load_database(
+            &root,
+            &path,
+            limit,
+            cursor.as_deref(),
+            view_id.as_deref(),
+            &ctx,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Groups every matching row by `group_by` (default: the board column).
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_group(
+    state: State<'_, SpaceState>,
+    path: String,
+    group_by: Option<String>,
+    view_id: Option<String>,
+    today: Option<String>,
+    week_start: Option<String>,
+) -> Result<DatabaseGroupResult, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseGroupResult, String> {
+        let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+        let (config, rows, truncated) = load_all_rows(&root, &path, view_id.as_deref(), &ctx)?;
+        let group_by = group_by
+            .or_else(|| config.view.board_group_by.clone())
+            .filter(|column_id| !column_id.trim().is_empty())
+            .ok_or_else(|| "database has no group column".to_string())?;
+        group_rows(&config, &rows, &group_by, &ctx, truncated)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Buckets every matching row by date over the month or week around
+/// `anchor` (default: today).
+#[allow(clippy::too_many_arguments)]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_calendar(
+    state: State<'_, SpaceState>,
+    path: String,
+    date_column: Option<String>,
+    range: Option<String>,
+    anchor: Option<String>,
+    today: Option<String>,
+    week_start: Option<String>,
+    view_id: Option<String>,
+) -> Result<DatabaseCalendarResult, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseCalendarResult, String> {
+        let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+        let (config, rows, truncated) = load_all_rows(&root, &path, view_id.as_deref(), &ctx)?;
+        let date_column = date_column
+            .or_else(|| config.view.calendar_date_column.clone())
+            .filter(|column_id| !column_id.trim().is_empty())
+            .ok_or_else(|| "database has no calendar date column".to_string())?;
+        let range = range
+            .or_else(|| config.view.calendar_range.clone())
+            .unwrap_or_else(|| "month".to_string());
+        let anchor = match anchor.as_deref().map(str::trim).filter(|a| !a.is_empty()) {
+            Some(anchor) => parse_natural_date(anchor, &ctx)?,
+            None => ctx.today_iso(),
+        };
+        calendar_rows(
+            &config,
+            &rows,
+            &date_column,
+            &range,
+            &anchor,
+            &ctx,
+            truncated,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command(rename_all = "snake_case")]
@@ -237,17 +532,29 @@ pub async fn database_update_cell(
     note_path: String,
     column: DatabaseColumn,
     value: DatabaseCellValue,
-) -> Result<DatabaseRow, String> {
+    database_path: Option<String>,
+) -> Result<DatabaseRow, PropertyError> {
     let root = state.current_root()?;
     let recent_local_changes = state.recent_local_changes();
-    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseRow, String> {
-        let rel = PathBuf::from(&note_path);
-        deny_hidden_rel_path(&rel)?;
-        let abs = paths::join_under(&root, &rel)?;
-        let markdown = std::fs::read_to_string(&abs).map_err(|e| e.to_string())?;
-        let next = apply_cell_update_to_markdown(&note_path, &markdown, &column, &value)?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseRow, PropertyError> {
+        let definitions = property_definitions(&root, database_path.as_deref())?;
+        let definition = column_definition(&definitions, &column);
+        let markdown = read_note(&root, &note_path)?;
+        let next =
+            apply_cell_update_to_markdown(&note_path, &markdown, &column, &value, definition)?;
+        let mapping = parse_frontmatter_mapping(split_frontmatter(&next).0)?;
+        if let Some(error) = missing_required_properties(&definitions.0, &definitions.1, &mapping)
+            .into_iter()
+            .next()
+        {
+            return Err(error.at(&note_path));
+        }
         write_markdown_note(&root, &recent_local_changes, &note_path, &next)?;
         let row = row_by_path(&root, &note_path)?;
+        // Validated values may have been canonicalized, so keep the indexed row.
+        if definition.is_some() {
+            return Ok(row);
+        }
         Ok(apply_cell_value_to_row(row, &column, &value))
     })
     .await
@@ -257,9 +564,15 @@ pub async fn database_update_cell(
 #[cfg(test)]
 mod tests {
     use super::{
-        apply_cell_update_to_markdown, create_new_row_markdown, DatabaseCellValue, DatabaseColumn,
+        apply_cell_update_to_markdown, apply_undo, bulk_update_cells, create_new_row_markdown,
+        delete_rows, duplicate_rows, open_db, read_note, write_markdown_note, DatabaseCellValue,
+        DatabaseColumn,
     };
+    use crate::crypto;
     use crate::notes::frontmatter::{parse_frontmatter_mapping, split_frontmatter};
+    use crate::notes::property_schema::{PropertyDefinition, PropertyOption};
+    use crate::notes::templates::{render_template, TemplateVars};
+    use crate::space::state::RecentLocalChanges;
     use serde_yaml::Value;
 
     #[test]
@@ -274,6 +587,9 @@ mod tests {
             visible: true,
             property_key: Some("status".to_string()),
             property_kind: Some("text".to_string()),
+            formula: None,
+            relation: None,
+            rollup: None,
         };
         let updated = apply_cell_update_to_markdown(
             "Projects/Task.md",
@@ -285,10 +601,48 @@ mod tests {
                 value_bool: None,
                 value_list: Vec::new(),
             },
+            None,
         )
         .expect("property should be inserted");
 
         assert!(updated.contains("status: In Progress"));
+
+        let definition = PropertyDefinition {
+            key: "Status".to_string(),
+            kind: "select".to_string(),
+            options: vec![PropertyOption {
+                value: "Done".to_string(),
+                color: Some("green".to_string()),
+            }],
+            required: false,
+            number_format: None,
+            default: None,
+        };
+        let cell = |text: &str| DatabaseCellValue {
+            kind: "text".to_string(),
+            value_text: Some(text.to_string()),
+            value_bool: None,
+            value_list: Vec::new(),
+        };
+        let canonical = apply_cell_update_to_markdown(
+            "Projects/Task.md",
+            markdown,
+            &column,
+            &cell("done"),
+            Some(&definition),
+        )
+        .expect("option should match case-insensitively");
+        assert!(canonical.contains("status: Done"));
+        let error = apply_cell_update_to_markdown(
+            "Projects/Task.md",
+            markdown,
+            &column,
+            &cell("Later"),
+            Some(&definition),
+        )
+        .expect_err("unknown option should be rejected");
+        assert_eq!(error.code, "invalid_option");
+        assert_eq!(error.note_path.as_deref(), Some("Projects/Task.md"));
     }
 
     #[test]
@@ -303,6 +657,9 @@ mod tests {
             visible: true,
             property_key: Some("project_priority".to_string()),
             property_kind: Some("text".to_string()),
+            formula: None,
+            relation: None,
+            rollup: None,
         };
         let updated = apply_cell_update_to_markdown(
             "Projects/Task.md",
@@ -314,6 +671,7 @@ mod tests {
                 value_bool: None,
                 value_list: Vec::new(),
             },
+            None,
         )
         .expect("frontmatter should be created");
 
@@ -323,8 +681,9 @@ mod tests {
 
     #[test]
     fn quotes_titles_when_creating_new_rows() {
-        let markdown = create_new_row_markdown("Projects/Inbox Today.md", "Inbox: Today #1")
-            .expect("new row markdown should render");
+        let markdown =
+            create_new_row_markdown("Projects/Inbox Today.md", "Inbox: Today #1", &[], None)
+                .expect("new row markdown should render");
         let mapping = parse_frontmatter_mapping(split_frontmatter(&markdown).0)
             .expect("frontmatter should stay valid yaml");
 
@@ -332,6 +691,148 @@ mod tests {
             mapping.get(Value::String("title".to_string())),
             Some(&Value::String("Inbox: Today #1".to_string()))
         );
+
+        let template = render_template(
+            "---\ntitle: Template\ntags: [meeting]\n---\n\n# {{title}}\n\n## Agenda\n",
+            &TemplateVars::new(time::OffsetDateTime::UNIX_EPOCH.date().midnight())
+                .with("title", "Inbox: Today #1".to_string()),
+        )
+        .unwrap();
+        let markdown = create_new_row_markdown(
+            "Projects/Inbox Today.md",
+            "Inbox: Today #1",
+            &[],
+            Some(template),
+        )
+        .expect("templated row should render");
+        assert!(markdown.contains("title: 'Inbox: Today #1'"));
+        assert!(markdown.contains("- meeting"));
+        assert!(markdown.ends_with("\n\n# Inbox: Today #1\n\n## Agenda\n"));
+    }
+
+    #[test]
+    fn bulk_operations_round_trip_through_undo() {
+        let root = std::env::temp_dir().join(format!("glyph-bulk-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let recent = RecentLocalChanges::default();
+        let paths = vec!["tasks/a.md".to_string(), "tasks/b.md".to_string()];
+        for path in &paths {
+            write_markdown_note(&root, &recent, path, "---\ntitle: Task\n---\n\nBody\n").unwrap();
+        }
+        let status_count = |value: &str| -> i64 {
+            open_db(&root)
+                .unwrap()
+                .query_row(
+                    "SELECT COUNT(*) FROM note_properties WHERE key = 'status' AND value_text = ?",
+                    [value],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+
+        let column = DatabaseColumn {
+            id: "property:status".to_string(),
+            column_type: "property".to_string(),
+            label: "Status".to_string(),
+            icon: None,
+            width: None,
+            visible: true,
+            property_key: Some("status".to_string()),
+            property_kind: Some("text".to_string()),
+            formula: None,
+            relation: None,
+            rollup: None,
+        };
+        let value = DatabaseCellValue {
+            kind: "text".to_string(),
+            value_text: Some("Done".to_string()),
+            value_bool: None,
+            value_list: Vec::new(),
+        };
+        let outcome = bulk_update_cells(&root, &recent, &paths, &column, &value, None).unwrap();
+        assert_eq!(status_count("Done"), 2);
+        apply_undo(&root, &recent, &outcome.undo).unwrap();
+        assert_eq!(status_count("Done"), 0);
+        assert_eq!(
+            read_note(&root, "tasks/a.md").unwrap(),
+            "---\ntitle: Task\n---\n\nBody\n"
+        );
+
+        let copies = duplicate_rows(&root, &recent, &paths);
+        assert!(copies.error.is_none());
+        assert_eq!(
+            copies.note_paths,
+            vec!["tasks/Task copy.md", "tasks/Task copy 2.md"]
+        );
+
+        let archived = delete_rows(&root, &recent, &paths[..1], "archive", "Archive").unwrap();
+        assert_eq!(archived.note_paths, vec!["Archive/tasks/a.md"]);
+        assert!(!root.join("tasks/a.md").exists());
+        apply_undo(&root, &recent, &archived.undo).unwrap();
+        assert!(root.join("tasks/a.md").exists());
+        assert!(!root.join("Archive/tasks/a.md").exists());
+        let indexed: i64 = open_db(&root)
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM notes WHERE id = 'tasks/a.md'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, 1);
+
+        // A note that cannot be archived stops the batch; the undo still
+        // covers the notes moved before it.
+        write_markdown_note(&root, &recent, "other/c.md", "---\ntitle: C\n---\n").unwrap();
+        std::fs::create_dir_all(root.join("Archive")).unwrap();
+        std::fs::write(root.join("Archive/other"), "").unwrap();
+        let batch = vec!["tasks/a.md".to_string(), "other/c.md".to_string()];
+        let partial = delete_rows(&root, &recent, &batch, "archive", "Archive").unwrap();
+        assert!(partial.error.is_some());
+        assert_eq!(partial.note_paths, vec!["Archive/tasks/a.md"]);
+        assert!(root.join("other/c.md").exists());
+        apply_undo(&root, &recent, &partial.undo).unwrap();
+        assert!(root.join("tasks/a.md").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn undoing_a_trashed_encrypted_note_keeps_it_encrypted() {
+        let root = std::env::temp_dir().join(format!("glyph-bulk-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let recent = RecentLocalChanges::default();
+        let path = "plan.md".to_string();
+        write_markdown_note(&root, &recent, &path, "---\ntitle: Plan\n---\n\nsecret\n").unwrap();
+        crypto::unlock(&root, "hunter2").unwrap();
+        crypto::encrypt_path(&root, &recent, &path).unwrap();
+
+        let trashed = delete_rows(
+            &root,
+            &recent,
+            std::slice::from_ref(&path),
+            "trash",
+            "Archive",
+        )
+        .unwrap();
+        assert!(trashed.error.is_none());
+        assert!(trashed.undo.restore[0].encrypted);
+        let payload = serde_json::to_string(&trashed.undo).unwrap();
+        assert!(!payload.contains("secret"));
+
+        apply_undo(&root, &recent, &trashed.undo).unwrap();
+        let bytes = std::fs::read(root.join(&path)).unwrap();
+        assert!(crypto::is_encrypted(&bytes));
+        assert_eq!(
+            read_note(&root, &path).unwrap(),
+            "---\ntitle: Plan\n---\n\nsecret\n"
+        );
+
+        let copies = duplicate_rows(&root, &recent, &[path]);
+        let copy = std::fs::read(root.join(&copies.note_paths[0])).unwrap();
+        assert!(crypto::is_encrypted(&copy));
+
+        let _ = std::fs::remove_dir_all(&root);
     }
 }
 
@@ -340,6 +841,7 @@ pub async fn database_create_row(
     state: State<'_, SpaceState>,
     database_path: String,
     title: Option<String>,
+    today: Option<String>,
 ) -> Result<DatabaseCreateRowResult, String> {
     let root = state.current_root()?;
     let recent_local_changes = state.recent_local_changes();
@@ -350,41 +852,681 @@ pub async fn database_create_row(
         let title = title
             .map(|value| value.trim().to_string())
             .filter(|value| !value.is_empty())
-            .unwrap_or_else(|| {
-                let prefix = config.new_note.title_prefix.trim();
-                if prefix.is_empty() {
-                    "Untitled".to_string()
-                } else {
-                    prefix.to_string()
+            .unwrap_or_else(|| default_row_title(&config));
+        let candidate = available_row_path(&root, &folder, &title)?;
+        let defaults = row_defaults(&config, &read_property_schema(&root).properties)
+            .map_err(|error| error.message)?;
+        let ctx = DateContext::new(today.as_deref(), None)?;
+        let template = row_template(
+            &root,
+            &database_path,
+            &database_markdown,
+            &config,
+            &title,
+            &ctx,
+        )?;
+        let next = create_new_row_markdown(&candidate, &title, &defaults, template)?;
+        write_markdown_note(&root, &recent_local_changes, &candidate, &next)?;
+        let row = row_by_path(&root, &candidate)?;
+        Ok(DatabaseCreateRowResult {
+            note_path: candidate,
+            row,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Creates one note per CSV row in the database's new-note folder and adds a
+/// typed property column for every CSV column the config does not have yet.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_import_csv(
+    state: State<'_, SpaceState>,
+    database_path: String,
+    csv_path: String,
+    title_column: Option<String>,
+) -> Result<DatabaseCsvImportResult, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseCsvImportResult, String> {
+        let bytes = std::fs::read(&csv_path).map_err(|e| e.to_string())?;
+        let text =
+            String::from_utf8(bytes).map_err(|_| "CSV file is not valid UTF-8".to_string())?;
+        let records = parse_csv(&text)?;
+        let plan = plan_import(&records, title_column.as_deref())?;
+
+        let database_markdown = read_database_markdown(&root, &database_path)?;
+        let mut config = parse_database_config(&database_markdown)?;
+        let folder = config.new_note.folder.trim_matches('/').to_string();
+
+        let mut note_paths = Vec::new();
+        let written = (|| -> Result<(), String> {
+            for record in records.iter().skip(1) {
+                let cell = |index: usize| record.get(index).map(String::as_str).unwrap_or("");
+                let title = Some(cell(plan.title_index).trim())
+                    .filter(|title| !title.is_empty())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| default_row_title(&config));
+                let mut mapping = new_row_mapping(&title);
+                if let Some(index) = plan.tags_index {
+                    mapping.insert(
+                        key("tags"),
+                        text_to_yaml_value("tags", "tags", cell(index))?,
+                    );
                 }
+                for property in &plan.properties {
+                    if let Some(value) = property.value(cell(property.index))? {
+                        mapping.insert(key(&property.key), value);
+                    }
+                }
+                let note_path = available_row_path(&root, &folder, &title)?;
+                let markdown = render_note_markdown(&note_path, "", mapping)?;
+                write_markdown_note(&root, &recent_local_changes, &note_path, &markdown)?;
+                note_paths.push(note_path);
+            }
+            Ok(())
+        })();
+        if let Err(error) = written {
+            // Leave no half-imported rows behind.
+            let undo = DatabaseUndoPayload {
+                remove: note_paths,
+                ..DatabaseUndoPayload::default()
+            };
+            return Err(match apply_undo(&root, &recent_local_changes, &undo) {
+                Ok(_) => error,
+                Err(rollback) => format!("{error}; imported rows were kept: {rollback}"),
             });
-        let slug = slugify_title(&title);
-        let mut candidate = if folder.is_empty() {
-            format!("{slug}.md")
-        } else {
-            format!("{folder}/{slug}.md")
+        }
+
+        let mut columns_added = Vec::new();
+        for property in &plan.properties {
+            let column = property.column();
+            let exists = config.columns.iter().any(|existing| {
+                existing.id == column.id || existing.property_key == column.property_key
+            });
+            if !exists {
+                columns_added.push(column.id.clone());
+                config.columns.push(column);
+            }
+        }
+        if !columns_added.is_empty() {
+            let next = render_database_markdown(&database_path, &database_markdown, &config)?;
+            write_markdown_note(&root, &recent_local_changes, &database_path, &next)?;
+        }
+
+        Ok(DatabaseCsvImportResult {
+            note_paths,
+            columns_added,
+            config,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Writes the database's filtered and sorted rows, with its visible columns,
+/// to `output_path` as CSV.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_export_csv(
+    state: State<'_, SpaceState>,
+    path: String,
+    output_path: String,
+    bom: Option<bool>,
+    view_id: Option<String>,
+    today: Option<String>,
+    week_start: Option<String>,
+) -> Result<DatabaseCsvExportResult, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseCsvExportResult, String> {
+        let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+        let (config, rows, truncated) = load_all_rows(&root, &path, view_id.as_deref(), &ctx)?;
+        let formulas = FormulaSet::new(&config.columns, &ctx);
+        let columns = config
+            .columns
+            .iter()
+            .filter(|column| column.visible)
+            .collect::<Vec<_>>();
+
+        let mut records = vec![columns
+            .iter()
+            .map(|column| column.label.clone())
+            .collect::<Vec<_>>()];
+        for row in &rows {
+            records.push(
+                columns
+                    .iter()
+                    .map(|column| match column.column_type.as_str() {
+                        "path" => row.note_path.clone(),
+                        _ => formulas
+                            .column_value(row, column)
+                            .map(|value| value.as_text())
+                            .unwrap_or_default(),
+                    })
+                    .collect(),
+            );
+        }
+        let csv = write_csv(&records, bom.unwrap_or(false));
+        io_atomic::write_atomic(Path::new(&output_path), csv.as_bytes())
+            .map_err(|e| e.to_string())?;
+        Ok(DatabaseCsvExportResult {
+            output_path,
+            row_count: rows.len() as u32,
+            truncated,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Drops repeated paths, keeping the first occurrence.
+fn unique_note_paths(note_paths: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    note_paths
+        .into_iter()
+        .filter(|path| seen.insert(path.clone()))
+        .collect()
+}
+
+/// The first free path like `rel_path`, numbering the file stem on collisions.
+pub(crate) fn available_note_path(root: &Path, rel_path: &str) -> Result<String, String> {
+    if !note_exists(root, rel_path)? {
+        return Ok(rel_path.to_string());
+    }
+    let path = Path::new(rel_path);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("Untitled");
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| format!(".{ext}"))
+        .unwrap_or_default();
+    let parent = path
+        .parent()
+        .and_then(|parent| parent.to_str())
+        .filter(|parent| !parent.is_empty());
+    for index in 2..=MAX_ROW_CREATE_COLLISION_INDEX {
+        let name = format!("{stem} {index}{ext}");
+        let candidate = match parent {
+            Some(parent) => format!("{parent}/{name}"),
+            None => name,
         };
-        let mut index = 2;
-        while note_exists(&root, &candidate)? {
-            if index > MAX_ROW_CREATE_COLLISION_INDEX {
-                return Err(format!(
-                    "reached note name collision limit while creating database row for slug '{slug}' in folder '{folder}' (last candidate: '{candidate}', next index: {index})"
-                ));
+        if !note_exists(root, &candidate)? {
+            return Ok(candidate);
+        }
+    }
+    Err(format!(
+        "reached note name collision limit for '{rel_path}'"
+    ))
+}
+
+fn move_note(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    from: &str,
+    to: &str,
+) -> Result<(), String> {
+    let from_abs = note_abs_path(root, from)?;
+    let to_abs = note_abs_path(root, to)?;
+    if let Some(parent) = to_abs.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    mark_recent_local_change(recent_local_changes, from);
+    mark_recent_local_change(recent_local_changes, to);
+    std::fs::rename(&from_abs, &to_abs).map_err(|e| e.to_string())?;
+    remove_note(root, from)
+}
+
+/// Writes every note, then indexes the ones that made it to disk in a single
+/// pass even when a later write fails. Notes listed in `sealed` came from
+/// encrypted files and are written encrypted. A failure comes with the
+/// number of notes written before it.
+fn write_and_index_notes(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    notes: &[(String, String)],
+    sealed: &[String],
+) -> Result<(), (usize, String)> {
+    let mut written = 0;
+    let mut failure = None;
+    for (note_path, markdown) in notes {
+        let source_encrypted = sealed.contains(note_path);
+        if let Err(error) = write_note_file(
+            root,
+            recent_local_changes,
+            note_path,
+            markdown,
+            source_encrypted,
+        ) {
+            failure = Some(error);
+            break;
+        }
+        written += 1;
+    }
+    index_notes(root, &notes[..written]).map_err(|error| (written, error))?;
+    failure.map_or(Ok(()), |error| Err((written, error)))
+}
+
+/// What a bulk operation did before it finished or stopped at `error`: the
+/// notes it wrote, created or moved to, and the undo for exactly those.
+#[derive(Default)]
+struct BulkOutcome {
+    note_paths: Vec<String>,
+    undo: DatabaseUndoPayload,
+    error: Option<String>,
+}
+
+fn bulk_update_cells(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    note_paths: &[String],
+    column: &DatabaseColumn,
+    value: &DatabaseCellValue,
+    definition: Option<&PropertyDefinition>,
+) -> Result<BulkOutcome, PropertyError> {
+    let mut edits = Vec::new();
+    let mut undo = DatabaseUndoPayload::default();
+    for note_path in note_paths {
+        let (markdown, undo_note) = read_note_for_undo(root, note_path)?;
+        let next = apply_cell_update_to_markdown(note_path, &markdown, column, value, definition)?;
+        undo.restore.push(undo_note);
+        edits.push((note_path.clone(), next));
+    }
+    let mut outcome = BulkOutcome {
+        note_paths: note_paths.to_vec(),
+        undo,
+        error: None,
+    };
+    if let Err((written, error)) = write_and_index_notes(root, recent_local_changes, &edits, &[]) {
+        outcome.note_paths.truncate(written);
+        outcome.undo.restore.truncate(written);
+        outcome.error = Some(error);
+    }
+    Ok(outcome)
+}
+
+/// Copies each note next to the original as "<title> copy" with fresh
+/// timestamps. Copies are written one by one so their names cannot collide.
+fn duplicate_rows(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    note_paths: &[String],
+) -> BulkOutcome {
+    let mut written = Vec::new();
+    let mut failure = None;
+    for note_path in note_paths {
+        let copy = (|| -> Result<(String, String), String> {
+            let (markdown, stored) = read_note_for_undo(root, note_path)?;
+            let (yaml, _body) = split_frontmatter(&markdown);
+            let mut mapping = parse_frontmatter_mapping(yaml)?;
+            let title = mapping
+                .get(key("title"))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|title| !title.is_empty())
+                .map(str::to_string)
+                .or_else(|| {
+                    Path::new(note_path)
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .map(str::to_string)
+                })
+                .unwrap_or_else(|| "Untitled".to_string());
+            let title = format!("{title} copy");
+            let folder = Path::new(note_path)
+                .parent()
+                .and_then(|parent| parent.to_str())
+                .unwrap_or("");
+            let copy_path = available_row_path(root, folder, &title)?;
+            let now = now_rfc3339();
+            mapping.insert(key("title"), Value::String(title));
+            mapping.insert(key("created"), Value::String(now.clone()));
+            mapping.insert(key("updated"), Value::String(now));
+            let next = render_note_markdown(&copy_path, &markdown, mapping)?;
+            write_note_file(
+                root,
+                recent_local_changes,
+                &copy_path,
+                &next,
+                stored.encrypted,
+            )?;
+            Ok((copy_path, next))
+        })();
+        match copy {
+            Ok(copy) => written.push(copy),
+            Err(error) => {
+                failure = Some(error);
+                break;
             }
-            candidate = if folder.is_empty() {
-                format!("{slug} {index}.md")
-            } else {
-                format!("{folder}/{slug} {index}.md")
-            };
-            index += 1;
         }
+    }
+    if let Err(error) = index_notes(root, &written) {
+        failure.get_or_insert(error);
+    }
+    let created = written
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    BulkOutcome {
+        undo: DatabaseUndoPayload {
+            remove: created.clone(),
+            ..DatabaseUndoPayload::default()
+        },
+        note_paths: created,
+        error: failure,
+    }
+}
 
-        let next = create_new_row_markdown(&candidate, &title)?;
-        write_markdown_note(&root, &recent_local_changes, &candidate, &next)?;
-        let row = row_by_path(&root, &candidate)?;
-        Ok(DatabaseCreateRowResult {
-            note_path: candidate,
-            row,
+/// Moves notes to the system trash (`mode` "trash") or under an archive
+/// folder, keeping their relative path (`mode` "archive"). Trashed notes are
+/// undone by rewriting their content; archived ones by moving them back.
+/// Stops at the first note that fails, keeping the undo for the ones before.
+fn delete_rows(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    note_paths: &[String],
+    mode: &str,
+    archive_folder: &str,
+) -> Result<BulkOutcome, String> {
+    let mut outcome = BulkOutcome::default();
+    match mode {
+        "trash" => {
+            for note_path in note_paths {
+                let undo = &mut outcome.undo;
+                let trashed = (|| -> Result<(), String> {
+                    let (_, undo_note) = read_note_for_undo(root, note_path)?;
+                    mark_recent_local_change(recent_local_changes, note_path);
+                    move_path_to_trash(&note_abs_path(root, note_path)?)?;
+                    undo.restore.push(undo_note);
+                    remove_note(root, note_path)
+                })();
+                if let Err(error) = trashed {
+                    outcome.error = Some(error);
+                    break;
+                }
+            }
+            Ok(outcome)
+        }
+        "archive" => {
+            let folder = archive_folder.trim().trim_matches('/');
+            if folder.is_empty() {
+                return Err("archive folder is empty".to_string());
+            }
+            let mut moved = Vec::new();
+            for note_path in note_paths {
+                if note_path.starts_with(&format!("{folder}/")) {
+                    continue;
+                }
+                let undo = &mut outcome.undo;
+                let archived = (|| -> Result<(), String> {
+                    let to = available_note_path(root, &format!("{folder}/{note_path}"))?;
+                    move_note(root, recent_local_changes, note_path, &to)?;
+                    undo.moves.push(DatabaseUndoMove {
+                        from: note_path.clone(),
+                        to: to.clone(),
+                    });
+                    moved.push((to.clone(), read_note(root, &to)?));
+                    Ok(())
+                })();
+                if let Err(error) = archived {
+                    outcome.error = Some(error);
+                    break;
+                }
+            }
+            if let Err(error) = index_notes(root, &moved) {
+                outcome.error.get_or_insert(error);
+            }
+            outcome.note_paths = outcome
+                .undo
+                .moves
+                .iter()
+                .map(|entry| entry.to.clone())
+                .collect();
+            Ok(outcome)
+        }
+        other => Err(format!("unsupported delete mode '{other}'")),
+    }
+}
+
+/// Reverses a bulk operation: moves notes back, trashes the notes it created
+/// and rewrites the notes it edited or trashed.
+fn apply_undo(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    undo: &DatabaseUndoPayload,
+) -> Result<Vec<String>, String> {
+    let mut restored = Vec::new();
+    for entry in undo.moves.iter().rev() {
+        if note_exists(root, &entry.from)? {
+            return Err(format!(
+                "cannot move '{}' back: '{}' already exists",
+                entry.to, entry.from
+            ));
+        }
+        move_note(root, recent_local_changes, &entry.to, &entry.from)?;
+        restored.push((entry.from.clone(), read_note(root, &entry.from)?));
+    }
+    index_notes(root, &restored)?;
+    for note_path in &undo.remove {
+        let abs = note_abs_path(root, note_path)?;
+        if abs.exists() {
+            mark_recent_local_change(recent_local_changes, note_path);
+            move_path_to_trash(&abs)?;
+        }
+        remove_note(root, note_path)?;
+    }
+    let mut rewrites = Vec::new();
+    let mut sealed = Vec::new();
+    for note in &undo.restore {
+        let markdown = if note.encrypted {
+            sealed.push(note.note_path.clone());
+            crypto::open_text(root, note.markdown.as_bytes())?
+        } else {
+            note.markdown.clone()
+        };
+        rewrites.push((note.note_path.clone(), markdown));
+    }
+    write_and_index_notes(root, recent_local_changes, &rewrites, &sealed)
+        .map_err(|(_, error)| error)?;
+    Ok(restored
+        .into_iter()
+        .chain(rewrites)
+        .map(|(path, _)| path)
+        .collect())
+}
+
+/// The result of a bulk operation. One that failed before changing anything
+/// is an error; one that stopped part way reports why in `errors` and keeps
+/// the undo for what it did.
+fn bulk_result(root: &Path, outcome: BulkOutcome) -> Result<DatabaseBulkResult, String> {
+    let BulkOutcome {
+        note_paths,
+        undo,
+        error,
+    } = outcome;
+    if undo.is_empty() {
+        if let Some(error) = error {
+            return Err(error);
+        }
+    }
+    let mut errors = error.into_iter().collect::<Vec<_>>();
+    let rows = open_db(root)
+        .and_then(|conn| hydrate_rows_by_paths(&conn, &note_paths))
+        .unwrap_or_else(|error| {
+            errors.push(error);
+            Vec::new()
+        });
+    Ok(DatabaseBulkResult {
+        note_paths,
+        rows,
+        undo,
+        errors,
+    })
+}
+
+/// Sets one column to the same value on every row, writing all notes before a
+/// single index pass.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_bulk_update_cells(
+    state: State<'_, SpaceState>,
+    note_paths: Vec<String>,
+    column: DatabaseColumn,
+    value: DatabaseCellValue,
+    database_path: Option<String>,
+) -> Result<DatabaseBulkResult, PropertyError> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseBulkResult, PropertyError> {
+        let note_paths = unique_note_paths(note_paths);
+        let definitions = property_definitions(&root, database_path.as_deref())?;
+        let definition = column_definition(&definitions, &column);
+        let outcome = bulk_update_cells(
+            &root,
+            &recent_local_changes,
+            &note_paths,
+            &column,
+            &value,
+            definition,
+        )?;
+        let mut result = bulk_result(&root, outcome)?;
+        if definition.is_none() {
+            result.rows = result
+                .rows
+                .into_iter()
+                .map(|row| apply_cell_value_to_row(row, &column, &value))
+                .collect();
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_duplicate_rows(
+    state: State<'_, SpaceState>,
+    note_paths: Vec<String>,
+) -> Result<DatabaseBulkResult, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseBulkResult, String> {
+        let note_paths = unique_note_paths(note_paths);
+        let outcome = duplicate_rows(&root, &recent_local_changes, &note_paths);
+        bulk_result(&root, outcome)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_delete_rows(
+    state: State<'_, SpaceState>,
+    note_paths: Vec<String>,
+    mode: Option<String>,
+    archive_folder: Option<String>,
+) -> Result<DatabaseBulkResult, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseBulkResult, String> {
+        let note_paths = unique_note_paths(note_paths);
+        let outcome = delete_rows(
+            &root,
+            &recent_local_changes,
+            &note_paths,
+            mode.as_deref().unwrap_or("trash"),
+            archive_folder.as_deref().unwrap_or(DEFAULT_ARCHIVE_FOLDER),
+        )?;
+        bulk_result(&root, outcome)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Applies the undo payload returned by a bulk command and returns the rows
+/// it brought back.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_undo(
+    state: State<'_, SpaceState>,
+    undo: DatabaseUndoPayload,
+) -> Result<Vec<DatabaseRow>, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || -> Result<Vec<DatabaseRow>, String> {
+        let restored = apply_undo(&root, &recent_local_changes, &undo)?;
+        let conn = open_db(&root)?;
+        hydrate_rows_by_paths(&conn, &restored)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Rewrites the database's rows so property values match their definitions:
+/// select options take their declared casing, numbers and booleans stored as
+/// text become typed. Values that cannot be normalized are reported as issues
+/// and left untouched, as are required properties a row lacks.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_normalize_properties(
+    state: State<'_, SpaceState>,
+    path: String,
+    today: Option<String>,
+    week_start: Option<String>,
+) -> Result<DatabaseNormalizeResult, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || -> Result<DatabaseNormalizeResult, String> {
+        let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+        let (config, rows, truncated) = load_all_rows(&root, &path, None, &ctx)?;
+        let space = read_property_schema(&root).properties;
+        let mut issues = Vec::new();
+        let mut edits = Vec::new();
+        let mut undo = DatabaseUndoPayload::default();
+        for row in &rows {
+            let (markdown, undo_note) = read_note_for_undo(&root, &row.note_path)?;
+            let (yaml, _body) = split_frontmatter(&markdown);
+            let mut mapping = parse_frontmatter_mapping(yaml)?;
+            let mut changed = false;
+            for (property_key, value) in mapping.iter_mut() {
+                let Some(definition) = property_key
+                    .as_str()
+                    .and_then(|name| find_definition(&config.properties, &space, name))
+                else {
+                    continue;
+                };
+                match validate_property_value(definition, value) {
+                    Ok(next) if next != *value => {
+                        *value = next;
+                        changed = true;
+                    }
+                    Ok(_) => {}
+                    Err(error) => issues.push(error.at(&row.note_path)),
+                }
+            }
+            issues.extend(
+                missing_required_properties(&config.properties, &space, &mapping)
+                    .into_iter()
+                    .map(|error| error.at(&row.note_path)),
+            );
+            if changed {
+                let next = render_note_markdown(&row.note_path, &markdown, mapping)?;
+                undo.restore.push(undo_note);
+                edits.push((row.note_path.clone(), next));
+            }
+        }
+        let mut errors = Vec::new();
+        if let Err((written, error)) =
+            write_and_index_notes(&root, &recent_local_changes, &edits, &[])
+        {
+            edits.truncate(written);
+            undo.restore.truncate(written);
+            errors.push(error);
+        }
+        Ok(DatabaseNormalizeResult {
+            note_paths: edits.into_iter().map(|(note_path, _)| note_path).collect(),
+            issues,
+            undo,
+            truncated,
+            errors,
         })
     })
     .await
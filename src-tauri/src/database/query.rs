@@ -1,9 +1,11 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
-use rusqlite::{params, Connection};
+use base64::Engine;
+use rusqlite::{types::Value, Connection};
 
-use crate::index::commands::parse_raw_search_query;
+use crate::dates::DateContext;
+use crate::index::commands::{parse_raw_search_query, parse_search_date_range};
 use crate::index::open_db;
 use crate::index::search_advanced::run_search_advanced;
 use crate::notes::frontmatter::split_frontmatter;
@@ -11,9 +13,21 @@ use crate::paths;
 use crate::space_fs::helpers::deny_hidden_rel_path;
 
 use super::config::parse_database_config;
-use super::types::{DatabaseCellValue, DatabaseLoadResult, DatabasePropertyOption, DatabaseRow};
+use super::filters::{
+    compare_values, filter_sql, keyset_sql, order_by_sql, sort_keys, value_matches_filter, SortKey,
+};
+use super::formula::FormulaSet;
+use super::layouts::fill_covers;
+use super::relations::{resolve_relations, LinkTarget};
+use super::types::{
+    DatabaseCellValue, DatabaseConfig, DatabaseLoadResult, DatabasePropertyOption, DatabaseRow,
+    DatabaseSource,
+};
+use super::views::select_view;
 
 const HARD_LIMIT: usize = 500;
+const SEARCH_SOURCE_LIMIT: usize = 2_000;
+const COMPUTED_SCAN_LIMIT: usize = 10_000;
 
 fn is_reserved_property(key: &str) -> bool {
     matches!(
@@ -51,11 +65,11 @@ fn property_value_from_index(
 
 fn direct_folder_clause(dir: &str) -> (String, Vec<String>) {
     if dir.is_empty() {
-        return ("instr(id, '/') = 0".to_string(), Vec::new());
+        return ("instr(n.id, '/') = 0".to_string(), Vec::new());
     }
 
     (
-        "id LIKE ? AND instr(substr(id, ?), '/') = 0".to_string(),
+        "n.id LIKE ? AND instr(substr(n.id, ?), '/') = 0".to_string(),
         vec![format!("{dir}/%"), (dir.len() + 2).to_string()],
     )
 }
@@ -64,86 +78,196 @@ fn recursive_folder_clause(dir: &str) -> (String, Vec<String>) {
     if dir.is_empty() {
         return ("1 = 1".to_string(), Vec::new());
     }
-    ("id LIKE ?".to_string(), vec![format!("{dir}/%")])
+    ("n.id LIKE ?".to_string(), vec![format!("{dir}/%")])
 }
 
-fn folder_source_ids(
-    conn: &Connection,
-    dir: &str,
-    recursive: bool,
-    limit: usize,
-) -> Result<Vec<String>, String> {
-    let (where_sql, bind_values) = if recursive {
-        recursive_folder_clause(dir)
+fn tag_source_clause(tag: &str) -> (String, Vec<String>) {
+    let normalized = if tag.starts_with('#') {
+        tag.to_string()
     } else {
-        direct_folder_clause(dir)
+        format!("#{tag}")
     };
-    let sql = format!("SELECT id FROM notes WHERE {where_sql} ORDER BY updated DESC LIMIT ?");
-    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    (
+        "EXISTS (SELECT 1 FROM tags t WHERE t.note_id = n.id AND t.tag = ?)".to_string(),
+        vec![normalized],
+    )
+}
 
-    let mut bind_params: Vec<rusqlite::types::Value> = bind_values
+fn search_source_ids(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+    ctx: &DateContext,
+) -> Result<Vec<String>, String> {
+    let request = parse_raw_search_query(query, Some(limit as u32), ctx);
+    Ok(run_search_advanced(conn, request)?
         .into_iter()
-        .map(rusqlite::types::Value::from)
-        .collect();
-    bind_params.push(rusqlite::types::Value::from(limit as i64));
+        .map(|result| result.id)
+        .collect())
+}
 
-    let mut rows = stmt
-        .query(rusqlite::params_from_iter(bind_params.iter()))
-        .map_err(|e| e.to_string())?;
-    let mut out = Vec::new();
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        out.push(row.get::<_, String>(0).map_err(|e| e.to_string())?);
+/// Notes whose `key` property equals `value` (or has it as a list item),
+/// ignoring case. An empty value matches any non-empty property.
+fn property_source_clause(key: &str, value: &str) -> (String, Vec<String>) {
+    let value = value.trim();
+    if value.is_empty() {
+        return (
+            "EXISTS (SELECT 1 FROM note_properties sp WHERE sp.note_id = n.id AND sp.key = ? COLLATE NOCASE AND trim(sp.value_text) != '')"
+                .to_string(),
+            vec![key.to_string()],
+        );
+    }
+    (
+        "EXISTS (SELECT 1 FROM note_properties sp WHERE sp.note_id = n.id AND sp.key = ? COLLATE NOCASE AND (lower(sp.value_text) = lower(?) OR EXISTS (SELECT 1 FROM json_each(sp.value_json) j WHERE j.type = 'text' AND lower(j.value) = lower(?))))"
+            .to_string(),
+        vec![key.to_string(), value.to_string(), value.to_string()],
+    )
+}
+
+/// Notes linking to `target`, written as `[[Title]]`, a title or a path.
+fn link_source_clause(target: &str) -> Result<(String, Vec<String>), String> {
+    let inner = target
+        .trim()
+        .trim_start_matches("[[")
+        .trim_end_matches("]]");
+    match LinkTarget::parse(inner) {
+        Some(LinkTarget::Path(path)) => Ok((
+            "EXISTS (SELECT 1 FROM links sl WHERE sl.from_id = n.id AND sl.to_id = ?)".to_string(),
+            vec![path],
+        )),
+        Some(LinkTarget::Title(title)) => Ok((
+            "EXISTS (SELECT 1 FROM links sl LEFT JOIN notes lt ON lt.id = sl.to_id WHERE sl.from_id = n.id AND (lt.title = ? COLLATE NOCASE OR lt.id = ? COLLATE NOCASE OR sl.to_title = ? COLLATE NOCASE))"
+                .to_string(),
+            vec![title.clone(), format!("{title}.md"), title],
+        )),
+        None => Err("link source needs a target note".to_string()),
     }
-    Ok(out)
 }
 
-fn tag_source_ids(conn: &Connection, tag: &str, limit: usize) -> Result<Vec<String>, String> {
-    let normalized = if tag.starts_with('#') {
-        tag.to_string()
+/// Notes with open tasks due in `range`: a date, period or `a..b` range as
+/// in search (`this week`, `today..eom`), `overdue`, or empty for any due
+/// date.
+fn tasks_due_source_clause(
+    range: &str,
+    ctx: &DateContext,
+) -> Result<(String, Vec<String>), String> {
+    let base = "EXISTS (SELECT 1 FROM tasks st WHERE st.note_id = n.id AND st.checked = 0 AND st.due_date IS NOT NULL";
+    let range = range.trim();
+    if range.is_empty() {
+        return Ok((format!("{base})"), Vec::new()));
+    }
+    if range.eq_ignore_ascii_case("overdue") {
+        return Ok((
+            format!("{base} AND st.due_date < ?)"),
+            vec![ctx.today_iso()],
+        ));
+    }
+    let (from, to) = parse_search_date_range(range, ctx)
+        .ok_or_else(|| format!("could not understand due date range '{range}'"))?;
+    let mut clause = base.to_string();
+    let mut values = Vec::new();
+    if let Some(from) = from {
+        clause.push_str(" AND st.due_date >= ?");
+        values.push(from);
+    }
+    if let Some(to) = to {
+        clause.push_str(" AND substr(st.due_date, 1, 10) <= ?");
+        values.push(to);
+    }
+    clause.push(')');
+    Ok((clause, values))
+}
+
+fn folder_clause(dir: &str, recursive: bool) -> (String, Vec<String>) {
+    let dir = dir.trim_matches('/');
+    if recursive {
+        recursive_folder_clause(dir)
     } else {
-        format!("#{tag}")
-    };
-    let mut stmt = conn
-        .prepare(
-            "SELECT n.id
-             FROM tags t
-             JOIN notes n ON n.id = t.note_id
-             WHERE t.tag = ?
-             ORDER BY n.updated DESC
-             LIMIT ?",
-        )
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt
-        .query(params![normalized, limit as i64])
-        .map_err(|e| e.to_string())?;
-    let mut out = Vec::new();
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        out.push(row.get::<_, String>(0).map_err(|e| e.to_string())?);
+        direct_folder_clause(dir)
     }
-    Ok(out)
 }
 
-fn search_source_ids(conn: &Connection, query: &str, limit: usize) -> Result<Vec<String>, String> {
-    let request = parse_raw_search_query(query, Some(limit as u32));
-    Ok(run_search_advanced(conn, request)?
-        .into_iter()
-        .map(|result| result.id)
-        .collect())
+/// The condition on `notes n` selecting the notes a database source covers.
+/// Relative dates in search and task sources resolve against `ctx`.
+pub(super) fn source_clause(
+    conn: &Connection,
+    source: &DatabaseSource,
+    ctx: &DateContext,
+) -> Result<(String, Vec<Value>), String> {
+    let (clause, values) = match source.kind.as_str() {
+        "folder" => folder_clause(&source.value, source.recursive),
+        "composite" => return composite_source_clause(conn, source, ctx),
+        "property" => {
+            let key = source
+                .property
+                .as_deref()
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .ok_or_else(|| "property source needs a property key".to_string())?;
+            property_source_clause(key, &source.value)
+        }
+        "link" => link_source_clause(&source.value)?,
+        "tasks_due" => tasks_due_source_clause(&source.value, ctx)?,
+        "tag" => tag_source_clause(&source.value),
+        "search" => {
+            let ids = search_source_ids(conn, &source.value, SEARCH_SOURCE_LIMIT, ctx)?;
+            if ids.is_empty() {
+                ("0 = 1".to_string(), Vec::new())
+            } else {
+                let placeholders = std::iter::repeat_n("?", ids.len())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                (format!("n.id IN ({placeholders})"), ids)
+            }
+        }
+        other => return Err(format!("unsupported database source kind '{other}'")),
+    };
+    let (clause, values) = match source.folder.as_deref() {
+        Some(folder) if !folder.trim_matches('/').is_empty() => {
+            let (scope, scope_values) = folder_clause(folder, source.recursive);
+            (
+                format!("({clause}) AND ({scope})"),
+                values.into_iter().chain(scope_values).collect(),
+            )
+        }
+        _ => (clause, values),
+    };
+    Ok((clause, values.into_iter().map(Value::from).collect()))
 }
 
-fn source_ids(
+/// The union (or intersection) of several sources, each of which may be
+/// composite itself.
+fn composite_source_clause(
     conn: &Connection,
-    kind: &str,
-    value: &str,
-    recursive: bool,
-    limit: usize,
-) -> Result<Vec<String>, String> {
-    match kind {
-        "folder" => folder_source_ids(conn, value.trim_matches('/'), recursive, limit),
-        "tag" => tag_source_ids(conn, value, limit),
-        "search" => search_source_ids(conn, value, limit),
-        other => Err(format!("unsupported database source kind '{other}'")),
+    source: &DatabaseSource,
+    ctx: &DateContext,
+) -> Result<(String, Vec<Value>), String> {
+    let joiner = match source.operator.as_deref().unwrap_or("union") {
+        "union" | "or" => " OR ",
+        "intersection" | "and" => " AND ",
+        other => return Err(format!("unsupported composite operator '{other}'")),
+    };
+    if source.sources.is_empty() {
+        return Ok(("0 = 1".to_string(), Vec::new()));
+    }
+    let mut clauses = Vec::with_capacity(source.sources.len());
+    let mut values = Vec::new();
+    for child in &source.sources {
+        let (clause, child_values) = source_clause(conn, child, ctx)?;
+        clauses.push(format!("({clause})"));
+        values.extend(child_values);
+    }
+    let mut clause = format!("({})", clauses.join(joiner));
+    if let Some(folder) = source
+        .folder
+        .as_deref()
+        .filter(|folder| !folder.trim_matches('/').is_empty())
+    {
+        let (scope, scope_values) = folder_clause(folder, source.recursive);
+        clause = format!("{clause} AND ({scope})");
+        values.extend(scope_values.into_iter().map(Value::from));
     }
+    Ok((clause, values))
 }
 
 pub(crate) fn hydrate_rows_by_paths(
@@ -180,6 +304,9 @@ pub(crate) fn hydrate_rows_by_paths(
                 preview: row.get(4).map_err(|e| e.to_string())?,
                 tags: Vec::new(),
                 properties: BTreeMap::new(),
+                computed: BTreeMap::new(),
+                relations: BTreeMap::new(),
+                cover: None,
             },
         );
     }
@@ -232,23 +359,264 @@ pub(crate) fn hydrate_rows_by_paths(
         .collect::<Vec<_>>())
 }
 
-fn collect_available_properties(rows: &[DatabaseRow]) -> Vec<DatabasePropertyOption> {
-    let mut counts = BTreeMap::<String, (String, u32)>::new();
-    for row in rows {
-        for (key, value) in &row.properties {
-            if is_reserved_property(key) {
-                continue;
-            }
-            let entry = counts
-                .entry(key.clone())
-                .or_insert_with(|| (value.kind.clone(), 0));
-            entry.1 += 1;
+/// Property keys used across the whole source (not just the loaded page),
+/// each reported with its most common kind.
+fn collect_available_properties(
+    conn: &Connection,
+    scope: &str,
+    scope_params: &[Value],
+) -> Result<Vec<DatabasePropertyOption>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT np.key, np.value_type, COUNT(*)
+             FROM note_properties np
+             JOIN notes n ON n.id = np.note_id
+             WHERE {scope}
+             GROUP BY np.key, np.value_type"
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(scope_params.iter()))
+        .map_err(|e| e.to_string())?;
+    let mut counts = BTreeMap::<String, (String, u32, u32)>::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let key = row.get::<_, String>(0).map_err(|e| e.to_string())?;
+        if is_reserved_property(&key) {
+            continue;
+        }
+        let kind = row.get::<_, String>(1).map_err(|e| e.to_string())?;
+        let count = row.get::<_, u32>(2).map_err(|e| e.to_string())?;
+        let entry = counts.entry(key).or_insert_with(|| (kind.clone(), 0, 0));
+        if count > entry.2 {
+            entry.0 = kind;
+            entry.2 = count;
+        }
+        entry.1 += count;
+    }
+    Ok(counts
+        .into_iter()
+        .map(|(key, (kind, count, _))| DatabasePropertyOption { key, kind, count })
+        .collect())
+}
+
+/// Where the next page starts: after the last row's sort key values when
+/// paging in SQL, or at an offset when rows are filtered or sorted by
+/// computed formula values in memory.
+enum PageCursor {
+    After(Vec<Value>),
+    Offset(usize),
+}
+
+fn encode_cursor(cursor: &PageCursor) -> String {
+    let json = match cursor {
+        PageCursor::After(values) => serde_json::Value::Array(
+            values
+                .iter()
+                .map(|value| match value {
+                    Value::Null | Value::Blob(_) => serde_json::Value::Null,
+                    Value::Integer(v) => serde_json::Value::from(*v),
+                    Value::Real(v) => serde_json::Value::from(*v),
+                    Value::Text(v) => serde_json::Value::from(v.clone()),
+                })
+                .collect(),
+        ),
+        PageCursor::Offset(offset) => serde_json::json!({ "offset": offset }),
+    };
+    base64::engine::general_purpose::STANDARD.encode(json.to_string())
+}
+
+fn decode_cursor(cursor: &str) -> Result<PageCursor, String> {
+    let invalid = || "invalid database cursor".to_string();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(cursor.trim())
+        .map_err(|_| invalid())?;
+    match serde_json::from_slice::<serde_json::Value>(&bytes).map_err(|_| invalid())? {
+        serde_json::Value::Object(object) => object
+            .get("offset")
+            .and_then(serde_json::Value::as_u64)
+            .map(|offset| PageCursor::Offset(offset as usize))
+            .ok_or_else(invalid),
+        serde_json::Value::Array(values) => values
+            .into_iter()
+            .map(|value| match value {
+                serde_json::Value::Null => Ok(Value::Null),
+                serde_json::Value::Number(n) => n
+                    .as_i64()
+                    .map(Value::Integer)
+                    .or_else(|| n.as_f64().map(Value::Real))
+                    .ok_or_else(invalid),
+                serde_json::Value::String(s) => Ok(Value::Text(s)),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(PageCursor::After),
+        _ => Err(invalid()),
+    }
+}
+
+struct Page {
+    rows: Vec<DatabaseRow>,
+    total_count: u32,
+    next_cursor: Option<PageCursor>,
+    /// Set when only the first `COMPUTED_SCAN_LIMIT` rows were evaluated.
+    scan_truncated: bool,
+}
+
+fn load_sql_page(
+    conn: &Connection,
+    where_sql: &str,
+    mut params: Vec<Value>,
+    keys: &[SortKey],
+    cursor: Option<PageCursor>,
+    limit: usize,
+) -> Result<Page, String> {
+    let total_count = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM notes n WHERE {where_sql}"),
+            rusqlite::params_from_iter(params.iter()),
+            |row| row.get::<_, u32>(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut page_where = where_sql.to_string();
+    match cursor {
+        Some(PageCursor::After(after)) if after.len() == keys.len() => {
+            page_where = format!("{page_where} AND {}", keyset_sql(keys, &after, &mut params));
         }
+        Some(_) => return Err("invalid database cursor".to_string()),
+        None => {}
     }
-    counts
+    params.push(Value::from(limit as i64 + 1));
+    let key_columns = keys
+        .iter()
+        .map(|key| key.expr.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT n.id, {key_columns} FROM notes n WHERE {page_where} ORDER BY {} LIMIT ?",
+            order_by_sql(keys)
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(params.iter()))
+        .map_err(|e| e.to_string())?;
+    let mut ids = Vec::new();
+    let mut last_keys = Vec::new();
+    let mut truncated = false;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        if ids.len() == limit {
+            truncated = true;
+            break;
+        }
+        ids.push(row.get::<_, String>(0).map_err(|e| e.to_string())?);
+        last_keys = (1..=keys.len())
+            .map(|index| row.get::<_, Value>(index))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(Page {
+        rows: hydrate_rows_by_paths(conn, &ids)?,
+        total_count,
+        next_cursor: truncated.then_some(PageCursor::After(last_keys)),
+        scan_truncated: false,
+    })
+}
+
+/// Pages through rows whose filters or sorts depend on computed columns:
+/// the SQL-expressible part narrows the candidates (up to
+/// `COMPUTED_SCAN_LIMIT`, flagged when there are more), the rest is
+/// applied to evaluated rows.
+#[allow(clippy::too_many_arguments)]
+fn load_computed_page(
+    root: &Path,
+    conn: &Connection,
+    config: &DatabaseConfig,
+    formulas: &FormulaSet<'_>,
+    ctx: &DateContext,
+    where_sql: &str,
+    mut params: Vec<Value>,
+    keys: &[SortKey],
+    cursor: Option<PageCursor>,
+    limit: usize,
+) -> Result<Page, String> {
+    let offset = match cursor {
+        Some(PageCursor::Offset(offset)) => offset,
+        Some(PageCursor::After(_)) => return Err("invalid database cursor".to_string()),
+        None => 0,
+    };
+    params.push(Value::from(COMPUTED_SCAN_LIMIT as i64 + 1));
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT n.id FROM notes n WHERE {where_sql} ORDER BY {} LIMIT ?",
+            order_by_sql(keys)
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut ids = stmt
+        .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let scan_truncated = ids.len() > COMPUTED_SCAN_LIMIT;
+    ids.truncate(COMPUTED_SCAN_LIMIT);
+
+    let column = |id: &str| config.columns.iter().find(|column| column.id == id);
+    let computed_filters = config
+        .filters
+        .iter()
+        .filter(|filter| formulas.is_computed_column(&filter.column_id))
+        .filter_map(|filter| column(&filter.column_id).map(|column| (column, filter)))
+        .collect::<Vec<_>>();
+    let sorts = config
+        .sorts
+        .iter()
+        .filter_map(|sort| {
+            column(&sort.column_id)
+                .map(|column| (column, sort.direction.eq_ignore_ascii_case("desc")))
+        })
+        .collect::<Vec<_>>();
+
+    let mut candidates = hydrate_rows_by_paths(conn, &ids)?;
+    resolve_relations(root, conn, config, ctx, &mut candidates)?;
+    let mut rows = candidates
         .into_iter()
-        .map(|(key, (kind, count))| DatabasePropertyOption { key, kind, count })
-        .collect()
+        .filter(|row| {
+            computed_filters.iter().all(|(column, filter)| {
+                value_matches_filter(&formulas.column_value(row, column), filter, ctx)
+            })
+        })
+        .map(|row| {
+            let values = sorts
+                .iter()
+                .map(|(column, _)| formulas.column_value(&row, column))
+                .collect::<Vec<_>>();
+            (values, row)
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by(|(a, _), (b, _)| {
+        sorts
+            .iter()
+            .zip(a.iter().zip(b))
+            .map(|((_, descending), (a, b))| compare_values(a, b, *descending))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let total_count = rows.len();
+    let end = (offset + limit).min(total_count);
+    Ok(Page {
+        rows: rows
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(_, row)| row)
+            .collect(),
+        total_count: total_count as u32,
+        next_cursor: (end < total_count).then_some(PageCursor::Offset(end)),
+        scan_truncated,
+    })
 }
 
 fn resolve_database_abs_path(root: &Path, database_path: &str) -> Result<PathBuf, String> {
@@ -257,38 +625,153 @@ fn resolve_database_abs_path(root: &Path, database_path: &str) -> Result<PathBuf
     paths::join_under(root, &rel)
 }
 
-pub fn load_database(
+/// Runs a database's source, filters and sorts and returns one page of
+/// evaluated rows together with the properties seen across the source.
+/// Relative dates in the source, filters and formulas resolve against `ctx`.
+fn query_database(
     root: &Path,
     database_path: &str,
-    limit: Option<u32>,
-) -> Result<DatabaseLoadResult, String> {
+    limit: usize,
+    cursor: Option<&str>,
+    view_id: Option<&str>,
+    ctx: &DateContext,
+) -> Result<(DatabaseConfig, Vec<DatabasePropertyOption>, Page), String> {
     let abs = resolve_database_abs_path(root, database_path)?;
     let markdown = std::fs::read_to_string(&abs).map_err(|e| e.to_string())?;
-    let config = parse_database_config(&markdown)?;
+    let mut config = parse_database_config(&markdown)?;
+    if view_id.is_some() {
+        select_view(&mut config, view_id)?;
+    }
     let conn = open_db(root)?;
+    let (source_sql, mut params) = source_clause(&conn, &config.source, ctx)?;
+    let scope = format!("{source_sql} AND n.id != ?");
+    params.push(Value::from(database_path.to_string()));
+    let available_properties = collect_available_properties(&conn, &scope, &params)?;
+
+    let mut conditions = vec![scope];
+    for filter in &config.filters {
+        conditions.extend(filter_sql(&config.columns, filter, ctx, &mut params));
+    }
+    let where_sql = conditions.join(" AND ");
+    let keys = sort_keys(&config.columns, &config.sorts);
+    let cursor = cursor
+        .map(str::trim)
+        .filter(|cursor| !cursor.is_empty())
+        .map(decode_cursor)
+        .transpose()?;
+
+    let formulas = FormulaSet::new(&config.columns, ctx);
+    let computed = config
+        .filters
+        .iter()
+        .map(|filter| &filter.column_id)
+        .chain(config.sorts.iter().map(|sort| &sort.column_id))
+        .any(|column_id| formulas.is_computed_column(column_id));
+    let mut page = if computed {
+        load_computed_page(
+            root, &conn, &config, &formulas, ctx, &where_sql, params, &keys, cursor, limit,
+        )?
+    } else {
+        let mut page = load_sql_page(&conn, &where_sql, params, &keys, cursor, limit)?;
+        resolve_relations(root, &conn, &config, ctx, &mut page.rows)?;
+        page
+    };
+    if !formulas.is_empty() {
+        for row in &mut page.rows {
+            formulas.evaluate_row(row);
+        }
+    }
+    Ok((config, available_properties, page))
+}
+
+/// The notes a database's source and SQL filters match, optionally limited to
+/// `note_paths`. Filters on computed columns are not applied.
+pub(super) fn matching_note_paths(
+    conn: &Connection,
+    database_path: &str,
+    config: &DatabaseConfig,
+    note_paths: Option<&[String]>,
+    ctx: &DateContext,
+) -> Result<HashSet<String>, String> {
+    let (source_sql, mut params) = source_clause(conn, &config.source, ctx)?;
+    let mut conditions = vec![source_sql, "n.id != ?".to_string()];
+    params.push(Value::from(database_path.to_string()));
+    for filter in &config.filters {
+        conditions.extend(filter_sql(&config.columns, filter, ctx, &mut params));
+    }
+    if let Some(note_paths) = note_paths {
+        if note_paths.is_empty() {
+            return Ok(HashSet::new());
+        }
+        conditions.push(format!(
+            "n.id IN ({})",
+            vec!["?"; note_paths.len()].join(", ")
+        ));
+        params.extend(note_paths.iter().cloned().map(Value::from));
+    }
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT n.id FROM notes n WHERE {}",
+            conditions.join(" AND ")
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Every row matching a database's source and the filters of `view_id`
+/// (default: the active view), in sort order, up to `COMPUTED_SCAN_LIMIT`.
+/// The flag tells whether rows were (or may have been) left out.
+pub(super) fn load_all_rows(
+    root: &Path,
+    database_path: &str,
+    view_id: Option<&str>,
+    ctx: &DateContext,
+) -> Result<(DatabaseConfig, Vec<DatabaseRow>, bool), String> {
+    let (config, _, page) =
+        query_database(root, database_path, COMPUTED_SCAN_LIMIT, None, view_id, ctx)?;
+    let truncated = page.next_cursor.is_some() || page.scan_truncated;
+    Ok((config, page.rows, truncated))
+}
+
+/// Loads one page of a database: the source and the configured filters are
+/// compiled into SQL, rows are ordered by the configured sorts and `cursor`
+/// (the previous page's `next_cursor`) continues after the last row served.
+/// `view_id` picks the view whose filters and sorts apply (default: the
+/// active view); `ctx` is the client's today and week start.
+pub fn load_database(
+    root: &Path,
+    database_path: &str,
+    limit: Option<u32>,
+    cursor: Option<&str>,
+    view_id: Option<&str>,
+    ctx: &DateContext,
+) -> Result<DatabaseLoadResult, String> {
     let effective_limit = limit
         .unwrap_or(HARD_LIMIT as u32)
         .clamp(1, HARD_LIMIT as u32) as usize;
-    let mut ids = source_ids(
-        &conn,
-        &config.source.kind,
-        &config.source.value,
-        config.source.recursive,
-        effective_limit + 1,
-    )?;
-    ids.retain(|id| id != database_path);
-    let truncated = ids.len() > effective_limit;
-    if truncated {
-        ids.truncate(effective_limit);
-    }
-    let rows = hydrate_rows_by_paths(&conn, &ids)?;
-    let available_properties = collect_available_properties(&rows);
+    let (config, available_properties, mut page) =
+        query_database(root, database_path, effective_limit, cursor, view_id, ctx)?;
+    if config.view.layout == "gallery" {
+        fill_covers(root, &mut page.rows);
+    }
+    let total_loaded = page.rows.len() as u32;
+    let truncated = page.next_cursor.is_some();
+    let next_cursor = page.next_cursor.as_ref().map(encode_cursor);
     Ok(DatabaseLoadResult {
         config,
-        rows,
+        rows: page.rows,
         available_properties,
         truncated,
-        total_loaded: ids.len() as u32,
+        total_loaded,
+        total_count: page.total_count,
+        next_cursor,
+        scan_truncated: page.scan_truncated,
     })
 }
 
@@ -301,3 +784,123 @@ pub fn read_database_markdown(root: &Path, path: &str) -> Result<String, String>
     }
     Ok(markdown)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::index_note;
+
+    fn source(kind: &str, value: &str) -> DatabaseSource {
+        DatabaseSource {
+            kind: kind.to_string(),
+            value: value.to_string(),
+            recursive: true,
+            property: None,
+            folder: None,
+            operator: None,
+            sources: Vec::new(),
+        }
+    }
+
+    fn matching(conn: &Connection, source: &DatabaseSource) -> Vec<String> {
+        matching_on(conn, source, &DateContext::default())
+    }
+
+    fn matching_on(conn: &Connection, source: &DatabaseSource, ctx: &DateContext) -> Vec<String> {
+        let (clause, params) = source_clause(conn, source, ctx).expect("source should compile");
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT n.id FROM notes n WHERE {clause} ORDER BY n.id"
+            ))
+            .unwrap();
+        stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            row.get::<_, String>(0)
+        })
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+    }
+
+    #[test]
+    fn compiles_property_link_task_and_composite_sources() {
+        let root =
+            std::env::temp_dir().join(format!("glyph-sources-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let today = DateContext::default().today_iso();
+        let notes = [
+            ("Reading List.md", "---\ntitle: Reading List\n---\n".to_string()),
+            (
+                "books/dune.md",
+                "---\ntitle: Dune\ntype: Book\n---\nOn the [[Reading List]]\n".to_string(),
+            ),
+            (
+                "books/old/emma.md",
+                "---\ntitle: Emma\ntype: [novel, book]\n---\n".to_string(),
+            ),
+            (
+                "work/plan.md",
+                format!("---\ntitle: Plan\n---\n- [ ] Ship 📅 {today}\n- [ ] Old 📅 2000-01-01\n"),
+            ),
+            (
+                "work/later.md",
+                "---\ntitle: Later\ntype: article\n---\n- [ ] Someday 📅 2999-01-01\n[[Reading List]]\n"
+                    .to_string(),
+            ),
+        ];
+        for (path, markdown) in &notes {
+            index_note(&root, path, markdown).unwrap();
+        }
+        let conn = open_db(&root).unwrap();
+
+        let mut books = source("property", "book");
+        books.property = Some("Type".to_string());
+        assert_eq!(
+            matching(&conn, &books),
+            vec!["books/dune.md", "books/old/emma.md"]
+        );
+        books.folder = Some("books".to_string());
+        books.recursive = false;
+        assert_eq!(matching(&conn, &books), vec!["books/dune.md"]);
+
+        let linking = source("link", "[[reading list]]");
+        assert_eq!(
+            matching(&conn, &linking),
+            vec!["books/dune.md", "work/later.md"]
+        );
+        assert_eq!(
+            matching(&conn, &source("link", "Reading List.md")),
+            vec!["books/dune.md", "work/later.md"]
+        );
+
+        assert_eq!(
+            matching(&conn, &source("tasks_due", "this week")),
+            vec!["work/plan.md"]
+        );
+        assert_eq!(
+            matching(&conn, &source("tasks_due", "overdue")),
+            vec!["work/plan.md"]
+        );
+        assert_eq!(
+            matching(&conn, &source("tasks_due", "")),
+            vec!["work/later.md", "work/plan.md"]
+        );
+        // Relative ranges follow the client's today, not the UTC date.
+        let client = DateContext::new(Some("2999-01-09"), Some("sunday")).unwrap();
+        assert_eq!(
+            matching_on(&conn, &source("tasks_due", "overdue"), &client),
+            vec!["work/later.md", "work/plan.md"]
+        );
+        assert!(matching_on(&conn, &source("tasks_due", "this week"), &client).is_empty());
+
+        let mut composite = source("composite", "");
+        composite.sources = vec![source("folder", "work"), linking.clone()];
+        assert_eq!(
+            matching(&conn, &composite),
+            vec!["books/dune.md", "work/later.md", "work/plan.md"]
+        );
+        composite.operator = Some("intersection".to_string());
+        assert_eq!(matching(&conn, &composite), vec!["work/later.md"]);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,578 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use rusqlite::{types::Value, Connection};
+
+use crate::dates::DateContext;
+use crate::index::links::normalize_rel_path;
+
+use super::config::parse_database_config;
+use super::formula::{cell_value, error_cell, FormulaValue};
+use super::query::{hydrate_rows_by_paths, read_database_markdown, source_clause};
+use super::types::{
+    DatabaseCellValue, DatabaseColumn, DatabaseConfig, DatabaseRelationTarget,
+    DatabaseRollupConfig, DatabaseRow,
+};
+
+/// A wikilink target as written in a property: a vault path (`[[a/b]]`,
+/// `[[b.md]]`) or a note title.
+#[derive(Debug, PartialEq)]
+pub(super) enum LinkTarget {
+    Path(String),
+    Title(String),
+}
+
+impl LinkTarget {
+    pub(super) fn parse(inner: &str) -> Option<Self> {
+        let inner = inner.trim();
+        let inner = inner.split('|').next().unwrap_or(inner).trim();
+        let inner = inner.split('#').next().unwrap_or(inner).trim();
+        if inner.is_empty() {
+            return None;
+        }
+        if inner.contains('/') || inner.ends_with(".md") {
+            let path = if inner.ends_with(".md") {
+                inner.to_string()
+            } else {
+                format!("{inner}.md")
+            };
+            normalize_rel_path(&path).map(Self::Path)
+        } else {
+            Some(Self::Title(inner.to_string()))
+        }
+    }
+
+    fn matches(&self, note_path: &str, title: &str) -> bool {
+        match self {
+            Self::Path(path) => path == note_path,
+            Self::Title(name) => {
+                let stem = Path::new(note_path)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or_default();
+                name.eq_ignore_ascii_case(title) || name.eq_ignore_ascii_case(stem)
+            }
+        }
+    }
+}
+
+fn wikilink_targets_in_text(text: &str, out: &mut Vec<LinkTarget>) {
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        let Some(end) = rest[start + 2..].find("]]") else {
+            break;
+        };
+        out.extend(LinkTarget::parse(&rest[start + 2..start + 2 + end]));
+        rest = &rest[start + 2 + end + 2..];
+    }
+}
+
+/// The wikilink targets of a property value, in order. An unquoted
+/// `[[Alpha]]` in YAML reads as a nested list, so a one-string list inside a
+/// list counts as a link too.
+fn wikilink_targets(value_json: &str) -> Vec<LinkTarget> {
+    fn walk(value: &serde_json::Value, nested: bool, out: &mut Vec<LinkTarget>) {
+        match value {
+            serde_json::Value::String(text) => {
+                let before = out.len();
+                wikilink_targets_in_text(text, out);
+                if nested && out.len() == before {
+                    out.extend(LinkTarget::parse(text));
+                }
+            }
+            serde_json::Value::Array(items) => match items.as_slice() {
+                [serde_json::Value::Array(inner)] if !nested => {
+                    if let [single @ serde_json::Value::String(_)] = inner.as_slice() {
+                        walk(single, true, out);
+                    } else {
+                        walk(&items[0], false, out);
+                    }
+                }
+                _ => {
+                    for item in items {
+                        match item {
+                            serde_json::Value::Array(inner) if inner.len() == 1 => {
+                                walk(&inner[0], true, out)
+                            }
+                            other => walk(other, false, out),
+                        }
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(value_json) {
+        walk(&value, false, &mut out);
+    }
+    out
+}
+
+fn placeholders(count: usize) -> String {
+    std::iter::repeat_n("?", count)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The notes another database's source covers, for relations restricted to
+/// that database.
+fn database_note_ids(
+    root: &Path,
+    conn: &Connection,
+    database_path: &str,
+    ctx: &DateContext,
+) -> Result<HashSet<String>, String> {
+    let config = parse_database_config(&read_database_markdown(root, database_path)?)?;
+    let (clause, params) = source_clause(conn, &config.source, ctx)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT n.id FROM notes n WHERE {clause} AND n.id != ?"
+        ))
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map(
+            rusqlite::params_from_iter(
+                params
+                    .iter()
+                    .chain(std::iter::once(&Value::from(database_path.to_string()))),
+            ),
+            |row| row.get::<_, String>(0),
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(ids)
+}
+
+/// Follows the wikilinks in each row's `key` property to the notes they
+/// resolved to in the `links` table.
+fn forward_relations(
+    conn: &Connection,
+    key: &str,
+    rows: &[DatabaseRow],
+) -> Result<HashMap<String, Vec<DatabaseRelationTarget>>, String> {
+    let ids = rows
+        .iter()
+        .map(|row| row.note_path.clone())
+        .collect::<Vec<_>>();
+    let marks = placeholders(ids.len());
+
+    let mut targets = HashMap::<String, Vec<LinkTarget>>::new();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT note_id, value_json FROM note_properties WHERE key = ? AND note_id IN ({marks})"
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut prop_rows = stmt
+        .query(rusqlite::params_from_iter(
+            std::iter::once(key).chain(ids.iter().map(String::as_str)),
+        ))
+        .map_err(|e| e.to_string())?;
+    while let Some(row) = prop_rows.next().map_err(|e| e.to_string())? {
+        let note_id = row.get::<_, String>(0).map_err(|e| e.to_string())?;
+        let value_json = row.get::<_, String>(1).map_err(|e| e.to_string())?;
+        targets.insert(note_id, wikilink_targets(&value_json));
+    }
+
+    let mut linked = HashMap::<String, Vec<DatabaseRelationTarget>>::new();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT DISTINCT l.from_id, l.to_id, n.title
+             FROM links l JOIN notes n ON n.id = l.to_id
+             WHERE l.kind = 'note' AND l.from_id IN ({marks})"
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut link_rows = stmt
+        .query(rusqlite::params_from_iter(ids.iter()))
+        .map_err(|e| e.to_string())?;
+    while let Some(row) = link_rows.next().map_err(|e| e.to_string())? {
+        let from_id = row.get::<_, String>(0).map_err(|e| e.to_string())?;
+        linked
+            .entry(from_id)
+            .or_default()
+            .push(DatabaseRelationTarget {
+                note_path: row.get(1).map_err(|e| e.to_string())?,
+                title: row.get(2).map_err(|e| e.to_string())?,
+            });
+    }
+
+    let mut out = HashMap::new();
+    for (note_id, targets) in targets {
+        let candidates = linked.remove(&note_id).unwrap_or_default();
+        let mut related = Vec::<DatabaseRelationTarget>::new();
+        for target in targets {
+            let found = candidates
+                .iter()
+                .find(|candidate| target.matches(&candidate.note_path, &candidate.title));
+            if let Some(found) = found {
+                if !related.iter().any(|seen| seen.note_path == found.note_path) {
+                    related.push(found.clone());
+                }
+            }
+        }
+        out.insert(note_id, related);
+    }
+    Ok(out)
+}
+
+/// Lists, for each row, the notes whose `key` property links to it.
+fn reverse_relations(
+    conn: &Connection,
+    key: &str,
+    rows: &[DatabaseRow],
+) -> Result<HashMap<String, Vec<DatabaseRelationTarget>>, String> {
+    let by_path = rows
+        .iter()
+        .map(|row| (row.note_path.as_str(), row))
+        .collect::<HashMap<_, _>>();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT DISTINCT l.to_id, l.from_id, src.title, np.value_json
+             FROM links l
+             JOIN notes src ON src.id = l.from_id
+             JOIN note_properties np ON np.note_id = l.from_id AND np.key = ?
+             WHERE l.kind = 'note' AND l.to_id IN ({})
+             ORDER BY src.title COLLATE NOCASE ASC, l.from_id ASC",
+            placeholders(rows.len())
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut link_rows = stmt
+        .query(rusqlite::params_from_iter(
+            std::iter::once(key).chain(rows.iter().map(|row| row.note_path.as_str())),
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut out = HashMap::<String, Vec<DatabaseRelationTarget>>::new();
+    while let Some(row) = link_rows.next().map_err(|e| e.to_string())? {
+        let to_id = row.get::<_, String>(0).map_err(|e| e.to_string())?;
+        let value_json = row.get::<_, String>(3).map_err(|e| e.to_string())?;
+        let Some(target_row) = by_path.get(to_id.as_str()) else {
+            continue;
+        };
+        let links_here = wikilink_targets(&value_json)
+            .iter()
+            .any(|target| target.matches(&target_row.note_path, &target_row.title));
+        if links_here {
+            out.entry(to_id).or_default().push(DatabaseRelationTarget {
+                note_path: row.get(1).map_err(|e| e.to_string())?,
+                title: row.get(2).map_err(|e| e.to_string())?,
+            });
+        }
+    }
+    Ok(out)
+}
+
+fn rollup_value(
+    rollup: &DatabaseRollupConfig,
+    related: &[&DatabaseRow],
+) -> Result<FormulaValue, String> {
+    let property = rollup.property.as_deref().unwrap_or("title");
+    let values = related
+        .iter()
+        .map(|row| match property {
+            "title" => FormulaValue::Text(row.title.clone()),
+            key => row
+                .properties
+                .get(key)
+                .map(|cell| cell_value(cell, None))
+                .unwrap_or(FormulaValue::Empty),
+        })
+        .collect::<Vec<_>>();
+    let filled = values.iter().filter(|value| !value.is_empty());
+    let numbers = || {
+        filled
+            .clone()
+            .map(FormulaValue::as_number)
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    Ok(match rollup.function.as_str() {
+        "count" => FormulaValue::Number(related.len() as f64),
+        "count_values" => FormulaValue::Number(filled.count() as f64),
+        "sum" => FormulaValue::Number(numbers()?.iter().sum()),
+        "average" | "avg" => {
+            let numbers = numbers()?;
+            if numbers.is_empty() {
+                FormulaValue::Empty
+            } else {
+                FormulaValue::Number(numbers.iter().sum::<f64>() / numbers.len() as f64)
+            }
+        }
+        "min" => filled
+            .min_by(|a, b| a.compare(b))
+            .cloned()
+            .unwrap_or(FormulaValue::Empty),
+        "max" => filled
+            .max_by(|a, b| a.compare(b))
+            .cloned()
+            .unwrap_or(FormulaValue::Empty),
+        "percent_checked" => {
+            if values.is_empty() {
+                FormulaValue::Empty
+            } else {
+                let checked = values.iter().filter(|value| value.truthy()).count();
+                let percent = checked as f64 * 100.0 / values.len() as f64;
+                FormulaValue::Number((percent * 100.0).round() / 100.0)
+            }
+        }
+        other => return Err(format!("unknown rollup function '{other}'")),
+    })
+}
+
+fn rollup_cell(
+    rollup: Option<&DatabaseRollupConfig>,
+    row: &DatabaseRow,
+    related: &HashMap<String, DatabaseRow>,
+) -> DatabaseCellValue {
+    let Some(rollup) = rollup else {
+        return error_cell("rollup column has no configuration".to_string());
+    };
+    let Some(targets) = row.relations.get(&rollup.relation_column) else {
+        return error_cell(format!(
+            "unknown relation column '{}'",
+            rollup.relation_column
+        ));
+    };
+    let related_rows = targets
+        .iter()
+        .filter_map(|target| related.get(&target.note_path))
+        .collect::<Vec<_>>();
+    match rollup_value(rollup, &related_rows) {
+        Ok(value) => value.into_cell(),
+        Err(message) => error_cell(message),
+    }
+}
+
+/// Fills `row.relations` for every relation column and `row.computed` for
+/// every rollup column of `config`.
+pub(super) fn resolve_relations(
+    root: &Path,
+    conn: &Connection,
+    config: &DatabaseConfig,
+    ctx: &DateContext,
+    rows: &mut [DatabaseRow],
+) -> Result<(), String> {
+    let relation_columns = config
+        .columns
+        .iter()
+        .filter(|column| column.column_type == "relation")
+        .collect::<Vec<&DatabaseColumn>>();
+    if rows.is_empty() || relation_columns.is_empty() {
+        return Ok(());
+    }
+
+    for column in relation_columns {
+        let Some(key) = column.property_key.as_deref().filter(|key| !key.is_empty()) else {
+            continue;
+        };
+        let relation = column.relation.clone().unwrap_or_default();
+        let mut related = if relation.reverse {
+            reverse_relations(conn, key, rows)?
+        } else {
+            forward_relations(conn, key, rows)?
+        };
+        let allowed = match relation.database.as_deref().filter(|path| !path.is_empty()) {
+            Some(path) => Some(database_note_ids(root, conn, path, ctx)?),
+            None => None,
+        };
+        for row in rows.iter_mut() {
+            let mut targets = related.remove(&row.note_path).unwrap_or_default();
+            if let Some(allowed) = &allowed {
+                targets.retain(|target| allowed.contains(&target.note_path));
+            }
+            row.relations.insert(column.id.clone(), targets);
+        }
+    }
+
+    let rollups = config
+        .columns
+        .iter()
+        .filter(|column| column.column_type == "rollup")
+        .collect::<Vec<_>>();
+    if rollups.is_empty() {
+        return Ok(());
+    }
+    let mut related_paths = rows
+        .iter()
+        .flat_map(|row| row.relations.values().flatten())
+        .map(|target| target.note_path.clone())
+        .collect::<Vec<_>>();
+    related_paths.sort();
+    related_paths.dedup();
+    let related = hydrate_rows_by_paths(conn, &related_paths)?
+        .into_iter()
+        .map(|row| (row.note_path.clone(), row))
+        .collect::<HashMap<_, _>>();
+    for row in rows.iter_mut() {
+        let cells = rollups
+            .iter()
+            .map(|column| {
+                (
+                    column.id.clone(),
+                    rollup_cell(column.rollup.as_ref(), row, &related),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        row.computed.extend(cells);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::config::starter_database_config;
+    use crate::database::types::DatabaseRelationConfig;
+
+    fn column(id: &str, column_type: &str, property_key: Option<&str>) -> DatabaseColumn {
+        DatabaseColumn {
+            id: id.to_string(),
+            column_type: column_type.to_string(),
+            label: id.to_string(),
+            icon: None,
+            width: None,
+            visible: true,
+            property_key: property_key.map(str::to_string),
+            property_kind: None,
+            formula: None,
+            relation: None,
+            rollup: None,
+        }
+    }
+
+    fn insert_note(conn: &Connection, path: &str, title: &str, props: &[(&str, &str, &str)]) {
+        conn.execute(
+            "INSERT INTO notes(id, title, created, updated, path, etag, preview) VALUES(?, ?, '', '', ?, '', '')",
+            rusqlite::params![path, title, path],
+        )
+        .unwrap();
+        for (ordinal, (key, value_type, value_json)) in props.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO note_properties(note_id, key, value_type, value_text, value_json, ordinal) VALUES(?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    path,
+                    key,
+                    value_type,
+                    value_json.trim_matches('"'),
+                    value_json,
+                    ordinal as i64
+                ],
+            )
+            .unwrap();
+        }
+    }
+
+    fn insert_link(conn: &Connection, from: &str, to: &str) {
+        conn.execute(
+            "INSERT INTO links(from_id, to_id, to_title, kind) VALUES(?, ?, NULL, 'note')",
+            rusqlite::params![from, to],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn parses_wikilink_targets_from_property_values() {
+        assert_eq!(
+            wikilink_targets(r#"["[[Alpha|A]]", "[[work/Beta#Plan]]"]"#),
+            vec![
+                LinkTarget::Title("Alpha".to_string()),
+                LinkTarget::Path("work/Beta.md".to_string()),
+            ]
+        );
+        assert_eq!(
+            wikilink_targets(r#"[["Alpha"]]"#),
+            vec![LinkTarget::Title("Alpha".to_string())]
+        );
+    }
+
+    #[test]
+    fn resolves_forward_and_reverse_relations_with_rollups() {
+        let root =
+            std::env::temp_dir().join(format!("glyph-relations-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let conn = crate::index::open_db(&root).unwrap();
+        insert_note(&conn, "projects/Alpha.md", "Alpha", &[]);
+        insert_note(
+            &conn,
+            "tasks/one.md",
+            "One",
+            &[
+                ("project", "text", r#""[[Alpha]]""#),
+                ("done", "checkbox", "true"),
+                ("points", "number", "3"),
+            ],
+        );
+        insert_note(
+            &conn,
+            "tasks/two.md",
+            "Two",
+            &[
+                ("project", "text", r#""[[projects/Alpha]]""#),
+                ("done", "checkbox", "false"),
+                ("points", "number", "5"),
+            ],
+        );
+        insert_note(
+            &conn,
+            "tasks/three.md",
+            "Three",
+            &[("see_also", "text", r#""[[Alpha]]""#)],
+        );
+        for from in ["tasks/one.md", "tasks/two.md", "tasks/three.md"] {
+            insert_link(&conn, from, "projects/Alpha.md");
+        }
+
+        let mut tasks = column("tasks", "relation", Some("project"));
+        tasks.relation = Some(DatabaseRelationConfig {
+            reverse: true,
+            database: None,
+        });
+        let mut progress = column("progress", "rollup", None);
+        progress.rollup = Some(DatabaseRollupConfig {
+            relation_column: "tasks".to_string(),
+            property: Some("done".to_string()),
+            function: "percent_checked".to_string(),
+        });
+        let mut points = column("points", "rollup", None);
+        points.rollup = Some(DatabaseRollupConfig {
+            relation_column: "tasks".to_string(),
+            property: Some("points".to_string()),
+            function: "sum".to_string(),
+        });
+        let mut config = starter_database_config("projects");
+        config.columns = vec![tasks, progress, points];
+        let mut rows = hydrate_rows_by_paths(&conn, &["projects/Alpha.md".to_string()]).unwrap();
+        resolve_relations(&root, &conn, &config, &DateContext::default(), &mut rows).unwrap();
+        let alpha = &rows[0];
+        let titles = alpha.relations["tasks"]
+            .iter()
+            .map(|target| target.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["One", "Two"]);
+        assert_eq!(alpha.computed["progress"].value_text.as_deref(), Some("50"));
+        assert_eq!(alpha.computed["points"].value_text.as_deref(), Some("8"));
+
+        let mut forward = config;
+        forward.columns = vec![column("project", "relation", Some("project"))];
+        let mut rows = hydrate_rows_by_paths(
+            &conn,
+            &["tasks/one.md".to_string(), "tasks/three.md".to_string()],
+        )
+        .unwrap();
+        resolve_relations(&root, &conn, &forward, &DateContext::default(), &mut rows).unwrap();
+        for row in &rows {
+            let expected = if row.note_path == "tasks/one.md" {
+                vec!["projects/Alpha.md"]
+            } else {
+                Vec::new()
+            };
+            let related = row.relations["project"]
+                .iter()
+                .map(|target| target.note_path.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(related, expected);
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -2,6 +2,8 @@ use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
+use crate::notes::property_schema::{PropertyDefinition, PropertyError};
+
 fn default_true() -> bool {
     true
 }
@@ -17,10 +19,24 @@ fn default_database_layout() -> String {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct DatabaseSource {
+    /// `folder`, `tag`, `search`, `property`, `link`, `tasks_due` or
+    /// `composite`.
     pub kind: String,
     pub value: String,
+    /// For `folder` sources and the `folder` scope: whether subfolders count.
     #[serde(default = "default_recursive")]
     pub recursive: bool,
+    /// Property key that `property` sources match `value` against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub property: Option<String>,
+    /// Limits any non-folder source to the notes under this folder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    /// `union` (the default) or `intersection` of a composite's `sources`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<DatabaseSource>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +45,10 @@ pub struct DatabaseNewNoteConfig {
     pub folder: String,
     #[serde(default)]
     pub title_prefix: String,
+    /// Note whose frontmatter and body seed every new row, with `{{title}}`,
+    /// `{{date}}` and `{{database}}` expanded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +58,12 @@ pub struct DatabaseViewState {
     pub layout: String,
     #[serde(default)]
     pub board_group_by: Option<String>,
+    /// Column whose date places rows on the `calendar` layout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar_date_column: Option<String>,
+    /// `month` (the default) or `week`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar_range: Option<String>,
 }
 
 impl Default for DatabaseViewState {
@@ -45,6 +71,8 @@ impl Default for DatabaseViewState {
         Self {
             layout: default_database_layout(),
             board_group_by: None,
+            calendar_date_column: None,
+            calendar_range: None,
         }
     }
 }
@@ -66,6 +94,44 @@ pub struct DatabaseColumn {
     pub property_key: Option<String>,
     #[serde(default)]
     pub property_kind: Option<String>,
+    /// Expression for `formula` columns, see `database::formula`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formula: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation: Option<DatabaseRelationConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<DatabaseRollupConfig>,
+}
+
+/// A `relation` column follows the wikilinks in `property_key`. A reverse
+/// relation lists the notes whose `property_key` links to the row instead.
+/// `database` limits related notes to another database's source.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseRelationConfig {
+    #[serde(default)]
+    pub reverse: bool,
+    #[serde(default)]
+    pub database: Option<String>,
+}
+
+/// A `rollup` column aggregates `property` (or `title`) over the rows of the
+/// relation column `relation_column`. `function` is one of `count`,
+/// `count_values`, `sum`, `average`, `min`, `max` or `percent_checked`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseRollupConfig {
+    pub relation_column: String,
+    #[serde(default)]
+    pub property: Option<String>,
+    pub function: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseRelationTarget {
+    pub note_path: String,
+    pub title: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,6 +167,45 @@ pub struct DatabaseConfig {
     pub sorts: Vec<DatabaseSort>,
     #[serde(default)]
     pub filters: Vec<DatabaseFilter>,
+    /// Property definitions for this database, taking precedence over the
+    /// space's property schema.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<PropertyDefinition>,
+    /// Named views. `view`, `sorts`, `filters` and the column visibility and
+    /// widths above mirror the active one.
+    #[serde(default)]
+    pub views: Vec<DatabaseView>,
+    #[serde(default)]
+    pub active_view: Option<String>,
+}
+
+/// How one view shows a column; columns a view does not list keep their
+/// own visibility and width.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseViewColumn {
+    pub id: String,
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+}
+
+/// A named way of looking at a database: its layout, grouping, filters,
+/// sorts and column visibility.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseView {
+    pub id: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub state: DatabaseViewState,
+    #[serde(default)]
+    pub columns: Vec<DatabaseViewColumn>,
+    #[serde(default)]
+    pub sorts: Vec<DatabaseSort>,
+    #[serde(default)]
+    pub filters: Vec<DatabaseFilter>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -126,6 +231,15 @@ pub struct DatabaseRow {
     pub preview: String,
     pub tags: Vec<String>,
     pub properties: BTreeMap<String, DatabaseCellValue>,
+    /// Formula and rollup results keyed by column id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub computed: BTreeMap<String, DatabaseCellValue>,
+    /// Related rows of relation columns keyed by column id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub relations: BTreeMap<String, Vec<DatabaseRelationTarget>>,
+    /// First image of the note, filled for the `gallery` layout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -142,8 +256,69 @@ pub struct DatabaseLoadResult {
     pub config: DatabaseConfig,
     pub rows: Vec<DatabaseRow>,
     pub available_properties: Vec<DatabasePropertyOption>,
+    /// Whether more rows match than were returned; `next_cursor` loads them.
     pub truncated: bool,
     pub total_loaded: u32,
+    /// Rows matching the source and filters across all pages.
+    pub total_count: u32,
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+    /// Filters or sorts on computed columns only saw the first rows of a
+    /// large source, so `rows` and `total_count` may be incomplete.
+    #[serde(default)]
+    pub scan_truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseGroupAggregate {
+    pub column_id: String,
+    pub sum: f64,
+    pub average: Option<f64>,
+    /// Rows with a numeric value in the column.
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseGroup {
+    /// The grouped value; empty for rows without one.
+    pub key: String,
+    pub label: String,
+    pub note_paths: Vec<String>,
+    pub count: u32,
+    pub aggregates: Vec<DatabaseGroupAggregate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseGroupResult {
+    pub group_by: String,
+    pub groups: Vec<DatabaseGroup>,
+    pub total_count: u32,
+    /// Whether more rows matched than could be grouped.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseCalendarDay {
+    pub date: String,
+    pub note_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseCalendarResult {
+    pub date_column: String,
+    pub range: String,
+    pub start: String,
+    pub end: String,
+    /// Every day from `start` to `end`, including empty ones.
+    pub days: Vec<DatabaseCalendarDay>,
+    /// Matching rows without a date in the column.
+    pub undated_count: u32,
+    pub truncated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -152,3 +327,97 @@ pub struct DatabaseCreateRowResult {
     pub note_path: String,
     pub row: DatabaseRow,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseCsvImportResult {
+    pub note_paths: Vec<String>,
+    /// Ids of the property columns added to the config.
+    pub columns_added: Vec<String>,
+    pub config: DatabaseConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseCsvExportResult {
+    pub output_path: String,
+    pub row_count: u32,
+    /// Whether more rows matched than were exported.
+    pub truncated: bool,
+}
+
+/// A note as it was stored before a bulk operation touched it. An encrypted
+/// note keeps its envelope in `markdown`, so undo never carries plaintext.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseUndoNote {
+    pub note_path: String,
+    pub markdown: String,
+    #[serde(default)]
+    pub encrypted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseUndoMove {
+    pub from: String,
+    pub to: String,
+}
+
+/// Everything `database_undo` needs to reverse a bulk operation: notes to
+/// write back, notes it created and moves to walk back.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseUndoPayload {
+    #[serde(default)]
+    pub restore: Vec<DatabaseUndoNote>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+    #[serde(default)]
+    pub moves: Vec<DatabaseUndoMove>,
+}
+
+impl DatabaseUndoPayload {
+    pub fn is_empty(&self) -> bool {
+        self.restore.is_empty() && self.remove.is_empty() && self.moves.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseBulkResult {
+    /// Paths of the notes the operation wrote, created or moved to.
+    pub note_paths: Vec<String>,
+    /// Rows for `note_paths` that are still notes in the space.
+    pub rows: Vec<DatabaseRow>,
+    /// Reverses what was done, including when the operation stopped early.
+    pub undo: DatabaseUndoPayload,
+    /// Why the operation stopped before every note was handled.
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseNormalizeResult {
+    /// Rows whose frontmatter was rewritten.
+    pub note_paths: Vec<String>,
+    /// Values that could not be normalized and were left alone.
+    pub issues: Vec<PropertyError>,
+    pub undo: DatabaseUndoPayload,
+    /// Whether the database had more rows than were checked.
+    pub truncated: bool,
+    /// Why the rewrite stopped before every row was written.
+    pub errors: Vec<String>,
+}
+
+/// Payload of `database:rows_changed`: rows of an open database that a
+/// re-index added, changed or dropped.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DatabaseRowsChangedEvent {
+    pub database_path: String,
+    pub inserted: Vec<DatabaseRow>,
+    pub updated: Vec<DatabaseRow>,
+    pub removed: Vec<String>,
+}
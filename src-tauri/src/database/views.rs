@@ -0,0 +1,346 @@
+use std::path::Path;
+
+use tauri::State;
+
+use crate::space::state::RecentLocalChanges;
+use crate::space::SpaceState;
+
+use super::config::{parse_database_config, render_database_markdown};
+use super::mutations::write_markdown_note;
+use super::query::read_database_markdown;
+use super::types::{DatabaseColumn, DatabaseConfig, DatabaseView, DatabaseViewColumn};
+
+/// Id of the view a single-view config is upgraded into.
+const DEFAULT_VIEW_ID: &str = "default";
+
+fn layout_name(layout: &str) -> String {
+    let mut chars = layout.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Table".to_string(),
+    }
+}
+
+fn view_columns(columns: &[DatabaseColumn]) -> Vec<DatabaseViewColumn> {
+    columns
+        .iter()
+        .map(|column| DatabaseViewColumn {
+            id: column.id.clone(),
+            visible: column.visible,
+            width: column.width,
+        })
+        .collect()
+}
+
+/// A view holding the config's current layout, sorts, filters and columns.
+fn snapshot_view(config: &DatabaseConfig, id: String, name: String) -> DatabaseView {
+    DatabaseView {
+        id,
+        name,
+        state: config.view.clone(),
+        columns: view_columns(&config.columns),
+        sorts: config.sorts.clone(),
+        filters: config.filters.clone(),
+    }
+}
+
+/// Turns a config from before named views into one with a single view
+/// holding its layout, sorts and filters, and points `active_view` at an
+/// existing view.
+pub(super) fn upgrade_views(config: &mut DatabaseConfig) {
+    if config.views.is_empty() {
+        let name = layout_name(&config.view.layout);
+        let view = snapshot_view(config, DEFAULT_VIEW_ID.to_string(), name);
+        config.views.push(view);
+    }
+    let active_exists = config
+        .active_view
+        .as_deref()
+        .is_some_and(|id| config.views.iter().any(|view| view.id == id));
+    if !active_exists {
+        config.active_view = Some(config.views[0].id.clone());
+    }
+}
+
+/// Makes `view_id` (default: the active view) the active view and mirrors
+/// its layout, sorts, filters and columns into the top-level fields.
+pub(super) fn select_view(
+    config: &mut DatabaseConfig,
+    view_id: Option<&str>,
+) -> Result<(), String> {
+    upgrade_views(config);
+    let view_id = view_id
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .or(config.active_view.as_deref())
+        .unwrap_or(DEFAULT_VIEW_ID)
+        .to_string();
+    let view = config
+        .views
+        .iter()
+        .find(|view| view.id == view_id)
+        .cloned()
+        .ok_or_else(|| format!("database has no view '{view_id}'"))?;
+    for column in &mut config.columns {
+        if let Some(shown) = view.columns.iter().find(|shown| shown.id == column.id) {
+            column.visible = shown.visible;
+            column.width = shown.width.or(column.width);
+        }
+    }
+    config.view = view.state;
+    config.sorts = view.sorts;
+    config.filters = view.filters;
+    config.active_view = Some(view_id);
+    Ok(())
+}
+
+/// Copies the top-level layout, sorts, filters and columns back into the
+/// active view, so saving a config edited the single-view way updates it.
+pub(super) fn sync_active_view(config: &mut DatabaseConfig) {
+    upgrade_views(config);
+    let Some(index) = config
+        .views
+        .iter()
+        .position(|view| Some(view.id.as_str()) == config.active_view.as_deref())
+    else {
+        return;
+    };
+    let view = &config.views[index];
+    config.views[index] = snapshot_view(config, view.id.clone(), view.name.clone());
+}
+
+fn unique_view_id(config: &DatabaseConfig, name: &str) -> String {
+    let slug = name
+        .trim()
+        .to_lowercase()
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if slug.is_empty() {
+        "view".to_string()
+    } else {
+        slug
+    };
+    let mut id = base.clone();
+    let mut suffix = 2;
+    while config.views.iter().any(|view| view.id == id) {
+        id = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+    id
+}
+
+fn view_index(config: &DatabaseConfig, view_id: &str) -> Result<usize, String> {
+    config
+        .views
+        .iter()
+        .position(|view| view.id == view_id)
+        .ok_or_else(|| format!("database has no view '{view_id}'"))
+}
+
+fn view_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("view name cannot be empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Adds a view copying `copy_from` (default: the active view), optionally
+/// with another layout, and makes it active.
+fn create_view(
+    config: &mut DatabaseConfig,
+    name: &str,
+    layout: Option<&str>,
+    copy_from: Option<&str>,
+) -> Result<(), String> {
+    let name = view_name(name)?;
+    select_view(config, copy_from)?;
+    let id = unique_view_id(config, &name);
+    let mut view = snapshot_view(config, id.clone(), name);
+    if let Some(layout) = layout.map(str::trim).filter(|layout| !layout.is_empty()) {
+        view.state.layout = layout.to_string();
+    }
+    config.views.push(view);
+    config.active_view = Some(id);
+    Ok(())
+}
+
+fn update_view(config: &mut DatabaseConfig, mut view: DatabaseView) -> Result<(), String> {
+    let index = view_index(config, &view.id)?;
+    view.name = view_name(&view.name)?;
+    config.views[index] = view;
+    Ok(())
+}
+
+fn delete_view(config: &mut DatabaseConfig, view_id: &str) -> Result<(), String> {
+    let index = view_index(config, view_id)?;
+    if config.views.len() == 1 {
+        return Err("a database needs at least one view".to_string());
+    }
+    config.views.remove(index);
+    if config.active_view.as_deref() == Some(view_id) {
+        config.active_view = Some(config.views[index.min(config.views.len() - 1)].id.clone());
+    }
+    Ok(())
+}
+
+/// Applies `edit` to a database's views, saves the note and returns the
+/// config with the resulting active view selected.
+fn edit_views(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    path: &str,
+    edit: impl FnOnce(&mut DatabaseConfig) -> Result<(), String>,
+) -> Result<DatabaseConfig, String> {
+    let existing = read_database_markdown(root, path)?;
+    let mut config = parse_database_config(&existing)?;
+    edit(&mut config)?;
+    select_view(&mut config, None)?;
+    let next = render_database_markdown(path, &existing, &config)?;
+    write_markdown_note(root, recent_local_changes, path, &next)?;
+    Ok(config)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_view_create(
+    state: State<'_, SpaceState>,
+    path: String,
+    name: String,
+    layout: Option<String>,
+    copy_from: Option<String>,
+) -> Result<DatabaseConfig, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        edit_views(&root, &recent_local_changes, &path, |config| {
+            create_view(config, &name, layout.as_deref(), copy_from.as_deref())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Replaces the view with the same id.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_view_update(
+    state: State<'_, SpaceState>,
+    path: String,
+    view: DatabaseView,
+) -> Result<DatabaseConfig, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        edit_views(&root, &recent_local_changes, &path, |config| {
+            update_view(config, view)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_view_delete(
+    state: State<'_, SpaceState>,
+    path: String,
+    view_id: String,
+) -> Result<DatabaseConfig, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        edit_views(&root, &recent_local_changes, &path, |config| {
+            delete_view(config, &view_id)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_view_set_active(
+    state: State<'_, SpaceState>,
+    path: String,
+    view_id: String,
+) -> Result<DatabaseConfig, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        edit_views(&root, &recent_local_changes, &path, |config| {
+            view_index(config, &view_id)?;
+            config.active_view = Some(view_id);
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Reorders views to follow `view_ids`; views left out keep their order
+/// after the listed ones.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn database_view_reorder(
+    state: State<'_, SpaceState>,
+    path: String,
+    view_ids: Vec<String>,
+) -> Result<DatabaseConfig, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        edit_views(&root, &recent_local_changes, &path, |config| {
+            config.views.sort_by_key(|view| {
+                view_ids
+                    .iter()
+                    .position(|id| *id == view.id)
+                    .unwrap_or(usize::MAX)
+            });
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGACY: &str = "---\ntitle: Tasks\nglyph:\n  kind: database\n  version: 1\n  database:\n    source:\n      kind: folder\n      value: tasks\n    new_note:\n      folder: tasks\n    view:\n      layout: board\n      board_group_by: property:status\n    columns:\n      - id: title\n        type: title\n        label: Title\n        width: 320\n      - id: property:due\n        type: property\n        label: Due\n        property_key: due\n    sorts:\n      - column_id: title\n        direction: asc\n    filters: []\n---\n";
+
+    #[test]
+    fn upgrades_single_view_configs_and_switches_views() {
+        let mut config = parse_database_config(LEGACY).unwrap();
+        assert_eq!(config.views.len(), 1);
+        assert_eq!(config.views[0].id, "default");
+        assert_eq!(config.views[0].name, "Board");
+        assert_eq!(config.active_view.as_deref(), Some("default"));
+        assert_eq!(config.views[0].sorts.len(), 1);
+
+        create_view(&mut config, "This week", Some("table"), None).unwrap();
+        select_view(&mut config, None).unwrap();
+        assert_eq!(config.active_view.as_deref(), Some("this-week"));
+        assert_eq!(config.view.layout, "table");
+        assert_eq!(config.sorts.len(), 1);
+
+        // Editing the top-level fields the single-view way lands in the
+        // active view only.
+        config.sorts.clear();
+        config.columns[1].visible = false;
+        let rendered = render_database_markdown("tasks.md", LEGACY, &config).unwrap();
+        assert!(rendered.contains("version: 2"));
+        let mut reparsed = parse_database_config(&rendered).unwrap();
+        assert!(reparsed.sorts.is_empty());
+        assert!(!reparsed.columns[1].visible);
+
+        select_view(&mut reparsed, Some("default")).unwrap();
+        assert_eq!(reparsed.view.layout, "board");
+        assert_eq!(reparsed.sorts.len(), 1);
+        assert!(reparsed.columns[1].visible);
+        assert_eq!(reparsed.columns[0].width, Some(320));
+
+        assert!(select_view(&mut reparsed, Some("missing")).is_err());
+        delete_view(&mut reparsed, "default").unwrap();
+        assert_eq!(reparsed.active_view.as_deref(), Some("this-week"));
+        assert!(delete_view(&mut reparsed, "this-week").is_err());
+    }
+}
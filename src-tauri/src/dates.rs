@@ -0,0 +1,405 @@
+use time::{Date, Duration, Month, OffsetDateTime, Weekday};
+
+/// Offsets past roughly ten thousand years are rejected before any date
+/// arithmetic, which would otherwise overflow.
+const MAX_OFFSET_DAYS: u64 = 3_650_000;
+const MAX_OFFSET_MONTHS: u64 = 120_000;
+
+fn out_of_range() -> String {
+    "date out of range".to_string()
+}
+
+/// The reference point for relative dates: what "today" is and which day a
+/// week starts on (for "next week", "end of week", "this friday", ...).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateContext {
+    pub today: Date,
+    pub week_start: Weekday,
+}
+
+impl Default for DateContext {
+    fn default() -> Self {
+        Self {
+            today: OffsetDateTime::now_utc().date(),
+            week_start: Weekday::Monday,
+        }
+    }
+}
+
+impl DateContext {
+    /// Builds a context from the frontend's local `today` (ISO) and
+    /// week-start name, falling back to the UTC date and Monday.
+    pub fn new(today: Option<&str>, week_start: Option<&str>) -> Result<Self, String> {
+        let mut ctx = Self::default();
+        if let Some(today) = today.map(str::trim).filter(|t| !t.is_empty()) {
+            ctx.today = date_from_iso(today).ok_or_else(|| "invalid today date".to_string())?;
+        }
+        if let Some(week_start) = week_start.map(str::trim).filter(|w| !w.is_empty()) {
+            ctx.week_start = parse_weekday(&week_start.to_lowercase())
+                .ok_or_else(|| format!("invalid week start '{week_start}'"))?;
+        }
+        Ok(ctx)
+    }
+
+    pub fn today_iso(&self) -> String {
+        date_to_iso(self.today)
+    }
+
+    pub fn start_of_week(&self, date: Date) -> Date {
+        date - Duration::days(days_until(self.week_start, date.weekday()))
+    }
+}
+
+pub fn date_from_iso(raw: &str) -> Option<Date> {
+    let bytes = raw.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let year = raw[0..4].parse::<i32>().ok()?;
+    let month = Month::try_from(raw[5..7].parse::<u8>().ok()?).ok()?;
+    let day = raw[8..10].parse::<u8>().ok()?;
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+pub fn date_to_iso(date: Date) -> String {
+    format!(
+        "{:04}-{:02}-{:02}",
+        date.year(),
+        u8::from(date.month()),
+        date.day()
+    )
+}
+
+pub fn shift_date(date: &str, days: i64) -> Result<String, String> {
+    let date = date_from_iso(date).ok_or_else(|| format!("invalid date '{date}'"))?;
+    add_days(date, days).map(date_to_iso)
+}
+
+pub fn add_days(date: Date, days: i64) -> Result<Date, String> {
+    if days.unsigned_abs() > MAX_OFFSET_DAYS {
+        return Err(out_of_range());
+    }
+    date.checked_add(Duration::days(days))
+        .ok_or_else(out_of_range)
+}
+
+/// Adds `months`, clamping the day to the length of the target month.
+pub fn add_months(date: Date, months: i64) -> Result<Date, String> {
+    if months.unsigned_abs() > MAX_OFFSET_MONTHS {
+        return Err(out_of_range());
+    }
+    let index = date.year() as i64 * 12 + (u8::from(date.month()) as i64 - 1) + months;
+    let year = i32::try_from(index.div_euclid(12)).map_err(|_| out_of_range())?;
+    let month = Month::try_from((index.rem_euclid(12) + 1) as u8).map_err(|e| e.to_string())?;
+    let day = date.day().min(month.length(year));
+    Date::from_calendar_date(year, month, day).map_err(|_| out_of_range())
+}
+
+fn parse_weekday(raw: &str) -> Option<Weekday> {
+    match raw {
+        "monday" | "mon" => Some(Weekday::Monday),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tuesday),
+        "wednesday" | "wed" => Some(Weekday::Wednesday),
+        "thursday" | "thu" | "thur" | "thurs" => Some(Weekday::Thursday),
+        "friday" | "fri" => Some(Weekday::Friday),
+        "saturday" | "sat" => Some(Weekday::Saturday),
+        "sunday" | "sun" => Some(Weekday::Sunday),
+        _ => None,
+    }
+}
+
+/// Days from `from` forward to the next `to` (0 when they match).
+fn days_until(from: Weekday, to: Weekday) -> i64 {
+    (to.number_days_from_monday() as i64 - from.number_days_from_monday() as i64).rem_euclid(7)
+}
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+fn parse_unit(raw: &str) -> Option<Unit> {
+    match raw {
+        "d" | "day" | "days" => Some(Unit::Day),
+        "w" | "wk" | "wks" | "week" | "weeks" => Some(Unit::Week),
+        "m" | "mo" | "month" | "months" => Some(Unit::Month),
+        "y" | "yr" | "yrs" | "year" | "years" => Some(Unit::Year),
+        _ => None,
+    }
+}
+
+fn offset(date: Date, amount: i64, unit: Unit) -> Result<Date, String> {
+    match unit {
+        Unit::Day => add_days(date, amount),
+        Unit::Week => add_days(date, amount.checked_mul(7).ok_or_else(out_of_range)?),
+        Unit::Month => add_months(date, amount),
+        Unit::Year => add_months(date, amount.checked_mul(12).ok_or_else(out_of_range)?),
+    }
+}
+
+/// Compact offsets such as `2w`, `+3d` or `-1m`.
+fn parse_compact_offset(raw: &str) -> Option<(i64, Unit)> {
+    let (sign, rest) = match raw.as_bytes().first()? {
+        b'+' => (1, &raw[1..]),
+        b'-' => (-1, &raw[1..]),
+        _ => (1, raw),
+    };
+    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    let amount = rest[..digits].parse::<i64>().ok()?;
+    let unit = parse_unit(rest[digits..].trim())?;
+    Some((sign * amount, unit))
+}
+
+fn period_start(ctx: &DateContext, date: Date, unit: Unit) -> Result<Date, String> {
+    match unit {
+        Unit::Day => Ok(date),
+        Unit::Week => Ok(ctx.start_of_week(date)),
+        Unit::Month => date.replace_day(1).map_err(|e| e.to_string()),
+        Unit::Year => {
+            Date::from_calendar_date(date.year(), Month::January, 1).map_err(|e| e.to_string())
+        }
+    }
+}
+
+fn period_end(ctx: &DateContext, date: Date, unit: Unit) -> Result<Date, String> {
+    let start = period_start(ctx, date, unit)?;
+    add_days(offset(start, 1, unit)?, -1)
+}
+
+/// `this`/`next`/`last` + a period, e.g. "next week" or "last month".
+fn parse_relative_period(words: &[&str]) -> Option<(i64, Unit)> {
+    let (shift, unit) = match words {
+        [unit] => (0, *unit),
+        ["this", unit] => (0, *unit),
+        ["next", unit] => (1, *unit),
+        ["last", unit] | ["previous", unit] => (-1, *unit),
+        _ => return None,
+    };
+    Some((shift, parse_unit(unit)?))
+}
+
+fn weekday_phrase<'a>(words: &[&'a str]) -> Option<(Option<&'a str>, Weekday)> {
+    match words {
+        [day] => parse_weekday(day).map(|day| (None, day)),
+        [qualifier @ ("this" | "next" | "last"), day] => {
+            parse_weekday(day).map(|day| (Some(*qualifier), day))
+        }
+        _ => None,
+    }
+}
+
+fn weekday_date(ctx: &DateContext, qualifier: Option<&str>, day: Weekday) -> Result<Date, String> {
+    let today = ctx.today;
+    match qualifier {
+        None => add_days(today, days_until(today.weekday(), day)),
+        Some("this") => add_days(ctx.start_of_week(today), days_until(ctx.week_start, day)),
+        Some("next") => add_days(
+            today,
+            match days_until(today.weekday(), day) {
+                0 => 7,
+                days => days,
+            },
+        ),
+        _ => add_days(
+            today,
+            -match days_until(day, today.weekday()) {
+                0 => 7,
+                days => days,
+            },
+        ),
+    }
+}
+
+/// Turns a period phrase (`this week`, `next month`, `last year`) into its
+/// first and last ISO dates.
+pub fn parse_natural_period(raw: &str, ctx: &DateContext) -> Option<(String, String)> {
+    let phrase = raw.trim().to_lowercase();
+    let words = phrase.split_whitespace().collect::<Vec<_>>();
+    if words.len() != 2 {
+        return None;
+    }
+    let (shift, unit) = parse_relative_period(&words)?;
+    let anchor = offset(ctx.today, shift, unit).ok()?;
+    Some((
+        date_to_iso(period_start(ctx, anchor, unit).ok()?),
+        date_to_iso(period_end(ctx, anchor, unit).ok()?),
+    ))
+}
+
+/// Turns a date phrase into an ISO `YYYY-MM-DD` date relative to `ctx`.
+///
+/// Accepts ISO dates, `today`/`tomorrow`/`yesterday`, weekday names
+/// (`fri` is the next Friday on or after today, `next fri` the first one
+/// after today, `this fri` the one in the current week, `last fri` the most
+/// recent one before today), offsets (`in 3 days`, `2 weeks ago`, `2w`,
+/// `-1m`), periods (`next week`, `next month` start on their first day) and
+/// boundaries (`end of month`, `start of next week`, `eow`, `eom`, `eoy`).
+pub fn parse_natural_date(raw: &str, ctx: &DateContext) -> Result<String, String> {
+    let phrase = raw.trim().to_lowercase();
+    if let Some(date) = date_from_iso(&phrase) {
+        return Ok(date_to_iso(date));
+    }
+    let words = phrase.split_whitespace().collect::<Vec<_>>();
+    if let Some((qualifier, day)) = weekday_phrase(&words) {
+        return weekday_date(ctx, qualifier, day).map(date_to_iso);
+    }
+    let today = ctx.today;
+    let date = match words.as_slice() {
+        [] => None,
+        ["today"] | ["tod"] | ["now"] => Some(today),
+        ["tomorrow"] | ["tmr"] | ["tmrw"] | ["tom"] => Some(add_days(today, 1)?),
+        ["yesterday"] => Some(add_days(today, -1)?),
+        ["weekend"] | ["this", "weekend"] => Some(match today.weekday() {
+            Weekday::Sunday => today,
+            weekday => add_days(today, days_until(weekday, Weekday::Saturday))?,
+        }),
+        ["eow"] => Some(period_end(ctx, today, Unit::Week)?),
+        ["eom"] => Some(period_end(ctx, today, Unit::Month)?),
+        ["eoy"] => Some(period_end(ctx, today, Unit::Year)?),
+        ["in", amount, unit] => match (amount.parse::<i64>(), parse_unit(unit)) {
+            (Ok(amount), Some(unit)) => Some(offset(today, amount, unit)?),
+            _ => None,
+        },
+        [amount, unit, "ago"] => match (amount.parse::<i64>(), parse_unit(unit)) {
+            (Ok(amount), Some(unit)) => Some(offset(
+                today,
+                amount.checked_neg().ok_or_else(out_of_range)?,
+                unit,
+            )?),
+            _ => None,
+        },
+        ["end", "of", rest @ ..] | ["start", "of", rest @ ..] | ["beginning", "of", rest @ ..] => {
+            match parse_relative_period(rest) {
+                Some((shift, unit)) => {
+                    let anchor = offset(today, shift, unit)?;
+                    Some(if words[0] == "end" {
+                        period_end(ctx, anchor, unit)?
+                    } else {
+                        period_start(ctx, anchor, unit)?
+                    })
+                }
+                None => None,
+            }
+        }
+        [compact] => match parse_compact_offset(compact) {
+            Some((amount, unit)) => Some(offset(today, amount, unit)?),
+            None => None,
+        },
+        [_, _] => match parse_relative_period(&words) {
+            Some((shift, unit)) => Some(period_start(ctx, offset(today, shift, unit)?, unit)?),
+            None => None,
+        },
+        _ => None,
+    };
+    date.map(date_to_iso)
+        .ok_or_else(|| format!("could not understand date '{}'", raw.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_natural_date, parse_natural_period, shift_date, DateContext};
+
+    // 2025-03-05 is a Wednesday.
+    fn ctx(week_start: &str) -> DateContext {
+        DateContext::new(Some("2025-03-05"), Some(week_start)).expect("context should build")
+    }
+
+    fn parse(raw: &str) -> String {
+        parse_natural_date(raw, &ctx("monday")).expect("date should parse")
+    }
+
+    #[test]
+    fn parses_relative_day_phrases() {
+        assert_eq!(parse("2025-12-31"), "2025-12-31");
+        assert_eq!(parse("Tomorrow"), "2025-03-06");
+        assert_eq!(parse("yesterday"), "2025-03-04");
+        assert_eq!(parse("fri"), "2025-03-07");
+        assert_eq!(parse("wed"), "2025-03-05");
+        assert_eq!(parse("next fri"), "2025-03-07");
+        assert_eq!(parse("next wednesday"), "2025-03-12");
+        assert_eq!(parse("last friday"), "2025-02-28");
+        assert_eq!(parse("this monday"), "2025-03-03");
+        assert_eq!(parse("weekend"), "2025-03-08");
+    }
+
+    #[test]
+    fn parses_offsets_and_period_boundaries() {
+        assert_eq!(parse("in 3 days"), "2025-03-08");
+        assert_eq!(parse("2w"), "2025-03-19");
+        assert_eq!(parse("-1d"), "2025-03-04");
+        assert_eq!(parse("2 weeks ago"), "2025-02-19");
+        assert_eq!(parse("in 1 month"), "2025-04-05");
+        assert_eq!(parse("end of month"), "2025-03-31");
+        assert_eq!(parse("eom"), "2025-03-31");
+        assert_eq!(parse("end of next month"), "2025-04-30");
+        assert_eq!(parse("start of month"), "2025-03-01");
+        assert_eq!(parse("next month"), "2025-04-01");
+        assert_eq!(parse("next week"), "2025-03-10");
+        assert_eq!(parse("end of week"), "2025-03-09");
+        assert_eq!(parse("end of year"), "2025-12-31");
+        assert!(parse_natural_date("someday", &ctx("monday")).is_err());
+        assert!(parse_natural_date("2025-02-30", &ctx("monday")).is_err());
+    }
+
+    #[test]
+    fn parses_period_phrases_into_ranges() {
+        let monday = ctx("monday");
+        let period = |raw: &str| parse_natural_period(raw, &monday);
+        assert_eq!(
+            period("this week"),
+            Some(("2025-03-03".to_string(), "2025-03-09".to_string()))
+        );
+        assert_eq!(
+            period("next month"),
+            Some(("2025-04-01".to_string(), "2025-04-30".to_string()))
+        );
+        assert_eq!(period("tomorrow"), None);
+    }
+
+    #[test]
+    fn honours_week_start_setting() {
+        let sunday = ctx("sunday");
+        assert_eq!(
+            parse_natural_date("next week", &sunday).unwrap(),
+            "2025-03-09"
+        );
+        assert_eq!(
+            parse_natural_date("end of week", &sunday).unwrap(),
+            "2025-03-08"
+        );
+        assert_eq!(
+            parse_natural_date("this sunday", &sunday).unwrap(),
+            "2025-03-02"
+        );
+    }
+
+    #[test]
+    fn clamps_month_offsets_to_month_length() {
+        let ctx = DateContext::new(Some("2024-01-31"), None).unwrap();
+        assert_eq!(
+            parse_natural_date("in 1 month", &ctx).unwrap(),
+            "2024-02-29"
+        );
+        assert_eq!(parse_natural_date("1y", &ctx).unwrap(), "2025-01-31");
+    }
+
+    #[test]
+    fn rejects_offsets_that_overflow() {
+        let monday = ctx("monday");
+        let err = |raw: &str| parse_natural_date(raw, &monday);
+        let out_of_range = Err("date out of range".to_string());
+        assert_eq!(err("in 200000000000000 days"), out_of_range);
+        assert_eq!(err("9223372036854775807 weeks ago"), out_of_range);
+        assert_eq!(err("-9223372036854775807d"), out_of_range);
+        assert_eq!(err("in 9223372036854775807 months"), out_of_range);
+        assert_eq!(err("+2000000000000000000y"), out_of_range);
+        assert_eq!(shift_date("2025-03-05", i64::MIN), out_of_range);
+        assert_eq!(err("in 10000 days"), Ok("2052-07-21".to_string()));
+    }
+}
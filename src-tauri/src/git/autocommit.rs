@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use tracing::warn;
+
+use super::repo::{commit_all, is_merging, is_repo};
+use super::settings::read_git_settings;
+
+/// Starts the auto-committer for the space at `root`. Each message on the
+/// returned sender reports a change; once changes stop for the configured
+/// delay, everything is committed with a generated message. The thread
+/// ends, committing what is pending, when the sender is dropped.
+pub(crate) fn spawn_auto_committer(root: PathBuf) -> mpsc::Sender<()> {
+    let (tx, rx) = mpsc::channel::<()>();
+    std::thread::spawn(move || {
+        while rx.recv().is_ok() {
+            let settings = read_git_settings(&root);
+            if !settings.auto_commit {
+                continue;
+            }
+            let quiet = Duration::from_secs(settings.auto_commit_delay_secs.max(1));
+            let mut closed = false;
+            loop {
+                match rx.recv_timeout(quiet) {
+                    Ok(()) => continue,
+                    Err(mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        closed = true;
+                        break;
+                    }
+                }
+            }
+            // A pull that stopped on conflicts is left for the user to finish.
+            if read_git_settings(&root).auto_commit && is_repo(&root) && !is_merging(&root) {
+                if let Err(error) = commit_all(&root, None) {
+                    warn!("auto-commit failed: {error}");
+                }
+            }
+            if closed {
+                return;
+            }
+        }
+    });
+    tx
+}
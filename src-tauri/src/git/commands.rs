@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::history::commands::NoteHistoryRestoreResult;
+use crate::history::{self, DiffLine, VersionSource};
+use crate::space::state::mark_recent_local_change;
+use crate::space::SpaceState;
+use crate::space_fs::helpers::{deny_hidden_rel_path, etag_for, file_mtime_ms};
+use crate::{crypto, index, io_atomic, paths, utils};
+
+use super::repo::{self, GitCommit, GitRepoInfo, GitStatusEntry};
+use super::settings::{read_git_settings, write_git_settings, GitSettings};
+use super::sync::{self, GitSyncResult};
+
+const DEFAULT_LOG_LIMIT: usize = 100;
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GitFileDiff {
+    pub commit: GitCommit,
+    /// Where the file was at the commit.
+    pub path: String,
+    pub lines: Vec<DiffLine>,
+}
+
+fn checked_rel_path(path: &str) -> Result<String, String> {
+    let rel = PathBuf::from(path);
+    deny_hidden_rel_path(&rel)?;
+    Ok(utils::to_slash(&rel))
+}
+
+/// The commit `revision` in the log of `rel_path`, with the file's path at
+/// that commit.
+fn file_commit(root: &Path, rel_path: &str, revision: &str) -> Result<(GitCommit, String), String> {
+    repo::check_revision(revision)?;
+    let commit = repo::log(root, Some(rel_path), usize::MAX)?
+        .into_iter()
+        .find(|commit| commit.hash.starts_with(revision))
+        .ok_or_else(|| format!("no commit '{revision}' in the history of {rel_path}"))?;
+    let path = commit.path.clone().unwrap_or_else(|| rel_path.to_string());
+    Ok((commit, path))
+}
+
+#[tauri::command]
+pub async fn git_repo_info(state: State<'_, SpaceState>) -> Result<GitRepoInfo, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || repo::repo_info(&root))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn git_status(state: State<'_, SpaceState>) -> Result<Vec<GitStatusEntry>, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || repo::status(&root))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn git_settings_get(state: State<'_, SpaceState>) -> Result<GitSettings, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || Ok(read_git_settings(&root)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn git_settings_set(
+    state: State<'_, SpaceState>,
+    settings: GitSettings,
+) -> Result<GitSettings, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        write_git_settings(&root, &settings)?;
+        Ok(settings)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Commits every change in the space. Without a message one is generated
+/// from the changed files. Returns `None` when there was nothing to commit.
+#[tauri::command]
+pub async fn git_commit(
+    state: State<'_, SpaceState>,
+    message: Option<String>,
+) -> Result<Option<GitCommit>, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || repo::commit_all(&root, message.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Commits that touched a file, following renames, newest first.
+#[tauri::command]
+pub async fn git_file_log(
+    state: State<'_, SpaceState>,
+    path: String,
+    limit: Option<usize>,
+) -> Result<Vec<GitCommit>, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let rel_path = checked_rel_path(&path)?;
+        repo::log(&root, Some(&rel_path), limit.unwrap_or(DEFAULT_LOG_LIMIT))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// What `commit` changed in a file.
+#[tauri::command]
+pub async fn git_file_diff(
+    state: State<'_, SpaceState>,
+    path: String,
+    commit: String,
+) -> Result<GitFileDiff, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let rel_path = checked_rel_path(&path)?;
+        let (commit, path) = file_commit(&root, &rel_path, &commit)?;
+        let parent = format!("{}^", commit.hash);
+        let show = |rev: &str| -> Result<String, String> {
+            let text = repo::show_file(&root, rev, &path)?.unwrap_or_default();
+            crypto::open_text(&root, text.as_bytes())
+        };
+        let old = show(&parent)?;
+        let new = show(&commit.hash)?;
+        Ok(GitFileDiff {
+            lines: history::diff_lines(&old, &new),
+            commit,
+            path,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Writes a file back as it was at `commit`. The content being replaced is
+/// kept in the note history.
+#[tauri::command]
+pub async fn git_file_restore(
+    state: State<'_, SpaceState>,
+    path: String,
+    commit: String,
+) -> Result<NoteHistoryRestoreResult, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        let rel_path = checked_rel_path(&path)?;
+        let (commit, path_at_commit) = file_commit(&root, &rel_path, &commit)?;
+        let stored = repo::show_file(&root, &commit.hash, &path_at_commit)?
+            .ok_or_else(|| format!("{rel_path} was deleted in {}", commit.short_hash))?;
+        let text = crypto::open_text(&root, stored.as_bytes())?;
+        let abs = paths::join_under(&root, Path::new(&rel_path))?;
+        let previous = std::fs::read(&abs).ok();
+        let bytes = crypto::seal_text(
+            &root,
+            &rel_path,
+            previous.as_deref(),
+            crypto::is_encrypted(stored.as_bytes()),
+            &text,
+        )?;
+        if let Some(parent) = abs.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        mark_recent_local_change(&recent_local_changes, &rel_path);
+        io_atomic::write_atomic(&abs, &bytes).map_err(|e| e.to_string())?;
+        if utils::is_markdown_path(&abs) {
+            let _ = index::index_note(&root, &rel_path, &text);
+        }
+        let _ = history::record_write(
+            &root,
+            &rel_path,
+            previous.as_deref(),
+            &bytes,
+            VersionSource::Restore,
+        );
+        Ok(NoteHistoryRestoreResult {
+            etag: etag_for(&bytes),
+            mtime_ms: file_mtime_ms(&abs),
+            text,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Pulls from `remote`, or the configured remote, after committing local
+/// changes.
+#[tauri::command]
+pub async fn git_pull(
+    state: State<'_, SpaceState>,
+    remote: Option<String>,
+) -> Result<GitSyncResult, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let remote = remote.unwrap_or_else(|| read_git_settings(&root).remote);
+        sync::pull(&root, &remote)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Pushes to `remote`, or the configured remote, after committing local
+/// changes.
+#[tauri::command]
+pub async fn git_push(
+    state: State<'_, SpaceState>,
+    remote: Option<String>,
+) -> Result<GitSyncResult, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let remote = remote.unwrap_or_else(|| read_git_settings(&root).remote);
+        sync::push(&root, &remote)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,7 @@
+pub(crate) mod autocommit;
+pub mod commands;
+mod repo;
+mod settings;
+mod sync;
+
+pub(crate) use repo::{status_map, GitFileStatus};
@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use serde::Serialize;
+
+use crate::glyph_paths::GLYPH_DIR_NAME;
+
+const FALLBACK_NAME: &str = "Glyph";
+const FALLBACK_EMAIL: &str = "glyph@localhost";
+/// Files named in a generated commit message before it switches to a count.
+const MESSAGE_MAX_FILES: usize = 3;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitFileStatus {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    Conflicted,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct GitStatusEntry {
+    /// Relative to the space root.
+    pub path: String,
+    pub status: GitFileStatus,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct GitRepoInfo {
+    pub is_repo: bool,
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    pub changed_files: usize,
+    /// A pull stopped on conflicts that are not committed yet.
+    pub merging: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct GitCommit {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub time_ms: u64,
+    pub subject: String,
+    /// Where the file was at this commit, relative to the space root, for
+    /// per-file logs that follow renames.
+    pub path: Option<String>,
+}
+
+fn command(root: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(root)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("LC_ALL", "C");
+    command
+}
+
+fn failure(args: &[&str], output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    format!(
+        "git {} failed: {detail}",
+        args.first().copied().unwrap_or_default()
+    )
+}
+
+/// Runs git in `root` and returns its output whether or not it succeeded.
+pub(super) fn run(root: &Path, args: &[&str]) -> Result<Output, String> {
+    command(root)
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run git: {e}"))
+}
+
+/// Runs git in `root` and returns its stdout, or its stderr as the error.
+pub(super) fn git(root: &Path, args: &[&str]) -> Result<String, String> {
+    let output = run(root, args)?;
+    if !output.status.success() {
+        return Err(failure(args, &output));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Like [`git`], for commands that create commits: supplies an author when
+/// the repository and user config have none.
+pub(super) fn git_committing(root: &Path, args: &[&str]) -> Result<Output, String> {
+    let mut command = command(root);
+    let has_identity =
+        git(root, &["config", "user.email"]).is_ok_and(|email| !email.trim().is_empty());
+    if !has_identity {
+        command
+            .arg("-c")
+            .arg(format!("user.name={FALLBACK_NAME}"))
+            .arg("-c")
+            .arg(format!("user.email={FALLBACK_EMAIL}"));
+    }
+    command
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run git: {e}"))
+}
+
+pub fn is_repo(root: &Path) -> bool {
+    git(root, &["rev-parse", "--is-inside-work-tree"]).is_ok_and(|out| out.trim() == "true")
+}
+
+fn require_repo(root: &Path) -> Result<(), String> {
+    if is_repo(root) {
+        Ok(())
+    } else {
+        Err("space is not a git repository".to_string())
+    }
+}
+
+/// The space root relative to the repository root, with a trailing slash
+/// (empty when the space is the repository root).
+fn space_prefix(root: &Path) -> Result<String, String> {
+    Ok(git(root, &["rev-parse", "--show-prefix"])?
+        .trim()
+        .to_string())
+}
+
+fn strip_prefix(prefix: &str, repo_path: &str) -> Option<String> {
+    repo_path.strip_prefix(prefix).map(str::to_string)
+}
+
+/// Pathspec for everything in the space except Glyph's own data.
+fn space_pathspec() -> [String; 2] {
+    [".".to_string(), format!(":(exclude){GLYPH_DIR_NAME}")]
+}
+
+pub(super) fn is_merging(root: &Path) -> bool {
+    git(root, &["rev-parse", "-q", "--verify", "MERGE_HEAD"]).is_ok()
+}
+
+fn parse_status_code(code: &[u8]) -> Option<GitFileStatus> {
+    let (x, y) = (code[0], code[1]);
+    Some(match (x, y) {
+        (b'?', b'?') => GitFileStatus::Untracked,
+        (b'!', b'!') => return None,
+        (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => GitFileStatus::Conflicted,
+        (b'R', _) | (_, b'R') => GitFileStatus::Renamed,
+        (b'A', _) => GitFileStatus::Added,
+        (b'D', _) | (_, b'D') => GitFileStatus::Deleted,
+        _ => GitFileStatus::Modified,
+    })
+}
+
+/// Parses `git status --porcelain=v1 -z` output into repository paths.
+fn parse_status(output: &str) -> Vec<(String, GitFileStatus)> {
+    let mut out = Vec::new();
+    let mut records = output.split('\0');
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        let code = &record.as_bytes()[..2];
+        if code[0] == b'R' || code[0] == b'C' {
+            // The source path of a rename follows as its own record.
+            records.next();
+        }
+        if let Some(status) = parse_status_code(code) {
+            out.push((record[3..].to_string(), status));
+        }
+    }
+    out
+}
+
+/// Changed files in the space, sorted by path.
+pub fn status(root: &Path) -> Result<Vec<GitStatusEntry>, String> {
+    require_repo(root)?;
+    let prefix = space_prefix(root)?;
+    let pathspec = space_pathspec();
+    let mut args = vec!["status", "--porcelain=v1", "-z", "-uall", "--"];
+    args.extend(pathspec.iter().map(String::as_str));
+    let mut entries = parse_status(&git(root, &args)?)
+        .into_iter()
+        .filter_map(|(path, status)| {
+            strip_prefix(&prefix, &path).map(|path| GitStatusEntry { path, status })
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Status of each changed file, plus `Modified` for every folder holding
+/// one, keyed by space-relative path.
+pub fn status_map(root: &Path) -> HashMap<String, GitFileStatus> {
+    let mut map = HashMap::new();
+    if !root.join(".git").exists() && !is_repo(root) {
+        return map;
+    }
+    for entry in status(root).unwrap_or_default() {
+        let mut dir = entry.path.as_str();
+        while let Some((parent, _)) = dir.rsplit_once('/') {
+            map.entry(parent.to_string())
+                .or_insert(GitFileStatus::Modified);
+            dir = parent;
+        }
+        map.insert(entry.path, entry.status);
+    }
+    map
+}
+
+pub fn repo_info(root: &Path) -> Result<GitRepoInfo, String> {
+    if !is_repo(root) {
+        return Ok(GitRepoInfo::default());
+    }
+    let head = git(root, &["rev-parse", "--verify", "-q", "HEAD"])
+        .ok()
+        .map(|hash| hash.trim().to_string());
+    let branch = git(root, &["symbolic-ref", "--short", "-q", "HEAD"])
+        .ok()
+        .map(|branch| branch.trim().to_string());
+    let upstream = git(
+        root,
+        &["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"],
+    )
+    .ok()
+    .map(|upstream| upstream.trim().to_string());
+    let (mut ahead, mut behind) = (0, 0);
+    if upstream.is_some() {
+        if let Ok(counts) = git(
+            root,
+            &["rev-list", "--left-right", "--count", "HEAD...@{u}"],
+        ) {
+            let mut counts = counts.split_whitespace().map(|n| n.parse().unwrap_or(0));
+            ahead = counts.next().unwrap_or(0);
+            behind = counts.next().unwrap_or(0);
+        }
+    }
+    Ok(GitRepoInfo {
+        is_repo: true,
+        branch,
+        head,
+        upstream,
+        ahead,
+        behind,
+        changed_files: status(root)?.len(),
+        merging: is_merging(root),
+    })
+}
+
+fn describe(verb: &str, paths: &[String]) -> String {
+    let names = paths
+        .iter()
+        .map(|path| path.rsplit('/').next().unwrap_or(path))
+        .collect::<Vec<_>>();
+    if names.len() <= MESSAGE_MAX_FILES {
+        format!("{verb} {}", names.join(", "))
+    } else {
+        format!("{verb} {} files", names.len())
+    }
+}
+
+/// A commit message naming what changed, e.g. "Update a.md; add b.md".
+fn generated_message(changes: &[GitStatusEntry]) -> String {
+    let mut groups: Vec<(&str, Vec<String>)> = vec![
+        ("update", Vec::new()),
+        ("add", Vec::new()),
+        ("delete", Vec::new()),
+        ("rename", Vec::new()),
+    ];
+    for change in changes {
+        let group = match change.status {
+            GitFileStatus::Added | GitFileStatus::Untracked => 1,
+            GitFileStatus::Deleted => 2,
+            GitFileStatus::Renamed => 3,
+            GitFileStatus::Modified | GitFileStatus::Conflicted => 0,
+        };
+        groups[group].1.push(change.path.clone());
+    }
+    let message = groups
+        .iter()
+        .filter(|(_, paths)| !paths.is_empty())
+        .map(|(verb, paths)| describe(verb, paths))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let mut chars = message.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Update notes".to_string(),
+    }
+}
+
+/// Commits every change in the space (Glyph's own data excepted) with
+/// `message`, or a generated one. Returns `None` when nothing changed.
+pub fn commit_all(root: &Path, message: Option<&str>) -> Result<Option<GitCommit>, String> {
+    require_repo(root)?;
+    let pathspec = space_pathspec();
+    let mut add = vec!["add", "-A", "--"];
+    add.extend(pathspec.iter().map(String::as_str));
+    git(root, &add)?;
+    let mut staged = vec!["diff", "--cached", "--quiet", "--"];
+    staged.extend(pathspec.iter().map(String::as_str));
+    if run(root, &staged)?.status.success() && !is_merging(root) {
+        return Ok(None);
+    }
+    let message = match message.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(message) => message.to_string(),
+        None => generated_message(&staged_changes(root)?),
+    };
+    let args = ["commit", "-q", "--no-verify", "-m", message.as_str()];
+    let output = git_committing(root, &args)?;
+    if !output.status.success() {
+        return Err(failure(&args, &output));
+    }
+    Ok(log(root, None, 1)?.into_iter().next())
+}
+
+fn staged_changes(root: &Path) -> Result<Vec<GitStatusEntry>, String> {
+    Ok(status(root)?
+        .into_iter()
+        .filter(|entry| entry.status != GitFileStatus::Untracked)
+        .collect())
+}
+
+const LOG_FORMAT: &str = "--format=%x1e%H%x1f%h%x1f%an%x1f%at%x1f%s";
+
+/// Commits touching `rel_path` (following renames), or all commits, newest
+/// first.
+pub fn log(root: &Path, rel_path: Option<&str>, limit: usize) -> Result<Vec<GitCommit>, String> {
+    require_repo(root)?;
+    if git(root, &["rev-parse", "--verify", "-q", "HEAD"]).is_err() {
+        return Ok(Vec::new());
+    }
+    let prefix = space_prefix(root)?;
+    let limit = limit.to_string();
+    let mut args = vec!["log", LOG_FORMAT, "-n", limit.as_str()];
+    if let Some(rel_path) = rel_path {
+        args.extend(["--follow", "--name-only", "--", rel_path]);
+    }
+    let output = git(root, &args)?;
+    Ok(output
+        .split('\u{1e}')
+        .filter_map(|record| {
+            let (header, names) = record.split_once('\n').unwrap_or((record, ""));
+            let mut fields = header.split('\u{1f}');
+            let hash = fields.next()?.to_string();
+            if hash.is_empty() {
+                return None;
+            }
+            Some(GitCommit {
+                hash,
+                short_hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                time_ms: fields.next()?.trim().parse::<u64>().ok()? * 1000,
+                subject: fields.next().unwrap_or_default().to_string(),
+                path: names
+                    .lines()
+                    .find(|line| !line.is_empty())
+                    .and_then(|path| strip_prefix(&prefix, path)),
+            })
+        })
+        .collect())
+}
+
+/// The content of `rel_path` at `revision`, or `None` when it did not exist.
+pub fn show_file(root: &Path, revision: &str, rel_path: &str) -> Result<Option<String>, String> {
+    let spec = format!("{revision}:./{rel_path}");
+    let output = run(root, &["show", spec.as_str()])?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    String::from_utf8(output.stdout)
+        .map(Some)
+        .map_err(|_| "file is not valid UTF-8 at that commit".to_string())
+}
+
+/// Rejects anything but a commit hash or ref name, so it cannot be read as
+/// an option.
+pub fn check_revision(revision: &str) -> Result<(), String> {
+    if revision.is_empty()
+        || revision.starts_with('-')
+        || !revision
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "/._-^~".contains(ch))
+    {
+        return Err(format!("invalid revision '{revision}'"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    pub(crate) fn temp_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("glyph-git-{label}-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    pub(crate) fn init_repo(dir: &Path) {
+        git(dir, &["init", "-q", "-b", "main"]).unwrap();
+    }
+
+    #[test]
+    fn reports_status_and_commits_with_generated_messages() {
+        let root = temp_dir("status");
+        init_repo(&root);
+        std::fs::create_dir_all(root.join("daily")).unwrap();
+        std::fs::create_dir_all(root.join(GLYPH_DIR_NAME)).unwrap();
+        std::fs::write(root.join("daily/today.md"), "# Today\n").unwrap();
+        std::fs::write(root.join("ideas.md"), "one\n").unwrap();
+        std::fs::write(root.join(GLYPH_DIR_NAME).join("glyph.sqlite"), "db").unwrap();
+
+        let map = status_map(&root);
+        assert_eq!(map.get("daily/today.md"), Some(&GitFileStatus::Untracked));
+        assert_eq!(map.get("daily"), Some(&GitFileStatus::Modified));
+        assert!(!map.keys().any(|path| path.starts_with(GLYPH_DIR_NAME)));
+
+        let first = commit_all(&root, None).unwrap().unwrap();
+        assert_eq!(first.subject, "Add today.md, ideas.md");
+        assert!(commit_all(&root, None).unwrap().is_none());
+
+        std::fs::write(root.join("ideas.md"), "one\ntwo\n").unwrap();
+        std::fs::remove_file(root.join("daily/today.md")).unwrap();
+        assert_eq!(
+            status(&root).unwrap(),
+            vec![
+                GitStatusEntry {
+                    path: "daily/today.md".to_string(),
+                    status: GitFileStatus::Deleted,
+                },
+                GitStatusEntry {
+                    path: "ideas.md".to_string(),
+                    status: GitFileStatus::Modified,
+                },
+            ]
+        );
+        let second = commit_all(&root, None).unwrap().unwrap();
+        assert_eq!(second.subject, "Update ideas.md; delete today.md");
+
+        let history = log(&root, Some("ideas.md"), 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].path.as_deref(), Some("ideas.md"));
+        assert_eq!(
+            show_file(&root, &history[1].hash, "ideas.md")
+                .unwrap()
+                .as_deref(),
+            Some("one\n")
+        );
+        assert_eq!(show_file(&root, &history[1].hash, "nope.md").unwrap(), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
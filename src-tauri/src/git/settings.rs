@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{glyph_paths, io_atomic};
+
+const GIT_SETTINGS_FILE: &str = "git.json";
+
+fn default_delay_secs() -> u64 {
+    60
+}
+
+fn default_remote() -> String {
+    "origin".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GitSettings {
+    /// Commit changes once the space has been quiet for the delay.
+    #[serde(default)]
+    pub auto_commit: bool,
+    #[serde(default = "default_delay_secs")]
+    pub auto_commit_delay_secs: u64,
+    /// Remote name or URL to pull from and push to.
+    #[serde(default = "default_remote")]
+    pub remote: String,
+}
+
+impl Default for GitSettings {
+    fn default() -> Self {
+        Self {
+            auto_commit: false,
+            auto_commit_delay_secs: default_delay_secs(),
+            remote: default_remote(),
+        }
+    }
+}
+
+fn git_settings_path(space_root: &Path) -> Result<PathBuf, String> {
+    Ok(glyph_paths::glyph_dir(space_root)?.join(GIT_SETTINGS_FILE))
+}
+
+pub fn read_git_settings(space_root: &Path) -> GitSettings {
+    let Ok(path) = git_settings_path(space_root) else {
+        return GitSettings::default();
+    };
+    let bytes = std::fs::read(path).unwrap_or_default();
+    serde_json::from_slice(&bytes).unwrap_or_default()
+}
+
+pub fn write_git_settings(space_root: &Path, settings: &GitSettings) -> Result<(), String> {
+    glyph_paths::ensure_glyph_dir(space_root)?;
+    let path = git_settings_path(space_root)?;
+    let bytes = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+    io_atomic::write_atomic(&path, &bytes).map_err(|e| e.to_string())
+}
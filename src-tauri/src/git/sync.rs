@@ -0,0 +1,213 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::repo::{commit_all, git, git_committing, is_merging, is_repo, run};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitSyncStatus {
+    UpToDate,
+    Updated,
+    /// The pull merged with conflicts; the files listed hold conflict
+    /// markers until they are fixed and committed.
+    Conflict,
+    /// The remote has commits this repository lacks; pull first.
+    Rejected,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct GitSyncResult {
+    pub status: GitSyncStatus,
+    /// Conflicted files, relative to the space root.
+    pub conflicts: Vec<String>,
+    pub message: Option<String>,
+}
+
+impl GitSyncResult {
+    fn new(status: GitSyncStatus) -> Self {
+        Self {
+            status,
+            conflicts: Vec::new(),
+            message: None,
+        }
+    }
+}
+
+fn check_remote(remote: &str) -> Result<(), String> {
+    if remote.trim().is_empty() || remote.starts_with('-') {
+        return Err(format!("invalid remote '{remote}'"));
+    }
+    Ok(())
+}
+
+/// Commits pending changes and returns the current branch, refusing while
+/// a conflicted pull is unfinished.
+fn prepare(root: &Path, remote: &str) -> Result<String, String> {
+    check_remote(remote)?;
+    if !is_repo(root) {
+        return Err("space is not a git repository".to_string());
+    }
+    if is_merging(root) {
+        return Err("resolve the conflicts from the last pull and commit first".to_string());
+    }
+    commit_all(root, None)?;
+    git(root, &["symbolic-ref", "--short", "-q", "HEAD"])
+        .map(|branch| branch.trim().to_string())
+        .map_err(|_| "not on a branch".to_string())
+}
+
+fn head(root: &Path) -> Option<String> {
+    git(root, &["rev-parse", "--verify", "-q", "HEAD"])
+        .ok()
+        .map(|hash| hash.trim().to_string())
+}
+
+/// Files with unresolved conflicts, relative to the space root.
+fn conflicted_files(root: &Path) -> Result<Vec<String>, String> {
+    let output = git(
+        root,
+        &["diff", "--name-only", "--relative", "--diff-filter=U"],
+    )?;
+    Ok(output.lines().map(str::to_string).collect())
+}
+
+/// Merges the branch of the same name from `remote` (a remote name, path or
+/// file:// URL) after committing local changes.
+pub fn pull(root: &Path, remote: &str) -> Result<GitSyncResult, String> {
+    let branch = prepare(root, remote)?;
+    let remote_heads = git(root, &["ls-remote", "--heads", remote, branch.as_str()])?;
+    if remote_heads.trim().is_empty() {
+        return Ok(GitSyncResult::new(GitSyncStatus::UpToDate));
+    }
+    let before = head(root);
+    let args = [
+        "pull",
+        "-q",
+        "--no-rebase",
+        "--no-edit",
+        remote,
+        branch.as_str(),
+    ];
+    let output = git_committing(root, &args)?;
+    if !output.status.success() {
+        let conflicts = conflicted_files(root)?;
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if conflicts.is_empty() {
+            return Err(format!("git pull failed: {message}"));
+        }
+        return Ok(GitSyncResult {
+            status: GitSyncStatus::Conflict,
+            conflicts,
+            message: Some(message).filter(|message| !message.is_empty()),
+        });
+    }
+    let status = if head(root) == before {
+        GitSyncStatus::UpToDate
+    } else {
+        GitSyncStatus::Updated
+    };
+    Ok(GitSyncResult::new(status))
+}
+
+/// Pushes the current branch to the branch of the same name on `remote`
+/// after committing local changes. Named remotes become the upstream.
+pub fn push(root: &Path, remote: &str) -> Result<GitSyncResult, String> {
+    let branch = prepare(root, remote)?;
+    if head(root).is_none() {
+        return Ok(GitSyncResult::new(GitSyncStatus::UpToDate));
+    }
+    let refspec = format!("HEAD:refs/heads/{branch}");
+    let is_named = git(root, &["remote"])?.lines().any(|name| name == remote);
+    let mut args = vec!["push", "--porcelain"];
+    if is_named {
+        args.push("--set-upstream");
+    }
+    args.extend([remote, refspec.as_str()]);
+    let output = run(root, &args)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stdout.lines().any(|line| line.starts_with('!')) {
+            return Ok(GitSyncResult {
+                status: GitSyncStatus::Rejected,
+                conflicts: Vec::new(),
+                message: Some("the remote has changes this space does not; pull first".to_string()),
+            });
+        }
+        return Err(format!("git push failed: {}", stderr.trim()));
+    }
+    let status = if stdout.lines().any(|line| line.starts_with('=')) {
+        GitSyncStatus::UpToDate
+    } else {
+        GitSyncStatus::Updated
+    };
+    Ok(GitSyncResult::new(status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::repo::tests::{init_repo, temp_dir};
+    use super::*;
+
+    fn clone(remote: &Path, label: &str) -> std::path::PathBuf {
+        let dir = temp_dir(label);
+        init_repo(&dir);
+        git(&dir, &["remote", "add", "origin", remote.to_str().unwrap()]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn syncs_through_a_bare_remote_and_reports_conflicts() {
+        let remote = temp_dir("remote");
+        git(&remote, &["init", "-q", "--bare", "-b", "main"]).unwrap();
+        let remote_url = format!("file://{}", remote.display());
+        let laptop = clone(&remote, "laptop");
+        let desktop = clone(&remote, "desktop");
+
+        assert_eq!(
+            pull(&laptop, "origin").unwrap().status,
+            GitSyncStatus::UpToDate
+        );
+        std::fs::write(laptop.join("plan.md"), "one\ntwo\nthree\n").unwrap();
+        assert_eq!(
+            push(&laptop, "origin").unwrap().status,
+            GitSyncStatus::Updated
+        );
+        assert_eq!(
+            push(&laptop, "origin").unwrap().status,
+            GitSyncStatus::UpToDate
+        );
+
+        assert_eq!(
+            pull(&desktop, &remote_url).unwrap().status,
+            GitSyncStatus::Updated
+        );
+        assert_eq!(
+            std::fs::read_to_string(desktop.join("plan.md")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+
+        std::fs::write(desktop.join("plan.md"), "one\ntwo (desktop)\nthree\n").unwrap();
+        assert_eq!(
+            push(&desktop, "origin").unwrap().status,
+            GitSyncStatus::Updated
+        );
+        std::fs::write(laptop.join("plan.md"), "one\ntwo (laptop)\nthree\n").unwrap();
+        assert_eq!(
+            push(&laptop, "origin").unwrap().status,
+            GitSyncStatus::Rejected
+        );
+
+        let result = pull(&laptop, "origin").unwrap();
+        assert_eq!(result.status, GitSyncStatus::Conflict);
+        assert_eq!(result.conflicts, vec!["plan.md".to_string()]);
+        assert!(is_merging(&laptop));
+        assert!(push(&laptop, "origin").is_err());
+
+        for dir in [remote, laptop, desktop] {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::space::state::mark_recent_local_change;
+use crate::space::SpaceState;
+use crate::space_fs::helpers::{deny_hidden_rel_path, etag_for, file_mtime_ms};
+use crate::{crypto, index, io_atomic, utils};
+
+use super::diff::{diff_lines, DiffLine};
+use super::store::{
+    abs_path, find_version, list_versions, record_write, version_text, NoteVersion, VersionSource,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct NoteHistoryDiff {
+    pub from: NoteVersion,
+    /// `None` when comparing against the file as it is on disk.
+    pub to: Option<NoteVersion>,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct NoteHistoryRestoreResult {
+    pub text: String,
+    pub etag: String,
+    pub mtime_ms: u64,
+}
+
+fn checked_rel_path(path: &str) -> Result<String, String> {
+    let rel = PathBuf::from(path);
+    deny_hidden_rel_path(&rel)?;
+    Ok(utils::to_slash(&rel))
+}
+
+#[tauri::command]
+pub async fn note_history_list(
+    state: State<'_, SpaceState>,
+    path: String,
+) -> Result<Vec<NoteVersion>, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let rel_path = checked_rel_path(&path)?;
+        list_versions(&root, &rel_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Diffs version `from` against version `to`, or against the file on disk
+/// when `to` is not given.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn note_history_diff(
+    state: State<'_, SpaceState>,
+    path: String,
+    from: String,
+    to: Option<String>,
+) -> Result<NoteHistoryDiff, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let rel_path = checked_rel_path(&path)?;
+        let from = find_version(&root, &rel_path, &from)?;
+        let old = crypto::open_text(&root, version_text(&root, &from)?.as_bytes())?;
+        let (to, new) = match to {
+            Some(to) => {
+                let to = find_version(&root, &rel_path, &to)?;
+                let text = version_text(&root, &to)?;
+                (Some(to), text)
+            }
+            None => {
+                let text = std::fs::read_to_string(abs_path(&root, &rel_path)?)
+                    .map_err(|e| e.to_string())?;
+                (None, text)
+            }
+        };
+        let new = crypto::open_text(&root, new.as_bytes())?;
+        Ok(NoteHistoryDiff {
+            lines: diff_lines(&old, &new),
+            from,
+            to,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Writes a version back to the file. The content being replaced is kept
+/// in history, so a restore can itself be undone.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn note_history_restore(
+    state: State<'_, SpaceState>,
+    path: String,
+    version_id: String,
+) -> Result<NoteHistoryRestoreResult, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        let rel_path = checked_rel_path(&path)?;
+        let version = find_version(&root, &rel_path, &version_id)?;
+        let stored = version_text(&root, &version)?;
+        let text = crypto::open_text(&root, stored.as_bytes())?;
+        let abs = abs_path(&root, &rel_path)?;
+        let previous = std::fs::read(&abs).ok();
+        // An encrypted note stays encrypted, even restored to a version
+        // from before it was, and an encrypted version restores encrypted.
+        let bytes = crypto::seal_text(
+            &root,
+            &rel_path,
+            previous.as_deref(),
+            crypto::is_encrypted(stored.as_bytes()),
+            &text,
+        )?;
+        if let Some(parent) = abs.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        mark_recent_local_change(&recent_local_changes, &rel_path);
+        io_atomic::write_atomic(&abs, &bytes).map_err(|e| e.to_string())?;
+        if utils::is_markdown_path(&abs) {
+            let _ = index::index_note(&root, &rel_path, &text);
+        }
+        let _ = record_write(
+            &root,
+            &rel_path,
+            previous.as_deref(),
+            &bytes,
+            VersionSource::Restore,
+        );
+        Ok(NoteHistoryRestoreResult {
+            etag: etag_for(&bytes),
+            mtime_ms: file_mtime_ms(&abs),
+            text,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,302 @@
+use serde::Serialize;
+
+/// Past this many edits the diff gives up on finding a minimal script and
+/// reports the changed middle as one delete plus one insert.
+const MAX_EDIT_DISTANCE: usize = 4_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Myers' shortest edit script between `a` and `b`.
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Option<Vec<Op>> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = a.len() + b.len();
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max as isize {
+        if d as usize > MAX_EDIT_DISTANCE {
+            return None;
+        }
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let (mut x, mut y) = (n, m);
+    let mut ops = Vec::new();
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let index = (k + offset) as usize;
+        let prev_k = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Equal(x as usize - 1, y as usize - 1));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                ops.push(Op::Insert(y as usize - 1));
+            } else {
+                ops.push(Op::Delete(x as usize - 1));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    Some(ops)
+}
+
+/// Edit script from `a` to `b`, trimming the common prefix and suffix first.
+pub(super) fn diff_ops<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops = (0..prefix).map(|i| Op::Equal(i, i)).collect::<Vec<_>>();
+    match myers(a_mid, b_mid) {
+        Some(middle) => ops.extend(middle.into_iter().map(|op| match op {
+            Op::Equal(i, j) => Op::Equal(i + prefix, j + prefix),
+            Op::Delete(i) => Op::Delete(i + prefix),
+            Op::Insert(j) => Op::Insert(j + prefix),
+        })),
+        None => {
+            ops.extend((0..a_mid.len()).map(|i| Op::Delete(i + prefix)));
+            ops.extend((0..b_mid.len()).map(|j| Op::Insert(j + prefix)));
+        }
+    }
+    ops.extend((0..suffix).map(|i| Op::Equal(a.len() - suffix + i, b.len() - suffix + i)));
+    ops
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// A run of a changed line, for word-level highlighting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DiffSpan {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+    /// Word-level changes against the line this one replaced, when the
+    /// change paired a deleted line with an inserted one.
+    pub spans: Option<Vec<DiffSpan>>,
+}
+
+/// Words, whitespace runs and single punctuation characters.
+fn words(line: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let class = |ch: char| {
+        if ch.is_alphanumeric() || ch == '_' {
+            0
+        } else if ch.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut previous = None;
+    for (index, ch) in line.char_indices() {
+        let current = class(ch);
+        if index > start && (previous != Some(current) || current == 2) {
+            out.push(&line[start..index]);
+            start = index;
+        }
+        previous = Some(current);
+    }
+    if start < line.len() {
+        out.push(&line[start..]);
+    }
+    out
+}
+
+fn push_span(spans: &mut Vec<DiffSpan>, kind: DiffKind, text: &str) {
+    match spans.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => spans.push(DiffSpan {
+            kind,
+            text: text.to_string(),
+        }),
+    }
+}
+
+/// Word-level spans of `old` and `new`: unchanged and deleted words for the
+/// old line, unchanged and inserted words for the new one.
+fn word_spans(old: &str, new: &str) -> (Vec<DiffSpan>, Vec<DiffSpan>) {
+    let (a, b) = (words(old), words(new));
+    let mut old_spans = Vec::new();
+    let mut new_spans = Vec::new();
+    for op in diff_ops(&a, &b) {
+        match op {
+            Op::Equal(i, _) => {
+                push_span(&mut old_spans, DiffKind::Equal, a[i]);
+                push_span(&mut new_spans, DiffKind::Equal, a[i]);
+            }
+            Op::Delete(i) => push_span(&mut old_spans, DiffKind::Delete, a[i]),
+            Op::Insert(j) => push_span(&mut new_spans, DiffKind::Insert, b[j]),
+        }
+    }
+    (old_spans, new_spans)
+}
+
+fn plain(kind: DiffKind, text: &str) -> DiffLine {
+    DiffLine {
+        kind,
+        text: text.to_string(),
+        spans: None,
+    }
+}
+
+/// Line diff from `old` to `new`. Deleted lines directly followed by
+/// inserted ones are paired up and carry word-level spans.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let a = old.lines().collect::<Vec<_>>();
+    let b = new.lines().collect::<Vec<_>>();
+    let ops = diff_ops(&a, &b);
+
+    let mut out = Vec::new();
+    let mut index = 0;
+    while index < ops.len() {
+        if let Op::Equal(i, _) = ops[index] {
+            out.push(plain(DiffKind::Equal, a[i]));
+            index += 1;
+            continue;
+        }
+        let mut deleted = Vec::new();
+        let mut inserted = Vec::new();
+        while let Some(op) = ops.get(index) {
+            match *op {
+                Op::Delete(i) => deleted.push(a[i]),
+                Op::Insert(j) => inserted.push(b[j]),
+                Op::Equal(..) => break,
+            }
+            index += 1;
+        }
+        let paired = deleted.len().min(inserted.len());
+        let mut new_lines = Vec::new();
+        for (position, old_line) in deleted.iter().enumerate() {
+            if position < paired {
+                let (old_spans, new_spans) = word_spans(old_line, inserted[position]);
+                out.push(DiffLine {
+                    kind: DiffKind::Delete,
+                    text: old_line.to_string(),
+                    spans: Some(old_spans),
+                });
+                new_lines.push(DiffLine {
+                    kind: DiffKind::Insert,
+                    text: inserted[position].to_string(),
+                    spans: Some(new_spans),
+                });
+            } else {
+                out.push(plain(DiffKind::Delete, old_line));
+            }
+        }
+        out.extend(new_lines);
+        out.extend(
+            inserted[paired..]
+                .iter()
+                .map(|line| plain(DiffKind::Insert, line)),
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(lines: &[DiffLine]) -> Vec<(DiffKind, &str)> {
+        lines
+            .iter()
+            .map(|line| (line.kind, line.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn diffs_lines_and_words() {
+        let old = "# Plan\nship the beta\nkeep\nold tail\n";
+        let new = "# Plan\nship the final release\nkeep\nnew line\n";
+        let lines = diff_lines(old, new);
+        assert_eq!(
+            kinds(&lines),
+            vec![
+                (DiffKind::Equal, "# Plan"),
+                (DiffKind::Delete, "ship the beta"),
+                (DiffKind::Insert, "ship the final release"),
+                (DiffKind::Equal, "keep"),
+                (DiffKind::Delete, "old tail"),
+                (DiffKind::Insert, "new line"),
+            ]
+        );
+        let spans = lines[2].spans.as_ref().unwrap();
+        assert_eq!(
+            spans
+                .iter()
+                .map(|span| (span.kind, span.text.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (DiffKind::Equal, "ship the "),
+                (DiffKind::Insert, "final release"),
+            ]
+        );
+
+        let inserted = diff_lines("a\nc\n", "a\nb\nc\n");
+        assert_eq!(
+            kinds(&inserted),
+            vec![
+                (DiffKind::Equal, "a"),
+                (DiffKind::Insert, "b"),
+                (DiffKind::Equal, "c"),
+            ]
+        );
+        assert!(diff_lines("same\n", "same\n")
+            .iter()
+            .all(|line| line.kind == DiffKind::Equal));
+    }
+}
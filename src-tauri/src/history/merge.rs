@@ -0,0 +1,195 @@
+use serde::Serialize;
+
+use super::diff::{diff_ops, Op};
+
+/// A piece of a three-way merge: text both sides agree on, or a region
+/// changed differently on disk and in the editor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MergeChunk {
+    Resolved {
+        text: String,
+    },
+    Conflict {
+        base: String,
+        disk: String,
+        editor: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeOutcome {
+    Clean(String),
+    Conflict(Vec<MergeChunk>),
+}
+
+/// `base[start..end]` replaced by `lines` on one side.
+#[derive(Debug)]
+struct Hunk<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+fn hunks<'a>(base: &[&'a str], side: &[&'a str]) -> Vec<Hunk<'a>> {
+    let mut out = Vec::new();
+    let mut pending: Option<Hunk<'a>> = None;
+    let mut position = 0;
+    for op in diff_ops(base, side) {
+        match op {
+            Op::Equal(i, _) => {
+                out.extend(pending.take());
+                position = i + 1;
+            }
+            Op::Delete(i) => {
+                let hunk = pending.get_or_insert(Hunk {
+                    start: i,
+                    end: i,
+                    lines: Vec::new(),
+                });
+                hunk.end = i + 1;
+                position = i + 1;
+            }
+            Op::Insert(j) => {
+                pending
+                    .get_or_insert(Hunk {
+                        start: position,
+                        end: position,
+                        lines: Vec::new(),
+                    })
+                    .lines
+                    .push(side[j]);
+            }
+        }
+    }
+    out.extend(pending);
+    out
+}
+
+/// `base[start..end]` with one side's hunks (all inside that range) applied.
+fn apply(base: &[&str], start: usize, end: usize, hunks: &[&Hunk<'_>]) -> String {
+    let mut out = String::new();
+    let mut position = start;
+    for hunk in hunks {
+        out.extend(base[position..hunk.start].iter().copied());
+        out.extend(hunk.lines.iter().copied());
+        position = hunk.end;
+    }
+    out.extend(base[position..end].iter().copied());
+    out
+}
+
+/// Merges the disk and editor versions of a file that both started from
+/// `base`, line by line. Changes on one side only are taken as they are;
+/// overlapping or adjacent changes that differ are conflicts.
+pub fn merge_texts(base: &str, disk: &str, editor: &str) -> MergeOutcome {
+    let base_lines = base.split_inclusive('\n').collect::<Vec<_>>();
+    let disk_lines = disk.split_inclusive('\n').collect::<Vec<_>>();
+    let editor_lines = editor.split_inclusive('\n').collect::<Vec<_>>();
+    let disk_hunks = hunks(&base_lines, &disk_lines);
+    let editor_hunks = hunks(&base_lines, &editor_lines);
+
+    let mut chunks: Vec<MergeChunk> = Vec::new();
+    let mut resolved = String::new();
+    let mut has_conflict = false;
+    let mut position = 0;
+    let (mut d, mut e) = (0, 0);
+    while d < disk_hunks.len() || e < editor_hunks.len() {
+        let first_is_disk = match (disk_hunks.get(d), editor_hunks.get(e)) {
+            (Some(disk), Some(editor)) => disk.start <= editor.start,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        let first = if first_is_disk {
+            &disk_hunks[d]
+        } else {
+            &editor_hunks[e]
+        };
+        let (start, mut end) = (first.start, first.end);
+        let mut from_disk = Vec::new();
+        let mut from_editor = Vec::new();
+        // Grow the region while either side has a hunk touching it.
+        loop {
+            if let Some(hunk) = disk_hunks.get(d).filter(|hunk| hunk.start <= end) {
+                end = end.max(hunk.end);
+                from_disk.push(hunk);
+                d += 1;
+            } else if let Some(hunk) = editor_hunks.get(e).filter(|hunk| hunk.start <= end) {
+                end = end.max(hunk.end);
+                from_editor.push(hunk);
+                e += 1;
+            } else {
+                break;
+            }
+        }
+
+        resolved.extend(base_lines[position..start].iter().copied());
+        position = end;
+        let disk_text = apply(&base_lines, start, end, &from_disk);
+        let editor_text = apply(&base_lines, start, end, &from_editor);
+        if from_editor.is_empty() || disk_text == editor_text {
+            resolved.push_str(&disk_text);
+        } else if from_disk.is_empty() {
+            resolved.push_str(&editor_text);
+        } else {
+            has_conflict = true;
+            if !resolved.is_empty() {
+                chunks.push(MergeChunk::Resolved {
+                    text: std::mem::take(&mut resolved),
+                });
+            }
+            chunks.push(MergeChunk::Conflict {
+                base: base_lines[start..end].concat(),
+                disk: disk_text,
+                editor: editor_text,
+            });
+        }
+    }
+    resolved.extend(base_lines[position..].iter().copied());
+
+    if !has_conflict {
+        return MergeOutcome::Clean(resolved);
+    }
+    if !resolved.is_empty() {
+        chunks.push(MergeChunk::Resolved { text: resolved });
+    }
+    MergeOutcome::Conflict(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_separate_changes_and_reports_overlapping_ones() {
+        let base = "title\n\none\ntwo\nthree\nfour\n";
+        let disk = "title\n\none\ntwo (synced)\nthree\nfour\n";
+        let editor = "title\n\none\ntwo\nthree\nfour\nfive\n";
+        assert_eq!(
+            merge_texts(base, disk, editor),
+            MergeOutcome::Clean("title\n\none\ntwo (synced)\nthree\nfour\nfive\n".to_string())
+        );
+        assert_eq!(
+            merge_texts(base, disk, disk),
+            MergeOutcome::Clean(disk.to_string())
+        );
+
+        let editor = "title\n\none\ntwo (local)\nthree\nfour\n";
+        assert_eq!(
+            merge_texts(base, disk, editor),
+            MergeOutcome::Conflict(vec![
+                MergeChunk::Resolved {
+                    text: "title\n\none\n".to_string()
+                },
+                MergeChunk::Conflict {
+                    base: "two\n".to_string(),
+                    disk: "two (synced)\n".to_string(),
+                    editor: "two (local)\n".to_string(),
+                },
+                MergeChunk::Resolved {
+                    text: "three\nfour\n".to_string()
+                },
+            ])
+        );
+    }
+}
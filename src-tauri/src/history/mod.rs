@@ -0,0 +1,8 @@
+pub mod commands;
+mod diff;
+mod merge;
+mod store;
+
+pub(crate) use diff::{diff_lines, DiffLine};
+pub(crate) use merge::{merge_texts, MergeChunk, MergeOutcome};
+pub(crate) use store::{content_by_hash, forget_versions, record_write, VersionSource};
@@ -0,0 +1,353 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::identity::note_uid;
+use crate::utils::{now_ms, sha256_hex};
+use crate::{glyph_paths, index, io_atomic, paths, utils};
+
+const HISTORY_DIR_NAME: &str = "history";
+const LOG_VERSION: u32 = 1;
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+const DAY_MS: u64 = 24 * HOUR_MS;
+/// Every save is kept this long, then thinned to one per hour.
+const KEEP_ALL_MS: u64 = DAY_MS;
+/// Hourly versions are kept this long, then thinned to one per day.
+const KEEP_HOURLY_MS: u64 = 7 * DAY_MS;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionSource {
+    /// Saved from the editor.
+    Save,
+    /// Written by an AI tool.
+    Ai,
+    /// Written by restoring an older version.
+    Restore,
+    /// Found on disk before a save: the original file or an outside edit.
+    Disk,
+    /// Written or removed by sync.
+    Sync,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct NoteVersion {
+    pub id: String,
+    pub hash: String,
+    pub path: String,
+    pub saved_at_ms: u64,
+    pub size: usize,
+    pub source: VersionSource,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct VersionLog {
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    versions: Vec<NoteVersion>,
+}
+
+fn history_dir(root: &Path) -> Result<PathBuf, String> {
+    Ok(glyph_paths::glyph_dir(root)?.join(HISTORY_DIR_NAME))
+}
+
+fn object_path(root: &Path, hash: &str) -> Result<PathBuf, String> {
+    if hash.len() < 3 || !hash.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return Err("invalid version hash".to_string());
+    }
+    Ok(history_dir(root)?
+        .join("objects")
+        .join(&hash[..2])
+        .join(hash))
+}
+
+fn logs_dir(root: &Path) -> Result<PathBuf, String> {
+    Ok(history_dir(root)?.join("notes"))
+}
+
+fn path_key(rel_path: &str) -> String {
+    format!("path-{}", sha256_hex(rel_path.as_bytes()))
+}
+
+/// Versions of a note are filed under its identity so they follow it
+/// across renames; other files are filed under their path.
+fn log_key(root: &Path, rel_path: &str) -> String {
+    if utils::is_markdown_path(Path::new(rel_path)) {
+        if let Ok(Some(uid)) = index::open_db(root).and_then(|conn| note_uid(&conn, rel_path)) {
+            return uid;
+        }
+    }
+    path_key(rel_path)
+}
+
+fn log_path(root: &Path, key: &str) -> Result<PathBuf, String> {
+    Ok(logs_dir(root)?.join(format!("{key}.json")))
+}
+
+fn read_log(path: &Path) -> Result<VersionLog, String> {
+    match std::fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| e.to_string()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(VersionLog::default()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// The log for `rel_path`, adopting versions filed under its path before
+/// the note had an identity.
+fn load_log(root: &Path, rel_path: &str) -> Result<(PathBuf, VersionLog), String> {
+    let key = log_key(root, rel_path);
+    let path = log_path(root, &key)?;
+    let fallback = log_path(root, &path_key(rel_path))?;
+    if path != fallback && !path.exists() && fallback.exists() {
+        std::fs::create_dir_all(logs_dir(root)?).map_err(|e| e.to_string())?;
+        std::fs::rename(&fallback, &path).map_err(|e| e.to_string())?;
+    }
+    let log = read_log(&path)?;
+    Ok((path, log))
+}
+
+fn write_log(path: &Path, log: &VersionLog) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let bytes = serde_json::to_vec_pretty(log).map_err(|e| e.to_string())?;
+    io_atomic::write_atomic(path, &bytes).map_err(|e| e.to_string())
+}
+
+fn store_object(root: &Path, hash: &str, bytes: &[u8]) -> Result<(), String> {
+    let path = object_path(root, hash)?;
+    if path.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    io_atomic::write_atomic(&path, bytes).map_err(|e| e.to_string())
+}
+
+pub(crate) fn read_object(root: &Path, hash: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(object_path(root, hash)?).map_err(|e| e.to_string())
+}
+
+/// Text saved in history whose hash (the same as its etag) is `hash`.
+pub(crate) fn content_by_hash(root: &Path, hash: &str) -> Option<String> {
+    String::from_utf8(read_object(root, hash).ok()?).ok()
+}
+
+fn push_version(
+    root: &Path,
+    log: &mut VersionLog,
+    rel_path: &str,
+    bytes: &[u8],
+    source: VersionSource,
+    saved_at_ms: u64,
+) -> Result<(), String> {
+    let hash = sha256_hex(bytes);
+    if log.versions.last().is_some_and(|last| last.hash == hash) {
+        return Ok(());
+    }
+    store_object(root, &hash, bytes)?;
+    let mut id = format!("{saved_at_ms}-{}", &hash[..12]);
+    if log.versions.iter().any(|version| version.id == id) {
+        id = format!("{id}-{}", log.versions.len());
+    }
+    log.versions.push(NoteVersion {
+        id,
+        hash,
+        path: rel_path.to_string(),
+        saved_at_ms,
+        size: bytes.len(),
+        source,
+    });
+    Ok(())
+}
+
+/// Thins `versions` (oldest first): every version from the last day, the
+/// newest per hour for the last week, and the newest per day before that.
+/// The newest version is always kept. Returns the hashes of dropped versions.
+fn prune_versions(versions: &mut Vec<NoteVersion>, now_ms: u64) -> Vec<String> {
+    let mut seen_buckets = HashSet::new();
+    let mut keep = vec![false; versions.len()];
+    for (index, version) in versions.iter().enumerate().rev() {
+        let age = now_ms.saturating_sub(version.saved_at_ms);
+        let bucket = if age < KEEP_ALL_MS {
+            (0, index as u64)
+        } else if age < KEEP_HOURLY_MS {
+            (1, version.saved_at_ms / HOUR_MS)
+        } else {
+            (2, version.saved_at_ms / DAY_MS)
+        };
+        keep[index] = index + 1 == versions.len() || seen_buckets.insert(bucket);
+    }
+    let mut dropped = Vec::new();
+    let mut index = 0;
+    versions.retain(|version| {
+        let kept = keep[index];
+        index += 1;
+        if !kept {
+            dropped.push(version.hash.clone());
+        }
+        kept
+    });
+    dropped
+}
+
+/// Deletes objects no version log refers to any more.
+fn collect_garbage(root: &Path, candidates: Vec<String>) -> Result<(), String> {
+    let mut unreferenced = candidates.into_iter().collect::<HashSet<_>>();
+    if unreferenced.is_empty() {
+        return Ok(());
+    }
+    let entries = match std::fs::read_dir(logs_dir(root)?) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(()),
+    };
+    for entry in entries.flatten() {
+        let log = read_log(&entry.path())?;
+        for version in log.versions {
+            unreferenced.remove(&version.hash);
+        }
+        if unreferenced.is_empty() {
+            return Ok(());
+        }
+    }
+    for hash in unreferenced {
+        let _ = std::fs::remove_file(object_path(root, &hash)?);
+    }
+    Ok(())
+}
+
+/// Records a write of `next` to `rel_path`. `previous` is what the file
+/// held before; it is recorded first when history does not have it yet, so
+/// the state before the first save and outside edits can be restored too.
+pub(crate) fn record_write(
+    root: &Path,
+    rel_path: &str,
+    previous: Option<&[u8]>,
+    next: &[u8],
+    source: VersionSource,
+) -> Result<(), String> {
+    let (path, mut log) = load_log(root, rel_path)?;
+    log.version = LOG_VERSION;
+    let now = now_ms();
+    if let Some(previous) = previous {
+        push_version(root, &mut log, rel_path, previous, VersionSource::Disk, now)?;
+    }
+    push_version(root, &mut log, rel_path, next, source, now)?;
+    let dropped = prune_versions(&mut log.versions, now);
+    write_log(&path, &log)?;
+    collect_garbage(root, dropped)
+}
+
+/// Drops every version of `rel_path`, e.g. the plaintext versions of a
+/// note that is now stored encrypted.
+pub(crate) fn forget_versions(root: &Path, rel_path: &str) -> Result<(), String> {
+    let (path, log) = load_log(root, rel_path)?;
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error.to_string()),
+    }
+    collect_garbage(
+        root,
+        log.versions
+            .into_iter()
+            .map(|version| version.hash)
+            .collect(),
+    )
+}
+
+/// Versions of `rel_path`, newest first.
+pub(crate) fn list_versions(root: &Path, rel_path: &str) -> Result<Vec<NoteVersion>, String> {
+    let (_path, log) = load_log(root, rel_path)?;
+    Ok(log.versions.into_iter().rev().collect())
+}
+
+pub(crate) fn find_version(
+    root: &Path,
+    rel_path: &str,
+    version_id: &str,
+) -> Result<NoteVersion, String> {
+    list_versions(root, rel_path)?
+        .into_iter()
+        .find(|version| version.id == version_id)
+        .ok_or_else(|| format!("no version '{version_id}' for {rel_path}"))
+}
+
+pub(crate) fn version_text(root: &Path, version: &NoteVersion) -> Result<String, String> {
+    String::from_utf8(read_object(root, &version.hash)?)
+        .map_err(|_| "version is not valid UTF-8".to_string())
+}
+
+pub(crate) fn abs_path(root: &Path, rel_path: &str) -> Result<PathBuf, String> {
+    paths::join_under(root, Path::new(rel_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(saved_at_ms: u64, hash: &str) -> NoteVersion {
+        NoteVersion {
+            id: format!("{saved_at_ms}"),
+            hash: hash.to_string(),
+            path: "note.md".to_string(),
+            saved_at_ms,
+            size: 0,
+            source: VersionSource::Save,
+        }
+    }
+
+    #[test]
+    fn thins_old_versions_by_hour_then_day() {
+        let now = 100 * DAY_MS;
+        let mut versions = vec![
+            version(now - 20 * DAY_MS, "a"),
+            version(now - 20 * DAY_MS + HOUR_MS, "b"),
+            version(now - 3 * DAY_MS, "c"),
+            version(now - 3 * DAY_MS + 60_000, "d"),
+            version(now - 3 * DAY_MS + 2 * HOUR_MS, "e"),
+            version(now - HOUR_MS, "f"),
+            version(now - 60_000, "g"),
+        ];
+        let dropped = prune_versions(&mut versions, now);
+        assert_eq!(dropped, vec!["a", "c"]);
+        let kept = versions
+            .iter()
+            .map(|version| version.hash.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(kept, vec!["b", "d", "e", "f", "g"]);
+    }
+
+    #[test]
+    fn records_deduplicated_versions_and_their_previous_content() {
+        let root =
+            std::env::temp_dir().join(format!("glyph-history-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        record_write(&root, "todo.txt", Some(b"one"), b"two", VersionSource::Save).unwrap();
+        record_write(&root, "todo.txt", Some(b"two"), b"two", VersionSource::Save).unwrap();
+        record_write(&root, "todo.txt", Some(b"two"), b"three", VersionSource::Ai).unwrap();
+
+        let versions = list_versions(&root, "todo.txt").unwrap();
+        let sources = versions
+            .iter()
+            .map(|version| version.source)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            vec![VersionSource::Ai, VersionSource::Save, VersionSource::Disk]
+        );
+        assert_eq!(version_text(&root, &versions[2]).unwrap(), "one");
+        assert_eq!(
+            find_version(&root, "todo.txt", &versions[0].id)
+                .unwrap()
+                .hash,
+            sha256_hex(b"three")
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -2,20 +2,32 @@ use std::path::Path;
 use tauri::{AppHandle, State};
 use tauri_plugin_notification::NotificationExt;
 
-use crate::space::state::mark_recent_local_change;
+use crate::crypto;
+use crate::dates::{parse_natural_date, parse_natural_period, shift_date, DateContext};
+use crate::space::state::{mark_recent_local_change, RecentLocalChanges};
 use crate::space::SpaceState;
+use crate::space_fs::helpers::deny_hidden_rel_path;
 
 use super::db::open_db;
+use super::identity::note_identity;
 use super::indexer::index_note;
 use super::indexer::rebuild;
 use super::search_advanced::{run_search_advanced, SearchAdvancedRequest};
 use super::search_hybrid::hybrid_search;
 use super::tags::normalize_tag;
+use super::tasks::settings::{read_task_settings, write_task_settings, TaskSettings};
+use super::tasks::views::{
+    parse_task_view_config, render_task_view_markdown, TaskViewConfig, TaskViewLoadResult,
+};
 use super::tasks::{
-    mutate_task_line, note_abs_path, query_tasks, write_note, IndexedTask, TaskBucket,
+    list_task_projects, mutate_task_line, nest_task_trees, note_abs_path, parse_task_priority,
+    parse_task_query, parse_task_status, query_tasks, reassign_task_projects, run_task_query,
+    subtask_lines, write_note, IndexedTask, TaskBucket, TaskMetadata, TaskProjectSummary,
+    TaskQueryResult, TaskScope, DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT,
 };
 use super::types::{
-    BacklinkItem, IndexRebuildResult, SearchResult, TagCount, TaskDateInfo, ViewNotePreview,
+    BacklinkItem, IndexRebuildResult, NoteIdentity, SearchResult, TagCount, TaskDateInfo,
+    ViewNotePreview,
 };
 
 fn tokenize_search_query(raw: &str) -> Vec<String> {
@@ -42,7 +54,55 @@ fn tokenize_search_query(raw: &str) -> Vec<String> {
     out
 }
 
-pub(crate) fn parse_raw_search_query(raw_query: &str, limit: Option<u32>) -> SearchAdvancedRequest {
+/// Parses `due:`/`updated:` values into inclusive bounds: a date or phrase
+/// (`due:tomorrow`), a period (`this week`), a comparison (`due:<eom`,
+/// `updated:>=2025-01-01`) or a range (`due:today..eow`, either side
+/// optional).
+pub(crate) fn parse_search_date_range(
+    raw: &str,
+    ctx: &DateContext,
+) -> Option<(Option<String>, Option<String>)> {
+    let raw = raw.trim();
+    if let Some((from, to)) = raw.split_once("..") {
+        let bound = |value: &str| -> Option<Option<String>> {
+            let value = value.trim();
+            if value.is_empty() {
+                Some(None)
+            } else {
+                parse_natural_date(value, ctx).ok().map(Some)
+            }
+        };
+        let range = (bound(from)?, bound(to)?);
+        return (range.0.is_some() || range.1.is_some()).then_some(range);
+    }
+    for (prefix, shift, upper) in [
+        ("<=", 0, true),
+        (">=", 0, false),
+        ("<", -1, true),
+        (">", 1, false),
+    ] {
+        if let Some(rest) = raw.strip_prefix(prefix) {
+            let date = parse_natural_date(rest, ctx).ok()?;
+            let date = shift_date(&date, shift).ok()?;
+            return Some(if upper {
+                (None, Some(date))
+            } else {
+                (Some(date), None)
+            });
+        }
+    }
+    if let Some((from, to)) = parse_natural_period(raw, ctx) {
+        return Some((Some(from), Some(to)));
+    }
+    let date = parse_natural_date(raw, ctx).ok()?;
+    Some((Some(date.clone()), Some(date)))
+}
+
+pub(crate) fn parse_raw_search_query(
+    raw_query: &str,
+    limit: Option<u32>,
+    ctx: &DateContext,
+) -> SearchAdvancedRequest {
     let mut req = SearchAdvancedRequest {
         limit: Some(limit.unwrap_or(1500).clamp(1, 2_000)),
         ..SearchAdvancedRequest::default()
@@ -75,6 +135,18 @@ pub(crate) fn parse_raw_search_query(raw_query: &str, limit: Option<u32>) -> Sea
             }
             continue;
         }
+        if let Some((field, value)) = lower.split_once(':') {
+            if field == "due" || field == "updated" {
+                if let Some((from, to)) = parse_search_date_range(value, ctx) {
+                    if field == "due" {
+                        (req.due_from, req.due_to) = (from, to);
+                    } else {
+                        (req.updated_from, req.updated_to) = (from, to);
+                    }
+                    continue;
+                }
+            }
+        }
         text_parts.push(token);
     }
 
@@ -198,6 +270,19 @@ pub async fn index_rebuild(
     Ok(res)
 }
 
+/// The persistent identity of the note at `path`, with the paths it had
+/// before being renamed or moved.
+#[tauri::command]
+pub async fn note_identity_get(
+    state: State<'_, SpaceState>,
+    path: String,
+) -> Result<Option<NoteIdentity>, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || note_identity(&root, &path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 pub async fn search(
     state: State<'_, SpaceState>,
@@ -226,15 +311,19 @@ pub async fn search_advanced(
     .map_err(|e| e.to_string())?
 }
 
+/// `today` and `week_start` anchor relative `due:`/`updated:` filters.
 #[tauri::command]
 pub async fn search_parse_and_run(
     state: State<'_, SpaceState>,
     raw_query: String,
     limit: Option<u32>,
+    today: Option<String>,
+    week_start: Option<String>,
 ) -> Result<Vec<SearchResult>, String> {
     let root = state.current_root()?;
+    let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
     tauri::async_runtime::spawn_blocking(move || -> Result<Vec<SearchResult>, String> {
-        let req = parse_raw_search_query(&raw_query, limit);
+        let req = parse_raw_search_query(&raw_query, limit, &ctx);
         let conn = open_db(&root)?;
         run_search_advanced(&conn, req)
     })
@@ -505,97 +594,433 @@ pub async fn tag_view_data(
     .map_err(|e| e.to_string())?
 }
 
-#[tauri::command]
+fn normalize_task_folders(folders: Option<Vec<String>>) -> Option<Vec<String>> {
+    folders.map(|folders| {
+        folders
+            .into_iter()
+            .map(|folder| folder.trim().trim_matches('/').replace('\\', "/"))
+            .filter(|folder| !folder.is_empty())
+            .collect::<Vec<_>>()
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+#[tauri::command(rename_all = "snake_case")]
 pub async fn tasks_query(
     state: State<'_, SpaceState>,
     bucket: String,
     today: String,
     limit: Option<u32>,
     folders: Option<Vec<String>>,
+    completed_from: Option<String>,
+    completed_to: Option<String>,
+    project: Option<String>,
+    tree: Option<bool>,
 ) -> Result<Vec<IndexedTask>, String> {
     let root = state.current_root()?;
     let bucket = TaskBucket::parse(&bucket)?;
-    let limit = limit.unwrap_or(500).min(5_000) as i64;
-    let folders = folders.map(|folders| {
-        folders
-            .into_iter()
-            .map(|folder| folder.trim().trim_matches('/').replace('\\', "/"))
-            .filter(|folder| !folder.is_empty())
-            .collect::<Vec<_>>()
-    });
+    let limit = (limit.map(i64::from).unwrap_or(DEFAULT_QUERY_LIMIT)).min(MAX_QUERY_LIMIT);
+    let folders = normalize_task_folders(folders);
     tauri::async_runtime::spawn_blocking(move || -> Result<Vec<IndexedTask>, String> {
         if folders.as_ref().is_some_and(|folders| folders.is_empty()) {
             return Ok(Vec::new());
         }
+        let completed_range = match (&completed_from, &completed_to) {
+            (Some(from), Some(to)) => Some((from.trim(), to.trim())),
+            (Some(from), None) => Some((from.trim(), today.as_str())),
+            _ => None,
+        };
+        let project = project.as_deref().map(str::trim).filter(|p| !p.is_empty());
+        let scope = TaskScope {
+            folders: folders.as_deref(),
+            completed_range,
+            project,
+            search: None,
+        };
+        let conn = open_db(&root)?;
+        let tasks = query_tasks(&conn, Some(bucket), &today, limit, &scope)?;
+        if tree.unwrap_or(false) {
+            nest_task_trees(&conn, tasks)
+        } else {
+            Ok(tasks)
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Full-text search over task text, tags and projects, optionally limited to
+/// a bucket, folders and/or a project.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn tasks_search(
+    state: State<'_, SpaceState>,
+    query: String,
+    today: String,
+    bucket: Option<String>,
+    limit: Option<u32>,
+    folders: Option<Vec<String>>,
+    project: Option<String>,
+) -> Result<Vec<IndexedTask>, String> {
+    let root = state.current_root()?;
+    let bucket = bucket
+        .as_deref()
+        .map(str::trim)
+        .filter(|bucket| !bucket.is_empty())
+        .map(TaskBucket::parse)
+        .transpose()?;
+    let limit = (limit.map(i64::from).unwrap_or(DEFAULT_QUERY_LIMIT)).min(MAX_QUERY_LIMIT);
+    let folders = normalize_task_folders(folders);
+    tauri::async_runtime::spawn_blocking(move || -> Result<Vec<IndexedTask>, String> {
+        let search = query.trim();
+        if search.is_empty() || folders.as_ref().is_some_and(|folders| folders.is_empty()) {
+            return Ok(Vec::new());
+        }
+        let project = project.as_deref().map(str::trim).filter(|p| !p.is_empty());
+        let scope = TaskScope {
+            folders: folders.as_deref(),
+            completed_range: None,
+            project,
+            search: Some(search),
+        };
+        let conn = open_db(&root)?;
+        query_tasks(&conn, bucket, &today, limit, &scope)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn tasks_projects(
+    state: State<'_, SpaceState>,
+) -> Result<Vec<TaskProjectSummary>, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<Vec<TaskProjectSummary>, String> {
         let conn = open_db(&root)?;
-        query_tasks(&conn, bucket, &today, limit, folders.as_deref())
+        list_task_projects(&conn)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn task_settings_get(state: State<'_, SpaceState>) -> Result<TaskSettings, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || Ok(read_task_settings(&root)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Saves the space's task settings and re-derives every task's project when
+/// the project rule changed.
+#[tauri::command]
+pub async fn task_settings_set(
+    state: State<'_, SpaceState>,
+    settings: TaskSettings,
+) -> Result<TaskSettings, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<TaskSettings, String> {
+        let previous = read_task_settings(&root);
+        write_task_settings(&root, &settings)?;
+        if previous.project_rule != settings.project_rule {
+            let mut conn = open_db(&root)?;
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            reassign_task_projects(&tx, settings.project_rule)?;
+            tx.commit().map_err(|e| e.to_string())?;
+        }
+        Ok(settings)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn run_task_query_source(
+    root: &Path,
+    query: &str,
+    ctx: &DateContext,
+    folders: Option<Vec<String>>,
+) -> Result<TaskQueryResult, String> {
+    let query = parse_task_query(query, ctx)?;
+    let folders = normalize_task_folders(folders).filter(|folders| !folders.is_empty());
+    let conn = open_db(root)?;
+    run_task_query(&conn, &query, DEFAULT_QUERY_LIMIT, folders.as_deref())
+}
+
+/// Runs a task query written in the filter language, e.g.
+/// `not done` / `due before next monday` / `group by note` / `limit 20`,
+/// one instruction per line. Relative dates resolve against `today` and the
+/// `week_start` day (Monday when omitted).
+#[tauri::command(rename_all = "snake_case")]
+pub async fn tasks_run_query(
+    state: State<'_, SpaceState>,
+    query: String,
+    today: String,
+    week_start: Option<String>,
+    folders: Option<Vec<String>>,
+) -> Result<TaskQueryResult, String> {
+    let root = state.current_root()?;
+    let ctx = DateContext::new(Some(&today), week_start.as_deref())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        run_task_query_source(&root, &query, &ctx, folders)
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
+fn task_view_abs_path(root: &Path, path: &str) -> Result<std::path::PathBuf, String> {
+    let rel = Path::new(path);
+    deny_hidden_rel_path(rel)?;
+    note_abs_path(root, path)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn task_view_load(
+    state: State<'_, SpaceState>,
+    path: String,
+    today: String,
+    week_start: Option<String>,
+) -> Result<TaskViewLoadResult, String> {
+    let root = state.current_root()?;
+    let ctx = DateContext::new(Some(&today), week_start.as_deref())?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<TaskViewLoadResult, String> {
+        let abs = task_view_abs_path(&root, &path)?;
+        let markdown = std::fs::read_to_string(&abs).map_err(|e| e.to_string())?;
+        let config = parse_task_view_config(&markdown)?;
+        let folders = Some(config.folders.clone());
+        let result = run_task_query_source(&root, &config.query, &ctx, folders)?;
+        Ok(TaskViewLoadResult { config, result })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Stores `config` in the note at `path`, creating the note when it does not
+/// exist yet. The query is validated before anything is written.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn task_view_save_config(
+    state: State<'_, SpaceState>,
+    path: String,
+    config: TaskViewConfig,
+    today: String,
+    week_start: Option<String>,
+) -> Result<TaskViewConfig, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    let ctx = DateContext::new(Some(&today), week_start.as_deref())?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<TaskViewConfig, String> {
+        parse_task_query(&config.query, &ctx)?;
+        let abs = task_view_abs_path(&root, &path)?;
+        let existing = match std::fs::read_to_string(&abs) {
+            Ok(markdown) => markdown,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.to_string()),
+        };
+        let next = render_task_view_markdown(&path, &existing, &config)?;
+        if let Some(parent) = abs.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        mark_recent_local_change(&recent_local_changes, &path);
+        write_note(&abs, next.as_bytes())?;
+        index_note(&root, &path, &next)?;
+        Ok(config)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn task_location(
+    conn: &rusqlite::Connection,
+    task_id: &str,
+) -> Result<(String, String, i64), String> {
+    let mut stmt = conn
+        .prepare("SELECT note_id, note_path, line_start FROM tasks WHERE task_id = ? LIMIT 1")
+        .map_err(|e| e.to_string())?;
+    stmt.query_row([task_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())
+}
+
+fn update_task_line(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    task_id: &str,
+    update: impl FnOnce(&mut TaskMetadata),
+) -> Result<(), String> {
+    let conn = open_db(root)?;
+    let (note_id, note_path, line_start) = task_location(&conn, task_id)?;
+
+    let abs = note_abs_path(root, &note_path)?;
+    let bytes = std::fs::read(&abs).map_err(|e| e.to_string())?;
+    let markdown = crypto::open_text(root, &bytes)?;
+    let next = mutate_task_line(&markdown, line_start, update)
+        .ok_or_else(|| "task line no longer exists".to_string())?;
+    mark_recent_local_change(recent_local_changes, &note_path);
+    write_note(
+        &abs,
+        &crypto::seal_text(root, &note_path, Some(&bytes), false, &next)?,
+    )?;
+    let _ = index_note(root, &note_id, &next);
+    Ok(())
+}
+
+/// Applies `update` to a task and every task nested under it in one write.
+fn update_task_subtree(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    task_id: &str,
+    update: impl Fn(&mut TaskMetadata),
+) -> Result<(), String> {
+    let conn = open_db(root)?;
+    let (note_id, note_path, line_start) = task_location(&conn, task_id)?;
+    let lines = subtask_lines(&conn, task_id)?;
+
+    let abs = note_abs_path(root, &note_path)?;
+    let bytes = std::fs::read(&abs).map_err(|e| e.to_string())?;
+    let mut next = crypto::open_text(root, &bytes)?;
+    for line in std::iter::once(line_start).chain(lines) {
+        next = mutate_task_line(&next, line, &update)
+            .ok_or_else(|| "task line no longer exists".to_string())?;
+    }
+    mark_recent_local_change(recent_local_changes, &note_path);
+    write_note(
+        &abs,
+        &crypto::seal_text(root, &note_path, Some(&bytes), false, &next)?,
+    )?;
+    let _ = index_note(root, &note_id, &next);
+    Ok(())
+}
+
+/// `None` leaves a date untouched, an empty string clears it and anything
+/// else is parsed as a (possibly natural-language) date.
+fn optional_task_date(
+    raw: Option<String>,
+    ctx: &DateContext,
+) -> Result<Option<Option<String>>, String> {
+    let Some(raw) = raw else {
+        return Ok(None);
+    };
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(Some(None));
+    }
+    Ok(Some(Some(parse_natural_date(trimmed, ctx)?)))
+}
+
+/// Checking a task marks it done on `today` unless it already has a
+/// completion date; unchecking drops the date.
+fn set_task_checked(metadata: &mut TaskMetadata, checked: bool, today: &str) {
+    if checked {
+        metadata.status = "done".to_string();
+        if metadata.completed_date.is_none() {
+            metadata.completed_date = Some(today.to_string());
+        }
+    } else {
+        metadata.status = "todo".to_string();
+        metadata.completed_date = None;
+    }
+}
+
+/// With `cascade`, every subtask is checked or unchecked along with the task.
+/// `today` is the frontend's local date, recorded as the completion date.
 #[tauri::command(rename_all = "snake_case")]
 pub async fn task_set_checked(
     state: State<'_, SpaceState>,
     task_id: String,
     checked: bool,
+    cascade: Option<bool>,
+    today: Option<String>,
 ) -> Result<(), String> {
     let root = state.current_root()?;
     let recent_local_changes = state.recent_local_changes();
+    let today = DateContext::new(today.as_deref(), None)?.today_iso();
     tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
-        let conn = open_db(&root)?;
-        let mut stmt = conn
-            .prepare("SELECT note_id, note_path, line_start FROM tasks WHERE task_id = ? LIMIT 1")
-            .map_err(|e| e.to_string())?;
-        let (note_id, note_path, line_start): (String, String, i64) = stmt
-            .query_row([task_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
-            .map_err(|e| e.to_string())?;
-
-        let abs = note_abs_path(&root, &note_path)?;
-        let markdown = std::fs::read_to_string(&abs).map_err(|e| e.to_string())?;
-        let next = mutate_task_line(&markdown, line_start, Some(checked), None, None)
-            .ok_or_else(|| "task line no longer exists".to_string())?;
-        mark_recent_local_change(&recent_local_changes, &note_path);
-        write_note(&abs, &next)?;
-        let _ = index_note(&root, &note_id, &next);
-        Ok(())
+        let set_status = |metadata: &mut TaskMetadata| set_task_checked(metadata, checked, &today);
+        if cascade.unwrap_or(false) {
+            update_task_subtree(&root, &recent_local_changes, &task_id, set_status)
+        } else {
+            update_task_line(&root, &recent_local_changes, &task_id, set_status)
+        }
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
+/// Replaces the scheduled and due dates (`None` or empty clears them).
+/// `start_date` is left untouched when omitted and cleared when empty. Dates
+/// may be phrases like `tomorrow`, `next fri` or `2w`, resolved against
+/// `today` and `week_start`.
+#[allow(clippy::too_many_arguments)]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn task_set_dates(
     state: State<'_, SpaceState>,
     task_id: String,
     scheduled_date: Option<String>,
     due_date: Option<String>,
+    start_date: Option<String>,
+    today: Option<String>,
+    week_start: Option<String>,
 ) -> Result<(), String> {
     let root = state.current_root()?;
     let recent_local_changes = state.recent_local_changes();
+    let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
     tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
-        let conn = open_db(&root)?;
-        let mut stmt = conn
-            .prepare("SELECT note_id, note_path, line_start FROM tasks WHERE task_id = ? LIMIT 1")
-            .map_err(|e| e.to_string())?;
-        let (note_id, note_path, line_start): (String, String, i64) = stmt
-            .query_row([task_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
-            .map_err(|e| e.to_string())?;
+        let scheduled_date = optional_task_date(scheduled_date, &ctx)?.flatten();
+        let due_date = optional_task_date(due_date, &ctx)?.flatten();
+        let start_date = optional_task_date(start_date, &ctx)?;
+        update_task_line(&root, &recent_local_changes, &task_id, |metadata| {
+            metadata.scheduled_date = scheduled_date;
+            metadata.due_date = due_date;
+            if let Some(start_date) = start_date {
+                metadata.start_date = start_date;
+            }
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-        let abs = note_abs_path(&root, &note_path)?;
-        let markdown = std::fs::read_to_string(&abs).map_err(|e| e.to_string())?;
-        let next = mutate_task_line(
-            &markdown,
-            line_start,
-            None,
-            scheduled_date.as_deref(),
-            due_date.as_deref(),
-        )
-        .ok_or_else(|| "task line no longer exists".to_string())?;
-        mark_recent_local_change(&recent_local_changes, &note_path);
-        write_note(&abs, &next)?;
-        let _ = index_note(&root, &note_id, &next);
-        Ok(())
+/// Sets the checkbox status. Moving to `done` records `completed_date` when
+/// given; any other status drops the completion date.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn task_set_status(
+    state: State<'_, SpaceState>,
+    task_id: String,
+    status: String,
+    completed_date: Option<String>,
+    today: Option<String>,
+    week_start: Option<String>,
+) -> Result<(), String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
+        let status = parse_task_status(&status)?;
+        let completed_date = optional_task_date(completed_date, &ctx)?.flatten();
+        update_task_line(&root, &recent_local_changes, &task_id, |metadata| {
+            metadata.status = status.to_string();
+            if status != "done" {
+                metadata.completed_date = None;
+            } else if completed_date.is_some() {
+                metadata.completed_date = completed_date;
+            }
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Accepts a 0-5 rank, a name (`highest` .. `lowest`, `none`) or a signifier emoji.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn task_set_priority(
+    state: State<'_, SpaceState>,
+    task_id: String,
+    priority: String,
+) -> Result<(), String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
+        let priority = parse_task_priority(&priority)?;
+        update_task_line(&root, &recent_local_changes, &task_id, |metadata| {
+            metadata.priority = priority;
+        })
     })
     .await
     .map_err(|e| e.to_string())?
@@ -620,13 +1045,24 @@ pub fn task_dates_by_ordinal(markdown: String, ordinal: u32) -> Option<TaskDateI
     None
 }
 
+/// Rewrites the dates of the `ordinal`-th task. Empty dates are removed;
+/// others may be natural-language phrases resolved against `today`.
 #[tauri::command(rename_all = "snake_case")]
 pub fn task_update_by_ordinal(
     markdown: String,
     ordinal: u32,
     scheduled_date: String,
     due_date: String,
-) -> Option<String> {
+    today: Option<String>,
+    week_start: Option<String>,
+) -> Result<Option<String>, String> {
+    let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+    let scheduled_date = optional_task_date(Some(scheduled_date), &ctx)?
+        .flatten()
+        .unwrap_or_default();
+    let due_date = optional_task_date(Some(due_date), &ctx)?
+        .flatten()
+        .unwrap_or_default();
     let newline = if markdown.contains("\r\n") {
         "\r\n"
     } else {
@@ -642,15 +1078,15 @@ pub fn task_update_by_ordinal(
             idx += 1;
             continue;
         }
-        let rebuilt = rewrite_task_dates(body, scheduled_date.trim(), due_date.trim());
+        let rebuilt = rewrite_task_dates(body, &scheduled_date, &due_date);
         *line = format!("{prefix}{rebuilt}");
         let mut next = lines.join(newline);
         if markdown.ends_with(newline) {
             next.push_str(newline);
         }
-        return Some(next);
+        return Ok(Some(next));
     }
-    None
+    Ok(None)
 }
 
 #[tauri::command(rename_all = "snake_case")]
@@ -692,3 +1128,50 @@ pub async fn backlinks(
     .await
     .map_err(|e| e.to_string())?
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_raw_search_query, set_task_checked};
+    use crate::dates::DateContext;
+    use crate::index::tasks::mutate_task_line;
+
+    #[test]
+    fn checking_a_task_records_its_completion_date() {
+        let markdown = "- [ ] Ship 📅 2025-03-07\n";
+        let checked = mutate_task_line(markdown, 1, |metadata| {
+            set_task_checked(metadata, true, "2025-03-05")
+        })
+        .unwrap();
+        assert_eq!(checked, "- [x] Ship 📅 2025-03-07 ✅ 2025-03-05\n");
+        let again = mutate_task_line(&checked, 1, |metadata| {
+            set_task_checked(metadata, true, "2025-03-06")
+        })
+        .unwrap();
+        assert_eq!(again, checked);
+        let unchecked = mutate_task_line(&checked, 1, |metadata| {
+            set_task_checked(metadata, false, "2025-03-06")
+        })
+        .unwrap();
+        assert_eq!(unchecked, markdown);
+    }
+
+    #[test]
+    fn parses_due_and_updated_search_filters() {
+        let ctx = DateContext::new(Some("2025-03-05"), None).unwrap();
+        let req = parse_raw_search_query(
+            "plan due:\"next fri\" updated:>=2025-01-01 #work due:nope",
+            None,
+            &ctx,
+        );
+        assert_eq!(req.due_from.as_deref(), Some("2025-03-07"));
+        assert_eq!(req.due_to.as_deref(), Some("2025-03-07"));
+        assert_eq!(req.updated_from.as_deref(), Some("2025-01-01"));
+        assert_eq!(req.updated_to, None);
+        assert_eq!(req.query.as_deref(), Some("plan due:nope"));
+
+        let req = parse_raw_search_query("due:today..eow updated:<today", None, &ctx);
+        assert_eq!(req.due_from.as_deref(), Some("2025-03-05"));
+        assert_eq!(req.due_to.as_deref(), Some("2025-03-09"));
+        assert_eq!(req.updated_to.as_deref(), Some("2025-03-04"));
+    }
+}
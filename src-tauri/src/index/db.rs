@@ -22,6 +22,10 @@ pub fn open_db(space_root: &Path) -> Result<rusqlite::Connection, String> {
     let conn = rusqlite::Connection::open(&path).map_err(|e| e.to_string())?;
     conn.pragma_update(None, "journal_mode", "WAL")
         .map_err(|e| e.to_string())?;
+    // Decrypted note bodies are dropped from the index on lock; overwrite
+    // them rather than leave them in free pages.
+    conn.pragma_update(None, "secure_delete", true)
+        .map_err(|e| e.to_string())?;
 
     let mut cache = schema_cache().lock().unwrap_or_else(|p| p.into_inner());
     if !cache.contains(&path) {
@@ -0,0 +1,316 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use rusqlite::{Connection, OptionalExtension};
+
+use super::db::open_db;
+use super::frontmatter::split_frontmatter;
+use super::helpers::now_sqlite_compatible_iso8601;
+use super::types::NoteIdentity;
+
+/// The UUID a note declares in its frontmatter `id`, as `note_create`
+/// writes it. Path-shaped ids are not identities: they change on rename.
+fn declared_uid(markdown: &str) -> Option<String> {
+    let (yaml, _body) = split_frontmatter(markdown);
+    if yaml.is_empty() {
+        return None;
+    }
+    let mapping = serde_yaml::from_str::<serde_yaml::Mapping>(yaml).ok()?;
+    let id = mapping.get("id")?.as_str()?.trim();
+    uuid::Uuid::parse_str(id).ok().map(|uid| uid.to_string())
+}
+
+pub(crate) fn note_uid(conn: &Connection, path: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT uid FROM note_identity WHERE path = ?",
+        [path],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Where `uid` was last seen and whether its note has been removed since.
+fn uid_path(conn: &Connection, uid: &str) -> Result<Option<(String, bool)>, String> {
+    conn.query_row(
+        "SELECT path, removed_at IS NOT NULL FROM note_identity WHERE uid = ?",
+        [uid],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Points `uid` at `to`, remembering the path it had before.
+fn move_identity(conn: &Connection, uid: &str, from: &str, to: &str) -> Result<(), String> {
+    if from == to {
+        return Ok(());
+    }
+    let now = now_sqlite_compatible_iso8601();
+    conn.execute(
+        "DELETE FROM note_identity WHERE path = ? AND uid != ?",
+        rusqlite::params![to, uid],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE note_identity SET path = ? WHERE uid = ?",
+        rusqlite::params![to, uid],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO note_path_history(uid, path, moved_at) VALUES(?, ?, ?)",
+        rusqlite::params![uid, from, now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The one identity whose note went away (removed, or its file is gone)
+/// with exactly this content, i.e. a note moved outside the app.
+fn moved_identity(
+    conn: &Connection,
+    space_root: &Path,
+    path: &str,
+    content_hash: &str,
+) -> Result<Option<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT uid, path, removed_at IS NOT NULL FROM note_identity
+             WHERE content_hash = ? AND path != ?",
+        )
+        .map_err(|e| e.to_string())?;
+    let candidates = stmt
+        .query_map(rusqlite::params![content_hash, path], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, bool>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut gone = candidates
+        .into_iter()
+        .filter(|(_, old_path, removed)| *removed || !space_root.join(old_path).exists())
+        .map(|(uid, old_path, _)| (uid, old_path));
+    match (gone.next(), gone.next()) {
+        (Some(found), None) => Ok(Some(found)),
+        _ => Ok(None),
+    }
+}
+
+/// Resolves the persistent identity of the note at `path`, following it
+/// across renames and moves, and records its current content hash. A
+/// declared id still held by a note on disk elsewhere marks a copy (e.g. a
+/// sync conflict copy), which gets an identity of its own.
+pub(crate) fn assign_note_identity(
+    conn: &Connection,
+    space_root: &Path,
+    path: &str,
+    markdown: &str,
+    content_hash: &str,
+) -> Result<String, String> {
+    let declared = declared_uid(markdown);
+    let current = note_uid(conn, path)?;
+    let uid = match (declared, current) {
+        (Some(declared), Some(current)) if current == declared => current,
+        (Some(declared), current) => match uid_path(conn, &declared)? {
+            Some((from, removed)) if removed || !space_root.join(&from).exists() => {
+                move_identity(conn, &declared, &from, path)?;
+                declared
+            }
+            Some(_) => current.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            None => {
+                conn.execute("DELETE FROM note_identity WHERE path = ?", [path])
+                    .map_err(|e| e.to_string())?;
+                declared
+            }
+        },
+        (None, Some(current)) => current,
+        (None, None) => match moved_identity(conn, space_root, path, content_hash)? {
+            Some((uid, from)) => {
+                move_identity(conn, &uid, &from, path)?;
+                uid
+            }
+            None => uuid::Uuid::new_v4().to_string(),
+        },
+    };
+    conn.execute(
+        "INSERT INTO note_identity(uid, path, content_hash, removed_at) VALUES(?, ?, ?, NULL)
+         ON CONFLICT(uid) DO UPDATE SET path = excluded.path,
+           content_hash = excluded.content_hash, removed_at = NULL",
+        rusqlite::params![uid, path, content_hash],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(uid)
+}
+
+/// Marks the identity at `path` as removed. It is kept so the note can be
+/// matched again if it reappears elsewhere.
+pub(crate) fn tombstone_note_identity(conn: &Connection, path: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE note_identity SET removed_at = ? WHERE path = ? AND removed_at IS NULL",
+        rusqlite::params![now_sqlite_compatible_iso8601(), path],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Tombstones identities whose path was not seen by a full rebuild.
+pub(crate) fn tombstone_missing_identities(
+    conn: &Connection,
+    seen: &HashSet<String>,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT path FROM note_identity WHERE removed_at IS NULL")
+        .map_err(|e| e.to_string())?;
+    let paths = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for path in paths {
+        if !seen.contains(&path) {
+            tombstone_note_identity(conn, &path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Records that the note at `from` was renamed to `to` by the app.
+pub fn record_note_move(space_root: &Path, from: &str, to: &str) -> Result<(), String> {
+    let conn = open_db(space_root)?;
+    let Some(uid) = note_uid(&conn, from)? else {
+        return Ok(());
+    };
+    move_identity(&conn, &uid, from, to)
+}
+
+/// Where the note once at `path` lives now: `path` itself if it is a
+/// current note, else the current path of the note that was moved away.
+pub fn current_note_path(space_root: &Path, path: &str) -> Result<Option<String>, String> {
+    let conn = open_db(space_root)?;
+    conn.query_row(
+        "SELECT i.path FROM note_identity i
+         WHERE i.removed_at IS NULL
+           AND (i.path = ?1 OR i.uid IN (SELECT uid FROM note_path_history WHERE path = ?1))
+         ORDER BY i.path = ?1 DESC
+         LIMIT 1",
+        [path],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+pub fn note_identity(space_root: &Path, path: &str) -> Result<Option<NoteIdentity>, String> {
+    let conn = open_db(space_root)?;
+    let Some(uid) = note_uid(&conn, path)? else {
+        return Ok(None);
+    };
+    let mut stmt = conn
+        .prepare("SELECT path FROM note_path_history WHERE uid = ? ORDER BY moved_at DESC")
+        .map_err(|e| e.to_string())?;
+    let previous_paths = stmt
+        .query_map([&uid], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|previous| previous != path)
+        .collect();
+    Ok(Some(NoteIdentity {
+        uid,
+        path: path.to_string(),
+        previous_paths,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{index_note, remove_note};
+
+    fn uid_at(root: &Path, path: &str) -> String {
+        note_uid(&open_db(root).unwrap(), path).unwrap().unwrap()
+    }
+
+    fn task_ids(root: &Path) -> Vec<String> {
+        open_db(root)
+            .unwrap()
+            .prepare("SELECT task_id FROM tasks")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn follows_notes_across_renames_and_external_moves() {
+        let root =
+            std::env::temp_dir().join(format!("glyph-identity-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("archive")).unwrap();
+        let markdown = "# Plan\n\n- [ ] Ship it\n";
+        std::fs::write(root.join("plan.md"), markdown).unwrap();
+        index_note(&root, "plan.md", markdown).unwrap();
+        let uid = uid_at(&root, "plan.md");
+        let first_task_ids = task_ids(&root);
+        assert_eq!(first_task_ids.len(), 1);
+
+        // Renamed by the app.
+        std::fs::rename(root.join("plan.md"), root.join("roadmap.md")).unwrap();
+        remove_note(&root, "plan.md").unwrap();
+        record_note_move(&root, "plan.md", "roadmap.md").unwrap();
+        index_note(&root, "roadmap.md", markdown).unwrap();
+        assert_eq!(uid_at(&root, "roadmap.md"), uid);
+
+        // Moved outside the app: the create may be seen before the delete.
+        std::fs::rename(root.join("roadmap.md"), root.join("archive/roadmap.md")).unwrap();
+        index_note(&root, "archive/roadmap.md", markdown).unwrap();
+        remove_note(&root, "roadmap.md").unwrap();
+        assert_eq!(uid_at(&root, "archive/roadmap.md"), uid);
+
+        let identity = note_identity(&root, "archive/roadmap.md").unwrap().unwrap();
+        assert_eq!(identity.previous_paths.len(), 2);
+        assert_eq!(
+            current_note_path(&root, "plan.md").unwrap().as_deref(),
+            Some("archive/roadmap.md")
+        );
+
+        // Task ids follow the note rather than its path.
+        assert_eq!(task_ids(&root), first_task_ids);
+
+        // A declared frontmatter id wins over the path.
+        let declared = "---\nid: 5f0c8a5e-8d7b-4a53-9a43-6d1f3f0b9a11\n---\nHello\n";
+        index_note(&root, "inbox.md", declared).unwrap();
+        assert_eq!(
+            uid_at(&root, "inbox.md"),
+            "5f0c8a5e-8d7b-4a53-9a43-6d1f3f0b9a11"
+        );
+
+        // A copy declaring the same id, e.g. a sync conflict copy, gets its
+        // own identity while the original is still on disk.
+        std::fs::write(root.join("inbox.md"), declared).unwrap();
+        std::fs::write(root.join("inbox (conflict).md"), declared).unwrap();
+        index_note(&root, "inbox (conflict).md", declared).unwrap();
+        let copy_uid = uid_at(&root, "inbox (conflict).md");
+        assert_ne!(copy_uid, "5f0c8a5e-8d7b-4a53-9a43-6d1f3f0b9a11");
+        assert_eq!(
+            uid_at(&root, "inbox.md"),
+            "5f0c8a5e-8d7b-4a53-9a43-6d1f3f0b9a11"
+        );
+        index_note(&root, "inbox (conflict).md", declared).unwrap();
+        assert_eq!(uid_at(&root, "inbox (conflict).md"), copy_uid);
+
+        // Once the original is gone, the declared id follows the note.
+        std::fs::rename(root.join("inbox.md"), root.join("archive/inbox.md")).unwrap();
+        index_note(&root, "archive/inbox.md", declared).unwrap();
+        assert_eq!(
+            uid_at(&root, "archive/inbox.md"),
+            "5f0c8a5e-8d7b-4a53-9a43-6d1f3f0b9a11"
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
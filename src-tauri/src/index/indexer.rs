@@ -3,16 +3,20 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::utils;
+use crate::{crypto, utils};
 
 use super::db::{open_db, resolve_title_to_id};
 use super::frontmatter::{
     parse_frontmatter_title_created_updated, preview_from_markdown, split_frontmatter,
 };
 use super::helpers::{path_to_slash_string, sha256_hex, should_skip_entry};
+use super::identity::{
+    assign_note_identity, tombstone_missing_identities, tombstone_note_identity,
+};
 use super::links::parse_outgoing_links;
 use super::properties::{delete_note_properties, reindex_note_properties};
 use super::tags::parse_all_tags;
+use super::tasks::settings::{read_task_settings, TaskProjectRule};
 use super::tasks::{delete_note_tasks, reindex_note_tasks};
 use super::types::IndexRebuildResult;
 
@@ -27,7 +31,7 @@ fn fts_body_with_frontmatter(markdown: &str) -> String {
     }
 }
 
-fn collect_markdown_files(space_root: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+pub(crate) fn collect_markdown_files(space_root: &Path) -> Result<Vec<(String, PathBuf)>, String> {
     let mut out: Vec<(String, PathBuf)> = Vec::new();
     let mut stack: Vec<PathBuf> = vec![space_root.to_path_buf()];
 
@@ -79,17 +83,38 @@ fn collect_markdown_files(space_root: &Path) -> Result<Vec<(String, PathBuf)>, S
 pub fn index_note(space_root: &Path, note_id: &str, markdown: &str) -> Result<(), String> {
     let conn = open_db(space_root)?;
     let file_path = space_root.join(note_id);
-    index_note_with_conn(&conn, note_id, markdown, &file_path)
+    let project_rule = read_task_settings(space_root).project_rule;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    index_note_with_conn(&tx, space_root, note_id, markdown, &file_path, project_rule)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Indexes `(note_id, markdown)` pairs in one transaction, for bulk edits.
+pub fn index_notes(space_root: &Path, notes: &[(String, String)]) -> Result<(), String> {
+    let conn = open_db(space_root)?;
+    let project_rule = read_task_settings(space_root).project_rule;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    for (note_id, markdown) in notes {
+        let file_path = space_root.join(note_id);
+        index_note_with_conn(&tx, space_root, note_id, markdown, &file_path, project_rule)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
 }
 
 fn index_note_with_conn(
-    conn: &rusqlite::Connection,
+    tx: &rusqlite::Transaction<'_>,
+    space_root: &Path,
     note_id: &str,
     markdown: &str,
     file_path: &Path,
+    project_rule: TaskProjectRule,
 ) -> Result<(), String> {
+    let markdown = &*crypto::indexable_text(space_root, markdown);
     let etag = sha256_hex(markdown.as_bytes());
-    let existing_etag: Option<String> = conn
+    assign_note_identity(tx, space_root, note_id, markdown, &etag)?;
+    let existing_etag: Option<String> = tx
         .query_row(
             "SELECT etag FROM notes WHERE id = ? LIMIT 1",
             [note_id],
@@ -100,8 +125,6 @@ fn index_note_with_conn(
         return Ok(());
     }
 
-    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
-
     let (mut title, created, updated) = parse_frontmatter_title_created_updated(markdown, file_path);
     if title == "Untitled" {
         if let Some(stem) = Path::new(note_id)
@@ -145,7 +168,7 @@ fn index_note_with_conn(
         )
         .map_err(|e| e.to_string())?;
     }
-    if let Err(error) = reindex_note_properties(&tx, note_id, markdown) {
+    if let Err(error) = reindex_note_properties(tx, note_id, markdown) {
         tracing::warn!(
             note_id = note_id,
             rel_path = rel_path,
@@ -153,7 +176,7 @@ fn index_note_with_conn(
             "Skipping note property indexing after frontmatter parse error"
         );
     }
-    reindex_note_tasks(&tx, note_id, &rel_path, &updated, &etag, markdown)?;
+    reindex_note_tasks(tx, note_id, &rel_path, &updated, &etag, markdown, project_rule)?;
 
     let (to_ids, to_titles) = parse_outgoing_links(note_id, markdown);
     let mut inserted = HashSet::<(Option<String>, Option<String>, &'static str)>::new();
@@ -163,7 +186,7 @@ fn index_note_with_conn(
     }
 
     for to_title in to_titles {
-        if let Some(to_id) = resolve_title_to_id(&tx, &to_title)? {
+        if let Some(to_id) = resolve_title_to_id(tx, &to_title)? {
             inserted.insert((Some(to_id), None, "note"));
         } else {
             inserted.insert((None, Some(to_title), "wikilink"));
@@ -178,7 +201,6 @@ fn index_note_with_conn(
         .map_err(|e| e.to_string())?;
     }
 
-    tx.commit().map_err(|e| e.to_string())?;
     Ok(())
 }
 
@@ -198,11 +220,13 @@ pub fn remove_note(space_root: &Path, note_id: &str) -> Result<(), String> {
         .map_err(|e| e.to_string())?;
     delete_note_properties(&tx, note_id)?;
     delete_note_tasks(&tx, note_id)?;
+    tombstone_note_identity(&tx, note_id)?;
     tx.commit().map_err(|e| e.to_string())?;
     Ok(())
 }
 
 pub fn rebuild(space_root: &Path) -> Result<IndexRebuildResult, String> {
+    let project_rule = read_task_settings(space_root).project_rule;
     let mut conn = open_db(space_root)?;
     let tx = conn.transaction().map_err(|e| e.to_string())?;
 
@@ -228,6 +252,7 @@ pub fn rebuild(space_root: &Path) -> Result<IndexRebuildResult, String> {
 
     for (rel, path) in &note_paths {
         let markdown = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let markdown = crypto::indexable_text(space_root, &markdown).into_owned();
 
         let (mut title, created, updated) = parse_frontmatter_title_created_updated(&markdown, path);
         if title == "Untitled" {
@@ -241,6 +266,7 @@ pub fn rebuild(space_root: &Path) -> Result<IndexRebuildResult, String> {
             }
         }
         let etag = sha256_hex(markdown.as_bytes());
+        assign_note_identity(&tx, space_root, rel, &markdown, &etag)?;
         let preview = preview_from_markdown(rel, &markdown);
 
         tx.execute(
@@ -273,7 +299,7 @@ pub fn rebuild(space_root: &Path) -> Result<IndexRebuildResult, String> {
                 "Skipping note property indexing during rebuild after frontmatter parse error"
             );
         }
-        reindex_note_tasks(&tx, rel, rel, &updated, &etag, &markdown)?;
+        reindex_note_tasks(&tx, rel, rel, &updated, &etag, &markdown, project_rule)?;
 
         let (to_ids, to_titles) = parse_outgoing_links(rel, &markdown);
         link_data.push((rel.clone(), to_ids, to_titles));
@@ -300,6 +326,12 @@ pub fn rebuild(space_root: &Path) -> Result<IndexRebuildResult, String> {
         }
     }
 
+    let seen = note_paths
+        .iter()
+        .map(|(rel, _)| rel.clone())
+        .collect::<HashSet<_>>();
+    tombstone_missing_identities(&tx, &seen)?;
+
     tx.commit().map_err(|e| e.to_string())?;
     Ok(IndexRebuildResult { indexed: count })
 }
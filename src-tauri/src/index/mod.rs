@@ -2,8 +2,9 @@ pub mod commands;
 pub(crate) mod db;
 mod frontmatter;
 mod helpers;
+pub(crate) mod identity;
 mod indexer;
-mod links;
+pub(crate) mod links;
 mod properties;
 mod schema;
 pub(crate) mod search_advanced;
@@ -13,4 +14,6 @@ mod tasks;
 mod types;
 
 pub use db::open_db;
-pub use indexer::{index_note, remove_note};
+pub use identity::{current_note_path, record_note_move};
+pub(crate) use indexer::collect_markdown_files;
+pub use indexer::{index_note, index_notes, remove_note};
@@ -1,6 +1,105 @@
 use rusqlite::Connection;
 
+const SCHEMA_VERSION: i64 = 2;
+
+const TASKS_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS tasks (
+  task_id TEXT PRIMARY KEY,
+  note_id TEXT NOT NULL,
+  note_path TEXT NOT NULL,
+  line_start INTEGER NOT NULL,
+  line_end INTEGER NOT NULL,
+  list_path TEXT NOT NULL,
+  indent INTEGER NOT NULL DEFAULT 0,
+  raw_text TEXT NOT NULL,
+  text_norm TEXT NOT NULL,
+  checked INTEGER NOT NULL CHECK (checked IN (0,1)),
+  status TEXT NOT NULL CHECK (status IN ('todo','in_progress','done','cancelled','deferred')),
+  priority INTEGER NOT NULL DEFAULT 3 CHECK (priority BETWEEN 0 AND 5),
+  due_date TEXT,
+  scheduled_date TEXT,
+  start_date TEXT,
+  completed_at TEXT,
+  recurrence_rule TEXT,
+  tags_json TEXT NOT NULL DEFAULT '[]',
+  project TEXT,
+  section TEXT,
+  source_hash TEXT NOT NULL,
+  note_etag TEXT NOT NULL,
+  note_updated TEXT NOT NULL,
+  indexed_at TEXT NOT NULL,
+  parent_task_id TEXT
+);
+"#;
+
+/// Columns of the version 0 `tasks` table, copied over by the version 1
+/// migration.
+const LEGACY_TASK_COLUMNS: &str = "task_id, note_id, note_path, line_start, line_end, list_path, \
+     indent, raw_text, text_norm, checked, status, priority, due_date, scheduled_date, \
+     start_date, completed_at, recurrence_rule, tags_json, project, section, source_hash, \
+     note_etag, note_updated, indexed_at";
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+        [name],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .map_err(|e| e.to_string())
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({table})"))
+        .map_err(|e| e.to_string())?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(names.iter().any(|name| name == column))
+}
+
+fn migrate_tasks_table(conn: &Connection) -> Result<(), String> {
+    if !table_exists(conn, "tasks")? {
+        return Ok(());
+    }
+    // SQLite cannot alter CHECK constraints in place, so copy the rows into a
+    // table with the widened status and priority ranges.
+    conn.execute_batch(&format!(
+        "BEGIN;
+         ALTER TABLE tasks RENAME TO tasks_legacy;
+         {TASKS_TABLE_SQL}
+         INSERT INTO tasks ({LEGACY_TASK_COLUMNS})
+           SELECT {LEGACY_TASK_COLUMNS} FROM tasks_legacy;
+         DROP TABLE tasks_legacy;
+         COMMIT;"
+    ))
+    .map_err(|e| e.to_string())
+}
+
+fn migrate(conn: &Connection) -> Result<(), String> {
+    let version: i64 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if version < 1 {
+        migrate_tasks_table(conn)?;
+    }
+    if version < 2
+        && table_exists(conn, "tasks")?
+        && !column_exists(conn, "tasks", "parent_task_id")?
+    {
+        conn.execute_batch("ALTER TABLE tasks ADD COLUMN parent_task_id TEXT;")
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    migrate(conn)?;
+    conn.execute_batch(TASKS_TABLE_SQL)
+        .map_err(|e| e.to_string())?;
     conn.execute_batch(
         r#"
 CREATE TABLE IF NOT EXISTS notes (
@@ -54,33 +153,6 @@ CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
   tokenize = 'porter'
 );
 
-CREATE TABLE IF NOT EXISTS tasks (
-  task_id TEXT PRIMARY KEY,
-  note_id TEXT NOT NULL,
-  note_path TEXT NOT NULL,
-  line_start INTEGER NOT NULL,
-  line_end INTEGER NOT NULL,
-  list_path TEXT NOT NULL,
-  indent INTEGER NOT NULL DEFAULT 0,
-  raw_text TEXT NOT NULL,
-  text_norm TEXT NOT NULL,
-  checked INTEGER NOT NULL CHECK (checked IN (0,1)),
-  status TEXT NOT NULL CHECK (status IN ('todo','in_progress','done','cancelled')),
-  priority INTEGER NOT NULL DEFAULT 3 CHECK (priority BETWEEN 1 AND 4),
-  due_date TEXT,
-  scheduled_date TEXT,
-  start_date TEXT,
-  completed_at TEXT,
-  recurrence_rule TEXT,
-  tags_json TEXT NOT NULL DEFAULT '[]',
-  project TEXT,
-  section TEXT,
-  source_hash TEXT NOT NULL,
-  note_etag TEXT NOT NULL,
-  note_updated TEXT NOT NULL,
-  indexed_at TEXT NOT NULL
-);
-
 CREATE UNIQUE INDEX IF NOT EXISTS tasks_note_loc_uidx
 ON tasks(note_id, list_path, line_start);
 
@@ -90,6 +162,34 @@ ON tasks(checked, scheduled_date, due_date, priority, note_updated DESC);
 CREATE INDEX IF NOT EXISTS tasks_note_idx ON tasks(note_id);
 CREATE INDEX IF NOT EXISTS tasks_schedule_idx ON tasks(scheduled_date);
 CREATE INDEX IF NOT EXISTS tasks_due_idx ON tasks(due_date);
+CREATE INDEX IF NOT EXISTS tasks_parent_idx ON tasks(parent_task_id);
+
+CREATE TABLE IF NOT EXISTS note_identity (
+  uid TEXT PRIMARY KEY,
+  path TEXT NOT NULL UNIQUE,
+  content_hash TEXT NOT NULL,
+  removed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS note_identity_hash_idx ON note_identity(content_hash);
+
+CREATE TABLE IF NOT EXISTS note_path_history (
+  uid TEXT NOT NULL,
+  path TEXT NOT NULL,
+  moved_at TEXT NOT NULL,
+  PRIMARY KEY (uid, path)
+);
+
+CREATE INDEX IF NOT EXISTS note_path_history_path_idx ON note_path_history(path);
+
+CREATE TABLE IF NOT EXISTS sync_files (
+  path TEXT PRIMARY KEY,
+  local_etag TEXT NOT NULL,
+  local_mtime_ms INTEGER NOT NULL,
+  local_size INTEGER NOT NULL,
+  remote_etag TEXT NOT NULL,
+  synced_at_ms INTEGER NOT NULL
+);
 
 CREATE VIRTUAL TABLE IF NOT EXISTS tasks_fts USING fts5(
   task_id UNINDEXED,
@@ -100,5 +200,7 @@ CREATE VIRTUAL TABLE IF NOT EXISTS tasks_fts USING fts5(
 );
 "#,
     )
-    .map_err(|e| e.to_string())
+    .map_err(|e| e.to_string())?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .map_err(|e| e.to_string())
 }
@@ -1,5 +1,7 @@
 use serde::Deserialize;
+use std::collections::HashSet;
 
+use rusqlite::types::Value;
 use rusqlite::Connection;
 
 use super::search_hybrid::hybrid_search;
@@ -18,6 +20,108 @@ pub struct SearchAdvancedRequest {
     pub tag_only: bool,
     #[serde(default)]
     pub limit: Option<u32>,
+    /// Inclusive ISO bounds on an open task's due date or the note's `due`
+    /// property.
+    #[serde(default)]
+    pub due_from: Option<String>,
+    #[serde(default)]
+    pub due_to: Option<String>,
+    /// Inclusive ISO bounds on the note's last-updated day.
+    #[serde(default)]
+    pub updated_from: Option<String>,
+    #[serde(default)]
+    pub updated_to: Option<String>,
+}
+
+fn push_bounds(
+    clause: &mut Vec<String>,
+    params: &mut Vec<Value>,
+    column: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) {
+    if let Some(from) = from {
+        clause.push(format!("{column} >= ?"));
+        params.push(Value::from(from.to_string()));
+    }
+    if let Some(to) = to {
+        clause.push(format!("{column} <= ?"));
+        params.push(Value::from(to.to_string()));
+    }
+}
+
+/// SQL conditions on `notes n` for the request's date filters, joined with
+/// `AND`; `None` when the request has no date filters.
+fn date_filter_sql(req: &SearchAdvancedRequest) -> Option<(String, Vec<Value>)> {
+    let bound = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let (due_from, due_to) = (bound(&req.due_from), bound(&req.due_to));
+    let (updated_from, updated_to) = (bound(&req.updated_from), bound(&req.updated_to));
+    let mut clauses = Vec::<String>::new();
+    let mut params = Vec::<Value>::new();
+
+    push_bounds(
+        &mut clauses,
+        &mut params,
+        "substr(n.updated, 1, 10)",
+        updated_from.as_deref(),
+        updated_to.as_deref(),
+    );
+    if due_from.is_some() || due_to.is_some() {
+        let mut task_clause = vec![
+            "dt.note_id = n.id".to_string(),
+            "dt.checked = 0".to_string(),
+        ];
+        push_bounds(
+            &mut task_clause,
+            &mut params,
+            "dt.due_date",
+            due_from.as_deref(),
+            due_to.as_deref(),
+        );
+        let mut property_clause = vec![
+            "np.note_id = n.id".to_string(),
+            "np.key = 'due'".to_string(),
+        ];
+        push_bounds(
+            &mut property_clause,
+            &mut params,
+            "substr(np.value_text, 1, 10)",
+            due_from.as_deref(),
+            due_to.as_deref(),
+        );
+        clauses.push(format!(
+            "(EXISTS (SELECT 1 FROM tasks dt WHERE {}) OR EXISTS (SELECT 1 FROM note_properties np WHERE {}))",
+            task_clause.join(" AND "),
+            property_clause.join(" AND ")
+        ));
+    }
+    if clauses.is_empty() {
+        None
+    } else {
+        Some((clauses.join(" AND "), params))
+    }
+}
+
+fn date_filtered_ids(
+    conn: &Connection,
+    (clause, params): &(String, Vec<Value>),
+) -> Result<HashSet<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT n.id FROM notes n WHERE {clause}"))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| e.to_string())
 }
 
 pub fn run_search_advanced(
@@ -25,6 +129,7 @@ pub fn run_search_advanced(
     req: SearchAdvancedRequest,
 ) -> Result<Vec<SearchResult>, String> {
     let limit = req.limit.unwrap_or(200).clamp(1, 2_000) as usize;
+    let date_filter = date_filter_sql(&req);
     let text = req.query.unwrap_or_default().trim().to_string();
     let mut tags = normalize_tags(req.tags)?;
     if req.tag_only {
@@ -41,18 +146,24 @@ pub fn run_search_advanced(
     let query_text = if req.tag_only { String::new() } else { text };
 
     let mut out = if !query_text.is_empty() && !req.title_only {
-        hybrid_search(
+        let mut results = hybrid_search(
             conn,
             &query_text,
             &tags,
             (limit as i64 * 8).clamp(200, 5_000),
-        )?
+        )?;
+        if let Some(filter) = &date_filter {
+            let allowed = date_filtered_ids(conn, filter)?;
+            results.retain(|result| allowed.contains(&result.id));
+        }
+        results
     } else {
         select_candidates(
             conn,
             &query_text,
             req.title_only,
             &tags,
+            date_filter.as_ref(),
             (limit as i64 * 8).clamp(200, 5_000),
         )?
         .into_iter()
@@ -75,6 +186,7 @@ fn select_candidates(
     text: &str,
     title_only: bool,
     tags: &[String],
+    date_filter: Option<&(String, Vec<Value>)>,
     limit: i64,
 ) -> Result<Vec<Candidate>, String> {
     let mut sql = String::from("SELECT n.id, n.title, n.preview FROM notes n ");
@@ -88,13 +200,21 @@ fn select_candidates(
         .iter()
         .map(|t| rusqlite::types::Value::from(t.clone()))
         .collect();
+    let mut conditions = Vec::<String>::new();
     if title_only && !text.is_empty() {
-        sql.push_str("WHERE lower(n.title) LIKE ? ");
+        conditions.push("lower(n.title) LIKE ?".to_string());
         params.push(rusqlite::types::Value::from(format!(
             "%{}%",
             text.to_lowercase()
         )));
     }
+    if let Some((clause, clause_params)) = date_filter {
+        conditions.push(clause.clone());
+        params.extend(clause_params.iter().cloned());
+    }
+    if !conditions.is_empty() {
+        sql.push_str(&format!("WHERE {} ", conditions.join(" AND ")));
+    }
     sql.push_str("ORDER BY n.updated DESC LIMIT ?");
     params.push(rusqlite::types::Value::from(limit));
 
@@ -1,8 +1,15 @@
 pub mod parse;
+pub mod query;
+pub mod settings;
 mod store;
 mod types;
+pub mod views;
 
+pub use parse::{parse_task_priority, parse_task_status};
+pub use query::{parse_task_query, DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT};
 pub use store::{
-    delete_note_tasks, mutate_task_line, note_abs_path, query_tasks, reindex_note_tasks, write_note,
+    delete_note_tasks, list_task_projects, mutate_task_line, nest_task_trees, note_abs_path,
+    query_tasks, reassign_task_projects, reindex_note_tasks, run_task_query, subtask_lines,
+    write_note, TaskScope,
 };
-pub use types::{IndexedTask, TaskBucket};
+pub use types::{IndexedTask, TaskBucket, TaskMetadata, TaskProjectSummary, TaskQueryResult};
@@ -1,10 +1,20 @@
-use super::types::ParsedTask;
+use super::types::{ParsedTask, TaskMetadata};
+
+pub const DEFAULT_TASK_PRIORITY: i64 = 3;
+
+const PRIORITY_SIGNIFIERS: [(&str, i64); 5] =
+    [("🔺", 0), ("⏫", 1), ("🔼", 2), ("🔽", 4), ("⏬", 5)];
+
+const START_DATE_SIGNIFIER: &str = "🛫";
+const SCHEDULED_DATE_SIGNIFIER: &str = "⏳";
+const DUE_DATE_SIGNIFIER: &str = "📅";
+const DONE_DATE_SIGNIFIER: &str = "✅";
 
 struct TaskLineMatch {
     leading_ws: usize,
     text_start: usize,
     marker: char,
-    checked: bool,
+    status_char: char,
 }
 
 fn parse_task_line(line: &str) -> Option<TaskLineMatch> {
@@ -28,18 +38,81 @@ fn parse_task_line(line: &str) -> Option<TaskLineMatch> {
     if bytes[1] != b' ' || bytes[2] != b'[' || bytes[4] != b']' || bytes[5] != b' ' {
         return None;
     }
-    let status = bytes[3] as char;
-    if status != ' ' && status != 'x' && status != 'X' {
-        return None;
-    }
+    let status_char = bytes[3] as char;
+    status_for_marker(status_char)?;
     Some(TaskLineMatch {
         leading_ws: ws,
         text_start: ws + 6,
         marker,
-        checked: status == 'x' || status == 'X',
+        status_char,
     })
 }
 
+pub fn status_for_marker(marker: char) -> Option<&'static str> {
+    match marker {
+        ' ' => Some("todo"),
+        'x' | 'X' => Some("done"),
+        '/' => Some("in_progress"),
+        '-' => Some("cancelled"),
+        '>' => Some("deferred"),
+        _ => None,
+    }
+}
+
+fn marker_for_status(status: &str) -> Option<char> {
+    match status {
+        "todo" => Some(' '),
+        "done" => Some('x'),
+        "in_progress" => Some('/'),
+        "cancelled" => Some('-'),
+        "deferred" => Some('>'),
+        _ => None,
+    }
+}
+
+/// Done and cancelled tasks are closed; they are indexed as checked so the
+/// open-task buckets skip them.
+pub fn is_closed_status(status: &str) -> bool {
+    matches!(status, "done" | "cancelled")
+}
+
+pub fn parse_task_status(raw: &str) -> Result<&'static str, String> {
+    let normalized = raw.trim().to_lowercase().replace([' ', '-'], "_");
+    match normalized.as_str() {
+        "todo" | "open" => Ok("todo"),
+        "in_progress" | "doing" => Ok("in_progress"),
+        "done" | "completed" => Ok("done"),
+        "cancelled" | "canceled" => Ok("cancelled"),
+        "deferred" | "forwarded" => Ok("deferred"),
+        _ => Err(format!("invalid task status '{}'", raw.trim())),
+    }
+}
+
+pub fn parse_task_priority(raw: &str) -> Result<i64, String> {
+    let trimmed = raw.trim();
+    if let Ok(value) = trimmed.parse::<i64>() {
+        return if (0..=5).contains(&value) {
+            Ok(value)
+        } else {
+            Err(format!(
+                "task priority must be between 0 and 5 (got {value})"
+            ))
+        };
+    }
+    if let Some(value) = priority_for_token(trimmed) {
+        return Ok(value);
+    }
+    match trimmed.to_lowercase().as_str() {
+        "highest" => Ok(0),
+        "high" => Ok(1),
+        "medium" => Ok(2),
+        "none" | "normal" => Ok(DEFAULT_TASK_PRIORITY),
+        "low" => Ok(4),
+        "lowest" => Ok(5),
+        _ => Err(format!("invalid task priority '{trimmed}'")),
+    }
+}
+
 pub fn is_valid_date(date: &str) -> bool {
     let bytes = date.as_bytes();
     if bytes.len() != 10 {
@@ -86,85 +159,157 @@ fn split_tokens(raw_text: &str) -> Vec<&str> {
         .collect()
 }
 
-fn extract_task_metadata(raw_text: &str) -> (Option<String>, Option<String>, Vec<String>) {
+fn without_variation_selector(token: &str) -> &str {
+    token.trim_end_matches('\u{fe0f}')
+}
+
+fn priority_for_token(token: &str) -> Option<i64> {
+    let token = without_variation_selector(token);
+    PRIORITY_SIGNIFIERS
+        .iter()
+        .find(|(signifier, _)| *signifier == token)
+        .map(|(_, priority)| *priority)
+}
+
+fn priority_token(priority: i64) -> Option<&'static str> {
+    PRIORITY_SIGNIFIERS
+        .iter()
+        .find(|(_, value)| *value == priority)
+        .map(|(signifier, _)| *signifier)
+}
+
+fn is_date_signifier(token: &str) -> bool {
+    matches!(
+        without_variation_selector(token),
+        START_DATE_SIGNIFIER | SCHEDULED_DATE_SIGNIFIER | DUE_DATE_SIGNIFIER | DONE_DATE_SIGNIFIER
+    )
+}
+
+struct ExtractedMetadata {
+    priority: i64,
+    due_date: Option<String>,
+    scheduled_date: Option<String>,
+    start_date: Option<String>,
+    completed_date: Option<String>,
+    tags: Vec<String>,
+}
+
+fn extract_task_metadata(raw_text: &str) -> ExtractedMetadata {
     let tokens = split_tokens(raw_text);
-    let mut due_date = None;
-    let mut scheduled_date = None;
-    let mut tags = Vec::new();
+    let mut out = ExtractedMetadata {
+        priority: DEFAULT_TASK_PRIORITY,
+        due_date: None,
+        scheduled_date: None,
+        start_date: None,
+        completed_date: None,
+        tags: Vec::new(),
+    };
     let mut i = 0usize;
     while i < tokens.len() {
         let t = tokens[i];
-        if t == "📅" && i + 1 < tokens.len() && is_valid_date(tokens[i + 1]) {
-            due_date = Some(tokens[i + 1].to_string());
+        if is_date_signifier(t) && i + 1 < tokens.len() && is_valid_date(tokens[i + 1]) {
+            let date = Some(tokens[i + 1].to_string());
+            match without_variation_selector(t) {
+                START_DATE_SIGNIFIER => out.start_date = date,
+                SCHEDULED_DATE_SIGNIFIER => out.scheduled_date = date,
+                DUE_DATE_SIGNIFIER => out.due_date = date,
+                _ => out.completed_date = date,
+            }
             i += 2;
             continue;
         }
-        if t == "⏳" && i + 1 < tokens.len() && is_valid_date(tokens[i + 1]) {
-            scheduled_date = Some(tokens[i + 1].to_string());
-            i += 2;
+        if let Some(priority) = priority_for_token(t) {
+            out.priority = priority;
+            i += 1;
             continue;
         }
         if t.starts_with('#') && t.len() > 1 {
-            tags.push(t.to_string());
+            out.tags.push(t.to_string());
         }
         i += 1;
     }
-    (due_date, scheduled_date, tags)
+    out
 }
 
-pub fn strip_schedule_tokens(raw_text: &str) -> String {
+pub fn strip_metadata_tokens(raw_text: &str) -> String {
     let tokens = split_tokens(raw_text);
     let mut kept: Vec<&str> = Vec::new();
     let mut i = 0usize;
     while i < tokens.len() {
         let t = tokens[i];
-        if (t == "📅" || t == "⏳") && i + 1 < tokens.len() && is_valid_date(tokens[i + 1]) {
+        if is_date_signifier(t) && i + 1 < tokens.len() && is_valid_date(tokens[i + 1]) {
             i += 2;
             continue;
         }
+        if priority_for_token(t).is_some() {
+            i += 1;
+            continue;
+        }
         kept.push(t);
         i += 1;
     }
     kept.join(" ")
 }
 
-pub fn apply_task_metadata(
-    line: &str,
-    checked: Option<bool>,
-    scheduled_date: Option<&str>,
-    due_date: Option<&str>,
-) -> Option<String> {
+pub fn task_line_metadata(line: &str) -> Option<TaskMetadata> {
     let m = parse_task_line(line)?;
-    let clean = strip_schedule_tokens(line[m.text_start..].trim());
-    let mut body = clean;
-    if let Some(v) = scheduled_date.filter(|d| is_valid_date(d)) {
+    let extracted = extract_task_metadata(line[m.text_start..].trim());
+    Some(TaskMetadata {
+        status: status_for_marker(m.status_char)?.to_string(),
+        priority: extracted.priority,
+        due_date: extracted.due_date,
+        scheduled_date: extracted.scheduled_date,
+        start_date: extracted.start_date,
+        completed_date: extracted.completed_date,
+    })
+}
+
+pub fn apply_task_metadata(line: &str, metadata: &TaskMetadata) -> Option<String> {
+    let m = parse_task_line(line)?;
+    let mut body = strip_metadata_tokens(line[m.text_start..].trim());
+    let mut push_token = |token: &str| {
         if !body.is_empty() {
             body.push(' ');
         }
-        body.push_str("⏳ ");
-        body.push_str(v);
+        body.push_str(token);
+    };
+    if let Some(token) = priority_token(metadata.priority) {
+        push_token(token);
     }
-    if let Some(v) = due_date.filter(|d| is_valid_date(d)) {
-        if !body.is_empty() {
-            body.push(' ');
+    for (signifier, date) in [
+        (START_DATE_SIGNIFIER, &metadata.start_date),
+        (SCHEDULED_DATE_SIGNIFIER, &metadata.scheduled_date),
+        (DUE_DATE_SIGNIFIER, &metadata.due_date),
+        (DONE_DATE_SIGNIFIER, &metadata.completed_date),
+    ] {
+        if let Some(v) = date.as_deref().filter(|d| is_valid_date(d)) {
+            push_token(&format!("{signifier} {v}"));
         }
-        body.push_str("📅 ");
-        body.push_str(v);
     }
 
-    let status = if checked.unwrap_or(m.checked) {
-        'x'
+    let status = if status_for_marker(m.status_char) == Some(metadata.status.as_str()) {
+        m.status_char
     } else {
-        ' '
+        marker_for_status(&metadata.status)?
     };
     let indent = &line[..m.leading_ws];
     Some(format!("{indent}{} [{}] {}", m.marker, status, body.trim()))
 }
 
+fn is_list_item(trimmed: &str) -> bool {
+    if trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ") {
+        return true;
+    }
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    digits > 0 && matches!(&trimmed[digits..].get(..2), Some(". ") | Some(") "))
+}
+
 pub fn parse_tasks(markdown: &str) -> Vec<ParsedTask> {
     let mut out = Vec::new();
     let mut headings: Vec<String> = Vec::new();
     let mut levels: Vec<(i64, i64)> = Vec::new();
+    // Open ancestors of the next task as (indent, index into `out`).
+    let mut ancestors: Vec<(i64, usize)> = Vec::new();
 
     for (idx, line) in markdown.lines().enumerate() {
         let trimmed = line.trim_start();
@@ -175,10 +320,24 @@ pub fn parse_tasks(markdown: &str) -> Vec<ParsedTask> {
                     headings.pop();
                 }
                 headings.push(trimmed[(level + 1)..].trim().to_string());
+                ancestors.clear();
             }
         }
 
         let Some(m) = parse_task_line(line) else {
+            let item_indent = (line.len() - trimmed.len()) as i64;
+            if is_list_item(trimmed) {
+                // A plain list item closes any task at its level or deeper.
+                while ancestors.last().is_some_and(|(n, _)| *n >= item_indent) {
+                    ancestors.pop();
+                }
+            } else if !trimmed.is_empty() && item_indent == 0 {
+                // A top-level paragraph ends the list, so later tasks start new trees.
+                ancestors.clear();
+            }
+            continue;
+        };
+        let Some(status) = status_for_marker(m.status_char) else {
             continue;
         };
 
@@ -186,6 +345,11 @@ pub fn parse_tasks(markdown: &str) -> Vec<ParsedTask> {
         while levels.last().map(|(n, _)| *n > indent).unwrap_or(false) {
             levels.pop();
         }
+        while ancestors.last().is_some_and(|(n, _)| *n >= indent) {
+            ancestors.pop();
+        }
+        let parent_index = ancestors.last().map(|(_, i)| *i);
+        ancestors.push((indent, out.len()));
         match levels.last_mut() {
             Some((n, c)) if *n == indent => *c += 1,
             _ => levels.push((indent, 0)),
@@ -197,8 +361,8 @@ pub fn parse_tasks(markdown: &str) -> Vec<ParsedTask> {
             .collect::<Vec<_>>()
             .join(".");
         let raw_text = line[m.text_start..].trim().to_string();
-        let text_norm = strip_schedule_tokens(&raw_text);
-        let (due_date, scheduled_date, tags) = extract_task_metadata(&raw_text);
+        let text_norm = strip_metadata_tokens(&raw_text);
+        let metadata = extract_task_metadata(&raw_text);
 
         out.push(ParsedTask {
             line_start: idx as i64 + 1,
@@ -206,11 +370,15 @@ pub fn parse_tasks(markdown: &str) -> Vec<ParsedTask> {
             indent,
             raw_text,
             text_norm: text_norm.clone(),
-            checked: m.checked,
-            status: if m.checked { "done" } else { "todo" }.to_string(),
-            due_date,
-            scheduled_date,
-            tags,
+            checked: is_closed_status(status),
+            status: status.to_string(),
+            priority: metadata.priority,
+            due_date: metadata.due_date,
+            scheduled_date: metadata.scheduled_date,
+            start_date: metadata.start_date,
+            completed_date: metadata.completed_date,
+            tags: metadata.tags,
+            parent_index,
             section: if headings.is_empty() {
                 None
             } else {
@@ -221,3 +389,68 @@ pub fn parse_tasks(markdown: &str) -> Vec<ParsedTask> {
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_task_metadata, parse_tasks, task_line_metadata};
+
+    #[test]
+    fn parses_statuses_priorities_and_dates() {
+        let markdown = "- [/] Draft spec ⏫ 🛫 2025-01-02 📅 2025-01-10\n- [-] Old idea 🔽\n- [>] Later\n- [x] Ship ✅ 2025-01-09\n";
+        let tasks = parse_tasks(markdown);
+        assert_eq!(tasks.len(), 4);
+
+        assert_eq!(tasks[0].status, "in_progress");
+        assert!(!tasks[0].checked);
+        assert_eq!(tasks[0].priority, 1);
+        assert_eq!(tasks[0].start_date.as_deref(), Some("2025-01-02"));
+        assert_eq!(tasks[0].due_date.as_deref(), Some("2025-01-10"));
+        assert_eq!(tasks[0].text_norm, "Draft spec");
+
+        assert_eq!(tasks[1].status, "cancelled");
+        assert!(tasks[1].checked);
+        assert_eq!(tasks[1].priority, 4);
+
+        assert_eq!(tasks[2].status, "deferred");
+        assert_eq!(tasks[2].priority, 3);
+
+        assert_eq!(tasks[3].status, "done");
+        assert_eq!(tasks[3].completed_date.as_deref(), Some("2025-01-09"));
+    }
+
+    #[test]
+    fn round_trips_task_metadata_through_apply() {
+        let line =
+            "  * [X] Review 🔺 🛫 2025-02-01 ⏳ 2025-02-02 📅 2025-02-03 ✅ 2025-02-04 #work";
+        let metadata = task_line_metadata(line).expect("task line should parse");
+        let rendered = apply_task_metadata(line, &metadata).expect("task line should render");
+        assert_eq!(
+            rendered,
+            "  * [X] Review #work 🔺 🛫 2025-02-01 ⏳ 2025-02-02 📅 2025-02-03 ✅ 2025-02-04"
+        );
+        assert_eq!(task_line_metadata(&rendered), Some(metadata));
+    }
+
+    #[test]
+    fn rewrites_status_marker_and_priority() {
+        let line = "- [ ] Call Sam 🔼 📅 2025-03-01";
+        let mut metadata = task_line_metadata(line).expect("task line should parse");
+        metadata.status = "in_progress".to_string();
+        metadata.priority = 3;
+        let rendered = apply_task_metadata(line, &metadata).expect("task line should render");
+        assert_eq!(rendered, "- [/] Call Sam 📅 2025-03-01");
+    }
+
+    #[test]
+    fn links_nested_tasks_to_their_parents() {
+        let markdown = "- [ ] Launch\n  - [x] Copy\n  - [ ] Assets\n    - [ ] Logo\n  - notes\n    - [ ] Under a plain item\n- [ ] Next\n\nParagraph\n  - [ ] Fresh tree\n";
+        let parents = parse_tasks(markdown)
+            .iter()
+            .map(|task| task.parent_index)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parents,
+            vec![None, Some(0), Some(0), Some(2), Some(0), None, None]
+        );
+    }
+}
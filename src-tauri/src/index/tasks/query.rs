@@ -0,0 +1,557 @@
+use rusqlite::types::Value;
+
+use super::parse::{parse_task_priority, parse_task_status};
+use crate::dates::{parse_natural_date, DateContext};
+
+pub const DEFAULT_QUERY_LIMIT: i64 = 500;
+pub const MAX_QUERY_LIMIT: i64 = 5_000;
+
+const DEFAULT_ORDER_SQL: &str = "COALESCE(t.scheduled_date, t.due_date) IS NULL, COALESCE(t.scheduled_date, t.due_date) ASC, t.priority ASC, n.title ASC, t.line_start ASC";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskDateField {
+    Due,
+    Scheduled,
+    Start,
+    Done,
+}
+
+impl TaskDateField {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "due" => Some(Self::Due),
+            "scheduled" => Some(Self::Scheduled),
+            "start" | "starts" => Some(Self::Start),
+            "done" | "completed" => Some(Self::Done),
+            _ => None,
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Self::Due => "t.due_date",
+            Self::Scheduled => "t.scheduled_date",
+            Self::Start => "t.start_date",
+            Self::Done => "t.completed_at",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskComparison {
+    Before,
+    OnOrBefore,
+    On,
+    OnOrAfter,
+    After,
+    Not,
+}
+
+impl TaskComparison {
+    fn sql(self) -> &'static str {
+        match self {
+            Self::Before => "<",
+            Self::OnOrBefore => "<=",
+            Self::On => "=",
+            Self::OnOrAfter => ">=",
+            Self::After => ">",
+            Self::Not => "!=",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskFilter {
+    Open(bool),
+    Status {
+        status: &'static str,
+        negate: bool,
+    },
+    Date {
+        field: TaskDateField,
+        comparison: TaskComparison,
+        date: String,
+    },
+    HasDate {
+        field: TaskDateField,
+        present: bool,
+    },
+    PathIncludes {
+        needle: String,
+        negate: bool,
+    },
+    TextIncludes {
+        needle: String,
+        negate: bool,
+    },
+    Tag {
+        tag: String,
+        negate: bool,
+    },
+    Priority {
+        comparison: TaskComparison,
+        priority: i64,
+    },
+    Project {
+        name: String,
+        negate: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskSortField {
+    Due,
+    Scheduled,
+    Start,
+    Done,
+    Priority,
+    Status,
+    Path,
+    Description,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskSort {
+    pub field: TaskSortField,
+    pub reverse: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskGroupField {
+    Note,
+    Folder,
+    Status,
+    Priority,
+    Due,
+    Tag,
+    Section,
+    Project,
+}
+
+impl TaskGroupField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Folder => "folder",
+            Self::Status => "status",
+            Self::Priority => "priority",
+            Self::Due => "due",
+            Self::Tag => "tag",
+            Self::Section => "section",
+            Self::Project => "project",
+        }
+    }
+}
+
+/// A parsed task query. Each non-empty line (or `;`-separated clause) of the
+/// source text is one instruction, e.g. `not done`, `due before next monday`,
+/// `sort by due` or `limit 20`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskQuery {
+    pub filters: Vec<TaskFilter>,
+    pub sorts: Vec<TaskSort>,
+    pub group_by: Option<TaskGroupField>,
+    pub limit: Option<i64>,
+}
+
+fn parse_date_filter(
+    field: TaskDateField,
+    rest: &str,
+    ctx: &DateContext,
+) -> Result<TaskFilter, String> {
+    let (comparison, phrase) = [
+        ("on or before ", TaskComparison::OnOrBefore),
+        ("on or after ", TaskComparison::OnOrAfter),
+        ("before ", TaskComparison::Before),
+        ("after ", TaskComparison::After),
+        ("on ", TaskComparison::On),
+    ]
+    .into_iter()
+    .find_map(|(prefix, comparison)| rest.strip_prefix(prefix).map(|p| (comparison, p)))
+    .unwrap_or((TaskComparison::On, rest));
+    Ok(TaskFilter::Date {
+        field,
+        comparison,
+        date: parse_natural_date(phrase, ctx)?,
+    })
+}
+
+fn parse_includes(rest: &str) -> Option<(String, bool)> {
+    if let Some(needle) = rest.strip_prefix("does not include ") {
+        return Some((needle.trim().to_string(), true));
+    }
+    rest.strip_prefix("includes ")
+        .or_else(|| rest.strip_prefix("include "))
+        .map(|needle| (needle.trim().to_string(), false))
+}
+
+fn normalize_tag(raw: &str) -> String {
+    format!("#{}", raw.trim().trim_start_matches('#'))
+}
+
+fn parse_sort(rest: &str) -> Result<TaskSort, String> {
+    let mut words = rest.split_whitespace();
+    let field = match words.next().unwrap_or_default() {
+        "due" => TaskSortField::Due,
+        "scheduled" => TaskSortField::Scheduled,
+        "start" => TaskSortField::Start,
+        "done" | "completed" => TaskSortField::Done,
+        "priority" => TaskSortField::Priority,
+        "status" => TaskSortField::Status,
+        "path" | "note" => TaskSortField::Path,
+        "description" | "text" => TaskSortField::Description,
+        other => return Err(format!("cannot sort tasks by '{other}'")),
+    };
+    let reverse = match words.next() {
+        None => false,
+        Some("reverse") => true,
+        Some(other) => return Err(format!("unexpected '{other}' in sort instruction")),
+    };
+    Ok(TaskSort { field, reverse })
+}
+
+fn parse_group(rest: &str) -> Result<TaskGroupField, String> {
+    match rest.trim() {
+        "note" | "path" | "filename" => Ok(TaskGroupField::Note),
+        "folder" => Ok(TaskGroupField::Folder),
+        "status" => Ok(TaskGroupField::Status),
+        "priority" => Ok(TaskGroupField::Priority),
+        "due" => Ok(TaskGroupField::Due),
+        "tag" | "tags" => Ok(TaskGroupField::Tag),
+        "section" | "heading" => Ok(TaskGroupField::Section),
+        "project" => Ok(TaskGroupField::Project),
+        other => Err(format!("cannot group tasks by '{other}'")),
+    }
+}
+
+fn parse_limit(rest: &str) -> Result<i64, String> {
+    let rest = rest.trim_start_matches("to ").trim_end_matches(" tasks");
+    let limit = rest
+        .trim()
+        .parse::<i64>()
+        .map_err(|_| format!("invalid task limit '{}'", rest.trim()))?;
+    Ok(limit.clamp(1, MAX_QUERY_LIMIT))
+}
+
+fn parse_instruction(
+    query: &mut TaskQuery,
+    original: &str,
+    ctx: &DateContext,
+) -> Result<(), String> {
+    let line = original.to_lowercase();
+    let line = line.as_str();
+    if line == "done" {
+        query.filters.push(TaskFilter::Open(false));
+        return Ok(());
+    }
+    if line == "not done" {
+        query.filters.push(TaskFilter::Open(true));
+        return Ok(());
+    }
+    if line == "overdue" {
+        query.filters.push(TaskFilter::Open(true));
+        query.filters.push(TaskFilter::Date {
+            field: TaskDateField::Due,
+            comparison: TaskComparison::Before,
+            date: ctx.today_iso(),
+        });
+        return Ok(());
+    }
+    if let Some(rest) = line.strip_prefix("status is ") {
+        let (raw, negate) = match rest.strip_prefix("not ") {
+            Some(raw) => (raw, true),
+            None => (rest, false),
+        };
+        query.filters.push(TaskFilter::Status {
+            status: parse_task_status(raw)?,
+            negate,
+        });
+        return Ok(());
+    }
+    for (prefix, is_path) in [("path ", true), ("description ", false), ("text ", false)] {
+        if let Some(rest) = line.strip_prefix(prefix) {
+            let (needle, negate) =
+                parse_includes(rest).ok_or_else(|| format!("invalid instruction '{original}'"))?;
+            query.filters.push(if is_path {
+                TaskFilter::PathIncludes { needle, negate }
+            } else {
+                TaskFilter::TextIncludes { needle, negate }
+            });
+            return Ok(());
+        }
+    }
+    if let Some(rest) = line
+        .strip_prefix("tags ")
+        .or_else(|| line.strip_prefix("tag "))
+    {
+        let (tag, negate) = parse_includes(rest).unwrap_or((rest.trim().to_string(), false));
+        query.filters.push(TaskFilter::Tag {
+            tag: normalize_tag(&tag),
+            negate,
+        });
+        return Ok(());
+    }
+    if let Some(rest) = line.strip_prefix("project is ") {
+        let (name, negate) = match rest.strip_prefix("not ") {
+            Some(name) => (name, true),
+            None => (rest, false),
+        };
+        query.filters.push(TaskFilter::Project {
+            name: name.trim().to_string(),
+            negate,
+        });
+        return Ok(());
+    }
+    if let Some(rest) = line.strip_prefix("priority ") {
+        let (comparison, raw) = [
+            ("is not ", TaskComparison::Not),
+            ("is ", TaskComparison::On),
+            // Lower numbers are more urgent, so "above" compares downwards.
+            ("above ", TaskComparison::Before),
+            ("below ", TaskComparison::After),
+        ]
+        .into_iter()
+        .find_map(|(prefix, comparison)| rest.strip_prefix(prefix).map(|r| (comparison, r)))
+        .unwrap_or((TaskComparison::On, rest));
+        query.filters.push(TaskFilter::Priority {
+            comparison,
+            priority: parse_task_priority(raw)?,
+        });
+        return Ok(());
+    }
+    if let Some(rest) = line
+        .strip_prefix("no ")
+        .and_then(|r| r.strip_suffix(" date"))
+    {
+        if let Some(field) = TaskDateField::parse(rest) {
+            query.filters.push(TaskFilter::HasDate {
+                field,
+                present: false,
+            });
+            return Ok(());
+        }
+    }
+    if let Some(rest) = line
+        .strip_prefix("has ")
+        .and_then(|r| r.strip_suffix(" date"))
+    {
+        if let Some(field) = TaskDateField::parse(rest) {
+            query.filters.push(TaskFilter::HasDate {
+                field,
+                present: true,
+            });
+            return Ok(());
+        }
+    }
+    if let Some((head, rest)) = line.split_once(' ') {
+        if let Some(field) = TaskDateField::parse(head) {
+            query
+                .filters
+                .push(parse_date_filter(field, rest.trim(), ctx)?);
+            return Ok(());
+        }
+    }
+    if let Some(rest) = line.strip_prefix("sort by ") {
+        query.sorts.push(parse_sort(rest)?);
+        return Ok(());
+    }
+    if let Some(rest) = line.strip_prefix("group by ") {
+        query.group_by = Some(parse_group(rest)?);
+        return Ok(());
+    }
+    if let Some(rest) = line.strip_prefix("limit ") {
+        query.limit = Some(parse_limit(rest)?);
+        return Ok(());
+    }
+    Err(format!("unknown task query instruction '{original}'"))
+}
+
+/// Parses a task query; relative dates resolve against `ctx`.
+pub fn parse_task_query(source: &str, ctx: &DateContext) -> Result<TaskQuery, String> {
+    let mut query = TaskQuery::default();
+    for instruction in source
+        .split(['\n', ';'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    {
+        parse_instruction(&mut query, instruction, ctx)?;
+    }
+    Ok(query)
+}
+
+impl TaskFilter {
+    fn to_sql(&self, params: &mut Vec<Value>) -> String {
+        match self {
+            Self::Open(open) => format!("t.checked = {}", if *open { 0 } else { 1 }),
+            Self::Status { status, negate } => {
+                params.push(Value::from(status.to_string()));
+                format!("t.status {} ?", if *negate { "!=" } else { "=" })
+            }
+            Self::Date {
+                field,
+                comparison,
+                date,
+            } => {
+                params.push(Value::from(date.clone()));
+                format!(
+                    "{col} IS NOT NULL AND {col} {op} ?",
+                    col = field.column(),
+                    op = comparison.sql()
+                )
+            }
+            Self::HasDate { field, present } => format!(
+                "{} IS {}NULL",
+                field.column(),
+                if *present { "NOT " } else { "" }
+            ),
+            Self::PathIncludes { needle, negate } => {
+                params.push(Value::from(needle.clone()));
+                format!(
+                    "instr(lower(t.note_path), ?) {} 0",
+                    if *negate { "=" } else { ">" }
+                )
+            }
+            Self::TextIncludes { needle, negate } => {
+                params.push(Value::from(needle.clone()));
+                format!(
+                    "instr(lower(t.text_norm), ?) {} 0",
+                    if *negate { "=" } else { ">" }
+                )
+            }
+            Self::Tag { tag, negate } => {
+                params.push(Value::from(tag.to_lowercase()));
+                format!(
+                    "{}EXISTS (SELECT 1 FROM json_each(t.tags_json) WHERE lower(json_each.value) = ?)",
+                    if *negate { "NOT " } else { "" }
+                )
+            }
+            Self::Priority {
+                comparison,
+                priority,
+            } => {
+                params.push(Value::from(*priority));
+                format!("t.priority {} ?", comparison.sql())
+            }
+            Self::Project { name, negate } => {
+                params.push(Value::from(name.clone()));
+                format!(
+                    "COALESCE(lower(t.project), '') {} ?",
+                    if *negate { "!=" } else { "=" }
+                )
+            }
+        }
+    }
+}
+
+impl TaskSort {
+    fn to_sql(self) -> String {
+        let direction = if self.reverse { "DESC" } else { "ASC" };
+        let column = match self.field {
+            TaskSortField::Due => "t.due_date",
+            TaskSortField::Scheduled => "t.scheduled_date",
+            TaskSortField::Start => "t.start_date",
+            TaskSortField::Done => "t.completed_at",
+            TaskSortField::Priority => return format!("t.priority {direction}"),
+            TaskSortField::Status => return format!("t.checked {direction}, t.status {direction}"),
+            TaskSortField::Path => {
+                return format!("t.note_path {direction}, t.line_start {direction}")
+            }
+            TaskSortField::Description => return format!("lower(t.text_norm) {direction}"),
+        };
+        // Tasks without the date always sort last, whichever the direction.
+        format!("{column} IS NULL, {column} {direction}")
+    }
+}
+
+impl TaskQuery {
+    /// Returns the WHERE clause (without the keyword) and ORDER BY list for
+    /// this query, pushing bound values onto `params` in order.
+    pub fn to_sql(&self, params: &mut Vec<Value>) -> (String, String) {
+        let where_sql = if self.filters.is_empty() {
+            "1 = 1".to_string()
+        } else {
+            self.filters
+                .iter()
+                .map(|filter| format!("({})", filter.to_sql(params)))
+                .collect::<Vec<_>>()
+                .join(" AND ")
+        };
+        let order_sql = if self.sorts.is_empty() {
+            DEFAULT_ORDER_SQL.to_string()
+        } else {
+            let mut parts = self
+                .sorts
+                .iter()
+                .map(|sort| sort.to_sql())
+                .collect::<Vec<_>>();
+            parts.push("n.title ASC, t.line_start ASC".to_string());
+            parts.join(", ")
+        };
+        (where_sql, order_sql)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_task_query, TaskComparison, TaskDateField, TaskFilter, TaskGroupField, TaskSort,
+        TaskSortField,
+    };
+    use crate::dates::DateContext;
+
+    // 2025-03-05 is a Wednesday.
+    fn ctx() -> DateContext {
+        DateContext::new(Some("2025-03-05"), None).expect("context should build")
+    }
+
+    #[test]
+    fn parses_filters_sorts_groups_and_limits() {
+        let query = parse_task_query(
+            "not done\ndue before next monday\nstatus is in_progress\npath includes Work\ntag #Urgent\npriority above medium\nsort by due reverse\ngroup by note\nlimit 20",
+            &ctx(),
+        )
+        .expect("query should parse");
+        assert_eq!(
+            query.filters,
+            vec![
+                TaskFilter::Open(true),
+                TaskFilter::Date {
+                    field: TaskDateField::Due,
+                    comparison: TaskComparison::Before,
+                    date: "2025-03-10".to_string(),
+                },
+                TaskFilter::Status {
+                    status: "in_progress",
+                    negate: false,
+                },
+                TaskFilter::PathIncludes {
+                    needle: "work".to_string(),
+                    negate: false,
+                },
+                TaskFilter::Tag {
+                    tag: "#urgent".to_string(),
+                    negate: false,
+                },
+                TaskFilter::Priority {
+                    comparison: TaskComparison::Before,
+                    priority: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            query.sorts,
+            vec![TaskSort {
+                field: TaskSortField::Due,
+                reverse: true,
+            }]
+        );
+        assert_eq!(query.group_by, Some(TaskGroupField::Note));
+        assert_eq!(query.limit, Some(20));
+    }
+
+    #[test]
+    fn rejects_unknown_instructions() {
+        let err = parse_task_query("not done; frobnicate", &ctx()).unwrap_err();
+        assert!(err.contains("frobnicate"));
+    }
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{glyph_paths, io_atomic};
+
+const TASK_SETTINGS_FILE: &str = "tasks.json";
+
+/// How a task's project is derived from the note that contains it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskProjectRule {
+    /// The note's `project` property, falling back to its top-level folder.
+    #[default]
+    PropertyThenFolder,
+    Property,
+    Folder,
+    None,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TaskSettings {
+    #[serde(default)]
+    pub project_rule: TaskProjectRule,
+}
+
+fn task_settings_path(space_root: &Path) -> Result<PathBuf, String> {
+    Ok(glyph_paths::glyph_dir(space_root)?.join(TASK_SETTINGS_FILE))
+}
+
+pub fn read_task_settings(space_root: &Path) -> TaskSettings {
+    let Ok(path) = task_settings_path(space_root) else {
+        return TaskSettings::default();
+    };
+    let bytes = std::fs::read(path).unwrap_or_default();
+    serde_json::from_slice(&bytes).unwrap_or_default()
+}
+
+pub fn write_task_settings(space_root: &Path, settings: &TaskSettings) -> Result<(), String> {
+    glyph_paths::ensure_glyph_dir(space_root)?;
+    let path = task_settings_path(space_root)?;
+    let bytes = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+    io_atomic::write_atomic(&path, &bytes).map_err(|e| e.to_string())
+}
@@ -1,14 +1,26 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 
-use crate::{io_atomic, paths};
+use crate::{dates::shift_date, io_atomic, paths};
+
+use rusqlite::{types::Value, OptionalExtension};
+
+use super::super::identity::note_uid;
 
 use super::{
-    parse::{apply_task_metadata, is_valid_date, parse_tasks},
-    types::{IndexedTask, ParsedTask, TaskBucket},
+    parse::{apply_task_metadata, is_valid_date, parse_tasks, task_line_metadata},
+    query::{TaskGroupField, TaskQuery},
+    settings::TaskProjectRule,
+    types::{
+        IndexedTask, ParsedTask, TaskBucket, TaskGroup, TaskMetadata, TaskProjectSummary,
+        TaskQueryResult,
+    },
 };
 
-fn task_id_for(note_id: &str, list_path: &str, line_start: i64, text_norm: &str) -> String {
-    let key = format!("{note_id}|{list_path}|{line_start}|{text_norm}");
+const DEFAULT_COMPLETED_RANGE_DAYS: i64 = 7;
+
+fn task_id_for(note_uid: &str, list_path: &str, line_start: i64, text_norm: &str) -> String {
+    let key = format!("{note_uid}|{list_path}|{line_start}|{text_norm}");
     super::super::helpers::sha256_hex(key.as_bytes())
 }
 
@@ -31,15 +43,29 @@ pub fn delete_note_tasks(conn: &rusqlite::Connection, note_id: &str) -> Result<(
     Ok(())
 }
 
+/// Note-level values shared by every task row inserted for one note.
+struct NoteTaskContext<'a> {
+    note_id: &'a str,
+    note_path: &'a str,
+    note_updated: &'a str,
+    note_etag: &'a str,
+    project: Option<&'a str>,
+}
+
 fn insert_task(
     conn: &rusqlite::Connection,
-    note_id: &str,
-    note_path: &str,
-    note_updated: &str,
-    note_etag: &str,
+    note: &NoteTaskContext<'_>,
+    task_id: &str,
+    parent_task_id: Option<&str>,
     task: &ParsedTask,
 ) -> Result<(), String> {
-    let task_id = task_id_for(note_id, &task.list_path, task.line_start, &task.text_norm);
+    let NoteTaskContext {
+        note_id,
+        note_path,
+        note_updated,
+        note_etag,
+        project,
+    } = *note;
     let tags_json = serde_json::to_string(&task.tags).map_err(|e| e.to_string())?;
     let indexed_at = super::super::helpers::now_sqlite_compatible_iso8601();
 
@@ -48,8 +74,8 @@ fn insert_task(
           task_id, note_id, note_path, line_start, line_end, list_path, indent,
           raw_text, text_norm, checked, status, priority, due_date, scheduled_date,
           start_date, completed_at, recurrence_rule, tags_json, project, section,
-          source_hash, note_etag, note_updated, indexed_at
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 3, ?, ?, NULL, NULL, NULL, ?, NULL, ?, ?, ?, ?, ?)",
+          source_hash, note_etag, note_updated, indexed_at, parent_task_id
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, ?, ?, ?, ?)",
         rusqlite::params![
             task_id,
             note_id,
@@ -62,21 +88,31 @@ fn insert_task(
             task.text_norm,
             if task.checked { 1 } else { 0 },
             task.status,
+            task.priority,
             task.due_date,
             task.scheduled_date,
+            task.start_date,
+            task.completed_date,
             tags_json,
+            project,
             task.section,
             super::super::helpers::sha256_hex(task.raw_text.as_bytes()),
             note_etag,
             note_updated,
             indexed_at,
+            parent_task_id,
         ],
     )
     .map_err(|e| e.to_string())?;
 
     conn.execute(
-        "INSERT OR REPLACE INTO tasks_fts(task_id, text, tags, project) VALUES(?, ?, ?, '')",
-        rusqlite::params![task_id, task.text_norm, task.tags.join(" ")],
+        "INSERT OR REPLACE INTO tasks_fts(task_id, text, tags, project) VALUES(?, ?, ?, ?)",
+        rusqlite::params![
+            task_id,
+            task.text_norm,
+            task.tags.join(" "),
+            project.unwrap_or_default()
+        ],
     )
     .map_err(|e| e.to_string())?;
 
@@ -90,6 +126,7 @@ pub fn reindex_note_tasks(
     note_updated: &str,
     note_etag: &str,
     markdown: &str,
+    project_rule: TaskProjectRule,
 ) -> Result<(), String> {
     conn.execute(
         "DELETE FROM tasks_fts WHERE task_id IN (SELECT task_id FROM tasks WHERE note_id = ?)",
@@ -98,96 +135,539 @@ pub fn reindex_note_tasks(
     .map_err(|e| e.to_string())?;
     conn.execute("DELETE FROM tasks WHERE note_id = ?", [note_id])
         .map_err(|e| e.to_string())?;
-    for task in parse_tasks(markdown) {
-        insert_task(conn, note_id, note_path, note_updated, note_etag, &task)?;
+    let tasks = parse_tasks(markdown);
+    if tasks.is_empty() {
+        return Ok(());
+    }
+    let project = resolve_note_project(conn, project_rule, note_id, note_path)?;
+    let note = NoteTaskContext {
+        note_id,
+        note_path,
+        note_updated,
+        note_etag,
+        project: project.as_deref(),
+    };
+    // Task ids hang off the note's identity so they survive renames.
+    let note_uid = note_uid(conn, note_path)?.unwrap_or_else(|| note_id.to_string());
+    let task_ids = tasks
+        .iter()
+        .map(|task| task_id_for(&note_uid, &task.list_path, task.line_start, &task.text_norm))
+        .collect::<Vec<_>>();
+    for (task, task_id) in tasks.iter().zip(&task_ids) {
+        let parent_task_id = task.parent_index.map(|index| task_ids[index].as_str());
+        insert_task(conn, &note, task_id, parent_task_id, task)?;
+    }
+    Ok(())
+}
+
+fn project_from_property(value_json: &str) -> Option<String> {
+    let value = serde_json::from_str::<serde_json::Value>(value_json).ok()?;
+    let raw = match value {
+        serde_json::Value::String(text) => text,
+        serde_json::Value::Array(items) => items.into_iter().find_map(|item| match item {
+            serde_json::Value::String(text) => Some(text),
+            _ => None,
+        })?,
+        _ => return None,
+    };
+    // `project: "[[Website]]"` links to the project note; use its name.
+    let trimmed = raw.trim();
+    let name = trimmed
+        .strip_prefix("[[")
+        .and_then(|inner| inner.strip_suffix("]]"))
+        .map(|inner| inner.split('|').next().unwrap_or(inner))
+        .unwrap_or(trimmed)
+        .trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn project_from_folder(note_path: &str) -> Option<String> {
+    note_path
+        .split_once('/')
+        .map(|(folder, _)| folder.trim().to_string())
+        .filter(|folder| !folder.is_empty())
+}
+
+/// Reads the note's `project` property from `note_properties`, so properties
+/// must be indexed before the note's tasks.
+fn resolve_note_project(
+    conn: &rusqlite::Connection,
+    rule: TaskProjectRule,
+    note_id: &str,
+    note_path: &str,
+) -> Result<Option<String>, String> {
+    let property = || -> Result<Option<String>, String> {
+        let value_json: Option<String> = conn
+            .query_row(
+                "SELECT value_json FROM note_properties WHERE note_id = ? AND lower(key) = 'project' ORDER BY ordinal LIMIT 1",
+                [note_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        Ok(value_json.as_deref().and_then(project_from_property))
+    };
+    Ok(match rule {
+        TaskProjectRule::PropertyThenFolder => {
+            property()?.or_else(|| project_from_folder(note_path))
+        }
+        TaskProjectRule::Property => property()?,
+        TaskProjectRule::Folder => project_from_folder(note_path),
+        TaskProjectRule::None => None,
+    })
+}
+
+/// Recomputes `tasks.project` for every indexed note after the project rule
+/// changes. Returns the number of notes updated.
+pub fn reassign_task_projects(
+    conn: &rusqlite::Connection,
+    rule: TaskProjectRule,
+) -> Result<usize, String> {
+    let notes = {
+        let mut stmt = conn
+            .prepare("SELECT DISTINCT note_id, note_path FROM tasks")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+    };
+    for (note_id, note_path) in &notes {
+        let project = resolve_note_project(conn, rule, note_id, note_path)?;
+        conn.execute(
+            "UPDATE tasks SET project = ? WHERE note_id = ?",
+            rusqlite::params![project, note_id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE tasks_fts SET project = ? WHERE task_id IN (SELECT task_id FROM tasks WHERE note_id = ?)",
+            rusqlite::params![project.unwrap_or_default(), note_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(notes.len())
+}
+
+pub fn list_task_projects(conn: &rusqlite::Connection) -> Result<Vec<TaskProjectSummary>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT project, SUM(CASE WHEN checked = 0 THEN 1 ELSE 0 END),
+                SUM(CASE WHEN status = 'done' THEN 1 ELSE 0 END)
+             FROM tasks WHERE project IS NOT NULL
+             GROUP BY project ORDER BY lower(project) ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TaskProjectSummary {
+                project: row.get(0)?,
+                open_count: row.get(1)?,
+                done_count: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+const TASK_SELECT_SQL: &str =
+    "SELECT t.task_id, t.note_id, n.title, t.note_path, t.line_start, t.raw_text, t.checked,
+        t.status, t.priority, t.due_date, t.scheduled_date, t.start_date, t.completed_at,
+        t.section, t.note_updated, t.tags_json, t.project, t.parent_task_id
+     FROM tasks t JOIN notes n ON n.id = t.note_id";
+
+fn folder_where(folders: Option<&[String]>, params: &mut Vec<Value>) -> String {
+    match folders {
+        Some(folders) if !folders.is_empty() => {
+            let clauses = std::iter::repeat_n("t.note_path LIKE ? ESCAPE '\\'", folders.len())
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            for folder in folders {
+                params.push(Value::from(like_prefix_pattern(folder)));
+            }
+            format!(" AND ({clauses})")
+        }
+        _ => String::new(),
+    }
+}
+
+fn read_task_row(row: &rusqlite::Row<'_>) -> Result<IndexedTask, String> {
+    let tags_json: String = row.get(15).map_err(|e| e.to_string())?;
+    Ok(IndexedTask {
+        task_id: row.get(0).map_err(|e| e.to_string())?,
+        note_id: row.get(1).map_err(|e| e.to_string())?,
+        note_title: row.get(2).map_err(|e| e.to_string())?,
+        note_path: row.get(3).map_err(|e| e.to_string())?,
+        line_start: row.get(4).map_err(|e| e.to_string())?,
+        raw_text: row.get(5).map_err(|e| e.to_string())?,
+        checked: row.get::<_, i64>(6).map_err(|e| e.to_string())? == 1,
+        status: row.get(7).map_err(|e| e.to_string())?,
+        priority: row.get(8).map_err(|e| e.to_string())?,
+        due_date: row.get(9).map_err(|e| e.to_string())?,
+        scheduled_date: row.get(10).map_err(|e| e.to_string())?,
+        start_date: row.get(11).map_err(|e| e.to_string())?,
+        completed_at: row.get(12).map_err(|e| e.to_string())?,
+        section: row.get(13).map_err(|e| e.to_string())?,
+        note_updated: row.get(14).map_err(|e| e.to_string())?,
+        tags: serde_json::from_str(&tags_json).unwrap_or_default(),
+        project: row.get(16).map_err(|e| e.to_string())?,
+        parent_task_id: row.get(17).map_err(|e| e.to_string())?,
+        subtasks_total: 0,
+        subtasks_done: 0,
+        children: Vec::new(),
+    })
+}
+
+fn select_tasks(
+    conn: &rusqlite::Connection,
+    where_sql: &str,
+    order_sql: &str,
+    params: &[Value],
+) -> Result<Vec<IndexedTask>, String> {
+    let sql = format!("{TASK_SELECT_SQL} WHERE {where_sql} ORDER BY {order_sql} LIMIT ?");
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(params.iter()))
+        .map_err(|e| e.to_string())?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        out.push(read_task_row(row)?);
+    }
+    Ok(out)
+}
+
+/// Loads every task of the given notes in document order, with subtask
+/// roll-ups filled in.
+fn note_task_forest(
+    conn: &rusqlite::Connection,
+    note_ids: &[&str],
+) -> Result<Vec<IndexedTask>, String> {
+    if note_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders = std::iter::repeat_n("?", note_ids.len())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut params = note_ids
+        .iter()
+        .map(|id| Value::from(id.to_string()))
+        .collect::<Vec<_>>();
+    params.push(Value::from(-1));
+    let mut forest = select_tasks(
+        conn,
+        &format!("t.note_id IN ({placeholders})"),
+        "t.note_id ASC, t.line_start ASC",
+        &params,
+    )?;
+    // Parents always precede their children within a note, so walking
+    // backwards sees every child's totals before its parent's.
+    let index = forest
+        .iter()
+        .enumerate()
+        .map(|(i, task)| (task.task_id.clone(), i))
+        .collect::<HashMap<_, _>>();
+    for i in (0..forest.len()).rev() {
+        let Some(parent) = forest[i]
+            .parent_task_id
+            .as_ref()
+            .and_then(|id| index.get(id).copied())
+        else {
+            continue;
+        };
+        let child = &forest[i];
+        let counted = child.status != "cancelled";
+        let total = child.subtasks_total + i64::from(counted);
+        let done = child.subtasks_done + i64::from(child.status == "done");
+        forest[parent].subtasks_total += total;
+        forest[parent].subtasks_done += done;
+    }
+    Ok(forest)
+}
+
+fn distinct_note_ids(tasks: &[IndexedTask]) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    tasks
+        .iter()
+        .map(|task| task.note_id.as_str())
+        .filter(|id| seen.insert(*id))
+        .collect()
+}
+
+/// Fills `subtasks_total` / `subtasks_done` ("3/5 done") for each task from
+/// all of its descendants. Cancelled subtasks are left out of the total.
+fn attach_subtask_rollups(
+    conn: &rusqlite::Connection,
+    tasks: &mut [IndexedTask],
+) -> Result<(), String> {
+    let forest = note_task_forest(conn, &distinct_note_ids(tasks))?;
+    let rollups = forest
+        .into_iter()
+        .map(|task| (task.task_id, (task.subtasks_total, task.subtasks_done)))
+        .collect::<HashMap<_, _>>();
+    for task in tasks {
+        if let Some((total, done)) = rollups.get(&task.task_id) {
+            task.subtasks_total = *total;
+            task.subtasks_done = *done;
+        }
     }
     Ok(())
 }
 
+/// Rebuilds matched tasks as trees. Each matched task that has no matched
+/// ancestor becomes a root carrying its full subtree, whether or not the
+/// subtasks matched themselves.
+pub fn nest_task_trees(
+    conn: &rusqlite::Connection,
+    tasks: Vec<IndexedTask>,
+) -> Result<Vec<IndexedTask>, String> {
+    let forest = note_task_forest(conn, &distinct_note_ids(&tasks))?;
+    let index = forest
+        .iter()
+        .enumerate()
+        .map(|(i, task)| (task.task_id.as_str(), i))
+        .collect::<HashMap<_, _>>();
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, task) in forest.iter().enumerate() {
+        if let Some(parent) = task.parent_task_id.as_deref().and_then(|id| index.get(id)) {
+            children.entry(*parent).or_default().push(i);
+        }
+    }
+    let matched = tasks
+        .iter()
+        .map(|task| task.task_id.as_str())
+        .collect::<HashSet<_>>();
+    let has_matched_ancestor = |task: &IndexedTask| {
+        let mut parent = task.parent_task_id.as_deref();
+        while let Some(id) = parent {
+            if matched.contains(id) {
+                return true;
+            }
+            parent = index
+                .get(id)
+                .and_then(|i| forest[*i].parent_task_id.as_deref());
+        }
+        false
+    };
+    fn build(
+        i: usize,
+        forest: &[IndexedTask],
+        children: &HashMap<usize, Vec<usize>>,
+    ) -> IndexedTask {
+        let mut node = forest[i].clone();
+        node.children = children
+            .get(&i)
+            .map(|kids| {
+                kids.iter()
+                    .map(|kid| build(*kid, forest, children))
+                    .collect()
+            })
+            .unwrap_or_default();
+        node
+    }
+    Ok(tasks
+        .iter()
+        .filter(|task| !has_matched_ancestor(task))
+        .filter_map(|task| index.get(task.task_id.as_str()))
+        .map(|i| build(*i, &forest, &children))
+        .collect())
+}
+
+/// Narrows a bucket query to a folder set, a project and/or a full-text match
+/// over `tasks_fts`.
+#[derive(Default)]
+pub struct TaskScope<'a> {
+    pub folders: Option<&'a [String]>,
+    pub completed_range: Option<(&'a str, &'a str)>,
+    pub project: Option<&'a str>,
+    pub search: Option<&'a str>,
+}
+
+/// Turns free text into an FTS5 query that matches every word as a prefix,
+/// quoting each word so user input cannot inject FTS syntax.
+fn fts_match_query(raw: &str) -> Option<String> {
+    let terms = raw
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\"*"))
+        .collect::<Vec<_>>();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn scope_where(scope: &TaskScope<'_>, params: &mut Vec<Value>) -> String {
+    let mut out = folder_where(scope.folders, params);
+    if let Some(project) = scope.project {
+        params.push(Value::from(project.to_lowercase()));
+        out.push_str(" AND lower(t.project) = ?");
+    }
+    if let Some(search) = scope.search.and_then(fts_match_query) {
+        params.push(Value::from(search));
+        out.push_str(" AND t.task_id IN (SELECT task_id FROM tasks_fts WHERE tasks_fts MATCH ?)");
+    }
+    out
+}
+
+/// Lists tasks in `bucket`, or every task when no bucket is given.
 pub fn query_tasks(
     conn: &rusqlite::Connection,
-    bucket: TaskBucket,
+    bucket: Option<TaskBucket>,
     today: &str,
     limit: i64,
-    folders: Option<&[String]>,
+    scope: &TaskScope<'_>,
 ) -> Result<Vec<IndexedTask>, String> {
     if !is_valid_date(today) {
         return Err("invalid today date".to_string());
     }
+    let mut params: Vec<Value> = Vec::new();
+    let Some(bucket) = bucket else {
+        let scope_where = scope_where(scope, &mut params);
+        params.push(Value::from(limit));
+        let mut tasks = select_tasks(
+            conn,
+            &format!("1 = 1{scope_where}"),
+            "t.checked ASC, t.note_updated DESC, n.title ASC, t.line_start ASC",
+            &params,
+        )?;
+        attach_subtask_rollups(conn, &mut tasks)?;
+        return Ok(tasks);
+    };
     let (where_sql, order_sql) = match bucket {
         TaskBucket::Inbox => (
-            "t.checked = 0 AND t.scheduled_date IS NULL AND t.due_date IS NULL",
+            "t.checked = 0 AND t.status != 'deferred' AND t.scheduled_date IS NULL AND t.due_date IS NULL",
             "t.note_updated DESC, n.title ASC, t.line_start ASC",
         ),
-        TaskBucket::Today => (
-            "t.checked = 0 AND ((t.scheduled_date IS NOT NULL AND t.scheduled_date <= ?) OR (t.due_date IS NOT NULL AND t.due_date <= ?))",
-            "COALESCE(t.scheduled_date, t.due_date) ASC, t.priority ASC, n.title ASC, t.line_start ASC",
-        ),
-        TaskBucket::Upcoming => (
-            "t.checked = 0 AND ((t.scheduled_date IS NOT NULL AND t.scheduled_date > ?) OR (t.due_date IS NOT NULL AND t.due_date > ?))",
-            "COALESCE(t.scheduled_date, t.due_date) ASC, t.priority ASC, n.title ASC, t.line_start ASC",
+        TaskBucket::Today => {
+            params.push(Value::from(today.to_string()));
+            params.push(Value::from(today.to_string()));
+            (
+                "t.checked = 0 AND ((t.scheduled_date IS NOT NULL AND t.scheduled_date <= ?) OR (t.due_date IS NOT NULL AND t.due_date <= ?))",
+                "COALESCE(t.scheduled_date, t.due_date) ASC, t.priority ASC, n.title ASC, t.line_start ASC",
+            )
+        }
+        TaskBucket::Upcoming => {
+            params.push(Value::from(today.to_string()));
+            params.push(Value::from(today.to_string()));
+            (
+                "t.checked = 0 AND ((t.scheduled_date IS NOT NULL AND t.scheduled_date > ?) OR (t.due_date IS NOT NULL AND t.due_date > ?))",
+                "COALESCE(t.scheduled_date, t.due_date) ASC, t.priority ASC, n.title ASC, t.line_start ASC",
+            )
+        }
+        TaskBucket::Overdue => {
+            params.push(Value::from(today.to_string()));
+            (
+                "t.checked = 0 AND t.due_date IS NOT NULL AND t.due_date < ?",
+                "t.due_date ASC, t.priority ASC, n.title ASC, t.line_start ASC",
+            )
+        }
+        TaskBucket::Someday => (
+            "t.checked = 0 AND (t.status = 'deferred' OR EXISTS (SELECT 1 FROM json_each(t.tags_json) WHERE lower(json_each.value) = '#someday'))",
+            "t.priority ASC, t.note_updated DESC, n.title ASC, t.line_start ASC",
         ),
-    };
-    let folder_where = match folders {
-        Some(folders) if !folders.is_empty() => {
-            let clauses = std::iter::repeat_n("t.note_path LIKE ? ESCAPE '\\'", folders.len())
-                .collect::<Vec<_>>()
-                .join(" OR ");
-            format!(" AND ({clauses})")
+        TaskBucket::Completed => {
+            let default_start = shift_date(today, -(DEFAULT_COMPLETED_RANGE_DAYS - 1))?;
+            let (start, end) = scope.completed_range.unwrap_or((&default_start, today));
+            if !is_valid_date(start) || !is_valid_date(end) {
+                return Err("invalid completed date range".to_string());
+            }
+            params.push(Value::from(start.to_string()));
+            params.push(Value::from(end.to_string()));
+            // Tasks checked off without a done date fall back to the note's
+            // modification day.
+            (
+                "t.status = 'done' AND COALESCE(t.completed_at, substr(t.note_updated, 1, 10)) BETWEEN ? AND ?",
+                "COALESCE(t.completed_at, substr(t.note_updated, 1, 10)) DESC, n.title ASC, t.line_start ASC",
+            )
         }
-        _ => String::new(),
     };
+    let scope_where = scope_where(scope, &mut params);
+    params.push(Value::from(limit));
+    let mut tasks = select_tasks(
+        conn,
+        &format!("{where_sql}{scope_where}"),
+        order_sql,
+        &params,
+    )?;
+    attach_subtask_rollups(conn, &mut tasks)?;
+    Ok(tasks)
+}
 
-    let sql = format!(
-        "SELECT t.task_id, t.note_id, n.title, t.note_path, t.line_start, t.raw_text, t.checked,
-            t.status, t.priority, t.due_date, t.scheduled_date, t.section, t.note_updated
-         FROM tasks t JOIN notes n ON n.id = t.note_id
-         WHERE {where_sql}{folder_where} ORDER BY {order_sql} LIMIT ?"
-    );
-    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
-    let mut params: Vec<rusqlite::types::Value> = Vec::new();
-    match bucket {
-        TaskBucket::Inbox => {}
-        _ => {
-            params.push(rusqlite::types::Value::from(today.to_string()));
-            params.push(rusqlite::types::Value::from(today.to_string()));
+pub fn run_task_query(
+    conn: &rusqlite::Connection,
+    query: &TaskQuery,
+    limit: i64,
+    folders: Option<&[String]>,
+) -> Result<TaskQueryResult, String> {
+    let mut params: Vec<Value> = Vec::new();
+    let (where_sql, order_sql) = query.to_sql(&mut params);
+    let folder_where = folder_where(folders, &mut params);
+    params.push(Value::from(query.limit.unwrap_or(limit)));
+    let mut tasks = select_tasks(
+        conn,
+        &format!("{where_sql}{folder_where}"),
+        &order_sql,
+        &params,
+    )?;
+    attach_subtask_rollups(conn, &mut tasks)?;
+    let groups = query
+        .group_by
+        .map(|field| group_tasks(&tasks, field))
+        .unwrap_or_default();
+    Ok(TaskQueryResult {
+        group_by: query.group_by.map(|field| field.as_str().to_string()),
+        tasks,
+        groups,
+    })
+}
+
+fn group_keys(task: &IndexedTask, field: TaskGroupField) -> Vec<String> {
+    let key = match field {
+        TaskGroupField::Note => Some(task.note_path.clone()),
+        TaskGroupField::Folder => task
+            .note_path
+            .rsplit_once('/')
+            .map(|(folder, _)| folder.to_string()),
+        TaskGroupField::Status => Some(task.status.clone()),
+        TaskGroupField::Priority => Some(task.priority.to_string()),
+        TaskGroupField::Due => task.due_date.clone(),
+        TaskGroupField::Section => task.section.clone(),
+        TaskGroupField::Project => task.project.clone(),
+        TaskGroupField::Tag => {
+            return if task.tags.is_empty() {
+                vec![String::new()]
+            } else {
+                task.tags.clone()
+            }
         }
-    }
-    for folder in folders.unwrap_or(&[]) {
-        params.push(rusqlite::types::Value::from(like_prefix_pattern(folder)));
-    }
-    params.push(rusqlite::types::Value::from(limit));
-    let mut rows = stmt
-        .query(rusqlite::params_from_iter(params.iter()))
-        .map_err(|e| e.to_string())?;
+    };
+    vec![key.unwrap_or_default()]
+}
 
-    let mut out = Vec::new();
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        out.push(IndexedTask {
-            task_id: row.get(0).map_err(|e| e.to_string())?,
-            note_id: row.get(1).map_err(|e| e.to_string())?,
-            note_title: row.get(2).map_err(|e| e.to_string())?,
-            note_path: row.get(3).map_err(|e| e.to_string())?,
-            line_start: row.get(4).map_err(|e| e.to_string())?,
-            raw_text: row.get(5).map_err(|e| e.to_string())?,
-            checked: row.get::<_, i64>(6).map_err(|e| e.to_string())? == 1,
-            status: row.get(7).map_err(|e| e.to_string())?,
-            priority: row.get(8).map_err(|e| e.to_string())?,
-            due_date: row.get(9).map_err(|e| e.to_string())?,
-            scheduled_date: row.get(10).map_err(|e| e.to_string())?,
-            section: row.get(11).map_err(|e| e.to_string())?,
-            note_updated: row.get(12).map_err(|e| e.to_string())?,
-        });
+/// Buckets already-sorted tasks by `field`. Groups are ordered by key with
+/// the empty key (no folder, no due date, ...) last; a task with several tags
+/// appears in each tag's group.
+fn group_tasks(tasks: &[IndexedTask], field: TaskGroupField) -> Vec<TaskGroup> {
+    let mut groups: BTreeMap<(bool, String), Vec<String>> = BTreeMap::new();
+    for task in tasks {
+        for key in group_keys(task, field) {
+            groups
+                .entry((key.is_empty(), key))
+                .or_default()
+                .push(task.task_id.clone());
+        }
     }
-    Ok(out)
+    groups
+        .into_iter()
+        .map(|((_, key), task_ids)| TaskGroup { key, task_ids })
+        .collect()
 }
 
 pub fn mutate_task_line(
     markdown: &str,
     line_start: i64,
-    checked: Option<bool>,
-    scheduled_date: Option<&str>,
-    due_date: Option<&str>,
+    update: impl FnOnce(&mut TaskMetadata),
 ) -> Option<String> {
     let newline = if markdown.contains("\r\n") {
         "\r\n"
@@ -197,7 +677,9 @@ pub fn mutate_task_line(
     let mut lines: Vec<String> = markdown.lines().map(|line| line.to_string()).collect();
     let idx = (line_start as usize).saturating_sub(1);
     let line = lines.get(idx)?.clone();
-    lines[idx] = apply_task_metadata(&line, checked, scheduled_date, due_date)?;
+    let mut metadata = task_line_metadata(&line)?;
+    update(&mut metadata);
+    lines[idx] = apply_task_metadata(&line, &metadata)?;
     let mut next = lines.join(newline);
     if markdown.ends_with(newline) {
         next.push_str(newline);
@@ -205,10 +687,61 @@ pub fn mutate_task_line(
     Some(next)
 }
 
+/// Line numbers of every task nested under `task_id`, in document order.
+pub fn subtask_lines(conn: &rusqlite::Connection, task_id: &str) -> Result<Vec<i64>, String> {
+    let mut stmt = conn
+        .prepare(
+            "WITH RECURSIVE subtree(task_id) AS (
+               SELECT task_id FROM tasks WHERE parent_task_id = ?1
+               UNION ALL
+               SELECT t.task_id FROM tasks t JOIN subtree s ON t.parent_task_id = s.task_id
+             )
+             SELECT line_start FROM tasks WHERE task_id IN subtree ORDER BY line_start",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([task_id], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
 pub fn note_abs_path(space_root: &Path, note_path: &str) -> Result<std::path::PathBuf, String> {
     paths::join_under(space_root, Path::new(note_path))
 }
 
-pub fn write_note(path: &Path, text: &str) -> Result<(), String> {
-    io_atomic::write_atomic(path, text.as_bytes()).map_err(|e| e.to_string())
+pub fn write_note(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    io_atomic::write_atomic(path, bytes).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fts_match_query, project_from_folder, project_from_property};
+
+    #[test]
+    fn derives_projects_from_property_values_and_folders() {
+        assert_eq!(
+            project_from_property("\"[[Website Redesign|Site]]\"").as_deref(),
+            Some("Website Redesign")
+        );
+        assert_eq!(
+            project_from_property("[\"Launch\", \"Other\"]").as_deref(),
+            Some("Launch")
+        );
+        assert_eq!(project_from_property("\"  \""), None);
+        assert_eq!(
+            project_from_folder("Work/Q1/plan.md").as_deref(),
+            Some("Work")
+        );
+        assert_eq!(project_from_folder("plan.md"), None);
+    }
+
+    #[test]
+    fn quotes_task_search_terms_as_prefixes() {
+        assert_eq!(
+            fts_match_query("call \"sam OR"),
+            Some("\"call\"* \"sam\"* \"OR\"*".to_string())
+        );
+        assert_eq!(fts_match_query("   "), None);
+    }
 }
@@ -5,6 +5,9 @@ pub enum TaskBucket {
     Inbox,
     Today,
     Upcoming,
+    Overdue,
+    Someday,
+    Completed,
 }
 
 impl TaskBucket {
@@ -13,6 +16,9 @@ impl TaskBucket {
             "inbox" => Ok(Self::Inbox),
             "today" => Ok(Self::Today),
             "upcoming" => Ok(Self::Upcoming),
+            "overdue" => Ok(Self::Overdue),
+            "someday" => Ok(Self::Someday),
+            "completed" => Ok(Self::Completed),
             _ => Err("invalid task bucket".to_string()),
         }
     }
@@ -27,13 +33,29 @@ pub struct ParsedTask {
     pub text_norm: String,
     pub checked: bool,
     pub status: String,
+    pub priority: i64,
     pub due_date: Option<String>,
     pub scheduled_date: Option<String>,
+    pub start_date: Option<String>,
+    pub completed_date: Option<String>,
     pub tags: Vec<String>,
+    /// Index (into the same parse result) of the closest enclosing task.
+    pub parent_index: Option<usize>,
     pub section: Option<String>,
 }
 
-#[derive(Serialize)]
+/// Status, priority and date signifiers carried by a single task line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskMetadata {
+    pub status: String,
+    pub priority: i64,
+    pub due_date: Option<String>,
+    pub scheduled_date: Option<String>,
+    pub start_date: Option<String>,
+    pub completed_date: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
 pub struct IndexedTask {
     pub task_id: String,
     pub note_id: String,
@@ -46,6 +68,36 @@ pub struct IndexedTask {
     pub priority: i64,
     pub due_date: Option<String>,
     pub scheduled_date: Option<String>,
+    pub start_date: Option<String>,
+    pub completed_at: Option<String>,
     pub section: Option<String>,
     pub note_updated: String,
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+    pub parent_task_id: Option<String>,
+    pub subtasks_total: i64,
+    pub subtasks_done: i64,
+    /// Nested subtasks; only populated when a tree is requested.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<IndexedTask>,
+}
+
+#[derive(Serialize)]
+pub struct TaskProjectSummary {
+    pub project: String,
+    pub open_count: i64,
+    pub done_count: i64,
+}
+
+#[derive(Serialize)]
+pub struct TaskGroup {
+    pub key: String,
+    pub task_ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TaskQueryResult {
+    pub tasks: Vec<IndexedTask>,
+    pub group_by: Option<String>,
+    pub groups: Vec<TaskGroup>,
 }
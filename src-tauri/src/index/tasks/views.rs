@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+use crate::notes::frontmatter::{
+    normalize_frontmatter_mapping, parse_frontmatter_mapping, split_frontmatter,
+};
+use crate::notes::frontmatter_edit::render_frontmatter_lossless;
+
+use super::types::TaskQueryResult;
+
+const TASK_VIEW_KIND: &str = "task_view";
+const TASK_VIEW_VERSION: i64 = 1;
+
+/// A saved task query, stored in a note's `glyph.task_view` frontmatter the
+/// same way database notes keep their config under `glyph.database`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TaskViewConfig {
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub folders: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TaskViewLoadResult {
+    pub config: TaskViewConfig,
+    pub result: TaskQueryResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlyphFrontmatter {
+    #[serde(default)]
+    glyph: Option<GlyphData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlyphData {
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    task_view: Option<TaskViewConfig>,
+}
+
+fn key(name: &str) -> Value {
+    Value::String(name.to_string())
+}
+
+pub fn parse_task_view_config(markdown: &str) -> Result<TaskViewConfig, String> {
+    let (yaml, _) = split_frontmatter(markdown);
+    let Some(yaml) = yaml else {
+        return Err("task view note is missing frontmatter".to_string());
+    };
+    let parsed = serde_yaml::from_str::<GlyphFrontmatter>(yaml).map_err(|e| e.to_string())?;
+    let glyph = parsed
+        .glyph
+        .ok_or_else(|| "task view note is missing glyph config".to_string())?;
+    if glyph.kind.as_deref() != Some(TASK_VIEW_KIND) {
+        return Err("note is not a task view".to_string());
+    }
+    glyph
+        .task_view
+        .ok_or_else(|| "task view note is missing glyph.task_view config".to_string())
+}
+
+pub fn render_task_view_markdown(
+    path: &str,
+    existing_markdown: &str,
+    config: &TaskViewConfig,
+) -> Result<String, String> {
+    let (yaml, body) = split_frontmatter(existing_markdown);
+    let mut mapping = parse_frontmatter_mapping(yaml)?;
+    let glyph = match mapping
+        .entry(key("glyph"))
+        .or_insert_with(|| Value::Mapping(Mapping::new()))
+    {
+        Value::Mapping(inner) => inner,
+        _ => return Err("'glyph' must be a mapping".to_string()),
+    };
+    glyph.insert(key("kind"), Value::String(TASK_VIEW_KIND.to_string()));
+    glyph.insert(
+        key("version"),
+        Value::Number(serde_yaml::Number::from(TASK_VIEW_VERSION)),
+    );
+    glyph.insert(
+        key("task_view"),
+        serde_yaml::to_value(config).map_err(|e| e.to_string())?,
+    );
+
+    let normalized = normalize_frontmatter_mapping(mapping, path, None, None);
+    let rendered_yaml = render_frontmatter_lossless(yaml, &normalized)?;
+    Ok(format!(
+        "---\n{rendered_yaml}---\n\n{}",
+        body.trim_start_matches('\n')
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_task_view_config, render_task_view_markdown, TaskViewConfig};
+
+    #[test]
+    fn round_trips_task_view_config() {
+        let config = TaskViewConfig {
+            query: "not done\ndue before next monday\ngroup by note".to_string(),
+            folders: vec!["Work".to_string()],
+        };
+        let rendered =
+            render_task_view_markdown("Views/Week.md", "---\ntitle: Week\n---\n\nNotes\n", &config)
+                .expect("render should succeed");
+        assert!(rendered.contains("kind: task_view"));
+        assert!(rendered.contains("Notes"));
+        let reparsed = parse_task_view_config(&rendered).expect("config should reparse");
+        assert_eq!(reparsed.query, config.query);
+        assert_eq!(reparsed.folders, config.folders);
+    }
+}
@@ -13,6 +13,15 @@ pub struct IndexRebuildResult {
     pub indexed: usize,
 }
 
+/// A note's persistent identity and the paths it was known by before.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct NoteIdentity {
+    pub uid: String,
+    pub path: String,
+    pub previous_paths: Vec<String>,
+}
+
 #[derive(Serialize)]
 pub struct BacklinkItem {
     pub id: String,
@@ -1,8 +1,12 @@
 mod ai_codex;
 mod ai_rig;
+mod crypto;
 mod database;
+mod dates;
 mod glyph_fs;
+mod git;
 mod glyph_paths;
+mod history;
 mod index;
 mod io_atomic;
 mod links;
@@ -12,6 +16,7 @@ mod notes;
 mod paths;
 mod space;
 mod space_fs;
+mod sync;
 mod system_fonts;
 pub(crate) mod utils;
 
@@ -434,10 +439,55 @@ pub fn run() {
             ai_rig::context::ai_context_resolve_paths,
             ai_rig::models::ai_models_list,
             database::mutations::database_load,
+            database::mutations::database_group,
+            database::mutations::database_calendar,
             database::mutations::database_save_config,
             database::mutations::database_update_cell,
             database::mutations::database_create_row,
+            database::mutations::database_import_csv,
+            database::mutations::database_export_csv,
+            database::mutations::database_bulk_update_cells,
+            database::mutations::database_duplicate_rows,
+            database::mutations::database_delete_rows,
+            database::mutations::database_undo,
+            database::mutations::database_normalize_properties,
+            database::live::database_watch,
+            database::live::database_unwatch,
+            database::views::database_view_create,
+            database::views::database_view_update,
+            database::views::database_view_delete,
+            database::views::database_view_set_active,
+            database::views::database_view_reorder,
+            notes::templates::template_list,
+            notes::templates::note_create_from_template,
+            notes::periodic::periodic_settings_get,
+            notes::periodic::periodic_settings_set,
+            notes::periodic::periodic_note_find,
+            notes::periodic::periodic_note_open,
             index::commands::index_rebuild,
+            index::commands::note_identity_get,
+            history::commands::note_history_list,
+            history::commands::note_history_diff,
+            history::commands::note_history_restore,
+            git::commands::git_repo_info,
+            git::commands::git_status,
+            git::commands::git_settings_get,
+            git::commands::git_settings_set,
+            git::commands::git_commit,
+            git::commands::git_file_log,
+            git::commands::git_file_diff,
+            git::commands::git_file_restore,
+            git::commands::git_pull,
+            git::commands::git_push,
+            sync::commands::sync_settings_get,
+            sync::commands::sync_settings_set,
+            sync::commands::sync_run,
+            crypto::commands::crypto_status,
+            crypto::commands::crypto_unlock,
+            crypto::commands::crypto_lock,
+            crypto::commands::crypto_change_passphrase,
+            crypto::commands::crypto_encrypt_path,
+            crypto::commands::crypto_decrypt_path,
             index::commands::search,
             index::commands::search_advanced,
             index::commands::search_parse_and_run,
@@ -450,6 +500,15 @@ pub fn run() {
             index::commands::tasks_query,
             index::commands::task_set_checked,
             index::commands::task_set_dates,
+            index::commands::task_set_status,
+            index::commands::task_set_priority,
+            index::commands::tasks_run_query,
+            index::commands::tasks_search,
+            index::commands::tasks_projects,
+            index::commands::task_settings_get,
+            index::commands::task_settings_set,
+            index::commands::task_view_load,
+            index::commands::task_view_save_config,
             index::commands::task_dates_by_ordinal,
             index::commands::task_update_by_ordinal,
             index::commands::backlinks,
@@ -484,6 +543,8 @@ pub fn run() {
             notes::commands::note_delete,
             notes::properties::note_frontmatter_parse_properties,
             notes::properties::note_frontmatter_render_properties,
+            notes::property_schema::property_schema_get,
+            notes::property_schema::property_schema_set,
             notes::attachments::note_attach_file,
             space::commands::space_create,
             space::commands::space_open,
@@ -493,6 +554,13 @@ pub fn run() {
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|app_handle, event| {
+            if let RunEvent::Exit = event {
+                // Don't leave decrypted notes in the index after quitting.
+                if let Ok(root) = app_handle.state::<space::SpaceState>().current_root() {
+                    let _ = crypto::purge_decrypted(&root);
+                }
+            }
+
             #[cfg(target_os = "macos")]
             if let RunEvent::Reopen { .. } = event {
                 if let Some(window) = app_handle.get_webview_window("main") {
@@ -11,7 +11,7 @@ use crate::space::SpaceState;
 use super::helpers::{assets_dir, note_rel_path};
 use super::types::AttachmentResult;
 
-fn is_image_ext(ext: &str) -> bool {
+pub(crate) fn is_image_ext(ext: &str) -> bool {
     matches!(ext, "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg")
 }
 
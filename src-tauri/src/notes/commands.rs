@@ -1,13 +1,15 @@
 use std::{ffi::OsStr, path::Path};
 use tauri::State;
 
+use crate::history::{self, VersionSource};
 use crate::space::state::mark_recent_local_change;
-use crate::{index, io_atomic, paths, space::SpaceState};
+use crate::{crypto, index, io_atomic, paths, space::SpaceState, utils};
 
 use super::frontmatter::{
     normalize_frontmatter_mapping, now_rfc3339, parse_frontmatter, parse_frontmatter_mapping,
     render_frontmatter_mapping_yaml, split_frontmatter,
 };
+use super::frontmatter_edit::render_frontmatter_lossless;
 use super::helpers::{
     etag_for, extract_meta, file_mtime_ms, note_abs_path, note_rel_path, notes_dir, read_to_string,
 };
@@ -36,8 +38,12 @@ pub async fn notes_list(state: State<'_, SpaceState>) -> Result<Vec<NoteMeta>, S
             if uuid::Uuid::parse_str(file_stem).is_err() {
                 continue;
             }
-            let markdown = read_to_string(&path)?;
-            out.push(extract_meta(file_stem, &markdown)?);
+            // Locked encrypted notes list as untitled.
+            let markdown = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            out.push(extract_meta(
+                file_stem,
+                &crypto::indexable_text(&root, &markdown),
+            )?);
         }
 
         out.sort_by(|a, b| b.updated.cmp(&a.updated));
@@ -82,8 +88,9 @@ pub async fn note_create(state: State<'_, SpaceState>, title: String) -> Result<
 
         let yaml = render_frontmatter_mapping_yaml(&fm)?;
         let markdown = format!("---\n{yaml}---\n\n");
+        let bytes = crypto::seal_text(&root, &utils::to_slash(&rel), None, false, &markdown)?;
         mark_recent_local_change(&recent_local_changes, &rel_path);
-        io_atomic::write_atomic(&path, markdown.as_bytes()).map_err(|e| e.to_string())?;
+        io_atomic::write_atomic(&path, &bytes).map_err(|e| e.to_string())?;
         let _ = index::index_note(&root, &id, &markdown);
 
         Ok(NoteMeta {
@@ -102,7 +109,7 @@ pub async fn note_read(state: State<'_, SpaceState>, id: String) -> Result<NoteD
     let root = state.current_root()?;
     tauri::async_runtime::spawn_blocking(move || -> Result<NoteDoc, String> {
         let path = note_abs_path(&root, &id)?;
-        let markdown = read_to_string(&path)?;
+        let markdown = read_to_string(&root, &path)?;
         let meta = extract_meta(&id, &markdown)?;
         Ok(NoteDoc {
             meta,
@@ -139,7 +146,8 @@ pub async fn note_write(
     let recent_local_changes = state.recent_local_changes();
     tauri::async_runtime::spawn_blocking(move || -> Result<NoteWriteResult, String> {
         let path = note_abs_path(&root, &id)?;
-        let current = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let current_bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+        let current = crypto::open_text(&root, &current_bytes)?;
         if let Some(base) = base_etag {
             let current_etag = etag_for(&current);
             if current_etag != base {
@@ -152,12 +160,26 @@ pub async fn note_write(
         let (yaml, body) = split_frontmatter(&markdown);
         let fm = parse_frontmatter_mapping(yaml)?;
         let fm = normalize_frontmatter_mapping(fm, &id, None, preserve_created.as_deref());
-        let yaml = render_frontmatter_mapping_yaml(&fm)?;
+        let yaml = render_frontmatter_lossless(yaml, &fm)?;
         let normalized = format!("---\n{yaml}---\n\n{}", body.trim_start_matches('\n'));
         let rel_path = note_rel_path(&id)?.to_string_lossy().to_string();
+        let bytes = crypto::seal_text(
+            &root,
+            &utils::to_slash(Path::new(&rel_path)),
+            Some(&current_bytes),
+            false,
+            &normalized,
+        )?;
         mark_recent_local_change(&recent_local_changes, &rel_path);
-        io_atomic::write_atomic(&path, normalized.as_bytes()).map_err(|e| e.to_string())?;
+        io_atomic::write_atomic(&path, &bytes).map_err(|e| e.to_string())?;
         let _ = index::index_note(&root, &id, &normalized);
+        let _ = history::record_write(
+            &root,
+            &rel_path,
+            Some(&current_bytes),
+            &bytes,
+            VersionSource::Save,
+        );
         let meta = extract_meta(&id, &normalized)?;
         Ok(NoteWriteResult {
             meta,
@@ -0,0 +1,349 @@
+use serde_yaml::{Mapping, Value};
+
+use super::frontmatter::render_frontmatter_mapping_yaml;
+
+/// Key the value renderer uses before the entry's own key text is put back.
+const PLACEHOLDER_KEY: &str = "k";
+
+/// One top-level piece of frontmatter text: a `key: value` entry with its
+/// continuation lines, or comments and blank lines between entries.
+#[derive(Debug, Clone)]
+enum Segment {
+    Trivia(String),
+    Entry { key: String, text: String },
+}
+
+/// Edits frontmatter YAML one top-level key at a time. Entries that are not
+/// edited, comments, blank lines and key order are kept byte-for-byte.
+#[derive(Debug, Clone)]
+pub(crate) struct FrontmatterEditor {
+    segments: Vec<Segment>,
+}
+
+/// The key a top-level entry line starts with, if the line starts one.
+fn entry_key(line: &str) -> Option<String> {
+    let first = line.chars().next()?;
+    if first.is_whitespace() || matches!(first, '#' | '-' | '?' | '{' | '[') {
+        return None;
+    }
+    let line = line.trim_end();
+    if let Some(quote @ ('"' | '\'')) = line.chars().next() {
+        let end = line[1..].find(quote)? + 1;
+        if !line[end + 1..].starts_with(':') {
+            return None;
+        }
+        let key = serde_yaml::from_str::<String>(&line[..=end]).ok()?;
+        return Some(key);
+    }
+    let colon = line
+        .match_indices(':')
+        .map(|(index, _)| index)
+        .find(|index| line[index + 1..].is_empty() || line[index + 1..].starts_with([' ', '\t']))?;
+    Some(line[..colon].trim_end().to_string())
+}
+
+/// Whether `line` continues the entry above it: indented, or a block
+/// sequence item at the key's own indentation.
+fn continues_entry(line: &str) -> bool {
+    line.starts_with([' ', '\t']) || line.trim_end() == "-" || line.starts_with("- ")
+}
+
+fn is_trivia(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+/// Renders `key: value` as block YAML, spelling the key as `key_text`.
+fn render_entry(key_text: &str, value: &Value) -> Result<String, String> {
+    let mut mapping = Mapping::new();
+    mapping.insert(Value::String(PLACEHOLDER_KEY.to_string()), value.clone());
+    let rendered = render_frontmatter_mapping_yaml(&mapping)?;
+    Ok(format!("{key_text}{}", &rendered[PLACEHOLDER_KEY.len()..]))
+}
+
+/// A scalar rendered for a flow sequence like `[a, b]`.
+fn flow_item(value: &Value) -> Result<Option<String>, String> {
+    if matches!(
+        value,
+        Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_)
+    ) {
+        return Ok(None);
+    }
+    let rendered = serde_yaml::to_string(value).map_err(|e| e.to_string())?;
+    let rendered = rendered.trim_end();
+    if rendered.contains('\n') {
+        return Ok(None);
+    }
+    let quoted = rendered.starts_with(['\'', '"']);
+    if !quoted && rendered.contains([',', '[', ']', '{', '}']) {
+        return Ok(Some(format!("'{}'", rendered.replace('\'', "''"))));
+    }
+    Ok(Some(rendered.to_string()))
+}
+
+fn parse_entry_value(text: &str, key: &str) -> Option<Value> {
+    serde_yaml::from_str::<Mapping>(text)
+        .ok()?
+        .get(key)
+        .cloned()
+}
+
+impl FrontmatterEditor {
+    pub(crate) fn parse(yaml: &str) -> Self {
+        let lines = yaml.split_inclusive('\n').collect::<Vec<_>>();
+        let mut segments = Vec::new();
+        let mut index = 0;
+        while index < lines.len() {
+            let line = lines[index];
+            let Some(key) = entry_key(line) else {
+                segments.push(Segment::Trivia(line.to_string()));
+                index += 1;
+                continue;
+            };
+            let mut end = index + 1;
+            let mut scan = end;
+            while scan < lines.len() {
+                if continues_entry(lines[scan]) {
+                    scan += 1;
+                    end = scan;
+                } else if is_trivia(lines[scan]) {
+                    scan += 1;
+                } else {
+                    break;
+                }
+            }
+            segments.push(Segment::Entry {
+                key,
+                text: lines[index..end].concat(),
+            });
+            index = end;
+        }
+        Self { segments }
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Entry { key, .. } => Some(key.clone()),
+                Segment::Trivia(_) => None,
+            })
+            .collect()
+    }
+
+    /// Sets `key` to `value`, rewriting only that entry. An entry whose value
+    /// is already equal keeps its text; flow sequences stay flow sequences
+    /// and a trailing comment on a scalar line is kept.
+    pub(crate) fn set(&mut self, key: &str, value: &Value) -> Result<(), String> {
+        let position = self.segments.iter().position(
+            |segment| matches!(segment, Segment::Entry { key: existing, .. } if existing == key),
+        );
+        let Some(position) = position else {
+            if let Some(Segment::Trivia(text) | Segment::Entry { text, .. }) =
+                self.segments.last_mut()
+            {
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+            }
+            let mut mapping = Mapping::new();
+            mapping.insert(Value::String(key.to_string()), value.clone());
+            self.segments.push(Segment::Entry {
+                key: key.to_string(),
+                text: render_frontmatter_mapping_yaml(&mapping)?,
+            });
+            return Ok(());
+        };
+        let Segment::Entry { text, .. } = &self.segments[position] else {
+            return Ok(());
+        };
+        if parse_entry_value(text, key).as_ref() == Some(value) {
+            return Ok(());
+        }
+        let next = rewrite_entry(text, key, value)?;
+        self.segments[position] = Segment::Entry {
+            key: key.to_string(),
+            text: next,
+        };
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) {
+        self.segments.retain(
+            |segment| !matches!(segment, Segment::Entry { key: existing, .. } if existing == key),
+        );
+    }
+
+    pub(crate) fn into_yaml(self) -> String {
+        self.segments
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Trivia(text) | Segment::Entry { text, .. } => text,
+            })
+            .collect()
+    }
+}
+
+/// `text` with its value replaced by `value`, keeping the key as written.
+fn rewrite_entry(text: &str, key: &str, value: &Value) -> Result<String, String> {
+    let first_line = text.lines().next().unwrap_or_default();
+    let key_text = if let Some(quote @ ('"' | '\'')) = first_line.chars().next() {
+        let end = first_line[1..].find(quote).map(|end| end + 2);
+        &first_line[..end.unwrap_or(first_line.len())]
+    } else {
+        first_line
+            .find(':')
+            .map(|colon| first_line[..colon].trim_end())
+            .unwrap_or(key)
+    };
+    let inline = first_line[key_text.len()..]
+        .trim_start()
+        .trim_start_matches(':')
+        .trim();
+    let single_line = !text.trim_end().contains('\n');
+    let ending = if text.ends_with('\n') { "\n" } else { "" };
+
+    if single_line && inline.starts_with('[') {
+        if let Value::Sequence(items) = value {
+            let items = items
+                .iter()
+                .map(flow_item)
+                .collect::<Result<Option<Vec<_>>, _>>()?;
+            if let Some(items) = items {
+                return Ok(format!("{key_text}: [{}]{ending}", items.join(", ")));
+            }
+        }
+    }
+
+    let rendered = render_entry(key_text, value)?;
+    if single_line && !rendered.trim_end().contains('\n') {
+        if let Some(comment) = trailing_comment(text, key, inline) {
+            return Ok(format!("{}{comment}{ending}", rendered.trim_end()));
+        }
+        return Ok(format!("{}{ending}", rendered.trim_end()));
+    }
+
+    // Keep the indentation the entry used for its nested lines.
+    let indent = text
+        .lines()
+        .skip(1)
+        .find(|line| !is_trivia(line))
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .unwrap_or_default();
+    let rendered_indent = rendered
+        .lines()
+        .nth(1)
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .unwrap_or_default();
+    if indent.len() > rendered_indent.len() && indent.trim().is_empty() {
+        let extra = &indent[rendered_indent.len()..];
+        let mut lines = rendered.lines();
+        let mut out = format!("{}\n", lines.next().unwrap_or_default());
+        for line in lines {
+            out.push_str(extra);
+            out.push_str(line);
+            out.push('\n');
+        }
+        return Ok(out);
+    }
+    Ok(rendered)
+}
+
+/// The ` # comment` ending an entry's single line, when it really is a
+/// comment and not part of the value.
+fn trailing_comment(text: &str, key: &str, inline: &str) -> Option<String> {
+    let value = parse_entry_value(text, key)?;
+    inline
+        .match_indices(" #")
+        .map(|(index, _)| index)
+        .find(|index| {
+            let candidate = format!("{PLACEHOLDER_KEY}: {}", &inline[..*index]);
+            parse_entry_value(&candidate, PLACEHOLDER_KEY).as_ref() == Some(&value)
+        })
+        .map(|index| inline[index..].trim_end().to_string())
+}
+
+/// Renders `mapping` as frontmatter YAML by editing `original` in place:
+/// removed keys are dropped, changed keys rewritten and new keys appended,
+/// leaving everything else as written. Falls back to a fresh rendering when
+/// the original cannot be edited safely (e.g. it uses anchors or flow
+/// mappings at the top level).
+pub fn render_frontmatter_lossless(
+    original: Option<&str>,
+    mapping: &Mapping,
+) -> Result<String, String> {
+    let Some(original) = original.filter(|yaml| !yaml.trim().is_empty()) else {
+        return render_frontmatter_mapping_yaml(mapping);
+    };
+    if mapping.is_empty() || mapping.keys().any(|key| !key.is_string()) {
+        return render_frontmatter_mapping_yaml(mapping);
+    }
+    let mut editor = FrontmatterEditor::parse(original);
+    for key in editor.keys() {
+        if !mapping.contains_key(key.as_str()) {
+            editor.remove(&key);
+        }
+    }
+    for (key, value) in mapping {
+        if let Some(key) = key.as_str() {
+            editor.set(key, value)?;
+        }
+    }
+    let mut yaml = editor.into_yaml();
+    if !yaml.ends_with('\n') {
+        yaml.push('\n');
+    }
+    match serde_yaml::from_str::<Mapping>(&yaml) {
+        Ok(parsed) if &parsed == mapping => Ok(yaml),
+        _ => render_frontmatter_mapping_yaml(mapping),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: &str = "# Project note\ntitle: \"Roadmap\"   # keep quoted\nstatus: doing # current\ntags: [alpha, beta]\n\n# people\nowners:\n  - ada\n  - grace\nnested:\n  a: 1 # one\n'odd: key': yes\n";
+
+    fn mapping(yaml: &str) -> Mapping {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn leaves_untouched_entries_byte_for_byte() {
+        let same = render_frontmatter_lossless(Some(ORIGINAL), &mapping(ORIGINAL)).unwrap();
+        assert_eq!(same, ORIGINAL);
+
+        let mut next = mapping(ORIGINAL);
+        next.insert("status".into(), "done".into());
+        next.insert(
+            "tags".into(),
+            serde_yaml::from_str("[alpha, beta, 'c, d']").unwrap(),
+        );
+        next.insert("owners".into(), serde_yaml::from_str("[ada]").unwrap());
+        next.insert("due".into(), "2024-05-01".into());
+        next.remove("nested");
+        let edited = render_frontmatter_lossless(Some(ORIGINAL), &next).unwrap();
+        assert_eq!(
+            edited,
+            "# Project note\ntitle: \"Roadmap\"   # keep quoted\nstatus: done # current\ntags: [alpha, beta, 'c, d']\n\n# people\nowners:\n  - ada\n'odd: key': yes\ndue: 2024-05-01\n"
+        );
+        assert_eq!(mapping(&edited), next);
+    }
+
+    #[test]
+    fn falls_back_when_text_cannot_be_edited_in_place() {
+        let anchored = "base: &base\n  a: 1\ncopy: *base\n";
+        let mut next = mapping(anchored);
+        next.insert("base".into(), serde_yaml::from_str("{a: 2}").unwrap());
+        let rendered = render_frontmatter_lossless(Some(anchored), &next).unwrap();
+        assert_eq!(mapping(&rendered), next);
+
+        let multiline = "title: A\nbody: |\n  line one\n  line two\n";
+        let mut next = mapping(multiline);
+        next.insert("body".into(), "changed\ntext".into());
+        let rendered = render_frontmatter_lossless(Some(multiline), &next).unwrap();
+        assert!(rendered.starts_with("title: A\n"));
+        assert_eq!(mapping(&rendered), next);
+    }
+}
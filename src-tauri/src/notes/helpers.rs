@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use crate::paths;
+use crate::{crypto, paths};
 
 use super::frontmatter::{now_rfc3339, parse_frontmatter, split_frontmatter};
 use super::types::NoteMeta;
@@ -25,8 +25,10 @@ pub fn assets_dir(space_root: &Path) -> Result<PathBuf, String> {
     paths::join_under(space_root, Path::new("assets"))
 }
 
-pub fn read_to_string(path: &Path) -> Result<String, String> {
-    std::fs::read_to_string(path).map_err(|e| e.to_string())
+/// Reads a note, decrypting it when it is stored encrypted.
+pub fn read_to_string(space_root: &Path, path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    crypto::open_text(space_root, &bytes)
 }
 
 pub fn etag_for(markdown: &str) -> String {
@@ -1,6 +1,10 @@
 pub mod attachments;
 pub mod commands;
 pub(crate) mod frontmatter;
+pub(crate) mod frontmatter_edit;
 mod helpers;
+pub mod periodic;
 pub mod properties;
+pub mod property_schema;
+pub(crate) mod templates;
 mod types;
@@ -0,0 +1,522 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use time::{Date, Month};
+
+use crate::database::mutations::{note_abs_path, read_note, write_markdown_note};
+use crate::dates::{add_days, add_months, date_from_iso, date_to_iso, DateContext};
+use crate::space::state::RecentLocalChanges;
+use crate::space::SpaceState;
+use crate::{glyph_paths, io_atomic};
+
+use super::templates::{format_moment_in_week, render_note_from_template, TemplateVars};
+
+const PERIODIC_NOTES_FILE: &str = "periodic_notes.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+}
+
+/// Where one period's notes live and how they are named.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PeriodSettings {
+    /// Folder relative to the space root; empty for the root.
+    #[serde(default)]
+    pub folder: String,
+    /// Moment-style file name format of the period's first day, without
+    /// `.md`. May contain `/` for subfolders.
+    pub format: String,
+    /// Template note new period notes start from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+impl PeriodSettings {
+    fn new(folder: &str, format: &str) -> Self {
+        Self {
+            folder: folder.to_string(),
+            format: format.to_string(),
+            template: None,
+        }
+    }
+}
+
+fn default_daily() -> PeriodSettings {
+    PeriodSettings::new("Daily", "YYYY-MM-DD")
+}
+
+fn default_weekly() -> PeriodSettings {
+    PeriodSettings::new("Weekly", "GGGG-[W]WW")
+}
+
+fn default_monthly() -> PeriodSettings {
+    PeriodSettings::new("Monthly", "YYYY-MM")
+}
+
+fn default_quarterly() -> PeriodSettings {
+    PeriodSettings::new("Quarterly", "YYYY-[Q]Q")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PeriodicNotesSettings {
+    #[serde(default = "default_daily")]
+    pub daily: PeriodSettings,
+    #[serde(default = "default_weekly")]
+    pub weekly: PeriodSettings,
+    #[serde(default = "default_monthly")]
+    pub monthly: PeriodSettings,
+    #[serde(default = "default_quarterly")]
+    pub quarterly: PeriodSettings,
+}
+
+impl Default for PeriodicNotesSettings {
+    fn default() -> Self {
+        Self {
+            daily: default_daily(),
+            weekly: default_weekly(),
+            monthly: default_monthly(),
+            quarterly: default_quarterly(),
+        }
+    }
+}
+
+impl PeriodicNotesSettings {
+    fn period(&self, period: Period) -> &PeriodSettings {
+        match period {
+            Period::Daily => &self.daily,
+            Period::Weekly => &self.weekly,
+            Period::Monthly => &self.monthly,
+            Period::Quarterly => &self.quarterly,
+        }
+    }
+
+    fn period_mut(&mut self, period: Period) -> &mut PeriodSettings {
+        match period {
+            Period::Daily => &mut self.daily,
+            Period::Weekly => &mut self.weekly,
+            Period::Monthly => &mut self.monthly,
+            Period::Quarterly => &mut self.quarterly,
+        }
+    }
+}
+
+/// The note of one period: the days it covers, its path and whether it
+/// exists yet.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PeriodicNote {
+    pub period: Period,
+    pub start: String,
+    pub end: String,
+    pub path: String,
+    pub exists: bool,
+    /// Whether this call created the note.
+    pub created: bool,
+}
+
+fn periodic_notes_path(space_root: &Path) -> Result<PathBuf, String> {
+    Ok(glyph_paths::glyph_dir(space_root)?.join(PERIODIC_NOTES_FILE))
+}
+
+pub fn read_periodic_settings(space_root: &Path) -> PeriodicNotesSettings {
+    let Ok(path) = periodic_notes_path(space_root) else {
+        return PeriodicNotesSettings::default();
+    };
+    let bytes = std::fs::read(path).unwrap_or_default();
+    serde_json::from_slice(&bytes).unwrap_or_default()
+}
+
+pub fn write_periodic_settings(
+    space_root: &Path,
+    settings: &PeriodicNotesSettings,
+) -> Result<(), String> {
+    glyph_paths::ensure_glyph_dir(space_root)?;
+    let path = periodic_notes_path(space_root)?;
+    let bytes = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+    io_atomic::write_atomic(&path, &bytes).map_err(|e| e.to_string())
+}
+
+fn month_start(year: i32, month: u8) -> Result<Date, String> {
+    let month = Month::try_from(month).map_err(|e| e.to_string())?;
+    Date::from_calendar_date(year, month, 1).map_err(|e| e.to_string())
+}
+
+/// The first and last day of the period containing `date`. Weeks start on
+/// `ctx.week_start`.
+fn period_range(period: Period, date: Date, ctx: &DateContext) -> Result<(Date, Date), String> {
+    let start = match period {
+        Period::Daily => date,
+        Period::Weekly => {
+            let since_start = i64::from(date.weekday().number_days_from_monday())
+                - i64::from(ctx.week_start.number_days_from_monday());
+            add_days(date, -since_start.rem_euclid(7))?
+        }
+        Period::Monthly => month_start(date.year(), u8::from(date.month()))?,
+        Period::Quarterly => {
+            let month = u8::from(date.month());
+            month_start(date.year(), month - (month - 1) % 3)?
+        }
+    };
+    let end = match period {
+        Period::Daily => start,
+        Period::Weekly => add_days(start, 6)?,
+        Period::Monthly => add_days(add_months(start, 1)?, -1)?,
+        Period::Quarterly => add_days(add_months(start, 3)?, -1)?,
+    };
+    Ok((start, end))
+}
+
+/// The period `offset` periods away from the one containing `date`.
+fn shift_period(
+    period: Period,
+    date: Date,
+    offset: i64,
+    ctx: &DateContext,
+) -> Result<Date, String> {
+    let (start, _) = period_range(period, date, ctx)?;
+    let out_of_range = || "date out of range".to_string();
+    match period {
+        Period::Daily => add_days(start, offset),
+        Period::Weekly => add_days(start, offset.checked_mul(7).ok_or_else(out_of_range)?),
+        Period::Monthly => add_months(start, offset),
+        Period::Quarterly => add_months(start, offset.checked_mul(3).ok_or_else(out_of_range)?),
+    }
+}
+
+/// The note path of the period running from `start` to `end`. Week tokens
+/// number the ISO week holding most of the period's days, so weeks that do
+/// not start on Monday keep the number of the week they mostly overlap.
+fn note_path(settings: &PeriodSettings, start: Date, end: Date) -> String {
+    let middle = add_days(start, (end - start).whole_days() / 2).unwrap_or(start);
+    let name = format_moment_in_week(start.midnight(), middle, &settings.format);
+    let name = name.trim().trim_matches('/');
+    let name = if name.is_empty() {
+        date_to_iso(start)
+    } else {
+        name.to_string()
+    };
+    match settings.folder.trim().trim_matches('/') {
+        "" => format!("{name}.md"),
+        folder => format!("{folder}/{name}.md"),
+    }
+}
+
+/// Looks up the note of the period containing `date`. Other features use
+/// this to find e.g. today's daily note.
+pub(crate) fn find_periodic_note(
+    root: &Path,
+    settings: &PeriodicNotesSettings,
+    period: Period,
+    date: Date,
+    ctx: &DateContext,
+) -> Result<PeriodicNote, String> {
+    let (start, end) = period_range(period, date, ctx)?;
+    let path = note_path(settings.period(period), start, end);
+    let exists = note_abs_path(root, &path)?.is_file();
+    Ok(PeriodicNote {
+        period,
+        start: date_to_iso(start),
+        end: date_to_iso(end),
+        path,
+        exists,
+        created: false,
+    })
+}
+
+fn open_periodic_note(
+    root: &Path,
+    recent_local_changes: &RecentLocalChanges,
+    settings: &PeriodicNotesSettings,
+    period: Period,
+    date: Date,
+    ctx: &DateContext,
+) -> Result<PeriodicNote, String> {
+    let mut note = find_periodic_note(root, settings, period, date, ctx)?;
+    if note.exists {
+        return Ok(note);
+    }
+    let title = Path::new(&note.path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let markdown = match settings.period(period).template.as_deref() {
+        Some(template_path) if !template_path.trim().is_empty() => {
+            let template = read_note(root, template_path)
+                .map_err(|e| format!("could not read template '{template_path}': {e}"))?;
+            let start = date_from_iso(&note.start).unwrap_or(date);
+            let vars = TemplateVars::new(start.midnight()).with("title", title.clone());
+            render_note_from_template(&note.path, &title, &template, &vars)?.0
+        }
+        _ => format!("# {title}\n"),
+    };
+    write_markdown_note(root, recent_local_changes, &note.path, &markdown)?;
+    note.exists = true;
+    note.created = true;
+    Ok(note)
+}
+
+/// `date` (default: `ctx.today`, the frontend's local date) moved by
+/// `offset` periods.
+fn target_date(
+    period: Period,
+    date: Option<&str>,
+    offset: Option<i64>,
+    ctx: &DateContext,
+) -> Result<Date, String> {
+    let date = match date.map(str::trim).filter(|date| !date.is_empty()) {
+        Some(date) => date_from_iso(date).ok_or_else(|| format!("invalid date '{date}'"))?,
+        None => ctx.today,
+    };
+    match offset.unwrap_or(0) {
+        0 => Ok(date),
+        offset => shift_period(period, date, offset, ctx),
+    }
+}
+
+#[tauri::command]
+pub async fn periodic_settings_get(
+    state: State<'_, SpaceState>,
+) -> Result<PeriodicNotesSettings, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || Ok(read_periodic_settings(&root)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn periodic_settings_set(
+    state: State<'_, SpaceState>,
+    settings: PeriodicNotesSettings,
+) -> Result<PeriodicNotesSettings, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        write_periodic_settings(&root, &settings)?;
+        Ok(settings)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Finds the note of the period containing `date` (default: today), moved
+/// by `offset` periods (-1 for the previous one), without creating it. Weeks
+/// start on `week_start` (Monday when omitted).
+#[tauri::command(rename_all = "snake_case")]
+pub async fn periodic_note_find(
+    state: State<'_, SpaceState>,
+    period: Period,
+    date: Option<String>,
+    offset: Option<i64>,
+    today: Option<String>,
+    week_start: Option<String>,
+) -> Result<PeriodicNote, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+        let date = target_date(period, date.as_deref(), offset, &ctx)?;
+        find_periodic_note(&root, &read_periodic_settings(&root), period, date, &ctx)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Like `periodic_note_find`, creating the note from the period's template
+/// when it does not exist. `folder` overrides the configured folder.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn periodic_note_open(
+    state: State<'_, SpaceState>,
+    period: Period,
+    date: Option<String>,
+    offset: Option<i64>,
+    today: Option<String>,
+    week_start: Option<String>,
+    folder: Option<String>,
+) -> Result<PeriodicNote, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || {
+        let ctx = DateContext::new(today.as_deref(), week_start.as_deref())?;
+        let date = target_date(period, date.as_deref(), offset, &ctx)?;
+        let mut settings = read_periodic_settings(&root);
+        if let Some(folder) = folder {
+            settings.period_mut(period).folder = folder;
+        }
+        open_periodic_note(&root, &recent_local_changes, &settings, period, date, &ctx)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(iso: &str) -> Date {
+        date_from_iso(iso).unwrap()
+    }
+
+    #[test]
+    fn maps_dates_to_period_notes_and_neighbours() {
+        let settings = PeriodicNotesSettings::default();
+        let ctx = DateContext::default();
+        let day = date("2024-12-31");
+        let cases = [
+            (
+                Period::Daily,
+                "2024-12-31",
+                "2024-12-31",
+                "Daily/2024-12-31.md",
+            ),
+            (
+                Period::Weekly,
+                "2024-12-30",
+                "2025-01-05",
+                "Weekly/2025-W01.md",
+            ),
+            (
+                Period::Monthly,
+                "2024-12-01",
+                "2024-12-31",
+                "Monthly/2024-12.md",
+            ),
+            (
+                Period::Quarterly,
+                "2024-10-01",
+                "2024-12-31",
+                "Quarterly/2024-Q4.md",
+            ),
+        ];
+        for (period, start, end, path) in cases {
+            let (range_start, range_end) = period_range(period, day, &ctx).unwrap();
+            assert_eq!(date_to_iso(range_start), start);
+            assert_eq!(date_to_iso(range_end), end);
+            assert_eq!(
+                note_path(settings.period(period), range_start, range_end),
+                path
+            );
+        }
+
+        assert_eq!(
+            date_to_iso(shift_period(Period::Daily, day, 1, &ctx).unwrap()),
+            "2025-01-01"
+        );
+        assert_eq!(
+            date_to_iso(shift_period(Period::Weekly, day, -1, &ctx).unwrap()),
+            "2024-12-23"
+        );
+        assert_eq!(
+            date_to_iso(shift_period(Period::Monthly, day, 2, &ctx).unwrap()),
+            "2025-02-01"
+        );
+        assert_eq!(
+            date_to_iso(shift_period(Period::Quarterly, day, -4, &ctx).unwrap()),
+            "2023-10-01"
+        );
+        assert_eq!(
+            date_to_iso(
+                period_range(Period::Monthly, date("2024-02-10"), &ctx)
+                    .unwrap()
+                    .1
+            ),
+            "2024-02-29"
+        );
+
+        for period in [
+            Period::Daily,
+            Period::Weekly,
+            Period::Monthly,
+            Period::Quarterly,
+        ] {
+            assert!(shift_period(period, day, i64::MAX, &ctx).is_err());
+            assert!(shift_period(period, day, i64::MIN, &ctx).is_err());
+        }
+        assert!(period_range(Period::Weekly, Date::MAX, &ctx).is_err());
+        assert!(period_range(Period::Quarterly, Date::MAX, &ctx).is_err());
+    }
+
+    #[test]
+    fn starts_weeks_on_the_configured_day() {
+        let settings = PeriodicNotesSettings::default();
+        let sunday = DateContext::new(None, Some("sunday")).unwrap();
+        let (start, end) = period_range(Period::Weekly, date("2024-12-31"), &sunday).unwrap();
+        assert_eq!(date_to_iso(start), "2024-12-29");
+        assert_eq!(date_to_iso(end), "2025-01-04");
+        assert_eq!(
+            note_path(&settings.weekly, start, end),
+            "Weekly/2025-W01.md"
+        );
+        // Sunday itself opens the week rather than closing the previous one.
+        let (start, _) = period_range(Period::Weekly, date("2025-01-05"), &sunday).unwrap();
+        assert_eq!(date_to_iso(start), "2025-01-05");
+        assert_eq!(
+            date_to_iso(shift_period(Period::Weekly, date("2025-01-05"), -1, &sunday).unwrap()),
+            "2024-12-29"
+        );
+        assert!(period_range(Period::Weekly, Date::MIN, &sunday).is_err());
+    }
+
+    #[test]
+    fn creates_missing_notes_from_templates() {
+        let root =
+            std::env::temp_dir().join(format!("glyph-periodic-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("Templates")).unwrap();
+        std::fs::write(
+            root.join("Templates/Week.md"),
+            "---\ntags: [weekly]\n---\n\n# Week of {{date:MMMM D}}\n",
+        )
+        .unwrap();
+        let mut settings = PeriodicNotesSettings::default();
+        settings.weekly.template = Some("Templates/Week.md".to_string());
+        let recent = RecentLocalChanges::default();
+        let ctx = DateContext::default();
+
+        let note = open_periodic_note(
+            &root,
+            &recent,
+            &settings,
+            Period::Weekly,
+            date("2024-05-08"),
+            &ctx,
+        )
+        .unwrap();
+        assert!(note.created);
+        assert_eq!(note.path, "Weekly/2024-W19.md");
+        let markdown = std::fs::read_to_string(root.join(&note.path)).unwrap();
+        assert!(markdown.contains("tags: [weekly]"));
+        assert!(markdown.ends_with("# Week of May 6\n"));
+
+        let again = open_periodic_note(
+            &root,
+            &recent,
+            &settings,
+            Period::Weekly,
+            date("2024-05-12"),
+            &ctx,
+        )
+        .unwrap();
+        assert!(again.exists && !again.created);
+        let daily = open_periodic_note(
+            &root,
+            &recent,
+            &settings,
+            Period::Daily,
+            date("2024-05-08"),
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join(&daily.path)).unwrap(),
+            "# 2024-05-08\n"
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
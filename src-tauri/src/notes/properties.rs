@@ -1,9 +1,15 @@
 use std::collections::HashSet;
 
 use serde_yaml::{Mapping, Number, Value};
+use tauri::State;
 
-use super::frontmatter::{
-    parse_frontmatter_mapping, render_frontmatter_mapping_yaml, split_frontmatter,
+use crate::space::SpaceState;
+
+use super::frontmatter::{parse_frontmatter_mapping, split_frontmatter};
+use super::frontmatter_edit::render_frontmatter_lossless;
+use super::property_schema::{
+    find_definition, missing_required_properties, read_property_schema, validate_property_value,
+    PropertyDefinition, PropertyError,
 };
 use super::types::NoteProperty;
 
@@ -61,7 +67,22 @@ fn infer_string_kind(value: &str) -> &'static str {
     "text"
 }
 
-fn is_iso8601_datetime(value: &str) -> bool {
+/// Infers a property kind for untyped text (a spreadsheet cell, say), where
+/// YAML has not already decided between strings, numbers and booleans.
+pub(crate) fn infer_text_kind(value: &str) -> &'static str {
+    let trimmed = value.trim();
+    if trimmed.eq_ignore_ascii_case("true") || trimmed.eq_ignore_ascii_case("false") {
+        return "checkbox";
+    }
+    // Leading zeros (zip codes, ids) would be lost as numbers.
+    let leading_zero = trimmed.len() > 1 && trimmed.starts_with('0') && !trimmed.starts_with("0.");
+    if !leading_zero && trimmed.parse::<f64>().is_ok_and(f64::is_finite) {
+        return "number";
+    }
+    infer_string_kind(trimmed)
+}
+
+pub(super) fn is_iso8601_datetime(value: &str) -> bool {
     let bytes = value.as_bytes();
     if bytes.len() < 20 {
         return false;
@@ -217,6 +238,24 @@ fn property_to_yaml_value(property: &NoteProperty) -> Result<Value, String> {
     }
 }
 
+/// Converts untyped text to the YAML value of a `kind` property. List kinds
+/// split on commas and semicolons.
+pub(crate) fn text_to_yaml_value(key: &str, kind: &str, text: &str) -> Result<Value, String> {
+    let trimmed = text.trim();
+    let property = NoteProperty {
+        key: key.to_string(),
+        kind: kind.to_string(),
+        value_text: Some(trimmed.to_string()),
+        value_bool: Some(trimmed.eq_ignore_ascii_case("true")),
+        value_list: trimmed
+            .split([',', ';'])
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect(),
+    };
+    property_to_yaml_value(&property)
+}
+
 #[tauri::command]
 pub fn note_frontmatter_parse_properties(
     frontmatter: Option<String>,
@@ -233,10 +272,14 @@ pub fn note_frontmatter_parse_properties(
         .collect()
 }
 
-#[tauri::command]
-pub fn note_frontmatter_render_properties(
+/// Renders properties as a frontmatter block, validating and canonicalizing
+/// the ones the space's property schema defines. Entries of `existing` whose
+/// values did not change keep their formatting and comments.
+fn render_properties(
+    definitions: &[PropertyDefinition],
+    existing: Option<&str>,
     properties: Vec<NoteProperty>,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, PropertyError> {
     let mut mapping = Mapping::new();
     let mut seen = HashSet::<String>::new();
 
@@ -247,9 +290,19 @@ pub fn note_frontmatter_render_properties(
         }
         let normalized = key.to_lowercase();
         if !seen.insert(normalized) {
-            return Err(format!("duplicate property key '{}'", key));
+            return Err(format!("duplicate property key '{}'", key).into());
         }
-        mapping.insert(Value::String(key), property_to_yaml_value(&property)?);
+        let mut value = property_to_yaml_value(&property)?;
+        if let Some(definition) = find_definition(definitions, &[], &key) {
+            value = validate_property_value(definition, &value)?;
+        }
+        mapping.insert(Value::String(key), value);
+    }
+    if let Some(error) = missing_required_properties(definitions, &[], &mapping)
+        .into_iter()
+        .next()
+    {
+        return Err(error);
     }
 
     if mapping.is_empty() {
@@ -257,13 +310,62 @@ pub fn note_frontmatter_render_properties(
     }
     Ok(Some(format!(
         "---\n{}---\n",
-        render_frontmatter_mapping_yaml(&mapping)?
+        render_frontmatter_lossless(raw_yaml(existing), &mapping)?
     )))
 }
 
+#[tauri::command]
+pub fn note_frontmatter_render_properties(
+    state: State<'_, SpaceState>,
+    properties: Vec<NoteProperty>,
+    frontmatter: Option<String>,
+) -> Result<Option<String>, PropertyError> {
+    let schema = state
+        .current_root()
+        .map(|root| read_property_schema(&root))
+        .unwrap_or_default();
+    render_properties(&schema.properties, frontmatter.as_deref(), properties)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::note_frontmatter_parse_properties;
+    use super::{note_frontmatter_parse_properties, render_properties};
+    use crate::notes::property_schema::{PropertyDefinition, PropertyOption};
+    use crate::notes::types::NoteProperty;
+
+    #[test]
+    fn validates_rendered_properties_against_definitions() {
+        let definitions = vec![PropertyDefinition {
+            key: "status".to_string(),
+            kind: "select".to_string(),
+            options: vec![PropertyOption {
+                value: "Done".to_string(),
+                color: None,
+            }],
+            required: true,
+            number_format: None,
+            default: None,
+        }];
+        let status = |text: &str| NoteProperty {
+            key: "Status".to_string(),
+            kind: "text".to_string(),
+            value_text: Some(text.to_string()),
+            value_bool: None,
+            value_list: Vec::new(),
+        };
+
+        let rendered = render_properties(&definitions, None, vec![status("done")])
+            .expect("known option should render")
+            .unwrap();
+        assert_eq!(rendered, "---\nStatus: Done\n---\n");
+        let error = render_properties(&definitions, None, vec![status("")])
+            .expect_err("required property cannot be empty");
+        assert_eq!(error.code, "required");
+        assert_eq!(error.key.as_deref(), Some("status"));
+        let error = render_properties(&definitions, None, Vec::new())
+            .expect_err("required property cannot be removed");
+        assert_eq!(error.code, "required");
+    }
 
     #[test]
     fn infers_existing_yaml_properties_from_frontmatter() {
@@ -0,0 +1,511 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Number, Value};
+use tauri::State;
+
+use crate::dates::date_from_iso;
+use crate::space::SpaceState;
+use crate::{glyph_paths, io_atomic};
+
+use super::properties::{is_iso8601_datetime, text_to_yaml_value};
+
+const PROPERTY_SCHEMA_FILE: &str = "properties.json";
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PropertyOption {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+/// The declared type of a frontmatter property, shared by a space or scoped
+/// to one database.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PropertyDefinition {
+    pub key: String,
+    /// `text`, `number`, `checkbox`, `date`, `datetime`, `url`, `select`,
+    /// `multi_select`, `list` or `tags`.
+    pub kind: String,
+    /// Allowed values of `select` and `multi_select` properties.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<PropertyOption>,
+    /// Whether the property must be present with a non-empty value.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+    /// `integer` only accepts whole numbers; other formats (`decimal`,
+    /// `percent`, `currency`) only change how numbers are shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number_format: Option<String>,
+    /// Text of the value new database rows start with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PropertySchema {
+    #[serde(default)]
+    pub properties: Vec<PropertyDefinition>,
+}
+
+/// A command error that says which property failed and why, so the UI can
+/// point at the field. Plain errors convert with the `error` code.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PropertyError {
+    /// `required`, `invalid_option`, `invalid_number`, `invalid_checkbox`,
+    /// `invalid_date`, `invalid_url`, `invalid_value` or `error`.
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note_path: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed: Vec<String>,
+}
+
+impl PropertyError {
+    fn invalid(definition: &PropertyDefinition, code: &str, message: String) -> Self {
+        Self {
+            code: code.to_string(),
+            key: Some(definition.key.clone()),
+            note_path: None,
+            message,
+            allowed: Vec::new(),
+        }
+    }
+
+    pub(crate) fn at(mut self, note_path: &str) -> Self {
+        self.note_path = Some(note_path.to_string());
+        self
+    }
+}
+
+impl From<String> for PropertyError {
+    fn from(message: String) -> Self {
+        Self {
+            code: "error".to_string(),
+            key: None,
+            note_path: None,
+            message,
+            allowed: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for PropertyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+fn property_schema_path(space_root: &Path) -> Result<PathBuf, String> {
+    Ok(glyph_paths::glyph_dir(space_root)?.join(PROPERTY_SCHEMA_FILE))
+}
+
+pub fn read_property_schema(space_root: &Path) -> PropertySchema {
+    let Ok(path) = property_schema_path(space_root) else {
+        return PropertySchema::default();
+    };
+    let bytes = std::fs::read(path).unwrap_or_default();
+    serde_json::from_slice(&bytes).unwrap_or_default()
+}
+
+pub fn write_property_schema(space_root: &Path, schema: &PropertySchema) -> Result<(), String> {
+    glyph_paths::ensure_glyph_dir(space_root)?;
+    let path = property_schema_path(space_root)?;
+    let bytes = serde_json::to_vec_pretty(schema).map_err(|e| e.to_string())?;
+    io_atomic::write_atomic(&path, &bytes).map_err(|e| e.to_string())
+}
+
+/// The definition of `key`, preferring a database's own definitions over the
+/// space-wide ones. Keys match case-insensitively.
+pub(crate) fn find_definition<'a>(
+    scoped: &'a [PropertyDefinition],
+    space: &'a [PropertyDefinition],
+    key: &str,
+) -> Option<&'a PropertyDefinition> {
+    let key = key.trim();
+    scoped
+        .iter()
+        .chain(space)
+        .find(|definition| definition.key.trim().eq_ignore_ascii_case(key))
+}
+
+fn is_empty_value(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(text) => text.trim().is_empty(),
+        Value::Sequence(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn scalar_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.trim().to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    }
+}
+
+fn string_items(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::Sequence(items) => items.iter().map(scalar_string).collect(),
+        other => scalar_string(other).map(|item| vec![item]),
+    }
+}
+
+fn canonical_option(definition: &PropertyDefinition, raw: &str) -> Result<String, PropertyError> {
+    if definition.options.is_empty() {
+        return Ok(raw.to_string());
+    }
+    definition
+        .options
+        .iter()
+        .find(|option| option.value.trim().eq_ignore_ascii_case(raw))
+        .map(|option| option.value.clone())
+        .ok_or_else(|| {
+            let mut error = PropertyError::invalid(
+                definition,
+                "invalid_option",
+                format!("'{raw}' is not an option of '{}'", definition.key),
+            );
+            error.allowed = definition
+                .options
+                .iter()
+                .map(|option| option.value.clone())
+                .collect();
+            error
+        })
+}
+
+fn validate_number(definition: &PropertyDefinition, value: &Value) -> Result<Value, PropertyError> {
+    let invalid = || {
+        PropertyError::invalid(
+            definition,
+            "invalid_number",
+            format!("'{}' must be a number", definition.key),
+        )
+    };
+    let number = match value {
+        Value::Number(number) => number.clone(),
+        Value::String(text) => {
+            let text = text.trim();
+            match text.parse::<i64>() {
+                Ok(int) => Number::from(int),
+                Err(_) => text
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|float| float.is_finite())
+                    .map(Number::from)
+                    .ok_or_else(invalid)?,
+            }
+        }
+        _ => return Err(invalid()),
+    };
+    if definition.number_format.as_deref() == Some("integer") {
+        let whole = number.as_i64().or_else(|| {
+            number
+                .as_f64()
+                .filter(|float| float.fract() == 0.0 && float.abs() < i64::MAX as f64)
+                .map(|float| float as i64)
+        });
+        let Some(whole) = whole else {
+            return Err(PropertyError::invalid(
+                definition,
+                "invalid_number",
+                format!("'{}' must be a whole number", definition.key),
+            ));
+        };
+        return Ok(Value::Number(Number::from(whole)));
+    }
+    Ok(Value::Number(number))
+}
+
+/// Checks `value` against `definition` and returns its canonical form: select
+/// options take their declared casing, numeric and boolean strings become
+/// numbers and booleans, and list kinds always become sequences.
+pub(crate) fn validate_property_value(
+    definition: &PropertyDefinition,
+    value: &Value,
+) -> Result<Value, PropertyError> {
+    if is_empty_value(value) {
+        if definition.required {
+            return Err(PropertyError::invalid(
+                definition,
+                "required",
+                format!("'{}' is required", definition.key),
+            ));
+        }
+        return Ok(value.clone());
+    }
+
+    let text = || {
+        scalar_string(value).ok_or_else(|| {
+            PropertyError::invalid(
+                definition,
+                "invalid_value",
+                format!("'{}' must be a single value", definition.key),
+            )
+        })
+    };
+    match definition.kind.as_str() {
+        "number" => validate_number(definition, value),
+        "checkbox" => match value {
+            Value::Bool(flag) => Ok(Value::Bool(*flag)),
+            Value::String(text) if text.trim().eq_ignore_ascii_case("true") => {
+                Ok(Value::Bool(true))
+            }
+            Value::String(text) if text.trim().eq_ignore_ascii_case("false") => {
+                Ok(Value::Bool(false))
+            }
+            _ => Err(PropertyError::invalid(
+                definition,
+                "invalid_checkbox",
+                format!("'{}' must be true or false", definition.key),
+            )),
+        },
+        "date" | "datetime" => {
+            let raw = text()?;
+            let valid = date_from_iso(&raw).is_some()
+                || (definition.kind == "datetime"
+                    && is_iso8601_datetime(&raw)
+                    && date_from_iso(&raw[..10]).is_some());
+            if !valid {
+                return Err(PropertyError::invalid(
+                    definition,
+                    "invalid_date",
+                    format!("'{raw}' is not a valid date for '{}'", definition.key),
+                ));
+            }
+            Ok(Value::String(raw))
+        }
+        "url" => {
+            let raw = text()?;
+            if !(raw.starts_with("http://") || raw.starts_with("https://")) {
+                return Err(PropertyError::invalid(
+                    definition,
+                    "invalid_url",
+                    format!("'{}' must be an http(s) URL", definition.key),
+                ));
+            }
+            Ok(Value::String(raw))
+        }
+        "select" => Ok(Value::String(canonical_option(definition, &text()?)?)),
+        "multi_select" | "list" | "tags" => {
+            let items = string_items(value).ok_or_else(|| {
+                PropertyError::invalid(
+                    definition,
+                    "invalid_value",
+                    format!("'{}' must be a list of values", definition.key),
+                )
+            })?;
+            let mut canonical = Vec::<String>::new();
+            for item in items.into_iter().filter(|item| !item.is_empty()) {
+                let item = if definition.kind == "multi_select" {
+                    canonical_option(definition, &item)?
+                } else {
+                    item
+                };
+                if !canonical.contains(&item) {
+                    canonical.push(item);
+                }
+            }
+            Ok(Value::Sequence(
+                canonical.into_iter().map(Value::String).collect(),
+            ))
+        }
+        _ => Ok(Value::String(text()?)),
+    }
+}
+
+/// A `required` issue for every required property `mapping` lacks. A
+/// database's own definitions shadow space-wide ones with the same key.
+pub(crate) fn missing_required_properties(
+    scoped: &[PropertyDefinition],
+    space: &[PropertyDefinition],
+    mapping: &Mapping,
+) -> Vec<PropertyError> {
+    let mut seen = Vec::<String>::new();
+    let mut missing = Vec::new();
+    for definition in scoped.iter().chain(space) {
+        let key = definition.key.trim().to_lowercase();
+        if key.is_empty() || seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+        let present = mapping.keys().any(|name| {
+            name.as_str()
+                .is_some_and(|name| name.trim().eq_ignore_ascii_case(definition.key.trim()))
+        });
+        if definition.required && !present {
+            missing.push(PropertyError::invalid(
+                definition,
+                "required",
+                format!("'{}' is required", definition.key),
+            ));
+        }
+    }
+    missing
+}
+
+/// The YAML value a definition's `default` text stands for.
+pub(crate) fn default_property_value(
+    definition: &PropertyDefinition,
+) -> Result<Option<Value>, PropertyError> {
+    let Some(raw) = definition
+        .default
+        .as_deref()
+        .map(str::trim)
+        .filter(|raw| !raw.is_empty())
+    else {
+        return Ok(None);
+    };
+    let kind = match definition.kind.as_str() {
+        "multi_select" => "list",
+        "select" => "text",
+        other => other,
+    };
+    let value = text_to_yaml_value(&definition.key, kind, raw)?;
+    validate_property_value(definition, &value).map(Some)
+}
+
+#[tauri::command]
+pub async fn property_schema_get(state: State<'_, SpaceState>) -> Result<PropertySchema, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || Ok(read_property_schema(&root)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn property_schema_set(
+    state: State<'_, SpaceState>,
+    schema: PropertySchema,
+) -> Result<PropertySchema, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        write_property_schema(&root, &schema)?;
+        Ok(schema)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(kind: &str) -> PropertyDefinition {
+        PropertyDefinition {
+            key: "status".to_string(),
+            kind: kind.to_string(),
+            options: ["Todo", "Done"]
+                .into_iter()
+                .map(|value| PropertyOption {
+                    value: value.to_string(),
+                    color: None,
+                })
+                .collect(),
+            required: true,
+            number_format: None,
+            default: None,
+        }
+    }
+
+    #[test]
+    fn canonicalizes_and_rejects_values() {
+        let select = definition("select");
+        assert_eq!(
+            validate_property_value(&select, &Value::String(" done ".to_string())),
+            Ok(Value::String("Done".to_string()))
+        );
+        let error = validate_property_value(&select, &Value::String("Later".to_string()))
+            .expect_err("unknown options are rejected");
+        assert_eq!(error.code, "invalid_option");
+        assert_eq!(error.allowed, vec!["Todo", "Done"]);
+        assert_eq!(
+            validate_property_value(&select, &Value::Null)
+                .unwrap_err()
+                .code,
+            "required"
+        );
+
+        let multi = definition("multi_select");
+        assert_eq!(
+            validate_property_value(&multi, &Value::String("todo".to_string())),
+            Ok(Value::Sequence(vec![Value::String("Todo".to_string())]))
+        );
+
+        let mut number = definition("number");
+        number.number_format = Some("integer".to_string());
+        assert_eq!(
+            validate_property_value(&number, &Value::String("3".to_string())),
+            Ok(Value::Number(Number::from(3)))
+        );
+        assert_eq!(
+            validate_property_value(&number, &Value::String("3.5".to_string()))
+                .unwrap_err()
+                .code,
+            "invalid_number"
+        );
+        assert_eq!(
+            validate_property_value(
+                &definition("date"),
+                &Value::String("2024-02-30".to_string())
+            )
+            .unwrap_err()
+            .code,
+            "invalid_date"
+        );
+        assert!(validate_property_value(
+            &definition("datetime"),
+            &Value::String("2024-02-03T10:00:00Z".to_string())
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn reports_missing_required_properties() {
+        let status = definition("select");
+        let mut due = definition("date");
+        due.key = "due".to_string();
+        due.required = false;
+        let mut optional_status = definition("text");
+        optional_status.required = false;
+
+        let mut mapping = Mapping::new();
+        mapping.insert(
+            Value::String("Title".to_string()),
+            Value::String("Plan".to_string()),
+        );
+        let missing = missing_required_properties(&[], &[status.clone(), due.clone()], &mapping);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].code, "required");
+        assert_eq!(missing[0].key.as_deref(), Some("status"));
+
+        // A database definition overrides the space's requirement.
+        assert!(missing_required_properties(
+            &[optional_status],
+            std::slice::from_ref(&status),
+            &mapping
+        )
+        .is_empty());
+
+        mapping.insert(
+            Value::String("Status".to_string()),
+            Value::String("Todo".to_string()),
+        );
+        assert!(missing_required_properties(&[], &[status, due], &mapping).is_empty());
+    }
+}
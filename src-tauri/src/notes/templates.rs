@@ -0,0 +1,676 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use tauri::State;
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+
+use crate::crypto;
+use crate::database::mutations::{
+    available_note_path, note_abs_path, read_note, slugify_title, write_markdown_note,
+};
+use crate::dates::date_from_iso;
+use crate::space::SpaceState;
+
+use super::frontmatter::{
+    normalize_frontmatter_mapping, parse_frontmatter_mapping, split_frontmatter,
+};
+use super::frontmatter_edit::render_frontmatter_lossless;
+
+/// Frontmatter keys a template never copies: the new note gets its own.
+const RESERVED_TEMPLATE_KEYS: [&str; 4] = ["id", "title", "created", "updated"];
+const DEFAULT_TEMPLATES_FOLDER: &str = "Templates";
+const CURSOR_MARKER: &str = "{{cursor}}";
+const PROMPT_PREFIX: &str = "prompt:";
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// What `{{name}}` placeholders expand to: named values, `date` and `time`
+/// (optionally `{{date:YYYY-MM-DD}}`-style formatted) and answers to
+/// `{{prompt:Name}}` / `{{prompt:Name|default}}`.
+#[derive(Debug, Clone)]
+pub(crate) struct TemplateVars {
+    values: Vec<(String, String)>,
+    now: PrimitiveDateTime,
+    prompts: HashMap<String, String>,
+}
+
+impl TemplateVars {
+    pub(crate) fn new(now: PrimitiveDateTime) -> Self {
+        Self {
+            values: Vec::new(),
+            now,
+            prompts: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn with(mut self, name: &str, value: String) -> Self {
+        self.values.push((name.to_string(), value));
+        self
+    }
+
+    pub(crate) fn with_prompts(mut self, prompts: HashMap<String, String>) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    fn resolve(&self, name: &str) -> Option<String> {
+        if let Some((_, value)) = self
+            .values
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        {
+            return Some(value.clone());
+        }
+        if let Some(prompt) = strip_prefix_ignore_case(name, PROMPT_PREFIX) {
+            let (prompt, default) = parse_prompt(prompt);
+            return Some(
+                self.prompts
+                    .get(&prompt)
+                    .cloned()
+                    .or(default)
+                    .unwrap_or_default(),
+            );
+        }
+        let (name, format) = match name.split_once(':') {
+            Some((name, format)) => (name.trim(), Some(format.trim())),
+            None => (name, None),
+        };
+        match name.to_ascii_lowercase().as_str() {
+            "date" => Some(format_moment(self.now, format.unwrap_or("YYYY-MM-DD"))),
+            "time" => Some(format_moment(self.now, format.unwrap_or("HH:mm"))),
+            _ => None,
+        }
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &text[prefix.len()..])
+}
+
+fn parse_prompt(prompt: &str) -> (String, Option<String>) {
+    match prompt.split_once('|') {
+        Some((name, default)) => (name.trim().to_string(), Some(default.trim().to_string())),
+        None => (prompt.trim().to_string(), None),
+    }
+}
+
+/// Formats with moment-style tokens (`YYYY`, `MMMM`, `MM`, `Do`, `dddd`,
+/// `HH`, `hh`, `mm`, `ss`, `A`, ISO week `GGGG`/`WW` and quarter `Q`, ...);
+/// text in `[brackets]` is kept as is.
+pub(crate) fn format_moment(now: PrimitiveDateTime, format: &str) -> String {
+    format_moment_in_week(now, now.date(), format)
+}
+
+/// Like [`format_moment`], with the ISO week tokens (`GGGG`, `WW`, `W`)
+/// taken from `week_of` instead of `now`.
+pub(crate) fn format_moment_in_week(now: PrimitiveDateTime, week_of: Date, format: &str) -> String {
+    const TOKENS: [&str; 24] = [
+        "GGGG", "WW", "W", "Q", "YYYY", "MMMM", "dddd", "MMM", "ddd", "YY", "MM", "DD", "Do", "HH",
+        "hh", "mm", "ss", "M", "D", "H", "h", "m", "s", "A",
+    ];
+    let hour12 = match now.hour() % 12 {
+        0 => 12,
+        hour => hour,
+    };
+    let month = usize::from(u8::from(now.month())) - 1;
+    let weekday = usize::from(now.weekday().number_days_from_monday());
+    let (iso_year, iso_week, _) = week_of.to_iso_week_date();
+    let mut out = String::new();
+    let mut rest = format;
+    while !rest.is_empty() {
+        if let Some(literal) = rest.strip_prefix('[') {
+            if let Some(end) = literal.find(']') {
+                out.push_str(&literal[..end]);
+                rest = &literal[end + 1..];
+                continue;
+            }
+        }
+        let Some(token) = TOKENS.iter().find(|token| rest.starts_with(*token)) else {
+            let ch = rest.chars().next().unwrap_or_default();
+            out.push(ch);
+            rest = &rest[ch.len_utf8()..];
+            continue;
+        };
+        let text = match *token {
+            "GGGG" => format!("{iso_year:04}"),
+            "WW" => format!("{iso_week:02}"),
+            "W" => iso_week.to_string(),
+            "Q" => (month / 3 + 1).to_string(),
+            "YYYY" => format!("{:04}", now.year()),
+            "YY" => format!("{:02}", now.year().rem_euclid(100)),
+            "MMMM" => MONTH_NAMES[month].to_string(),
+            "MMM" => MONTH_NAMES[month][..3].to_string(),
+            "MM" => format!("{:02}", month + 1),
+            "M" => (month + 1).to_string(),
+            "DD" => format!("{:02}", now.day()),
+            "D" => now.day().to_string(),
+            "Do" => ordinal(now.day()),
+            "dddd" => WEEKDAY_NAMES[weekday].to_string(),
+            "ddd" => WEEKDAY_NAMES[weekday][..3].to_string(),
+            "HH" => format!("{:02}", now.hour()),
+            "H" => now.hour().to_string(),
+            "hh" => format!("{hour12:02}"),
+            "h" => hour12.to_string(),
+            "mm" => format!("{:02}", now.minute()),
+            "m" => now.minute().to_string(),
+            "ss" => format!("{:02}", now.second()),
+            "s" => now.second().to_string(),
+            _ => if now.hour() < 12 { "AM" } else { "PM" }.to_string(),
+        };
+        out.push_str(&text);
+        rest = &rest[token.len()..];
+    }
+    out
+}
+
+fn ordinal(day: u8) -> String {
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{day}{suffix}")
+}
+
+/// The frontend's local `now` (`YYYY-MM-DDTHH:MM[:SS]`), or the UTC time.
+fn parse_now(raw: Option<&str>) -> Result<PrimitiveDateTime, String> {
+    let Some(raw) = raw.map(str::trim).filter(|raw| !raw.is_empty()) else {
+        let now = OffsetDateTime::now_utc();
+        return Ok(PrimitiveDateTime::new(now.date(), now.time()));
+    };
+    let invalid = || format!("invalid local time '{raw}'");
+    let (date, time) = raw.split_once(['T', ' ']).unwrap_or((raw, "00:00"));
+    let date: Date = date_from_iso(date).ok_or_else(invalid)?;
+    let mut parts = time.split(':').map(|part| part.parse::<u8>());
+    let (Some(Ok(hour)), Some(Ok(minute))) = (parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let second = match parts.next() {
+        Some(second) => second.map_err(|_| invalid())?,
+        None => 0,
+    };
+    let time = Time::from_hms(hour, minute, second).map_err(|_| invalid())?;
+    Ok(PrimitiveDateTime::new(date, time))
+}
+
+/// Replaces `{{name}}` (whitespace inside the braces allowed) with its value.
+/// Unknown variables, including `{{cursor}}`, are left as written.
+pub(crate) fn expand_variables(text: &str, vars: &TemplateVars) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        match vars.resolve(after[..end].trim()) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Removes every `{{cursor}}` marker, returning where the first one was in
+/// UTF-16 code units (as the editor counts).
+fn take_cursor(text: &str) -> (String, Option<usize>) {
+    let cursor = text
+        .find(CURSOR_MARKER)
+        .map(|index| text[..index].encode_utf16().count());
+    (text.replace(CURSOR_MARKER, ""), cursor)
+}
+
+fn expand_value(value: Value, vars: &TemplateVars) -> Value {
+    match value {
+        Value::String(text) => Value::String(take_cursor(&expand_variables(&text, vars)).0),
+        Value::Sequence(items) => Value::Sequence(
+            items
+                .into_iter()
+                .map(|item| expand_value(item, vars))
+                .collect(),
+        ),
+        Value::Mapping(mapping) => Value::Mapping(
+            mapping
+                .into_iter()
+                .map(|(key, value)| (key, expand_value(value, vars)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// A template note split into the frontmatter a new note inherits and its
+/// body scaffold, with variables expanded in both. Variables are expanded
+/// after parsing so values like titles cannot break the YAML.
+pub(crate) fn render_template(
+    markdown: &str,
+    vars: &TemplateVars,
+) -> Result<(Mapping, String), String> {
+    let (yaml, body) = split_frontmatter(markdown);
+    let mut mapping = Mapping::new();
+    for (key, value) in parse_frontmatter_mapping(yaml)? {
+        let reserved = key
+            .as_str()
+            .is_some_and(|key| RESERVED_TEMPLATE_KEYS.contains(&key));
+        if !reserved {
+            mapping.insert(key, expand_value(value, vars));
+        }
+    }
+    Ok((
+        mapping,
+        expand_variables(body.trim_start_matches('\n'), vars),
+    ))
+}
+
+/// Adds template frontmatter to a note's: keys the note lacks are copied,
+/// lists are joined without duplicates and other values the note already
+/// has are kept.
+fn merge_frontmatter(note: &mut Mapping, template: Mapping) {
+    for (key, value) in template {
+        match (note.get_mut(&key), value) {
+            (None, value) => {
+                note.insert(key, value);
+            }
+            (Some(Value::Sequence(existing)), Value::Sequence(items)) => {
+                for item in items {
+                    if !existing.contains(&item) {
+                        existing.push(item);
+                    }
+                }
+            }
+            (Some(Value::Null), value) => {
+                note.insert(key, value);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TemplatePrompt {
+    pub name: String,
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TemplateInfo {
+    pub path: String,
+    pub name: String,
+    /// Values to ask for before applying the template.
+    pub prompts: Vec<TemplatePrompt>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TemplateApplyResult {
+    pub path: String,
+    pub markdown: String,
+    /// Where `{{cursor}}` ended up, in UTF-16 code units into the note body.
+    pub cursor: Option<usize>,
+}
+
+/// The distinct `{{prompt:...}}` placeholders of a template, in order.
+fn template_prompts(markdown: &str) -> Vec<TemplatePrompt> {
+    let mut prompts: Vec<TemplatePrompt> = Vec::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        if let Some(prompt) = strip_prefix_ignore_case(after[..end].trim(), PROMPT_PREFIX) {
+            let (name, default) = parse_prompt(prompt);
+            if !name.is_empty() && !prompts.iter().any(|known| known.name == name) {
+                prompts.push(TemplatePrompt { name, default });
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    prompts
+}
+
+fn collect_templates(root: &Path, dir: &Path, out: &mut Vec<TemplateInfo>) -> Result<(), String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error.to_string()),
+    };
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_templates(root, &path, out)?;
+            continue;
+        }
+        if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            continue;
+        }
+        let Ok(rel) = path.strip_prefix(root) else {
+            continue;
+        };
+        let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+        let markdown = crypto::open_text(root, &bytes)?;
+        out.push(TemplateInfo {
+            path: rel.to_string_lossy().replace('\\', "/"),
+            name: path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .to_string(),
+            prompts: template_prompts(&markdown),
+        });
+    }
+    Ok(())
+}
+
+fn templates_folder(folder: Option<&str>) -> &str {
+    folder
+        .map(|folder| folder.trim().trim_matches('/'))
+        .filter(|folder| !folder.is_empty())
+        .unwrap_or(DEFAULT_TEMPLATES_FOLDER)
+}
+
+/// A new note at `path` titled `title` and seeded from the template, with
+/// where `{{cursor}}` ended up in its body.
+pub(crate) fn render_note_from_template(
+    path: &str,
+    title: &str,
+    template: &str,
+    vars: &TemplateVars,
+) -> Result<(String, Option<usize>), String> {
+    let (template_yaml, _) = split_frontmatter(template);
+    let (template_mapping, body) = render_template(template, vars)?;
+    let (body, cursor) = take_cursor(&body);
+    let mut mapping = Mapping::new();
+    mapping.insert(
+        Value::String("title".to_string()),
+        Value::String(title.to_string()),
+    );
+    merge_frontmatter(&mut mapping, template_mapping);
+    let normalized = normalize_frontmatter_mapping(mapping, path, Some(title), None);
+    let rendered_yaml = render_frontmatter_lossless(template_yaml, &normalized)?;
+    Ok((format!("---\n{rendered_yaml}---\n\n{body}"), cursor))
+}
+
+/// A new note titled `title` in `folder`, seeded from the template.
+fn create_from_template(
+    root: &Path,
+    template: &str,
+    title: &str,
+    folder: &str,
+    vars: &TemplateVars,
+) -> Result<TemplateApplyResult, String> {
+    let file_name = format!("{}.md", slugify_title(title));
+    let path = match folder.trim().trim_matches('/') {
+        "" => file_name,
+        folder => format!("{folder}/{file_name}"),
+    };
+    let path = available_note_path(root, &path)?;
+    let (markdown, cursor) = render_note_from_template(&path, title, template, vars)?;
+    Ok(TemplateApplyResult {
+        path,
+        markdown,
+        cursor,
+    })
+}
+
+/// The note at `path` with the template's body inserted at `insert_at`
+/// (UTF-16 code units into the body; default: the end) and its frontmatter
+/// merged in.
+fn insert_into_note(
+    path: &str,
+    markdown: &str,
+    template: &str,
+    insert_at: Option<usize>,
+    vars: &TemplateVars,
+) -> Result<TemplateApplyResult, String> {
+    let (yaml, body) = split_frontmatter(markdown);
+    let mut mapping = parse_frontmatter_mapping(yaml)?;
+    let body = body.trim_start_matches('\n');
+    let (template_mapping, inserted) = render_template(template, vars)?;
+    let (inserted, cursor) = take_cursor(&inserted);
+    merge_frontmatter(&mut mapping, template_mapping);
+
+    let mut index = body.len();
+    if let Some(insert_at) = insert_at {
+        let mut units = 0;
+        for (byte, ch) in body.char_indices() {
+            if units >= insert_at {
+                index = byte;
+                break;
+            }
+            units += ch.len_utf16();
+        }
+    }
+    let offset = body[..index].encode_utf16().count();
+    let next_body = format!("{}{inserted}{}", &body[..index], &body[index..]);
+    let title = mapping
+        .get("title")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            Path::new(path)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("Untitled")
+                .to_string()
+        });
+    let preserve_created = mapping
+        .get("created")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let normalized =
+        normalize_frontmatter_mapping(mapping, path, Some(&title), preserve_created.as_deref());
+    let rendered_yaml = render_frontmatter_lossless(yaml, &normalized)?;
+    Ok(TemplateApplyResult {
+        path: path.to_string(),
+        markdown: format!("---\n{rendered_yaml}---\n\n{next_body}"),
+        cursor: cursor.map(|cursor| offset + cursor),
+    })
+}
+
+/// Lists the markdown templates under `folder` (default: `Templates`).
+#[tauri::command(rename_all = "snake_case")]
+pub async fn template_list(
+    state: State<'_, SpaceState>,
+    folder: Option<String>,
+) -> Result<Vec<TemplateInfo>, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<Vec<TemplateInfo>, String> {
+        let dir = note_abs_path(&root, templates_folder(folder.as_deref()))?;
+        let mut templates = Vec::new();
+        collect_templates(&root, &dir, &mut templates)?;
+        templates.sort_by_key(|template| template.path.to_lowercase());
+        Ok(templates)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Creates a note titled `title` in `folder` from a template, or inserts the
+/// template into the existing note at `target_path`. `prompts` answers the
+/// template's `{{prompt:...}}` placeholders and `now` is the local time
+/// dates and times expand to.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn note_create_from_template(
+    state: State<'_, SpaceState>,
+    template_path: String,
+    title: Option<String>,
+    folder: Option<String>,
+    target_path: Option<String>,
+    insert_at: Option<usize>,
+    prompts: Option<HashMap<String, String>>,
+    now: Option<String>,
+) -> Result<TemplateApplyResult, String> {
+    let root = state.current_root()?;
+    let recent_local_changes = state.recent_local_changes();
+    tauri::async_runtime::spawn_blocking(move || -> Result<TemplateApplyResult, String> {
+        let template = read_note(&root, &template_path)
+            .map_err(|e| format!("could not read template '{template_path}': {e}"))?;
+        let now = parse_now(now.as_deref())?;
+        let prompts = prompts.unwrap_or_default();
+        let result = match target_path {
+            Some(target_path) => {
+                let markdown = read_note(&root, &target_path)?;
+                let title = parse_frontmatter_mapping(split_frontmatter(&markdown).0)?
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_default();
+                let vars = TemplateVars::new(now)
+                    .with("title", title)
+                    .with_prompts(prompts);
+                insert_into_note(&target_path, &markdown, &template, insert_at, &vars)?
+            }
+            None => {
+                let title = title
+                    .map(|title| title.trim().to_string())
+                    .filter(|title| !title.is_empty())
+                    .unwrap_or_else(|| "Untitled".to_string());
+                let vars = TemplateVars::new(now)
+                    .with("title", title.clone())
+                    .with_prompts(prompts);
+                create_from_template(
+                    &root,
+                    &template,
+                    &title,
+                    folder.as_deref().unwrap_or_default(),
+                    &vars,
+                )?
+            }
+        };
+        write_markdown_note(&root, &recent_local_changes, &result.path, &result.markdown)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> TemplateVars {
+        TemplateVars::new(parse_now(Some("2024-05-06T09:05:03")).unwrap())
+    }
+
+    #[test]
+    fn expands_variables_in_frontmatter_and_body() {
+        let vars = vars().with("title", "Standup: Monday".to_string());
+        let (mapping, body) = render_template(
+            "---\ntitle: Meeting template\ncreated: 2020-01-01T00:00:00Z\ntopic: \"{{ title }}\"\nattendees: []\n---\n\n# {{title}} ({{date}})\n\n## Agenda\n\n## Actions\n{{unknown}} {{",
+            &vars,
+        )
+        .unwrap();
+        assert_eq!(
+            mapping.get("topic"),
+            Some(&Value::String("Standup: Monday".to_string()))
+        );
+        assert!(mapping.get("title").is_none());
+        assert!(mapping.get("created").is_none());
+        assert_eq!(
+            body,
+            "# Standup: Monday (2024-05-06)\n\n## Agenda\n\n## Actions\n{{unknown}} {{"
+        );
+    }
+
+    #[test]
+    fn formats_dates_times_and_prompts() {
+        let vars = vars().with_prompts(HashMap::from([(
+            "Project".to_string(),
+            "Glyph".to_string(),
+        )]));
+        assert_eq!(
+            expand_variables(
+                "{{date:dddd, MMMM Do YYYY}} {{time}} {{time:h:mm:ss A}} [{{date:[W]YY-M-D}}]",
+                &vars
+            ),
+            "Monday, May 6th 2024 09:05 9:05:03 AM [W24-5-6]"
+        );
+        assert_eq!(
+            expand_variables("{{prompt:Project}} / {{prompt: Owner | me }}", &vars),
+            "Glyph / me"
+        );
+        assert_eq!(
+            template_prompts("{{prompt:Project}} {{prompt:Owner|me}} {{PROMPT:Project}}"),
+            vec![
+                TemplatePrompt {
+                    name: "Project".to_string(),
+                    default: None
+                },
+                TemplatePrompt {
+                    name: "Owner".to_string(),
+                    default: Some("me".to_string())
+                },
+            ]
+        );
+        assert!(parse_now(Some("2024-05-06T25:00")).is_err());
+    }
+
+    #[test]
+    fn inserts_template_into_note_and_merges_frontmatter() {
+        let note = "---\ntitle: Log\ncreated: 2024-01-01T00:00:00Z\ntags: [work]\nstatus: open\n---\n\nBefore é after\n";
+        let template =
+            "---\ntags: [work, meeting]\nstatus: draft\nowner: '{{prompt:Owner}}'\n---\n\n## {{title}}\n{{cursor}}\n";
+        let vars = vars()
+            .with("title", "Log".to_string())
+            .with_prompts(HashMap::from([("Owner".to_string(), "Sam".to_string())]));
+        let result = insert_into_note("log.md", note, template, Some(8), &vars).unwrap();
+        let (yaml, body) = split_frontmatter(&result.markdown);
+        let mapping = parse_frontmatter_mapping(yaml).unwrap();
+        assert_eq!(
+            mapping.get("tags"),
+            Some(&serde_yaml::from_str::<Value>("[work, meeting]").unwrap())
+        );
+        assert_eq!(mapping.get("status").and_then(Value::as_str), Some("open"));
+        assert_eq!(mapping.get("owner").and_then(Value::as_str), Some("Sam"));
+        assert_eq!(
+            mapping.get("created").and_then(Value::as_str),
+            Some("2024-01-01T00:00:00Z")
+        );
+        let body = body.trim_start_matches('\n');
+        assert_eq!(body, "Before é## Log\n\n after\n");
+        assert_eq!(result.cursor, Some(15));
+    }
+}
@@ -1,12 +1,28 @@
 use std::path::PathBuf;
 use tauri::State;
 
+use crate::crypto;
 use crate::index::db::reset_schema_cache;
 
 use super::helpers::{canonicalize_dir, create_or_open_impl, SpaceInfo};
 use super::state::SpaceState;
 use super::watcher::set_notes_watcher;
 
+/// Makes `info` the current space. A space left behind is locked and its
+/// decrypted notes dropped from the index.
+fn set_current_space(state: &SpaceState, info: &SpaceInfo) -> Result<(), String> {
+    let mut guard = state
+        .current
+        .lock()
+        .map_err(|_| "space state poisoned".to_string())?;
+    let previous = guard.replace(PathBuf::from(&info.root));
+    drop(guard);
+    if let Some(previous) = previous.filter(|previous| previous.as_os_str() != info.root.as_str()) {
+        let _ = crypto::purge_decrypted(&previous);
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn space_create(
     app: tauri::AppHandle,
@@ -17,18 +33,15 @@ pub async fn space_create(
     let info = tauri::async_runtime::spawn_blocking(move || -> Result<SpaceInfo, String> {
         std::fs::create_dir_all(&root).map_err(|e| e.to_string())?;
         let root = canonicalize_dir(&root)?;
-        create_or_open_impl(&root)
+        let info = create_or_open_impl(&root)?;
+        crypto::purge_decrypted(&root)?;
+        Ok(info)
     })
     .await
     .map_err(|e| e.to_string())??;
 
     reset_schema_cache();
-    let mut guard = state
-        .current
-        .lock()
-        .map_err(|_| "space state poisoned".to_string())?;
-    *guard = Some(PathBuf::from(&info.root));
-    drop(guard);
+    set_current_space(&state, &info)?;
     let _ = set_notes_watcher(&state, app, PathBuf::from(&info.root));
     Ok(info)
 }
@@ -42,18 +55,16 @@ pub async fn space_open(
     let root = PathBuf::from(path);
     let info = tauri::async_runtime::spawn_blocking(move || -> Result<SpaceInfo, String> {
         let root = canonicalize_dir(&root)?;
-        create_or_open_impl(&root)
+        let info = create_or_open_impl(&root)?;
+        // A previous session may have quit while the space was unlocked.
+        crypto::purge_decrypted(&root)?;
+        Ok(info)
     })
     .await
     .map_err(|e| e.to_string())??;
 
     reset_schema_cache();
-    let mut guard = state
-        .current
-        .lock()
-        .map_err(|_| "space state poisoned".to_string())?;
-    *guard = Some(PathBuf::from(&info.root));
-    drop(guard);
+    set_current_space(&state, &info)?;
     let _ = set_notes_watcher(&state, app, PathBuf::from(&info.root));
     Ok(info)
 }
@@ -65,19 +76,31 @@ pub fn space_get_current(state: State<'_, SpaceState>) -> Option<String> {
 }
 
 #[tauri::command]
-pub fn space_close(state: State<'_, SpaceState>) -> Result<(), String> {
-    {
+pub async fn space_close(state: State<'_, SpaceState>) -> Result<(), String> {
+    let closed = {
         let mut guard = state
             .current
             .lock()
             .map_err(|_| "space state poisoned".to_string())?;
-        *guard = None;
+        guard.take()
+    };
+    {
+        let mut watcher_guard = state
+            .notes_watcher
+            .lock()
+            .map_err(|_| "space watcher state poisoned".to_string())?;
+        *watcher_guard = None;
+        state
+            .open_databases()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+    if let Some(root) = closed {
+        tauri::async_runtime::spawn_blocking(move || crypto::purge_decrypted(&root))
+            .await
+            .map_err(|e| e.to_string())??;
     }
-    let mut watcher_guard = state
-        .notes_watcher
-        .lock()
-        .map_err(|_| "space watcher state poisoned".to_string())?;
-    *watcher_guard = None;
     reset_schema_cache();
     Ok(())
 }
@@ -5,6 +5,8 @@ use std::{
     time::{Duration, Instant},
 };
 
+use crate::database::live::OpenDatabases;
+
 const RECENT_LOCAL_CHANGE_TTL: Duration = Duration::from_secs(2);
 
 pub(crate) type RecentLocalChanges = Arc<Mutex<HashMap<String, Instant>>>;
@@ -54,6 +56,7 @@ pub struct SpaceState {
     pub(crate) current: Mutex<Option<PathBuf>>,
     pub(crate) notes_watcher: Mutex<Option<notify::RecommendedWatcher>>,
     recent_local_changes: RecentLocalChanges,
+    open_databases: OpenDatabases,
 }
 
 impl Default for SpaceState {
@@ -62,6 +65,7 @@ impl Default for SpaceState {
             current: Mutex::new(None),
             notes_watcher: Mutex::new(None),
             recent_local_changes: Arc::new(Mutex::new(HashMap::new())),
+            open_databases: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
@@ -71,6 +75,10 @@ impl SpaceState {
         Arc::clone(&self.recent_local_changes)
     }
 
+    pub(crate) fn open_databases(&self) -> OpenDatabases {
+        Arc::clone(&self.open_databases)
+    }
+
     pub fn current_root(&self) -> Result<PathBuf, String> {
         let guard = self
             .current
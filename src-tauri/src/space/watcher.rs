@@ -5,7 +5,8 @@ use std::path::PathBuf;
 use std::sync::mpsc as std_mpsc;
 use tauri::Emitter;
 
-use crate::{index, utils};
+use crate::database::live::emit_rows_changed;
+use crate::{git, index, utils};
 
 use super::state::{has_recent_local_change, SpaceState};
 
@@ -27,15 +28,22 @@ pub fn set_notes_watcher(
         .lock()
         .map_err(|_| "space watcher state poisoned".to_string())?;
     *guard = None;
+    let open_databases = state.open_databases();
+    open_databases
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clear();
 
-    let (idx_tx, idx_rx) = std_mpsc::channel::<(String, bool)>();
+    // (rel path, removed, written by this app and already indexed)
+    let (idx_tx, idx_rx) = std_mpsc::channel::<(String, bool, bool)>();
 
     let root_idx = root.clone();
+    let app_idx = app.clone();
     std::thread::spawn(move || {
         let debounce = std::time::Duration::from_millis(DEBOUNCE_MS);
         while let Ok(first) = idx_rx.recv() {
             let mut pending = HashMap::new();
-            pending.insert(first.0, first.1);
+            pending.insert(first.0, (first.1, first.2));
 
             let deadline = std::time::Instant::now() + debounce;
             loop {
@@ -44,30 +52,44 @@ pub fn set_notes_watcher(
                     break;
                 }
                 match idx_rx.recv_timeout(remaining) {
-                    Ok((rel, remove)) => {
-                        pending.insert(rel, remove);
+                    Ok((rel, remove, local)) => {
+                        let local = local
+                            && pending
+                                .get(&rel)
+                                .is_none_or(|(_, was_local)| *was_local);
+                        pending.insert(rel, (remove, local));
                     }
                     Err(std_mpsc::RecvTimeoutError::Timeout) => break,
                     Err(std_mpsc::RecvTimeoutError::Disconnected) => return,
                 }
             }
 
-            for (rel_s, is_remove) in pending {
-                if is_remove {
-                    let _ = index::remove_note(&root_idx, &rel_s);
+            for (rel_s, (is_remove, local)) in &pending {
+                if *local {
+                    continue;
+                }
+                if *is_remove {
+                    let _ = index::remove_note(&root_idx, rel_s);
                 } else {
-                    let abs = root_idx.join(&rel_s);
+                    let abs = root_idx.join(rel_s);
                     if let Ok(markdown) = std::fs::read_to_string(&abs) {
-                        let _ = index::index_note(&root_idx, &rel_s, &markdown);
+                        let _ = index::index_note(&root_idx, rel_s, &markdown);
                     }
                 }
             }
+
+            let changed = pending
+                .into_iter()
+                .map(|(rel_s, (is_remove, _))| (rel_s, is_remove))
+                .collect::<Vec<_>>();
+            emit_rows_changed(&app_idx, &root_idx, &open_databases, &changed);
         }
     });
 
     let app2 = app.clone();
     let root2 = root.clone();
     let recent_local_changes = state.recent_local_changes();
+    let auto_commit_tx = git::autocommit::spawn_auto_committer(root.clone());
 
     let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
         let event = match res {
@@ -100,8 +122,10 @@ pub fn set_notes_watcher(
             }
 
             if utils::is_markdown_path(&path) {
-	                if !has_recent_local_change(&recent_local_changes, &rel_s) {
-	                    let _ = idx_tx.send((rel_s.clone(), is_remove));
+	                if has_recent_local_change(&recent_local_changes, &rel_s) {
+	                    let _ = idx_tx.send((rel_s.clone(), is_remove, true));
+	                } else {
+	                    let _ = idx_tx.send((rel_s.clone(), is_remove, false));
 
 	                    let _ = app2.emit(
 	                        "notes:external_changed",
@@ -113,6 +137,7 @@ pub fn set_notes_watcher(
 	                }
 	            }
 
+	            let _ = auto_commit_tx.send(());
 	            let _ = app2.emit(
 	                "space:fs_changed",
 	                ExternalChangeEvent {
@@ -1,11 +1,22 @@
 use std::path::{Path, PathBuf};
 use tauri::State;
 
-use crate::{paths, space::SpaceState, utils};
+use crate::{git, paths, space::SpaceState, utils};
 
 use super::helpers::{deny_hidden_rel_path, should_hide};
 use super::types::FsEntry;
 
+/// Fills in `git_status` when the space is a git repository.
+fn annotate_git_status(root: &Path, entries: &mut [FsEntry]) {
+    let statuses = git::status_map(root);
+    if statuses.is_empty() {
+        return;
+    }
+    for entry in entries {
+        entry.git_status = statuses.get(&entry.rel_path.replace('\\', "/")).copied();
+    }
+}
+
 #[tauri::command]
 pub async fn space_list_dirs(
     state: State<'_, SpaceState>,
@@ -60,6 +71,7 @@ pub async fn space_list_dirs(
                     rel_path: child_rel.to_string_lossy().to_string(),
                     kind: "dir".to_string(),
                     is_markdown: false,
+                    git_status: None,
                 });
                 if out.len() >= max_count {
                     break;
@@ -72,6 +84,7 @@ pub async fn space_list_dirs(
         }
 
         out.sort_by_cached_key(|entry| entry.rel_path.to_lowercase());
+        annotate_git_status(&root, &mut out);
         Ok(out)
     })
     .await
@@ -123,6 +136,7 @@ pub async fn space_list_markdown_files(
                     rel_path: rel_path.to_string_lossy().to_string(),
                     kind: "file".to_string(),
                     is_markdown: true,
+                    git_status: None,
                 });
                 if out.len() >= limit {
                     break;
@@ -165,6 +179,7 @@ pub async fn space_list_markdown_files(
                         rel_path: child_rel.to_string_lossy().to_string(),
                         kind: "file".to_string(),
                         is_markdown: true,
+                        git_status: None,
                     });
                     if out.len() >= limit {
                         break;
@@ -177,6 +192,7 @@ pub async fn space_list_markdown_files(
         }
 
         out.sort_by_cached_key(|e| e.rel_path.to_lowercase());
+        annotate_git_status(&root, &mut out);
         Ok(out)
     })
     .await
@@ -226,6 +242,7 @@ pub async fn space_list_files(
                     rel_path: rel_path.to_string_lossy().to_string(),
                     kind: "file".to_string(),
                     is_markdown,
+                    git_status: None,
                 });
                 if out.len() >= limit {
                     break;
@@ -266,6 +283,7 @@ pub async fn space_list_files(
                         rel_path: child_rel.to_string_lossy().to_string(),
                         kind: "file".to_string(),
                         is_markdown,
+                        git_status: None,
                     });
                     if out.len() >= limit {
                         break;
@@ -278,6 +296,7 @@ pub async fn space_list_files(
         }
 
         out.sort_by_cached_key(|e| e.rel_path.to_lowercase());
+        annotate_git_status(&root, &mut out);
         Ok(out)
     })
     .await
@@ -322,11 +341,13 @@ pub async fn space_list_dir(
                 rel_path: rel_path.to_string_lossy().to_string(),
                 kind: kind.to_string(),
                 is_markdown,
+                git_status: None,
             });
         }
 
         entries
             .sort_by_cached_key(|e| (if e.kind == "dir" { 0u8 } else { 1 }, e.name.to_lowercase()));
+        annotate_git_status(&root, &mut entries);
 
         Ok(entries)
     })
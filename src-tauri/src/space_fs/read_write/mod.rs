@@ -1,4 +1,4 @@
 pub mod paths;
 pub mod preview;
 pub mod text;
-mod trash;
+pub(crate) mod trash;
@@ -139,6 +139,7 @@ fn reindex_after_rename(
                         mark_recent_local_change(recent_local_changes, &old_id);
                         mark_recent_local_change(recent_local_changes, &new_id);
                         let _ = index::remove_note(root, &old_id);
+                        let _ = index::record_note_move(root, &old_id, &new_id);
                         let abs = root.join(&new_id);
                         if let Ok(markdown) = std::fs::read_to_string(&abs) {
                             let _ = index::index_note(root, &new_id, &markdown);
@@ -151,6 +152,7 @@ fn reindex_after_rename(
         mark_recent_local_change(recent_local_changes, from_path);
         mark_recent_local_change(recent_local_changes, to_path);
         let _ = index::remove_note(root, from_path);
+        let _ = index::record_note_move(root, from_path, to_path);
         if let Ok(markdown) = std::fs::read_to_string(to_abs) {
             let _ = index::index_note(root, to_path, &markdown);
         }
@@ -1,12 +1,17 @@
-use std::{ffi::OsStr, io::Write, path::PathBuf};
+use std::{
+    ffi::OsStr,
+    io::Write,
+    path::{Path, PathBuf},
+};
 use tauri::State;
 
+use crate::history::{self, MergeOutcome, VersionSource};
 use crate::space::state::mark_recent_local_change;
-use crate::{index, io_atomic, paths, space::SpaceState};
+use crate::{crypto, index, io_atomic, paths, space::SpaceState, utils};
 
 use super::super::helpers::{deny_hidden_rel_path, etag_for, file_mtime_ms};
 use super::super::types::{
-    OpenOrCreateTextResult, TextFileDoc, TextFileDocBatch, TextFileWriteResult,
+    OpenOrCreateTextResult, TextFileDoc, TextFileDocBatch, TextFileWriteResult, TextWriteStatus,
 };
 
 #[tauri::command]
@@ -20,8 +25,7 @@ pub async fn space_read_text(
         deny_hidden_rel_path(&rel)?;
         let abs = paths::join_under(&root, &rel)?;
         let bytes = std::fs::read(&abs).map_err(|e| e.to_string())?;
-        let text =
-            String::from_utf8(bytes.clone()).map_err(|_| "file is not valid UTF-8".to_string())?;
+        let text = crypto::open_text(&root, &bytes)?;
         Ok(TextFileDoc {
             rel_path: rel.to_string_lossy().to_string(),
             etag: etag_for(&bytes),
@@ -47,8 +51,7 @@ pub async fn space_read_texts_batch(
                 deny_hidden_rel_path(&rel)?;
                 let abs = paths::join_under(&root, &rel)?;
                 let bytes = std::fs::read(&abs).map_err(|e| e.to_string())?;
-                let text = String::from_utf8(bytes.clone())
-                    .map_err(|_| "file is not valid UTF-8".to_string())?;
+                let text = crypto::open_text(&root, &bytes)?;
                 Ok(TextFileDocBatch {
                     rel_path: rel.to_string_lossy().to_string(),
                     text: Some(text),
@@ -74,12 +77,52 @@ pub async fn space_read_texts_batch(
     .map_err(|e| e.to_string())?
 }
 
+/// The text to write when the file changed on disk since the editor loaded
+/// `base`: the editor text merged with the disk changes, or the conflicts.
+fn merge_with_disk(
+    root: &Path,
+    abs: &Path,
+    text: String,
+    base_text: Option<String>,
+    base_etag: Option<&str>,
+) -> Result<Result<(String, TextWriteStatus), TextFileWriteResult>, String> {
+    let disk = std::fs::read(abs).map_err(|e| e.to_string())?;
+    let disk_etag = etag_for(&disk);
+    let disk = crypto::open_text(root, &disk)?;
+    if disk == text {
+        return Ok(Ok((text, TextWriteStatus::Written)));
+    }
+    let base = base_text.or_else(|| {
+        let base = history::content_by_hash(root, base_etag?)?;
+        crypto::open_text(root, base.as_bytes()).ok()
+    });
+    let Some(base) = base else {
+        return Err("conflict: on-disk file changed since it was opened".to_string());
+    };
+    match history::merge_texts(&base, &disk, &text) {
+        MergeOutcome::Clean(merged) => Ok(Ok((merged, TextWriteStatus::Merged))),
+        MergeOutcome::Conflict(chunks) => Ok(Err(TextFileWriteResult {
+            etag: disk_etag,
+            mtime_ms: file_mtime_ms(abs),
+            status: TextWriteStatus::Conflict,
+            text: None,
+            chunks: Some(chunks),
+        })),
+    }
+}
+
+/// Writes `text` to `path`. When the file changed on disk since the editor
+/// loaded it (per `base_mtime_ms` or `base_etag`), the save is merged with
+/// the disk changes against `base_text` (or the saved version whose etag is
+/// `base_etag`); overlapping changes come back as conflicts instead.
 #[tauri::command(rename_all = "snake_case")]
 pub async fn space_write_text(
     state: State<'_, SpaceState>,
     path: String,
     text: String,
     base_mtime_ms: Option<u64>,
+    base_text: Option<String>,
+    base_etag: Option<String>,
 ) -> Result<TextFileWriteResult, String> {
     let root = state.current_root()?;
     let recent_local_changes = state.recent_local_changes();
@@ -87,32 +130,55 @@ pub async fn space_write_text(
         let rel = PathBuf::from(&path);
         deny_hidden_rel_path(&rel)?;
         let abs = paths::join_under(&root, &rel)?;
-        if let Some(expected) = base_mtime_ms {
-            let actual = file_mtime_ms(&abs);
-            if actual != 0 && actual != expected {
-                return Err("conflict: on-disk file changed since it was opened".to_string());
+        let actual_mtime = file_mtime_ms(&abs);
+        let mtime_changed =
+            base_mtime_ms.is_some_and(|expected| actual_mtime != 0 && actual_mtime != expected);
+        let etag_changed = base_etag.as_deref().is_some_and(|expected| {
+            std::fs::read(&abs).is_ok_and(|bytes| etag_for(&bytes) != expected)
+        });
+        let (text, status) = if mtime_changed || etag_changed {
+            match merge_with_disk(&root, &abs, text, base_text, base_etag.as_deref())? {
+                Ok(merged) => merged,
+                Err(conflict) => return Ok(conflict),
             }
-        }
+        } else {
+            (text, TextWriteStatus::Written)
+        };
         if let Some(parent) = abs.parent() {
             std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
 
         let rel_path = rel.to_string_lossy().to_string();
         let should_index = rel.extension() == Some(OsStr::new("md"));
-        let bytes = text.into_bytes();
+        let previous = std::fs::read(&abs).ok();
+        let bytes = crypto::seal_text(
+            &root,
+            &utils::to_slash(&rel),
+            previous.as_deref(),
+            false,
+            &text,
+        )?;
         if should_index {
             mark_recent_local_change(&recent_local_changes, &rel_path);
         }
         io_atomic::write_atomic(&abs, &bytes).map_err(|e| e.to_string())?;
         if should_index {
-            if let Ok(markdown) = std::str::from_utf8(&bytes) {
-                let _ = index::index_note(&root, &rel_path, markdown);
-            }
+            let _ = index::index_note(&root, &rel_path, &text);
         }
+        let _ = history::record_write(
+            &root,
+            &rel_path,
+            previous.as_deref(),
+            &bytes,
+            VersionSource::Save,
+        );
 
         Ok(TextFileWriteResult {
             etag: etag_for(&bytes),
             mtime_ms: file_mtime_ms(&abs),
+            text: (status == TextWriteStatus::Merged).then_some(text),
+            status,
+            chunks: None,
         })
     })
     .await
@@ -138,6 +204,7 @@ pub async fn space_open_or_create_text(
             });
         }
 
+        let bytes = crypto::seal_text(&root, &utils::to_slash(&rel), None, false, &text)?;
         if let Some(parent) = abs.parent() {
             std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
@@ -147,7 +214,7 @@ pub async fn space_open_or_create_text(
             .open(&abs)
         {
             Ok(mut file) => {
-                file.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+                file.write_all(&bytes).map_err(|e| e.to_string())?;
             }
             Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
                 return Ok(OpenOrCreateTextResult {
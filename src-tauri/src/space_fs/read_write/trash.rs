@@ -54,7 +54,7 @@ fn unique_trash_dest(trash_dir: &Path, src: &Path) -> Result<PathBuf, String> {
     Err("unable to find an available Trash destination name".to_string())
 }
 
-pub(super) fn move_path_to_trash(src: &Path) -> Result<(), String> {
+pub(crate) fn move_path_to_trash(src: &Path) -> Result<(), String> {
     let trash_dir = resolve_trash_dir()?;
     std::fs::create_dir_all(&trash_dir).map_err(|e| e.to_string())?;
     let dest = unique_trash_dest(&trash_dir, src)?;
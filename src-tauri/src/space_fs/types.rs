@@ -1,11 +1,16 @@
 use serde::Serialize;
 
+use crate::git::GitFileStatus;
+use crate::history::MergeChunk;
+
 #[derive(Serialize)]
 pub struct FsEntry {
     pub name: String,
     pub rel_path: String,
     pub kind: String,
     pub is_markdown: bool,
+    /// Git status when the space is a repository and the entry changed.
+    pub git_status: Option<GitFileStatus>,
 }
 
 #[derive(Serialize)]
@@ -16,10 +21,26 @@ pub struct TextFileDoc {
     pub mtime_ms: u64,
 }
 
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextWriteStatus {
+    Written,
+    /// The file changed on disk and the save was merged with it.
+    Merged,
+    /// The file changed on disk in the same places; nothing was written.
+    Conflict,
+}
+
 #[derive(Serialize)]
 pub struct TextFileWriteResult {
     pub etag: String,
     pub mtime_ms: u64,
+    pub status: TextWriteStatus,
+    /// The merged text that was written, for `Merged`.
+    pub text: Option<String>,
+    /// The merge with disk split into agreed and conflicting chunks, for
+    /// `Conflict`. `etag` and `mtime_ms` then describe the file on disk.
+    pub chunks: Option<Vec<MergeChunk>>,
 }
 
 #[derive(Serialize)]
@@ -67,6 +67,7 @@ fn list_files(root: &Path, dir: &Path, limit: usize) -> Result<Vec<FsEntry>, Str
                 rel_path: utils::to_slash(&child_rel),
                 kind: "file".to_string(),
                 is_markdown: utils::is_markdown_path(&child_rel),
+                git_status: None,
             });
             if out.len() >= limit {
                 break;
@@ -141,6 +142,7 @@ pub async fn space_folder_view_data(
                     rel_path: rel,
                     kind: "file".to_string(),
                     is_markdown: md,
+                    git_status: None,
                 },
             );
         }
@@ -0,0 +1,99 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::index;
+use crate::space::SpaceState;
+
+use super::engine::{run_sync, SyncReport};
+use super::settings::{
+    read_sync_settings, save_sync_settings, sync_settings_status, SyncSettings, SyncSettingsStatus,
+};
+use super::state::clear_records;
+
+const PROGRESS_EVENT: &str = "sync:progress";
+
+static SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Holds the single sync slot until dropped.
+struct SyncSlot;
+
+impl SyncSlot {
+    fn acquire() -> Result<Self, String> {
+        if SYNC_RUNNING.swap(true, Ordering::SeqCst) {
+            return Err("a sync is already running".to_string());
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for SyncSlot {
+    fn drop(&mut self) {
+        SYNC_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// The sync settings without their secret, which is only reported as set
+/// or not.
+#[tauri::command]
+pub async fn sync_settings_get(state: State<'_, SpaceState>) -> Result<SyncSettingsStatus, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || sync_settings_status(&root))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Saves the sync settings. An empty password or secret key keeps the
+/// stored one. Moving to another remote forgets what was synced, so the
+/// first sync there compares files instead of deleting the ones the new
+/// remote does not have.
+#[tauri::command]
+pub async fn sync_settings_set(
+    state: State<'_, SpaceState>,
+    settings: SyncSettings,
+) -> Result<SyncSettingsStatus, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let previous = read_sync_settings(&root)
+            .remote
+            .map(|remote| remote.location());
+        let _slot = SyncSlot::acquire()?;
+        save_sync_settings(&root, &settings)?;
+        if previous != settings.remote.as_ref().map(|remote| remote.location()) {
+            clear_records(&index::open_db(&root)?)?;
+        }
+        sync_settings_status(&root)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Syncs the space with its configured remote, emitting `sync:progress`
+/// as files transfer. A sync that would delete most local files fails
+/// unless `confirm_deletes` is set.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn sync_run(
+    app: AppHandle,
+    state: State<'_, SpaceState>,
+    confirm_deletes: Option<bool>,
+) -> Result<SyncReport, String> {
+    let root = state.current_root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let remote = read_sync_settings(&root)
+            .remote
+            .ok_or_else(|| "sync is not set up for this space".to_string())?
+            .with_stored_secret(&root)?;
+        let store = remote.open()?;
+        let _slot = SyncSlot::acquire()?;
+        run_sync(
+            &root,
+            store.as_ref(),
+            confirm_deletes.unwrap_or(false),
+            &mut |progress| {
+                let _ = app.emit(PROGRESS_EVENT, progress);
+            },
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
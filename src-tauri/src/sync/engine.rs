@@ -0,0 +1,720 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::history::{self, MergeOutcome, VersionSource};
+use crate::space_fs::read_write::trash::move_path_to_trash;
+use crate::{crypto, glyph_paths, index, io_atomic, paths, utils};
+
+use super::remote::{RemoteFile, RemoteStore};
+use super::state::{load_records, remove_record, save_record, SyncRecord};
+
+const PARTIAL_DIR: &str = "sync-partial";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LocalFile {
+    etag: String,
+    mtime_ms: u64,
+    size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Upload,
+    Download,
+    DeleteRemote,
+    DeleteLocal,
+    /// Gone on both sides; only the record is left.
+    Forget,
+    /// Changed on both sides, or new on both.
+    Reconcile,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPhase {
+    Scanning,
+    Transferring,
+    Done,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncProgress {
+    pub phase: SyncPhase,
+    pub path: Option<String>,
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncConflict {
+    pub path: String,
+    /// Where the remote version was saved next to the local one.
+    pub copy_path: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncError {
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncReport {
+    pub uploaded: Vec<String>,
+    pub downloaded: Vec<String>,
+    pub deleted_local: Vec<String>,
+    pub deleted_remote: Vec<String>,
+    /// Markdown changed on both sides and merged without conflicts.
+    pub merged: Vec<String>,
+    pub conflicts: Vec<SyncConflict>,
+    /// Files that failed; they are retried on the next sync.
+    pub errors: Vec<SyncError>,
+}
+
+/// Synced files skip hidden paths (Glyph's data, VCS folders, temp files)
+/// and dependency caches.
+fn is_synced_name(name: &str) -> bool {
+    !utils::should_hide(name) && !name.eq_ignore_ascii_case("node_modules")
+}
+
+/// Local files, hashing only those whose size or mtime moved since the
+/// last sync.
+fn scan_local(
+    root: &Path,
+    records: &HashMap<String, SyncRecord>,
+) -> Result<HashMap<String, LocalFile>, String> {
+    let mut out = HashMap::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if !is_synced_name(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            if meta.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            if !meta.is_file() {
+                continue;
+            }
+            let rel = utils::to_slash(path.strip_prefix(root).map_err(|e| e.to_string())?);
+            let mtime_ms = utils::file_mtime_ms(&path);
+            let size = meta.len();
+            let etag = match records.get(&rel) {
+                Some(record) if record.local_mtime_ms == mtime_ms && record.local_size == size => {
+                    record.local_etag.clone()
+                }
+                _ => match std::fs::read(&path) {
+                    Ok(bytes) => utils::sha256_hex(&bytes),
+                    Err(_) => continue,
+                },
+            };
+            out.insert(
+                rel,
+                LocalFile {
+                    etag,
+                    mtime_ms,
+                    size,
+                },
+            );
+        }
+    }
+    Ok(out)
+}
+
+/// What to do with each path, given both sides and the last synced state.
+fn plan(
+    local: &HashMap<String, LocalFile>,
+    remote: &HashMap<String, RemoteFile>,
+    records: &HashMap<String, SyncRecord>,
+) -> Vec<(String, Action)> {
+    let paths = local
+        .keys()
+        .chain(remote.keys())
+        .chain(records.keys())
+        .collect::<BTreeSet<_>>();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let action = match (local.get(path), remote.get(path), records.get(path)) {
+                (Some(local), Some(remote), Some(record)) => {
+                    let local_changed = local.etag != record.local_etag;
+                    let remote_changed = remote.etag != record.remote_etag;
+                    match (local_changed, remote_changed) {
+                        (false, false) => return None,
+                        (true, false) => Action::Upload,
+                        (false, true) => Action::Download,
+                        (true, true) => Action::Reconcile,
+                    }
+                }
+                // A change on one side wins over a delete on the other.
+                (Some(local), None, Some(record)) if local.etag != record.local_etag => {
+                    Action::Upload
+                }
+                (Some(_), None, Some(_)) => Action::DeleteLocal,
+                (None, Some(remote), Some(record)) if remote.etag != record.remote_etag => {
+                    Action::Download
+                }
+                (None, Some(_), Some(_)) => Action::DeleteRemote,
+                (None, None, Some(_)) => Action::Forget,
+                (Some(_), None, None) => Action::Upload,
+                (None, Some(_), None) => Action::Download,
+                (Some(_), Some(_), None) => Action::Reconcile,
+                (None, None, None) => return None,
+            };
+            Some((path.clone(), action))
+        })
+        .collect()
+}
+
+struct Syncer<'a> {
+    root: &'a Path,
+    conn: Connection,
+    store: &'a dyn RemoteStore,
+    records: HashMap<String, SyncRecord>,
+    report: SyncReport,
+}
+
+impl Syncer<'_> {
+    fn abs(&self, path: &str) -> Result<PathBuf, String> {
+        paths::join_under(self.root, Path::new(path))
+    }
+
+    fn read_local(&self, path: &str) -> Result<Vec<u8>, String> {
+        std::fs::read(self.abs(path)?).map_err(|e| e.to_string())
+    }
+
+    /// Records that `path` now holds `bytes` locally and `remote_etag` on
+    /// the remote. Markdown also goes into note history, which later merges
+    /// use as their base.
+    fn record(&mut self, path: &str, bytes: &[u8], remote_etag: String) -> Result<(), String> {
+        let abs = self.abs(path)?;
+        let record = SyncRecord {
+            local_etag: utils::sha256_hex(bytes),
+            local_mtime_ms: utils::file_mtime_ms(&abs),
+            local_size: bytes.len() as u64,
+            remote_etag,
+        };
+        save_record(&self.conn, path, &record)?;
+        self.records.insert(path.to_string(), record);
+        if utils::is_markdown_path(&abs) {
+            let _ = history::record_write(self.root, path, None, bytes, VersionSource::Sync);
+        }
+        Ok(())
+    }
+
+    fn forget(&mut self, path: &str) -> Result<(), String> {
+        remove_record(&self.conn, path)?;
+        self.records.remove(path);
+        Ok(())
+    }
+
+    fn write_local(&self, path: &str, bytes: &[u8]) -> Result<(), String> {
+        let abs = self.abs(path)?;
+        let previous = std::fs::read(&abs).ok();
+        if let Some(parent) = abs.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        io_atomic::write_atomic(&abs, bytes).map_err(|e| e.to_string())?;
+        if utils::is_markdown_path(&abs) {
+            let _ = history::record_write(
+                self.root,
+                path,
+                previous.as_deref(),
+                bytes,
+                VersionSource::Sync,
+            );
+        }
+        Ok(())
+    }
+
+    /// Downloads `file`, continuing a transfer an earlier sync left
+    /// unfinished when the remote supports ranges. A resumed download whose
+    /// length does not match the listing starts over from the first byte.
+    fn fetch(&self, file: &RemoteFile) -> Result<Vec<u8>, String> {
+        let dir = glyph_paths::glyph_cache_dir(self.root)?.join(PARTIAL_DIR);
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let partial = dir.join(utils::sha256_hex(
+            format!("{}\n{}", file.path, file.etag).as_bytes(),
+        ));
+        let mut offset = std::fs::metadata(&partial)
+            .map(|meta| meta.len())
+            .unwrap_or(0);
+        loop {
+            let mut download = self.store.download(file, offset)?;
+            let resumed = download.resumed && offset > 0;
+            let mut out = OpenOptions::new()
+                .create(true)
+                .append(resumed)
+                .write(true)
+                .truncate(!resumed)
+                .open(&partial)
+                .map_err(|e| e.to_string())?;
+            std::io::copy(&mut download.body, &mut out).map_err(|e| e.to_string())?;
+            drop(out);
+            let bytes = std::fs::read(&partial).map_err(|e| e.to_string())?;
+            let _ = std::fs::remove_file(&partial);
+            // A size of 0 means the remote did not report one.
+            if resumed && file.size > 0 && bytes.len() as u64 != file.size {
+                offset = 0;
+                continue;
+            }
+            return Ok(bytes);
+        }
+    }
+
+    fn upload(&mut self, path: &str) -> Result<(), String> {
+        let bytes = self.read_local(path)?;
+        let etag = self.store.upload(path, &bytes)?;
+        self.record(path, &bytes, etag)?;
+        self.report.uploaded.push(path.to_string());
+        Ok(())
+    }
+
+    fn download(&mut self, file: &RemoteFile) -> Result<(), String> {
+        let bytes = self.fetch(file)?;
+        self.write_local(&file.path, &bytes)?;
+        self.record(&file.path, &bytes, file.etag.clone())?;
+        self.report.downloaded.push(file.path.clone());
+        Ok(())
+    }
+
+    fn delete_local(&mut self, path: &str) -> Result<(), String> {
+        let abs = self.abs(path)?;
+        if utils::is_markdown_path(&abs) {
+            // Keep the last content restorable from note history.
+            if let Ok(bytes) = std::fs::read(&abs) {
+                let _ = history::record_write(self.root, path, None, &bytes, VersionSource::Sync);
+            }
+        }
+        if abs.exists() {
+            move_path_to_trash(&abs)?;
+        }
+        if utils::is_markdown_path(&abs) {
+            let _ = index::remove_note(self.root, path);
+        }
+        self.forget(path)?;
+        self.report.deleted_local.push(path.to_string());
+        Ok(())
+    }
+
+    fn delete_remote(&mut self, path: &str) -> Result<(), String> {
+        self.store.delete(path)?;
+        self.forget(path)?;
+        self.report.deleted_remote.push(path.to_string());
+        Ok(())
+    }
+
+    /// Both sides changed: identical content is just recorded, markdown is
+    /// merged against the last synced version, and anything else keeps the
+    /// local file and saves the remote one as a conflict copy.
+    fn reconcile(&mut self, file: &RemoteFile) -> Result<(), String> {
+        let path = file.path.as_str();
+        let remote_bytes = self.fetch(file)?;
+        let local_bytes = self.read_local(path)?;
+        if remote_bytes == local_bytes {
+            return self.record(path, &local_bytes, file.etag.clone());
+        }
+        let base = self
+            .records
+            .get(path)
+            .and_then(|record| history::content_by_hash(self.root, &record.local_etag));
+        // Encrypted notes cannot be merged line by line.
+        let mergeable = utils::is_markdown_path(Path::new(path))
+            && !crypto::is_encrypted(&remote_bytes)
+            && !crypto::is_encrypted(&local_bytes);
+        if let (true, Some(base), Ok(remote_text), Ok(local_text)) = (
+            mergeable,
+            base,
+            std::str::from_utf8(&remote_bytes),
+            std::str::from_utf8(&local_bytes),
+        ) {
+            if let MergeOutcome::Clean(merged) =
+                history::merge_texts(&base, remote_text, local_text)
+            {
+                self.write_local(path, merged.as_bytes())?;
+                let etag = self.store.upload(path, merged.as_bytes())?;
+                self.record(path, merged.as_bytes(), etag)?;
+                self.report.merged.push(path.to_string());
+                return Ok(());
+            }
+        }
+
+        let copy_path = conflict_copy_path(self.root, path, OffsetDateTime::now_utc())?;
+        self.write_local(&copy_path, &remote_bytes)?;
+        let copy_etag = self.store.upload(&copy_path, &remote_bytes)?;
+        self.record(&copy_path, &remote_bytes, copy_etag)?;
+        let etag = self.store.upload(path, &local_bytes)?;
+        self.record(path, &local_bytes, etag)?;
+        self.report.conflicts.push(SyncConflict {
+            path: path.to_string(),
+            copy_path,
+        });
+        Ok(())
+    }
+
+    fn apply(
+        &mut self,
+        path: &str,
+        action: Action,
+        remote: &HashMap<String, RemoteFile>,
+    ) -> Result<(), String> {
+        let remote_file = || {
+            remote
+                .get(path)
+                .ok_or_else(|| format!("{path} is not on the remote"))
+        };
+        match action {
+            Action::Upload => self.upload(path),
+            Action::Download => self.download(remote_file()?),
+            Action::DeleteLocal => self.delete_local(path),
+            Action::DeleteRemote => self.delete_remote(path),
+            Action::Forget => self.forget(path),
+            Action::Reconcile => self.reconcile(remote_file()?),
+        }
+    }
+}
+
+/// `notes/plan (conflict 2026-10-18 1420).md` for `notes/plan.md`, made
+/// unique among existing files.
+fn conflict_copy_path(root: &Path, path: &str, now: OffsetDateTime) -> Result<String, String> {
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{dir}/"), name),
+        None => (String::new(), path),
+    };
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name, String::new()),
+    };
+    let stamp = format!(
+        "{:04}-{:02}-{:02} {:02}{:02}",
+        now.year(),
+        u8::from(now.month()),
+        now.day(),
+        now.hour(),
+        now.minute()
+    );
+    for attempt in 1..1000 {
+        let suffix = if attempt == 1 {
+            String::new()
+        } else {
+            format!(" {attempt}")
+        };
+        let candidate = format!("{dir}{stem} (conflict {stamp}{suffix}){ext}");
+        if !paths::join_under(root, Path::new(&candidate))?.exists() {
+            return Ok(candidate);
+        }
+    }
+    Err(format!("no free conflict copy name for {path}"))
+}
+
+/// Whether `actions` would delete most of the `local` files, as when the
+/// remote lists as empty by mistake.
+fn deletes_most_local_files(actions: &[(String, Action)], local: usize) -> Option<usize> {
+    let deletes = actions
+        .iter()
+        .filter(|(_, action)| *action == Action::DeleteLocal)
+        .count();
+    (deletes > 1 && deletes * 2 > local).then_some(deletes)
+}
+
+/// Syncs the space at `root` with `store` in both directions. Each file's
+/// state is saved as soon as it transfers, so an interrupted sync picks up
+/// where it stopped; failures are reported per file rather than aborting.
+/// Deleted local files go to the trash, and a sync that would delete most
+/// of them does nothing unless `confirm_deletes` is set.
+pub fn run_sync(
+    root: &Path,
+    store: &dyn RemoteStore,
+    confirm_deletes: bool,
+    progress: &mut dyn FnMut(SyncProgress),
+) -> Result<SyncReport, String> {
+    progress(SyncProgress {
+        phase: SyncPhase::Scanning,
+        path: None,
+        done: 0,
+        total: 0,
+    });
+    let conn = index::open_db(root)?;
+    let records = load_records(&conn)?;
+    let local = scan_local(root, &records)?;
+    let remote = store
+        .list()?
+        .into_iter()
+        .filter(|file| file.path.split('/').all(is_synced_name))
+        .map(|file| (file.path.clone(), file))
+        .collect::<HashMap<_, _>>();
+    let actions = plan(&local, &remote, &records);
+    if let Some(deletes) = deletes_most_local_files(&actions, local.len()) {
+        if !confirm_deletes {
+            return Err(format!(
+                "sync would delete {deletes} of {} local files; confirm to continue",
+                local.len()
+            ));
+        }
+    }
+
+    let mut syncer = Syncer {
+        root,
+        conn,
+        store,
+        records,
+        report: SyncReport::default(),
+    };
+    let total = actions.len();
+    for (done, (path, action)) in actions.into_iter().enumerate() {
+        progress(SyncProgress {
+            phase: SyncPhase::Transferring,
+            path: Some(path.clone()),
+            done,
+            total,
+        });
+        if let Err(message) = syncer.apply(&path, action, &remote) {
+            syncer.report.errors.push(SyncError { path, message });
+        }
+    }
+    progress(SyncProgress {
+        phase: SyncPhase::Done,
+        path: None,
+        done: total,
+        total,
+    });
+    Ok(syncer.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::super::folder::FolderStore;
+    use super::super::remote::Download;
+    use super::*;
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("glyph-sync-{label}-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sync(root: &Path, store: &dyn RemoteStore) -> SyncReport {
+        let report = run_sync(root, store, false, &mut |_| {}).unwrap();
+        assert_eq!(report.errors, Vec::new());
+        report
+    }
+
+    fn read(root: &Path, path: &str) -> String {
+        std::fs::read_to_string(root.join(path)).unwrap()
+    }
+
+    #[test]
+    fn syncs_two_spaces_merging_markdown_and_copying_conflicts() {
+        let remote_dir = temp_dir("remote");
+        let store = FolderStore::new(remote_dir.clone()).unwrap();
+        let laptop = temp_dir("laptop");
+        let desktop = temp_dir("desktop");
+
+        std::fs::create_dir_all(laptop.join("notes")).unwrap();
+        std::fs::create_dir_all(laptop.join(".glyph/cache")).unwrap();
+        std::fs::write(laptop.join("notes/plan.md"), "one\ntwo\nthree\nfour\n").unwrap();
+        std::fs::write(laptop.join("photo.png"), [1u8, 2, 3]).unwrap();
+        std::fs::write(laptop.join(".glyph/cache/blob"), "cache").unwrap();
+
+        assert_eq!(
+            sync(&laptop, &store).uploaded,
+            vec!["notes/plan.md", "photo.png"]
+        );
+        assert!(!remote_dir.join(".glyph").exists());
+        assert_eq!(
+            sync(&desktop, &store).downloaded,
+            vec!["notes/plan.md", "photo.png"]
+        );
+        assert_eq!(sync(&desktop, &store), SyncReport::default());
+
+        // Separate edits to the same note merge.
+        std::fs::write(
+            laptop.join("notes/plan.md"),
+            "one (laptop)\ntwo\nthree\nfour\n",
+        )
+        .unwrap();
+        sync(&laptop, &store);
+        std::fs::write(
+            desktop.join("notes/plan.md"),
+            "one\ntwo\nthree\nfour (desktop)\n",
+        )
+        .unwrap();
+        assert_eq!(sync(&desktop, &store).merged, vec!["notes/plan.md"]);
+        assert_eq!(
+            read(&desktop, "notes/plan.md"),
+            "one (laptop)\ntwo\nthree\nfour (desktop)\n"
+        );
+        sync(&laptop, &store);
+        assert_eq!(
+            read(&laptop, "notes/plan.md"),
+            read(&desktop, "notes/plan.md")
+        );
+
+        // Overlapping edits keep the local note and copy the remote one.
+        std::fs::write(laptop.join("notes/plan.md"), "laptop wins?\n").unwrap();
+        sync(&laptop, &store);
+        std::fs::write(desktop.join("notes/plan.md"), "desktop wins?\n").unwrap();
+        let report = sync(&desktop, &store);
+        assert_eq!(report.conflicts.len(), 1);
+        let copy = &report.conflicts[0].copy_path;
+        assert!(copy.starts_with("notes/plan (conflict "));
+        assert_eq!(read(&desktop, "notes/plan.md"), "desktop wins?\n");
+        assert_eq!(read(&desktop, copy), "laptop wins?\n");
+
+        // Deletes propagate.
+        std::fs::remove_file(desktop.join("photo.png")).unwrap();
+        assert_eq!(sync(&desktop, &store).deleted_remote, vec!["photo.png"]);
+        let report = sync(&laptop, &store);
+        assert_eq!(report.deleted_local, vec!["photo.png"]);
+        assert!(!laptop.join("photo.png").exists());
+        assert_eq!(read(&laptop, copy), "laptop wins?\n");
+
+        for dir in [remote_dir, laptop, desktop] {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    #[test]
+    fn asks_before_a_remote_listing_as_empty_deletes_local_files() {
+        let remote_dir = temp_dir("empty-remote");
+        let store = FolderStore::new(remote_dir.clone()).unwrap();
+        let root = temp_dir("empty-local");
+        for name in ["a.md", "b.md", "c.md"] {
+            std::fs::write(root.join(name), name).unwrap();
+        }
+        sync(&root, &store);
+
+        // An unmounted share is an error, not an empty remote.
+        assert!(FolderStore::new(remote_dir.join("missing")).is_err());
+
+        for name in ["a.md", "b.md", "c.md"] {
+            std::fs::remove_file(remote_dir.join(name)).unwrap();
+        }
+        let refused = run_sync(&root, &store, false, &mut |_| {});
+        assert!(refused.unwrap_err().contains("delete 3 of 3"));
+        assert!(root.join("a.md").exists());
+
+        let report = run_sync(&root, &store, true, &mut |_| {}).unwrap();
+        assert_eq!(report.deleted_local, vec!["a.md", "b.md", "c.md"]);
+        assert!(!root.join("a.md").exists());
+
+        for dir in [remote_dir, root] {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    /// A remote whose first download breaks off halfway.
+    struct FlakyStore {
+        inner: FolderStore,
+        failed: AtomicBool,
+    }
+
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("connection reset"))
+        }
+    }
+
+    impl RemoteStore for FlakyStore {
+        fn list(&self) -> Result<Vec<RemoteFile>, String> {
+            self.inner.list()
+        }
+
+        fn download(&self, file: &RemoteFile, offset: u64) -> Result<Download, String> {
+            let download = self.inner.download(file, offset)?;
+            if self.failed.swap(true, Ordering::SeqCst) {
+                return Ok(download);
+            }
+            Ok(Download {
+                resumed: download.resumed,
+                body: Box::new(download.body.take(4).chain(Broken)),
+            })
+        }
+
+        fn upload(&self, path: &str, bytes: &[u8]) -> Result<String, String> {
+            self.inner.upload(path, bytes)
+        }
+
+        fn delete(&self, path: &str) -> Result<(), String> {
+            self.inner.delete(path)
+        }
+    }
+
+    #[test]
+    fn resumes_interrupted_downloads() {
+        let remote_dir = temp_dir("flaky-remote");
+        std::fs::write(remote_dir.join("big.txt"), "0123456789").unwrap();
+        let store = FlakyStore {
+            inner: FolderStore::new(remote_dir.clone()).unwrap(),
+            failed: AtomicBool::new(false),
+        };
+        let root = temp_dir("flaky-local");
+
+        let report = run_sync(&root, &store, false, &mut |_| {}).unwrap();
+        assert_eq!(report.errors.len(), 1);
+        assert!(!root.join("big.txt").exists());
+
+        let mut phases = Vec::new();
+        let report = run_sync(&root, &store, false, &mut |progress| {
+            phases.push(progress.phase)
+        })
+        .unwrap();
+        assert_eq!(report.downloaded, vec!["big.txt"]);
+        assert_eq!(read(&root, "big.txt"), "0123456789");
+        assert_eq!(
+            phases,
+            vec![
+                SyncPhase::Scanning,
+                SyncPhase::Transferring,
+                SyncPhase::Done
+            ]
+        );
+
+        // A stale partial download is thrown away, not completed.
+        std::fs::write(remote_dir.join("small.txt"), "abc").unwrap();
+        let listed = store.list().unwrap();
+        let small = listed.iter().find(|file| file.path == "small.txt").unwrap();
+        let partial = glyph_paths::glyph_cache_dir(&root)
+            .unwrap()
+            .join(PARTIAL_DIR)
+            .join(utils::sha256_hex(
+                format!("{}\n{}", small.path, small.etag).as_bytes(),
+            ));
+        std::fs::write(&partial, "wxyz").unwrap();
+        sync(&root, &store);
+        assert_eq!(read(&root, "small.txt"), "abc");
+        assert!(!partial.exists());
+
+        // The folder remote refuses to serve a file that changed since it
+        // was listed.
+        std::fs::write(remote_dir.join("small.txt"), "abcd").unwrap();
+        assert!(store.inner.download(small, 1).is_err());
+
+        for dir in [remote_dir, root] {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::io::{Cursor, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::{io_atomic, paths, utils};
+
+use super::remote::{Download, RemoteFile, RemoteStore};
+
+/// A plain directory as the remote, such as a mounted network share.
+pub struct FolderStore {
+    root: PathBuf,
+}
+
+impl FolderStore {
+    /// The folder must exist: an unmounted share must not look like an
+    /// empty remote.
+    pub fn new(root: PathBuf) -> Result<Self, String> {
+        if !root.is_dir() {
+            return Err(format!("sync folder {} does not exist", root.display()));
+        }
+        Ok(Self { root })
+    }
+
+    fn abs(&self, path: &str) -> Result<PathBuf, String> {
+        paths::join_under(&self.root, Path::new(path))
+    }
+}
+
+fn walk(root: &Path, dir: &Path, out: &mut Vec<RemoteFile>) -> Result<(), String> {
+    for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if utils::should_hide(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        let path = entry.path();
+        let meta = entry.metadata().map_err(|e| e.to_string())?;
+        if meta.is_dir() {
+            walk(root, &path, out)?;
+        } else if meta.is_file() {
+            let rel = path.strip_prefix(root).map_err(|e| e.to_string())?;
+            let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+            out.push(RemoteFile {
+                path: utils::to_slash(rel),
+                etag: utils::sha256_hex(&bytes),
+                size: meta.len(),
+            });
+        }
+    }
+    Ok(())
+}
+
+impl RemoteStore for FolderStore {
+    fn list(&self) -> Result<Vec<RemoteFile>, String> {
+        let mut out = Vec::new();
+        walk(&self.root, &self.root, &mut out)?;
+        Ok(out)
+    }
+
+    /// Like `If-Match`: fails when the file no longer has the listed etag,
+    /// so a resumed download never mixes two versions.
+    fn download(&self, file: &RemoteFile, offset: u64) -> Result<Download, String> {
+        let bytes = std::fs::read(self.abs(&file.path)?).map_err(|e| e.to_string())?;
+        if !file.etag.is_empty() && utils::sha256_hex(&bytes) != file.etag {
+            return Err(format!("{} changed on the remote during sync", file.path));
+        }
+        let mut body = Cursor::new(bytes);
+        body.seek(SeekFrom::Start(offset))
+            .map_err(|e| e.to_string())?;
+        Ok(Download {
+            resumed: true,
+            body: Box::new(body),
+        })
+    }
+
+    fn upload(&self, path: &str, bytes: &[u8]) -> Result<String, String> {
+        let abs = self.abs(path)?;
+        if let Some(parent) = abs.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        io_atomic::write_atomic(&abs, bytes).map_err(|e| e.to_string())?;
+        Ok(utils::sha256_hex(bytes))
+    }
+
+    fn delete(&self, path: &str) -> Result<(), String> {
+        match std::fs::remove_file(self.abs(path)?) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod commands;
+mod engine;
+mod folder;
+mod remote;
+mod s3;
+mod settings;
+mod state;
+mod webdav;
@@ -0,0 +1,105 @@
+use std::io::Read;
+
+/// A file on the remote, addressed by its path relative to the space root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFile {
+    pub path: String,
+    /// Opaque version tag from the remote; changes whenever the file does.
+    pub etag: String,
+    pub size: u64,
+}
+
+/// A download in progress: the body from `offset` when the remote resumed
+/// the transfer, or from the start when it did not.
+pub struct Download {
+    pub resumed: bool,
+    pub body: Box<dyn Read + Send>,
+}
+
+/// A place files are synced to. Paths are space-relative with `/`
+/// separators.
+pub trait RemoteStore: Send {
+    /// Every file under the remote root.
+    fn list(&self) -> Result<Vec<RemoteFile>, String>;
+    /// Reads `file`, from byte `offset` when the remote supports it.
+    fn download(&self, file: &RemoteFile, offset: u64) -> Result<Download, String>;
+    /// Writes `bytes` to `path` and returns the file's new etag.
+    fn upload(&self, path: &str, bytes: &[u8]) -> Result<String, String>;
+    fn delete(&self, path: &str) -> Result<(), String>;
+}
+
+/// Undoes `%XX` escapes in a URL path.
+pub(super) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                out.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        out.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Undoes the XML escapes servers use in listings.
+pub(super) fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// The text of each `<tag>` element (any namespace prefix) in `xml`.
+pub(super) fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let pattern = format!(r"(?s)<(?:[\w-]+:)?{tag}(?:\s[^>]*)?>(.*?)</(?:[\w-]+:)?{tag}>");
+    let Ok(regex) = regex::Regex::new(&pattern) else {
+        return Vec::new();
+    };
+    regex
+        .captures_iter(xml)
+        .filter_map(|captures| captures.get(1))
+        .map(|text| text.as_str())
+        .collect()
+}
+
+/// Whether a self-closing or empty `<tag/>` appears in `xml`.
+pub(super) fn xml_has_element(xml: &str, tag: &str) -> bool {
+    let pattern = format!(r"<(?:[\w-]+:)?{tag}[\s/>]");
+    regex::Regex::new(&pattern).is_ok_and(|regex| regex.is_match(xml))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_paths_and_reads_namespaced_xml() {
+        assert_eq!(
+            percent_decode("/dav/My%20Notes/caf%C3%A9.md"),
+            "/dav/My Notes/café.md"
+        );
+        assert_eq!(percent_decode("100%"), "100%");
+        let xml = r#"<d:multistatus xmlns:d="DAV:"><d:response><d:href>/a.md</d:href>
+            <d:getetag>&quot;abc&quot;</d:getetag><d:resourcetype/></d:response>
+            <D:response><D:href>/dir/</D:href><D:resourcetype><D:collection/></D:resourcetype></D:response>
+            </d:multistatus>"#;
+        let responses = xml_elements(xml, "response");
+        assert_eq!(responses.len(), 2);
+        assert_eq!(xml_elements(responses[0], "href"), vec!["/a.md"]);
+        assert_eq!(
+            xml_unescape(xml_elements(responses[0], "getetag")[0]),
+            "\"abc\""
+        );
+        assert!(!xml_has_element(responses[0], "collection"));
+        assert!(xml_has_element(responses[1], "collection"));
+    }
+}
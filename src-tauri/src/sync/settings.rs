@@ -0,0 +1,270 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{glyph_paths, io_atomic};
+
+use super::folder::FolderStore;
+use super::remote::RemoteStore;
+use super::s3::S3Store;
+use super::webdav::WebDavStore;
+
+const SYNC_SETTINGS_FILE: &str = "sync.json";
+const SYNC_SECRET_FILE: &str = "sync_secret.json";
+
+/// Where a space syncs to. The WebDAV password and S3 secret key are
+/// write-only: they are kept apart from `sync.json` and never serialized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncRemote {
+    Webdav {
+        url: String,
+        #[serde(default)]
+        username: String,
+        #[serde(default, skip_serializing)]
+        password: String,
+    },
+    S3 {
+        endpoint: String,
+        bucket: String,
+        #[serde(default)]
+        region: String,
+        #[serde(default)]
+        prefix: String,
+        access_key_id: String,
+        #[serde(default, skip_serializing)]
+        secret_access_key: String,
+    },
+    /// A local or mounted folder.
+    Folder { path: String },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncSettings {
+    /// `None` while sync is not set up.
+    #[serde(default)]
+    pub remote: Option<SyncRemote>,
+}
+
+/// The sync settings as the frontend sees them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SyncSettingsStatus {
+    pub remote: Option<SyncRemote>,
+    /// Whether a password or secret key is stored for the remote.
+    pub secret_configured: bool,
+}
+
+impl SyncRemote {
+    fn secret(&self) -> Option<&str> {
+        match self {
+            Self::Webdav { password, .. } => Some(password),
+            Self::S3 {
+                secret_access_key, ..
+            } => Some(secret_access_key),
+            Self::Folder { .. } => None,
+        }
+    }
+
+    fn secret_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Self::Webdav { password, .. } => Some(password),
+            Self::S3 {
+                secret_access_key, ..
+            } => Some(secret_access_key),
+            Self::Folder { .. } => None,
+        }
+    }
+
+    /// Fills in the stored secret unless one was given.
+    pub fn with_stored_secret(mut self, space_root: &Path) -> Result<Self, String> {
+        if let Some(secret) = self.secret_mut().filter(|secret| secret.is_empty()) {
+            *secret = read_sync_secret(space_root)?.unwrap_or_default();
+        }
+        Ok(self)
+    }
+
+    /// Where the files live, ignoring credentials.
+    pub fn location(&self) -> String {
+        match self {
+            Self::Webdav { url, .. } => url.trim_end_matches('/').to_string(),
+            Self::S3 {
+                endpoint,
+                bucket,
+                prefix,
+                ..
+            } => format!(
+                "{}/{bucket}/{}",
+                endpoint.trim_end_matches('/'),
+                prefix.trim_matches('/')
+            ),
+            Self::Folder { path } => path.clone(),
+        }
+    }
+
+    pub fn open(&self) -> Result<Box<dyn RemoteStore>, String> {
+        Ok(match self {
+            Self::Webdav {
+                url,
+                username,
+                password,
+            } => Box::new(WebDavStore::new(url, username, password)?),
+            Self::S3 {
+                endpoint,
+                bucket,
+                region,
+                prefix,
+                access_key_id,
+                secret_access_key,
+            } => Box::new(S3Store::new(
+                endpoint,
+                bucket,
+                region,
+                prefix,
+                access_key_id,
+                secret_access_key,
+            )?),
+            Self::Folder { path } => {
+                if path.trim().is_empty() {
+                    return Err("sync folder is required".to_string());
+                }
+                Box::new(FolderStore::new(PathBuf::from(path))?)
+            }
+        })
+    }
+}
+
+fn sync_settings_path(space_root: &Path) -> Result<PathBuf, String> {
+    Ok(glyph_paths::glyph_dir(space_root)?.join(SYNC_SETTINGS_FILE))
+}
+
+fn sync_secret_path(space_root: &Path) -> Result<PathBuf, String> {
+    Ok(glyph_paths::ensure_glyph_app_dir(space_root)?.join(SYNC_SECRET_FILE))
+}
+
+fn read_sync_secret(space_root: &Path) -> Result<Option<String>, String> {
+    let bytes = std::fs::read(sync_secret_path(space_root)?).unwrap_or_default();
+    Ok(serde_json::from_slice::<Option<String>>(&bytes)
+        .unwrap_or_default()
+        .filter(|secret| !secret.is_empty()))
+}
+
+fn write_sync_secret(space_root: &Path, secret: Option<&str>) -> Result<(), String> {
+    let bytes = serde_json::to_vec(&secret).map_err(|e| e.to_string())?;
+    io_atomic::write_atomic(&sync_secret_path(space_root)?, &bytes).map_err(|e| e.to_string())
+}
+
+/// Reads the sync settings. Secrets older versions wrote into `sync.json`
+/// move to the local secret file on the way.
+pub fn read_sync_settings(space_root: &Path) -> SyncSettings {
+    let Ok(path) = sync_settings_path(space_root) else {
+        return SyncSettings::default();
+    };
+    let bytes = std::fs::read(path).unwrap_or_default();
+    let mut settings: SyncSettings = serde_json::from_slice(&bytes).unwrap_or_default();
+    let legacy = settings
+        .remote
+        .as_ref()
+        .and_then(SyncRemote::secret)
+        .filter(|secret| !secret.is_empty())
+        .map(str::to_string);
+    if let Some(secret) = legacy {
+        if write_sync_secret(space_root, Some(&secret)).is_ok()
+            && write_sync_settings(space_root, &settings).is_ok()
+        {
+            if let Some(stored) = settings.remote.as_mut().and_then(SyncRemote::secret_mut) {
+                stored.clear();
+            }
+        }
+    }
+    settings
+}
+
+/// What the frontend may see of the sync settings.
+pub fn sync_settings_status(space_root: &Path) -> Result<SyncSettingsStatus, String> {
+    let remote = read_sync_settings(space_root).remote;
+    let secret_configured = match remote.as_ref().and_then(SyncRemote::secret) {
+        Some(secret) => !secret.is_empty() || read_sync_secret(space_root)?.is_some(),
+        None => false,
+    };
+    Ok(SyncSettingsStatus {
+        remote,
+        secret_configured,
+    })
+}
+
+/// Saves `settings`, storing a given secret apart from them. An empty
+/// secret keeps the stored one while the remote stays where it was.
+pub fn save_sync_settings(space_root: &Path, settings: &SyncSettings) -> Result<(), String> {
+    let previous = read_sync_settings(space_root)
+        .remote
+        .map(|remote| remote.location());
+    let location = settings.remote.as_ref().map(SyncRemote::location);
+    match settings.remote.as_ref().and_then(SyncRemote::secret) {
+        Some(secret) if !secret.is_empty() => write_sync_secret(space_root, Some(secret))?,
+        Some(_) if previous == location => {}
+        _ => write_sync_secret(space_root, None)?,
+    }
+    write_sync_settings(space_root, settings)
+}
+
+fn write_sync_settings(space_root: &Path, settings: &SyncSettings) -> Result<(), String> {
+    glyph_paths::ensure_glyph_dir(space_root)?;
+    let path = sync_settings_path(space_root)?;
+    let bytes = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+    io_atomic::write_atomic(&path, &bytes).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_secrets_out_of_the_settings_file() {
+        let root = std::env::temp_dir().join(format!("glyph-sync-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let remote = |password: &str| SyncRemote::Webdav {
+            url: "https://dav.example.com/notes".to_string(),
+            username: "ada".to_string(),
+            password: password.to_string(),
+        };
+
+        // Settings written by older versions hold the password in plain text.
+        glyph_paths::ensure_glyph_dir(&root).unwrap();
+        std::fs::write(
+            sync_settings_path(&root).unwrap(),
+            r#"{"remote":{"kind":"webdav","url":"https://dav.example.com/notes","username":"ada","password":"hunter2"}}"#,
+        )
+        .unwrap();
+        let status = sync_settings_status(&root).unwrap();
+        assert_eq!(status.remote, Some(remote("")));
+        assert!(status.secret_configured);
+        let file = std::fs::read_to_string(sync_settings_path(&root).unwrap()).unwrap();
+        assert!(!file.contains("hunter2"));
+        assert!(!serde_json::to_string(&status).unwrap().contains("hunter2"));
+
+        let stored = remote("").with_stored_secret(&root).unwrap();
+        assert_eq!(stored, remote("hunter2"));
+
+        // Saving without a password keeps the stored one.
+        let settings = SyncSettings {
+            remote: Some(remote("")),
+        };
+        save_sync_settings(&root, &settings).unwrap();
+        assert!(sync_settings_status(&root).unwrap().secret_configured);
+
+        // Another remote does not inherit it.
+        let settings = SyncSettings {
+            remote: Some(SyncRemote::Webdav {
+                url: "https://other.example.com".to_string(),
+                username: "ada".to_string(),
+                password: String::new(),
+            }),
+        };
+        save_sync_settings(&root, &settings).unwrap();
+        assert!(!sync_settings_status(&root).unwrap().secret_configured);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
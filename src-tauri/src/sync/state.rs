@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection};
+
+use crate::utils::now_ms;
+
+/// A file as it was when it was last synced: the local file's etag, mtime
+/// and size, and the remote's etag for the same content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncRecord {
+    pub local_etag: String,
+    pub local_mtime_ms: u64,
+    pub local_size: u64,
+    pub remote_etag: String,
+}
+
+pub fn load_records(conn: &Connection) -> Result<HashMap<String, SyncRecord>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path, local_etag, local_mtime_ms, local_size, remote_etag FROM sync_files")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                SyncRecord {
+                    local_etag: row.get(1)?,
+                    local_mtime_ms: row.get::<_, i64>(2)? as u64,
+                    local_size: row.get::<_, i64>(3)? as u64,
+                    remote_etag: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn save_record(conn: &Connection, path: &str, record: &SyncRecord) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO sync_files (path, local_etag, local_mtime_ms, local_size, remote_etag, synced_at_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(path) DO UPDATE SET
+           local_etag = excluded.local_etag,
+           local_mtime_ms = excluded.local_mtime_ms,
+           local_size = excluded.local_size,
+           remote_etag = excluded.remote_etag,
+           synced_at_ms = excluded.synced_at_ms",
+        params![
+            path,
+            record.local_etag,
+            record.local_mtime_ms as i64,
+            record.local_size as i64,
+            record.remote_etag,
+            now_ms() as i64
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Forgets every synced file, e.g. when the space moves to another remote.
+pub fn clear_records(conn: &Connection) -> Result<(), String> {
+    conn.execute("DELETE FROM sync_files", [])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn remove_record(conn: &Connection, path: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM sync_files WHERE path = ?", [path])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
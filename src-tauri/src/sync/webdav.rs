@@ -0,0 +1,321 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_RANGE, RANGE};
+use reqwest::{Method, StatusCode};
+use url::Url;
+
+use crate::utils;
+
+use super::remote::{
+    percent_decode, xml_elements, xml_has_element, xml_unescape, Download, RemoteFile, RemoteStore,
+};
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/><d:getetag/><d:getcontentlength/><d:getlastmodified/></d:prop></d:propfind>"#;
+
+pub struct WebDavStore {
+    client: Client,
+    base: Url,
+    username: String,
+    password: String,
+    /// Collections known to exist, so uploads create each folder once.
+    created_dirs: Mutex<HashSet<String>>,
+}
+
+fn method(name: &str) -> Method {
+    Method::from_bytes(name.as_bytes()).unwrap_or(Method::GET)
+}
+
+fn check(response: Response, action: &str) -> Result<Response, String> {
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(format!("WebDAV {action} failed: {}", response.status()))
+    }
+}
+
+/// A listed entry: its decoded path relative to the base, whether it is a
+/// folder, and its etag.
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    path: String,
+    is_dir: bool,
+    etag: String,
+    size: u64,
+}
+
+/// Parses a PROPFIND multistatus body. `base_path` is the decoded path of
+/// the sync root, with a trailing slash.
+fn parse_multistatus(xml: &str, base_path: &str) -> Vec<Entry> {
+    xml_elements(xml, "response")
+        .into_iter()
+        .filter_map(|response| {
+            let href = xml_unescape(xml_elements(response, "href").first()?.trim());
+            let href_path = match Url::parse(&href) {
+                Ok(url) => url.path().to_string(),
+                Err(_) => href,
+            };
+            let path = percent_decode(&href_path);
+            let rel = path.strip_prefix(base_path.trim_end_matches('/'))?;
+            let rel = rel.trim_matches('/').to_string();
+            let first = |tag| {
+                xml_elements(response, tag)
+                    .first()
+                    .map(|value| xml_unescape(value.trim()))
+            };
+            let size = first("getcontentlength")
+                .and_then(|len| len.parse().ok())
+                .unwrap_or(0);
+            let etag = first("getetag")
+                .filter(|etag| !etag.is_empty())
+                .or_else(|| first("getlastmodified").map(|modified| format!("{modified}-{size}")))
+                .unwrap_or_default();
+            Some(Entry {
+                path: rel,
+                is_dir: xml_has_element(response, "collection"),
+                etag,
+                size,
+            })
+        })
+        .collect()
+}
+
+impl WebDavStore {
+    pub fn new(url: &str, username: &str, password: &str) -> Result<Self, String> {
+        let mut base = Url::parse(url.trim()).map_err(|e| format!("invalid WebDAV url: {e}"))?;
+        if !matches!(base.scheme(), "http" | "https") {
+            return Err("WebDAV url must be http(s)".to_string());
+        }
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(20))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            client,
+            base,
+            username: username.to_string(),
+            password: password.to_string(),
+            created_dirs: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn url_for(&self, path: &str, is_dir: bool) -> Result<Url, String> {
+        let mut url = self.base.clone();
+        {
+            let mut segments = url
+                .path_segments_mut()
+                .map_err(|_| "invalid WebDAV url".to_string())?;
+            segments.pop_if_empty();
+            segments.extend(path.split('/').filter(|segment| !segment.is_empty()));
+            if is_dir {
+                segments.push("");
+            }
+        }
+        Ok(url)
+    }
+
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        if self.username.is_empty() {
+            request
+        } else {
+            request.basic_auth(&self.username, Some(&self.password))
+        }
+    }
+
+    fn propfind(&self, dir: &str) -> Result<Option<Vec<Entry>>, String> {
+        let response = self
+            .request(method("PROPFIND"), self.url_for(dir, true)?)
+            .header("Depth", "1")
+            .header(CONTENT_TYPE, "application/xml")
+            .body(PROPFIND_BODY)
+            .send()
+            .map_err(|e| e.to_string())?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body = check(response, "listing")?
+            .text()
+            .map_err(|e| e.to_string())?;
+        Ok(Some(parse_multistatus(
+            &body,
+            &percent_decode(self.base.path()),
+        )))
+    }
+
+    fn ensure_dirs(&self, path: &str) -> Result<(), String> {
+        let Some((parent, _)) = path.rsplit_once('/') else {
+            return Ok(());
+        };
+        let mut created = self
+            .created_dirs
+            .lock()
+            .map_err(|_| "WebDAV state poisoned".to_string())?;
+        let mut dir = String::new();
+        for segment in parent.split('/') {
+            if !dir.is_empty() {
+                dir.push('/');
+            }
+            dir.push_str(segment);
+            if created.contains(&dir) {
+                continue;
+            }
+            let response = self
+                .request(method("MKCOL"), self.url_for(&dir, true)?)
+                .send()
+                .map_err(|e| e.to_string())?;
+            // 405: the collection already exists.
+            if !response.status().is_success()
+                && response.status() != StatusCode::METHOD_NOT_ALLOWED
+            {
+                return Err(format!(
+                    "WebDAV folder creation failed: {}",
+                    response.status()
+                ));
+            }
+            created.insert(dir.clone());
+        }
+        Ok(())
+    }
+}
+
+impl RemoteStore for WebDavStore {
+    fn list(&self) -> Result<Vec<RemoteFile>, String> {
+        let mut files = Vec::new();
+        let mut pending = vec![String::new()];
+        while let Some(dir) = pending.pop() {
+            let Some(entries) = self.propfind(&dir)? else {
+                // A missing root would otherwise look like an empty remote.
+                if dir.is_empty() {
+                    return Err("WebDAV folder not found".to_string());
+                }
+                continue;
+            };
+            for entry in entries {
+                if entry.path == dir {
+                    continue;
+                }
+                let name = entry.path.rsplit('/').next().unwrap_or(&entry.path);
+                if utils::should_hide(name) {
+                    continue;
+                }
+                if entry.is_dir {
+                    pending.push(entry.path);
+                } else {
+                    files.push(RemoteFile {
+                        path: entry.path,
+                        etag: entry.etag,
+                        size: entry.size,
+                    });
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn download(&self, file: &RemoteFile, offset: u64) -> Result<Download, String> {
+        let mut request = self.request(Method::GET, self.url_for(&file.path, false)?);
+        if offset > 0 && !file.etag.is_empty() {
+            request = request
+                .header(RANGE, format!("bytes={offset}-"))
+                .header(IF_RANGE, &file.etag);
+        }
+        let response = check(request.send().map_err(|e| e.to_string())?, "download")?;
+        Ok(Download {
+            resumed: response.status() == StatusCode::PARTIAL_CONTENT,
+            body: Box::new(response),
+        })
+    }
+
+    fn upload(&self, path: &str, bytes: &[u8]) -> Result<String, String> {
+        self.ensure_dirs(path)?;
+        let url = self.url_for(path, false)?;
+        let response = check(
+            self.request(Method::PUT, url.clone())
+                .body(bytes.to_vec())
+                .send()
+                .map_err(|e| e.to_string())?,
+            "upload",
+        )?;
+        let etag = |response: &Response| {
+            response
+                .headers()
+                .get(ETAG)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        if let Some(etag) = etag(&response) {
+            return Ok(etag);
+        }
+        // Not every server returns the new etag from PUT.
+        let head = self
+            .request(Method::HEAD, url)
+            .send()
+            .map_err(|e| e.to_string())?;
+        Ok(etag(&head).unwrap_or_default())
+    }
+
+    fn delete(&self, path: &str) -> Result<(), String> {
+        let response = self
+            .request(Method::DELETE, self.url_for(path, false)?)
+            .send()
+            .map_err(|e| e.to_string())?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        check(response, "delete").map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_propfind_listings() {
+        let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response><d:href>/dav/My%20Space/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat></d:response>
+  <d:response><d:href>https://host/dav/My%20Space/notes/a%20b.md</d:href>
+    <d:propstat><d:prop><d:resourcetype/><d:getetag>"e1"</d:getetag>
+    <d:getcontentlength>12</d:getcontentlength></d:prop></d:propstat></d:response>
+  <d:response><d:href>/dav/My%20Space/notes/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat></d:response>
+</d:multistatus>"#;
+        assert_eq!(
+            parse_multistatus(xml, "/dav/My Space/"),
+            vec![
+                Entry {
+                    path: String::new(),
+                    is_dir: true,
+                    etag: String::new(),
+                    size: 0,
+                },
+                Entry {
+                    path: "notes/a b.md".to_string(),
+                    is_dir: false,
+                    etag: "\"e1\"".to_string(),
+                    size: 12,
+                },
+                Entry {
+                    path: "notes".to_string(),
+                    is_dir: true,
+                    etag: String::new(),
+                    size: 0,
+                },
+            ]
+        );
+        let store = WebDavStore::new("https://host/dav/My Space", "", "").unwrap();
+        assert_eq!(
+            store.url_for("notes/a b.md", false).unwrap().as_str(),
+            "https://host/dav/My%20Space/notes/a%20b.md"
+        );
+    }
+}
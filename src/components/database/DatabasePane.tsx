@@ -54,8 +54,18 @@ export function DatabasePane({
 	const [columnsOpen, setColumnsOpen] = useState(false);
 	const [sourceOpen, setSourceOpen] = useState(false);
 	const [actionError, setActionError] = useState("");
-	const { data, loading, error, reload, saveConfig, updateCell, createRow } =
-		useDatabaseNote(relPath, noteKind === "database");
+	const {
+		data,
+		loading,
+		error,
+		reload,
+		saveConfig,
+		updateCell,
+		createRow,
+		setActiveView,
+		createView,
+		deleteView,
+	} = useDatabaseNote(relPath, noteKind === "database");
 	const currentConfig = data?.config;
 	const normalizedRelPath = normalizeRelPath(relPath);
 
@@ -149,6 +159,10 @@ export function DatabasePane({
 				}
 				case "tag":
 				case "search":
+				case "property":
+				case "link":
+				case "tasks_due":
+				case "composite":
 					return true;
 			}
 		},
@@ -203,6 +217,15 @@ export function DatabasePane({
 		[saveConfig],
 	);
 
+	const runViewAction = useCallback(async (action: Promise<void>) => {
+		try {
+			setActionError("");
+			await action;
+		} catch (error) {
+			setActionError(extractErrorMessage(error));
+		}
+	}, []);
+
 	const handleDatabaseViewChange = useCallback(
 		(nextView: "table" | "board") => {
 			startTransition(() => setDatabaseView(nextView));
@@ -320,6 +343,17 @@ export function DatabasePane({
 			{currentConfig ? (
 				<>
 					<DatabaseToolbar
+						views={currentConfig.views ?? []}
+						activeViewId={currentConfig.active_view ?? null}
+						onActiveViewChange={(viewId) =>
+							void runViewAction(setActiveView(viewId))
+						}
+						onCreateView={() =>
+							void runViewAction(
+								createView(`View ${(currentConfig.views?.length ?? 0) + 1}`),
+							)
+						}
+						onDeleteView={(viewId) => void runViewAction(deleteView(viewId))}
 						databaseView={databaseView}
 						groupColumns={groupColumns}
 						groupColumnId={currentConfig.view.board_group_by ?? null}
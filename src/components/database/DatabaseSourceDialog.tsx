@@ -407,6 +407,27 @@ export function DatabaseSourceDialog({
 								}
 							/>
 						</div>
+						<div className="settingsField">
+							<div>
+								<label className="settingsLabel" htmlFor="databaseRowTemplate">
+									Row Template
+								</label>
+							</div>
+							<Input
+								id="databaseRowTemplate"
+								value={config.new_note.template ?? ""}
+								placeholder="Templates/Meeting.md"
+								onChange={(event) =>
+									void onChangeConfig({
+										...config,
+										new_note: {
+											...config.new_note,
+											template: event.target.value || null,
+										},
+									})
+								}
+							/>
+						</div>
 					</section>
 				</div>
 				<div className="databaseDialogActions">
@@ -1,10 +1,15 @@
 import { EditTableIcon, FilterEditIcon } from "@hugeicons/core-free-icons";
 import { HugeiconsIcon } from "@hugeicons/react";
-import type { DatabaseColumn } from "../../lib/database/types";
-import { Kanban, Plus, RefreshCw, Table } from "../Icons";
+import type { DatabaseColumn, DatabaseView } from "../../lib/database/types";
+import { Kanban, Plus, RefreshCw, Table, Trash2 } from "../Icons";
 import { Button } from "../ui/shadcn/button";
 
 interface DatabaseToolbarProps {
+	views: DatabaseView[];
+	activeViewId: string | null;
+	onActiveViewChange: (viewId: string) => void;
+	onCreateView: () => void;
+	onDeleteView: (viewId: string) => void;
 	databaseView: "table" | "board";
 	groupColumns: DatabaseColumn[];
 	groupColumnId: string | null;
@@ -17,6 +22,11 @@ interface DatabaseToolbarProps {
 }
 
 export function DatabaseToolbar({
+	views,
+	activeViewId,
+	onActiveViewChange,
+	onCreateView,
+	onDeleteView,
 	databaseView,
 	groupColumns,
 	groupColumnId,
@@ -30,6 +40,46 @@ export function DatabaseToolbar({
 	return (
 		<div className="databaseToolbar">
 			<div className="databaseToolbarPrimary">
+				{views.length > 0 ? (
+					<label className="databaseToolbarGroupBy">
+						<span className="databaseToolbarGroupByLabel">View</span>
+						<select
+							className="databaseToolbarGroupBySelect"
+							value={activeViewId ?? ""}
+							onChange={(event) => onActiveViewChange(event.target.value)}
+						>
+							{views.map((view) => (
+								<option key={view.id} value={view.id}>
+									{view.name}
+								</option>
+							))}
+						</select>
+					</label>
+				) : null}
+				<Button
+					type="button"
+					variant="ghost"
+					size="icon-sm"
+					className="databaseToolbarChip"
+					onClick={onCreateView}
+					title="New view"
+					aria-label="New view"
+				>
+					<Plus size={14} />
+				</Button>
+				{views.length > 1 && activeViewId ? (
+					<Button
+						type="button"
+						variant="ghost"
+						size="icon-sm"
+						className="databaseToolbarChip"
+						onClick={() => onDeleteView(activeViewId)}
+						title="Delete view"
+						aria-label="Delete view"
+					>
+						<Trash2 size={14} />
+					</Button>
+				) : null}
 				<div
 					className="databaseModeSwitch"
 					role="tablist"
@@ -107,6 +107,7 @@ export function NotePropertiesPanel({
 		pruneRowScopedState(nextRowIds);
 		void invoke("note_frontmatter_render_properties", {
 			properties: nextProperties,
+			frontmatter,
 		})
 			.then((nextFrontmatter) => {
 				if (requestId !== renderRequestIdRef.current) return;
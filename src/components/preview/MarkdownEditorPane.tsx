@@ -210,37 +210,34 @@ export function MarkdownEditorPane({
 					path,
 					text: nextText,
 					base_mtime_ms: mtimeRef.current,
+					base_text: savedTextRef.current,
 				});
-				applySaveState(nextText, result.mtime_ms);
-				return true;
-			} catch (e) {
-				const message = extractErrorMessage(e);
-				const isConflict = message.includes(
-					"conflict: on-disk file changed since it was opened",
-				);
-				if (!isConflict) {
-					setError(message);
+				if (result.status === "conflict") {
+					const count =
+						result.chunks?.filter((chunk) => chunk.kind === "conflict")
+							.length ?? 0;
+					setError(
+						`Not saved: ${count} change${count === 1 ? "" : "s"} on disk conflict with your edits`,
+					);
 					return false;
 				}
-
-				// Conflict recovery: refresh latest mtime/content and retry save once.
-				try {
-					const latest = await invoke("space_read_text", { path });
-					if (latest.text === nextText) {
-						applySaveState(nextText, latest.mtime_ms);
+				if (result.status === "merged" && result.text !== null) {
+					const merged = result.text;
+					// With further typing since this save started, keep the old mtime
+					// so the next save merges those edits into the merged file too.
+					if (path === relPath && textRef.current !== nextText) {
+						applySaveState(nextText, mtimeRef.current ?? result.mtime_ms);
 						return true;
 					}
-					const retry = await invoke("space_write_text", {
-						path,
-						text: nextText,
-						base_mtime_ms: latest.mtime_ms,
-					});
-					applySaveState(nextText, retry.mtime_ms);
+					if (path === relPath) setText(merged);
+					applySaveState(merged, result.mtime_ms);
 					return true;
-				} catch (retryError) {
-					setError(extractErrorMessage(retryError));
-					return false;
 				}
+				applySaveState(nextText, result.mtime_ms);
+				return true;
+			} catch (e) {
+				setError(extractErrorMessage(e));
+				return false;
 			}
 		},
 		[relPath],
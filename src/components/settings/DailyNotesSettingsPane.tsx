@@ -5,6 +5,20 @@ import { FolderOpen } from "../Icons/NavigationIcons";
 import { Button } from "../ui/shadcn/button";
 import { SettingsRow, SettingsSection } from "./SettingsScaffold";
 
+/** Keeps the space's periodic notes settings, which backend features read
+ * to find daily notes, on the chosen folder. */
+async function syncPeriodicDailyFolder(folder: string | null): Promise<void> {
+	if (folder === null) return;
+	try {
+		const settings = await invoke("periodic_settings_get");
+		await invoke("periodic_settings_set", {
+			settings: { ...settings, daily: { ...settings.daily, folder } },
+		});
+	} catch {
+		// best-effort: opening a daily note passes the folder explicitly
+	}
+}
+
 export function DailyNotesSettingsPane() {
 	const [currentFolder, setCurrentFolder] = useState<string | null>(null);
 	const [isLoading, setIsLoading] = useState(true);
@@ -52,6 +66,7 @@ export function DailyNotesSettingsPane() {
 					.slice(normSpace.length)
 					.replace(/^\/+/, "");
 				await setDailyNotesFolder(relativePath || null);
+				await syncPeriodicDailyFolder(relativePath || null);
 				setCurrentFolder(relativePath || null);
 			}
 		} catch (err) {
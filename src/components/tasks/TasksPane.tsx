@@ -134,6 +134,7 @@ export function TasksPane({ onOpenFile, onClosePane }: TasksPaneProps) {
 				await invoke("task_set_checked", {
 					task_id: task.task_id,
 					checked,
+					today: todayIsoDateLocal(),
 				});
 				await loadTasks();
 			} catch (e) {
@@ -14,6 +14,8 @@ import {
 	type DatabaseRow,
 	invoke,
 } from "../../lib/tauri";
+import { useTauriEvent } from "../../lib/tauriEvents";
+import { todayIsoDateLocal } from "../../lib/tasks";
 
 function applyCellValueToRow(
 	row: DatabaseRow,
@@ -66,6 +68,9 @@ interface UseDatabaseNoteResult {
 		value: DatabaseCellValue,
 	) => Promise<void>;
 	createRow: (title?: string) => Promise<string | null>;
+	setActiveView: (viewId: string) => Promise<void>;
+	createView: (name: string) => Promise<void>;
+	deleteView: (viewId: string) => Promise<void>;
 }
 
 export function useDatabaseNote(
@@ -83,7 +88,11 @@ export function useDatabaseNote(
 		setLoading(true);
 		setError("");
 		try {
-			const next = await invoke("database_load", { path: relPath, limit: 500 });
+			const next = await invoke("database_load", {
+				path: relPath,
+				limit: 500,
+				today: todayIsoDateLocal(),
+			});
 			if (requestVersionRef.current !== version) return;
 			setData(next);
 		} catch (error) {
@@ -107,6 +116,49 @@ export function useDatabaseNote(
 		void reload();
 	}, [enabled, reload]);
 
+	useEffect(() => {
+		if (!enabled) return;
+		const watching = invoke("database_watch", {
+			path: relPath,
+			today: todayIsoDateLocal(),
+		}).catch(() => undefined);
+		return () => {
+			void watching.then(() =>
+				invoke("database_unwatch", { path: relPath }).catch(() => undefined),
+			);
+		};
+	}, [enabled, relPath]);
+
+	useTauriEvent("database:rows_changed", (payload) => {
+		if (payload.database_path !== relPath) return;
+		const removed = new Set(payload.removed);
+		const updated = new Map(payload.updated.map((row) => [row.note_path, row]));
+		startTransition(() => {
+			setData((current) => {
+				if (!current) return current;
+				const known = new Set(current.rows.map((row) => row.note_path));
+				const rows = current.rows
+					.filter((row) => !removed.has(row.note_path))
+					.map((row) => {
+						const next = updated.get(row.note_path);
+						// Pushed rows carry no computed values; keep the loaded ones.
+						return next
+							? {
+									...next,
+									computed: row.computed,
+									relations: row.relations,
+									cover: row.cover,
+								}
+							: row;
+					});
+				const inserted = payload.inserted.filter(
+					(row) => !known.has(row.note_path),
+				);
+				return { ...current, rows: [...inserted, ...rows] };
+			});
+		});
+	});
+
 	const saveConfig = useCallback(
 		async (config: DatabaseConfig) => {
 			requestVersionRef.current += 1;
@@ -152,6 +204,7 @@ export function useDatabaseNote(
 					note_path: notePath,
 					column,
 					value,
+					database_path: relPath,
 				});
 				startTransition(() => {
 					setData((current) => {
@@ -170,7 +223,7 @@ export function useDatabaseNote(
 			}
 			void reload();
 		},
-		[reload],
+		[relPath, reload],
 	);
 
 	const createRow = useCallback(
@@ -179,6 +232,7 @@ export function useDatabaseNote(
 			const created = await invoke("database_create_row", {
 				database_path: relPath,
 				title: title ?? null,
+				today: todayIsoDateLocal(),
 			});
 			startTransition(() => {
 				setData((current) => {
@@ -195,6 +249,42 @@ export function useDatabaseNote(
 		[relPath, reload],
 	);
 
+	const applyViewChange = useCallback(
+		async (change: Promise<DatabaseConfig>) => {
+			requestVersionRef.current += 1;
+			const config = await change;
+			startTransition(() => {
+				setData((current) => (current ? { ...current, config } : current));
+			});
+			void reload();
+		},
+		[reload],
+	);
+
+	const setActiveView = useCallback(
+		(viewId: string) =>
+			applyViewChange(
+				invoke("database_view_set_active", { path: relPath, view_id: viewId }),
+			),
+		[applyViewChange, relPath],
+	);
+
+	const createView = useCallback(
+		(name: string) =>
+			applyViewChange(
+				invoke("database_view_create", { path: relPath, name }),
+			),
+		[applyViewChange, relPath],
+	);
+
+	const deleteView = useCallback(
+		(viewId: string) =>
+			applyViewChange(
+				invoke("database_view_delete", { path: relPath, view_id: viewId }),
+			),
+		[applyViewChange, relPath],
+	);
+
 	return {
 		data,
 		loading,
@@ -203,5 +293,8 @@ export function useDatabaseNote(
 		saveConfig,
 		updateCell,
 		createRow,
+		setActiveView,
+		createView,
+		deleteView,
 	};
 }
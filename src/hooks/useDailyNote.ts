@@ -1,9 +1,5 @@
 import { useCallback, useRef, useState } from "react";
-import {
-	getDailyNoteContent,
-	getDailyNotePath,
-	getTodayDateString,
-} from "../lib/dailyNotes";
+import { getTodayDateString } from "../lib/dailyNotes";
 import { invoke } from "../lib/tauri";
 
 export interface UseDailyNoteOptions {
@@ -27,15 +23,13 @@ export function useDailyNote(options: UseDailyNoteOptions): UseDailyNoteReturn {
 			lockRef.current = true;
 			setIsCreating(true);
 			try {
-				const todayDate = getTodayDateString();
-				const notePath = getDailyNotePath(folder, todayDate);
-				const content = getDailyNoteContent(todayDate);
-				await invoke("space_open_or_create_text", {
-					path: notePath,
-					text: content,
+				const note = await invoke("periodic_note_open", {
+					period: "daily",
+					today: getTodayDateString(),
+					folder,
 				});
-				await onOpenFile(notePath);
-				return notePath;
+				await onOpenFile(note.path);
+				return note.path;
 			} catch (err) {
 				const message =
 					err instanceof Error ? err.message : "Failed to open daily note";
@@ -77,6 +77,9 @@ const BUILT_IN_DATABASE_COLUMN_ICONS: Record<
 	path: "route",
 	created: "calendar",
 	updated: "clock",
+	formula: "code",
+	relation: "link",
+	rollup: "chart",
 };
 
 const PROPERTY_KIND_DATABASE_COLUMN_ICONS: Record<string, string> = {
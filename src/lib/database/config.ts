@@ -161,11 +161,22 @@ export function createStarterDatabaseMarkdown(
 		"    new_note:",
 		`      folder: ${yamlString(config.new_note.folder)}`,
 		`      title_prefix: ${yamlString(config.new_note.title_prefix)}`,
+		...(config.new_note.template
+			? [`      template: ${yamlString(config.new_note.template)}`]
+			: []),
 		"    view:",
 		`      layout: ${config.view.layout}`,
 		...(config.view.board_group_by
 			? [`      board_group_by: ${yamlString(config.view.board_group_by)}`]
 			: []),
+		...(config.view.calendar_date_column
+			? [
+					`      calendar_date_column: ${yamlString(config.view.calendar_date_column)}`,
+				]
+			: []),
+		...(config.view.calendar_range
+			? [`      calendar_range: ${config.view.calendar_range}`]
+			: []),
 		"    columns:",
 		columnsYaml,
 		`    sorts:${sortsYaml}`,
@@ -255,6 +266,22 @@ export function databaseCellValueFromRow(
 					value_list: [],
 				}
 			);
+		case "relation":
+			return {
+				kind: "relation",
+				value_list: (row.relations?.[column.id] ?? []).map(
+					(target) => target.title,
+				),
+			};
+		case "formula":
+		case "rollup":
+			return (
+				row.computed?.[column.id] ?? {
+					kind: "empty",
+					value_text: null,
+					value_list: [],
+				}
+			);
 	}
 }
 
@@ -354,5 +381,15 @@ export function sourceSummary(config: DatabaseConfig): string {
 			return `Tag: ${config.source.value}`;
 		case "search":
 			return `Search: ${config.source.value}`;
+		case "property":
+			return `Property: ${config.source.property ?? ""} = ${config.source.value}`;
+		case "link":
+			return `Links to: ${config.source.value}`;
+		case "tasks_due":
+			return config.source.value
+				? `Tasks due: ${config.source.value}`
+				: "Tasks with due dates";
+		case "composite":
+			return `${config.source.operator === "intersection" ? "All" : "Any"} of ${config.source.sources?.length ?? 0} sources`;
 	}
 }
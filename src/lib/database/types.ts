@@ -10,5 +10,7 @@ export type {
 	DatabaseRow,
 	DatabaseSort,
 	DatabaseSource,
+	DatabaseView,
+	DatabaseViewColumn,
 	DatabaseViewState,
 } from "../tauri";
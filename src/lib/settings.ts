@@ -113,6 +113,9 @@ async function emitSettingsUpdated(payload: {
 	dailyNotes?: {
 		folder?: string | null;
 	};
+	templates?: {
+		folder?: string | null;
+	};
 	tasks?: {
 		source?: TaskSourceSetting;
 	};
@@ -147,6 +150,9 @@ interface AppSettings {
 	dailyNotes: {
 		folder: string | null;
 	};
+	templates: {
+		folder: string | null;
+	};
 	tasks: {
 		source: TaskSourceSetting;
 	};
@@ -165,6 +171,7 @@ const KEYS = {
 	monoFontFamily: "ui.monoFontFamily",
 	fontSize: "ui.fontSize",
 	dailyNotesFolder: "dailyNotes.folder",
+	templatesFolder: "templates.folder",
 	taskSource: "tasks.source",
 } as const;
 
@@ -246,6 +253,7 @@ export async function loadSettings(): Promise<AppSettings> {
 		rawMonoFontFamily,
 		rawFontSize,
 		dailyNotesFolderRaw,
+		templatesFolderRaw,
 		taskSourceRaw,
 	] = await Promise.all([
 		store.get<string | null>(KEYS.currentSpacePath),
@@ -260,6 +268,7 @@ export async function loadSettings(): Promise<AppSettings> {
 		store.get<unknown>(KEYS.monoFontFamily),
 		store.get<unknown>(KEYS.fontSize),
 		store.get<string | null>(KEYS.dailyNotesFolder),
+		store.get<string | null>(KEYS.templatesFolder),
 		store.get<unknown>(KEYS.taskSource),
 	]);
 	const currentSpacePath = currentSpacePathRaw ?? null;
@@ -298,6 +307,9 @@ export async function loadSettings(): Promise<AppSettings> {
 		dailyNotes: {
 			folder: dailyNotesFolder,
 		},
+		templates: {
+			folder: templatesFolderRaw ?? null,
+		},
 		tasks: {
 			source: taskSource,
 		},
@@ -407,6 +419,24 @@ export async function setDailyNotesFolder(
 	void emitSettingsUpdated({ dailyNotes: { folder } });
 }
 
+export async function getTemplatesFolder(): Promise<string | null> {
+	const store = await getStore();
+	return (await store.get<string | null>(KEYS.templatesFolder)) ?? null;
+}
+
+export async function setTemplatesFolder(
+	folder: string | null,
+): Promise<void> {
+	const store = await getStore();
+	if (folder === null) {
+		await store.delete(KEYS.templatesFolder);
+	} else {
+		await store.set(KEYS.templatesFolder, folder);
+	}
+	await store.save();
+	void emitSettingsUpdated({ templates: { folder } });
+}
+
 export async function setTaskSource(source: TaskSourceSetting): Promise<void> {
 	const store = await getStore();
 	const next = normalizeTaskSourceSetting(source);
@@ -35,11 +35,20 @@ export interface SpaceInfo {
 	schema_version: number;
 }
 
+export type GitFileStatus =
+	| "modified"
+	| "added"
+	| "deleted"
+	| "renamed"
+	| "untracked"
+	| "conflicted";
+
 export interface FsEntry {
 	name: string;
 	rel_path: string;
 	kind: "dir" | "file";
 	is_markdown: boolean;
+	git_status?: GitFileStatus | null;
 }
 
 export interface RecentEntry {
@@ -56,9 +65,16 @@ export interface TextFileDoc {
 	mtime_ms: number;
 }
 
+export type MergeChunk =
+	| { kind: "resolved"; text: string }
+	| { kind: "conflict"; base: string; disk: string; editor: string };
+
 export interface TextFileWriteResult {
 	etag: string;
 	mtime_ms: number;
+	status: "written" | "merged" | "conflict";
+	text: string | null;
+	chunks: MergeChunk[] | null;
 }
 
 export interface OpenOrCreateTextResult {
@@ -113,6 +129,42 @@ export interface NoteWriteResult {
 	mtime_ms: number;
 }
 
+export interface TemplatePrompt {
+	name: string;
+	default: string | null;
+}
+
+export interface TemplateInfo {
+	path: string;
+	name: string;
+	prompts: TemplatePrompt[];
+}
+
+export interface TemplateApplyResult {
+	path: string;
+	markdown: string;
+	cursor: number | null;
+}
+
+export type Period = "daily" | "weekly" | "monthly" | "quarterly";
+
+export interface PeriodSettings {
+	folder: string;
+	format: string;
+	template?: string | null;
+}
+
+export type PeriodicNotesSettings = Record<Period, PeriodSettings>;
+
+export interface PeriodicNote {
+	period: Period;
+	start: string;
+	end: string;
+	path: string;
+	exists: boolean;
+	created: boolean;
+}
+
 export interface AttachmentResult {
 	asset_rel_path: string;
 	markdown: string;
@@ -126,31 +178,129 @@ export interface NoteProperty {
 	value_list: string[];
 }
 
+export interface PropertyOption {
+	value: string;
+	color?: string | null;
+}
+
+export type PropertyDefinitionKind =
+	| "text"
+	| "number"
+	| "checkbox"
+	| "date"
+	| "datetime"
+	| "url"
+	| "select"
+	| "multi_select"
+	| "list"
+	| "tags";
+
+export interface PropertyDefinition {
+	key: string;
+	kind: PropertyDefinitionKind;
+	options?: PropertyOption[];
+	required?: boolean;
+	number_format?: "integer" | "decimal" | "percent" | "currency" | null;
+	default?: string | null;
+}
+
+export interface PropertySchema {
+	properties: PropertyDefinition[];
+}
+
+export type PropertyErrorCode =
+	| "required"
+	| "invalid_option"
+	| "invalid_number"
+	| "invalid_checkbox"
+	| "invalid_date"
+	| "invalid_url"
+	| "invalid_value"
+	| "error";
+
+/** Error payload of commands that validate property values. */
+export interface PropertyError {
+	code: PropertyErrorCode;
+	key?: string;
+	note_path?: string;
+	message: string;
+	allowed?: string[];
+}
+
 export interface DatabaseSource {
-	kind: "folder" | "tag" | "search";
+	kind:
+		| "folder"
+		| "tag"
+		| "search"
+		| "property"
+		| "link"
+		| "tasks_due"
+		| "composite";
 	value: string;
 	recursive: boolean;
+	property?: string | null;
+	folder?: string | null;
+	operator?: "union" | "intersection" | null;
+	sources?: DatabaseSource[];
 }
 
 export interface DatabaseNewNoteConfig {
 	folder: string;
 	title_prefix: string;
+	template?: string | null;
 }
 
 export interface DatabaseViewState {
-	layout: "table" | "board";
+	layout: "table" | "board" | "calendar" | "gallery";
 	board_group_by?: string | null;
+	calendar_date_column?: string | null;
+	calendar_range?: "month" | "week" | null;
 }
 
 export interface DatabaseColumn {
 	id: string;
-	type: "title" | "tags" | "path" | "created" | "updated" | "property";
+	type:
+		| "title"
+		| "tags"
+		| "path"
+		| "created"
+		| "updated"
+		| "property"
+		| "formula"
+		| "relation"
+		| "rollup";
 	label: string;
 	icon?: string | null;
 	width?: number | null;
 	visible: boolean;
 	property_key?: string | null;
 	property_kind?: string | null;
+	formula?: string | null;
+	relation?: DatabaseRelationConfig | null;
+	rollup?: DatabaseRollupConfig | null;
+}
+
+export interface DatabaseRelationConfig {
+	reverse?: boolean;
+	database?: string | null;
+}
+
+export interface DatabaseRollupConfig {
+	relation_column: string;
+	property?: string | null;
+	function:
+		| "count"
+		| "count_values"
+		| "sum"
+		| "average"
+		| "min"
+		| "max"
+		| "percent_checked";
+}
+
+export interface DatabaseRelationTarget {
+	note_path: string;
+	title: string;
 }
 
 export interface DatabaseSort {
@@ -167,7 +317,12 @@ export interface DatabaseFilter {
 		| "is_not_empty"
 		| "is_true"
 		| "is_false"
-		| "tags_contains";
+		| "tags_contains"
+		| "in_list"
+		| "before"
+		| "after"
+		| "on_or_before"
+		| "on_or_after";
 	value_text?: string | null;
 	value_bool?: boolean | null;
 	value_list: string[];
@@ -180,6 +335,23 @@ export interface DatabaseConfig {
 	columns: DatabaseColumn[];
 	sorts: DatabaseSort[];
 	filters: DatabaseFilter[];
+	properties?: PropertyDefinition[];
+	views?: DatabaseView[];
+	active_view?: string | null;
+}
+
+export interface DatabaseViewColumn {
+	id: string;
+	visible: boolean;
+	width?: number | null;
+}
+
+export interface DatabaseView extends DatabaseViewState {
+	id: string;
+	name: string;
+	columns: DatabaseViewColumn[];
+	sorts: DatabaseSort[];
+	filters: DatabaseFilter[];
 }
 
 export interface DatabaseCellValue {
@@ -197,6 +369,46 @@ export interface DatabaseRow {
 	preview?: string;
 	tags: string[];
 	properties: Record<string, DatabaseCellValue>;
+	computed?: Record<string, DatabaseCellValue>;
+	relations?: Record<string, DatabaseRelationTarget[]>;
+	cover?: string | null;
+}
+
+export interface DatabaseGroupAggregate {
+	column_id: string;
+	sum: number;
+	average: number | null;
+	count: number;
+}
+
+export interface DatabaseGroup {
+	key: string;
+	label: string;
+	note_paths: string[];
+	count: number;
+	aggregates: DatabaseGroupAggregate[];
+}
+
+export interface DatabaseGroupResult {
+	group_by: string;
+	groups: DatabaseGroup[];
+	total_count: number;
+	truncated: boolean;
+}
+
+export interface DatabaseCalendarDay {
+	date: string;
+	note_paths: string[];
+}
+
+export interface DatabaseCalendarResult {
+	date_column: string;
+	range: "month" | "week";
+	start: string;
+	end: string;
+	days: DatabaseCalendarDay[];
+	undated_count: number;
+	truncated: boolean;
 }
 
 export interface DatabasePropertyOption {
@@ -211,6 +423,10 @@ export interface DatabaseLoadResult {
 	available_properties: DatabasePropertyOption[];
 	truncated: boolean;
 	total_loaded: number;
+	total_count: number;
+	next_cursor?: string | null;
+	/** Computed-column filters or sorts saw only part of a large source. */
+	scan_truncated: boolean;
 }
 
 export interface DatabaseCreateRowResult {
@@ -218,6 +434,62 @@ export interface DatabaseCreateRowResult {
 	row: DatabaseRow;
 }
 
+export interface DatabaseCsvImportResult {
+	note_paths: string[];
+	columns_added: string[];
+	config: DatabaseConfig;
+}
+
+export interface DatabaseCsvExportResult {
+	output_path: string;
+	row_count: number;
+	truncated: boolean;
+}
+
+export interface DatabaseUndoNote {
+	note_path: string;
+	/** The note as stored: its encrypted envelope when `encrypted`. */
+	markdown: string;
+	encrypted?: boolean;
+}
+
+export interface DatabaseUndoMove {
+	from: string;
+	to: string;
+}
+
+export interface DatabaseUndoPayload {
+	restore: DatabaseUndoNote[];
+	remove: string[];
+	moves: DatabaseUndoMove[];
+}
+
+export interface DatabaseBulkResult {
+	note_paths: string[];
+	rows: DatabaseRow[];
+	/** Reverses what was done, including when the operation stopped early. */
+	undo: DatabaseUndoPayload;
+	/** Why the operation stopped before every note was handled. */
+	errors: string[];
+}
+
+export type DatabaseDeleteMode = "trash" | "archive";
+
+export interface DatabaseRowsChangedEvent {
+	database_path: string;
+	inserted: DatabaseRow[];
+	updated: DatabaseRow[];
+	removed: string[];
+}
+
+export interface DatabaseNormalizeResult {
+	note_paths: string[];
+	issues: PropertyError[];
+	undo: DatabaseUndoPayload;
+	truncated: boolean;
+	errors: string[];
+}
+
 export interface SearchResult {
 	id: string;
 	title: string;
@@ -231,6 +503,10 @@ export interface SearchAdvancedRequest {
 	title_only?: boolean;
 	tag_only?: boolean;
 	limit?: number | null;
+	due_from?: string | null;
+	due_to?: string | null;
+	updated_from?: string | null;
+	updated_to?: string | null;
 }
 
 export interface BacklinkItem {
@@ -248,6 +524,138 @@ export interface IndexRebuildResult {
 	indexed: number;
 }
 
+export interface NoteIdentity {
+	uid: string;
+	path: string;
+	previous_paths: string[];
+}
+
+export type NoteVersionSource = "save" | "ai" | "restore" | "disk" | "sync";
+
+export interface NoteVersion {
+	id: string;
+	hash: string;
+	path: string;
+	saved_at_ms: number;
+	size: number;
+	source: NoteVersionSource;
+}
+
+export type DiffKind = "equal" | "delete" | "insert";
+
+export interface DiffSpan {
+	kind: DiffKind;
+	text: string;
+}
+
+export interface DiffLine {
+	kind: DiffKind;
+	text: string;
+	spans: DiffSpan[] | null;
+}
+
+export interface NoteHistoryDiff {
+	from: NoteVersion;
+	to: NoteVersion | null;
+	lines: DiffLine[];
+}
+
+export interface NoteHistoryRestoreResult {
+	text: string;
+	etag: string;
+	mtime_ms: number;
+}
+
+export interface GitStatusEntry {
+	path: string;
+	status: GitFileStatus;
+}
+
+export interface GitRepoInfo {
+	is_repo: boolean;
+	branch: string | null;
+	head: string | null;
+	upstream: string | null;
+	ahead: number;
+	behind: number;
+	changed_files: number;
+	merging: boolean;
+}
+
+export interface GitSettings {
+	auto_commit: boolean;
+	auto_commit_delay_secs: number;
+	remote: string;
+}
+
+export interface GitCommit {
+	hash: string;
+	short_hash: string;
+	author: string;
+	time_ms: number;
+	subject: string;
+	path: string | null;
+}
+
+export interface GitFileDiff {
+	commit: GitCommit;
+	path: string;
+	lines: DiffLine[];
+}
+
+/** Secrets are write-only: they are never returned, and an empty one keeps the stored secret. */
+export type SyncRemote =
+	| { kind: "webdav"; url: string; username: string; password?: string }
+	| {
+			kind: "s3";
+			endpoint: string;
+			bucket: string;
+			region: string;
+			prefix: string;
+			access_key_id: string;
+			secret_access_key?: string;
+	  }
+	| { kind: "folder"; path: string };
+
+export interface SyncSettings {
+	remote: SyncRemote | null;
+}
+
+export interface SyncSettingsStatus {
+	remote: SyncRemote | null;
+	/** Whether a password or secret key is stored for the remote. */
+	secret_configured: boolean;
+}
+
+export interface SyncProgress {
+	phase: "scanning" | "transferring" | "done";
+	path: string | null;
+	done: number;
+	total: number;
+}
+
+export interface SyncReport {
+	uploaded: string[];
+	downloaded: string[];
+	deleted_local: string[];
+	deleted_remote: string[];
+	merged: string[];
+	conflicts: { path: string; copy_path: string }[];
+	errors: { path: string; message: string }[];
+}
+
+export interface CryptoStatus {
+	configured: boolean;
+	unlocked: boolean;
+	folders: string[];
+}
+
+export interface GitSyncResult {
+	status: "up_to_date" | "updated" | "conflict" | "rejected";
+	conflicts: string[];
+	message: string | null;
+}
+
 export interface ViewNotePreview {
 	id: string;
 	title: string;
@@ -312,7 +720,20 @@ export interface FolderViewData {
 	note_previews: ViewNotePreview[];
 }
 
-export type TaskBucket = "inbox" | "today" | "upcoming";
+export type TaskBucket =
+	| "inbox"
+	| "today"
+	| "upcoming"
+	| "overdue"
+	| "someday"
+	| "completed";
+
+export type TaskStatus =
+	| "todo"
+	| "in_progress"
+	| "done"
+	| "cancelled"
+	| "deferred";
 
 export interface TaskItem {
 	task_id: string;
@@ -322,12 +743,57 @@ export interface TaskItem {
 	line_start: number;
 	raw_text: string;
 	checked: boolean;
-	status: string;
+	status: TaskStatus;
 	priority: number;
 	due_date: string | null;
 	scheduled_date: string | null;
+	start_date: string | null;
+	completed_at: string | null;
 	section: string | null;
 	note_updated: string;
+	tags: string[];
+	project: string | null;
+	parent_task_id: string | null;
+	subtasks_total: number;
+	subtasks_done: number;
+	children?: TaskItem[];
+}
+
+export interface TaskProjectSummary {
+	project: string;
+	open_count: number;
+	done_count: number;
+}
+
+export type TaskProjectRule =
+	| "property_then_folder"
+	| "property"
+	| "folder"
+	| "none";
+
+export interface TaskSettings {
+	project_rule: TaskProjectRule;
+}
+
+export interface TaskGroup {
+	key: string;
+	task_ids: string[];
+}
+
+export interface TaskQueryResult {
+	tasks: TaskItem[];
+	group_by: string | null;
+	groups: TaskGroup[];
+}
+
+export interface TaskViewConfig {
+	query: string;
+	folders: string[];
+}
+
+export interface TaskViewLoadResult {
+	config: TaskViewConfig;
+	result: TaskQueryResult;
 }
 
 export interface LinkPreview {
@@ -518,7 +984,13 @@ interface TauriCommands {
 		BinaryFilePreviewDoc
 	>;
 	space_write_text: CommandDef<
-		{ path: string; text: string; base_mtime_ms?: number | null },
+		{
+			path: string;
+			text: string;
+			base_mtime_ms?: number | null;
+			base_text?: string | null;
+			base_etag?: string | null;
+		},
 		TextFileWriteResult
 	>;
 	space_open_or_create_text: CommandDef<
@@ -574,17 +1046,87 @@ interface TauriCommands {
 		NoteProperty[]
 	>;
 	note_frontmatter_render_properties: CommandDef<
-		{ properties: NoteProperty[] },
+		{ properties: NoteProperty[]; frontmatter?: string | null },
 		string | null
 	>;
+	property_schema_get: CommandDef<void, PropertySchema>;
+	property_schema_set: CommandDef<{ schema: PropertySchema }, PropertySchema>;
+	template_list: CommandDef<{ folder?: string | null }, TemplateInfo[]>;
+	note_create_from_template: CommandDef<
+		{
+			template_path: string;
+			title?: string | null;
+			folder?: string | null;
+			target_path?: string | null;
+			insert_at?: number | null;
+			prompts?: Record<string, string> | null;
+			now?: string | null;
+		},
+		TemplateApplyResult
+	>;
+	periodic_settings_get: CommandDef<void, PeriodicNotesSettings>;
+	periodic_settings_set: CommandDef<
+		{ settings: PeriodicNotesSettings },
+		PeriodicNotesSettings
+	>;
+	periodic_note_find: CommandDef<
+		{
+			period: Period;
+			date?: string | null;
+			offset?: number | null;
+			today?: string | null;
+			week_start?: string | null;
+		},
+		PeriodicNote
+	>;
+	periodic_note_open: CommandDef<
+		{
+			period: Period;
+			date?: string | null;
+			offset?: number | null;
+			today?: string | null;
+			week_start?: string | null;
+			folder?: string | null;
+		},
+		PeriodicNote
+	>;
 	note_attach_file: CommandDef<
 		{ note_id: string; source_path: string },
 		AttachmentResult
 	>;
 	database_load: CommandDef<
-		{ path: string; limit?: number | null },
+		{
+			path: string;
+			limit?: number | null;
+			cursor?: string | null;
+			view_id?: string | null;
+			today?: string | null;
+			week_start?: string | null;
+		},
 		DatabaseLoadResult
 	>;
+	database_group: CommandDef<
+		{
+			path: string;
+			group_by?: string | null;
+			view_id?: string | null;
+			today?: string | null;
+			week_start?: string | null;
+		},
+		DatabaseGroupResult
+	>;
+	database_calendar: CommandDef<
+		{
+			path: string;
+			date_column?: string | null;
+			range?: "month" | "week" | null;
+			anchor?: string | null;
+			today?: string | null;
+			week_start?: string | null;
+			view_id?: string | null;
+		},
+		DatabaseCalendarResult
+	>;
 	database_save_config: CommandDef<
 		{ path: string; config: DatabaseConfig },
 		DatabaseConfig
@@ -594,22 +1136,144 @@ interface TauriCommands {
 			note_path: string;
 			column: DatabaseColumn;
 			value: DatabaseCellValue;
+			database_path?: string | null;
 		},
 		DatabaseRow
 	>;
 	database_create_row: CommandDef<
-		{ database_path: string; title?: string | null },
+		{ database_path: string; title?: string | null; today?: string | null },
 		DatabaseCreateRowResult
 	>;
+	database_import_csv: CommandDef<
+		{
+			database_path: string;
+			csv_path: string;
+			title_column?: string | null;
+		},
+		DatabaseCsvImportResult
+	>;
+	database_export_csv: CommandDef<
+		{
+			path: string;
+			output_path: string;
+			bom?: boolean | null;
+			view_id?: string | null;
+			today?: string | null;
+			week_start?: string | null;
+		},
+		DatabaseCsvExportResult
+	>;
+	database_bulk_update_cells: CommandDef<
+		{
+			note_paths: string[];
+			column: DatabaseColumn;
+			value: DatabaseCellValue;
+			database_path?: string | null;
+		},
+		DatabaseBulkResult
+	>;
+	database_duplicate_rows: CommandDef<
+		{ note_paths: string[] },
+		DatabaseBulkResult
+	>;
+	database_delete_rows: CommandDef<
+		{
+			note_paths: string[];
+			mode?: DatabaseDeleteMode | null;
+			archive_folder?: string | null;
+		},
+		DatabaseBulkResult
+	>;
+	database_undo: CommandDef<{ undo: DatabaseUndoPayload }, DatabaseRow[]>;
+	database_normalize_properties: CommandDef<
+		{ path: string; today?: string | null; week_start?: string | null },
+		DatabaseNormalizeResult
+	>;
+	database_watch: CommandDef<
+		{ path: string; today?: string | null; week_start?: string | null },
+		void
+	>;
+	database_unwatch: CommandDef<{ path: string }, void>;
+	database_view_create: CommandDef<
+		{
+			path: string;
+			name: string;
+			layout?: DatabaseViewState["layout"] | null;
+			copy_from?: string | null;
+		},
+		DatabaseConfig
+	>;
+	database_view_update: CommandDef<
+		{ path: string; view: DatabaseView },
+		DatabaseConfig
+	>;
+	database_view_delete: CommandDef<
+		{ path: string; view_id: string },
+		DatabaseConfig
+	>;
+	database_view_set_active: CommandDef<
+		{ path: string; view_id: string },
+		DatabaseConfig
+	>;
+	database_view_reorder: CommandDef<
+		{ path: string; view_ids: string[] },
+		DatabaseConfig
+	>;
 
 	index_rebuild: CommandDef<void, IndexRebuildResult>;
+	note_identity_get: CommandDef<{ path: string }, NoteIdentity | null>;
+	note_history_list: CommandDef<{ path: string }, NoteVersion[]>;
+	note_history_diff: CommandDef<
+		{ path: string; from: string; to?: string | null },
+		NoteHistoryDiff
+	>;
+	note_history_restore: CommandDef<
+		{ path: string; version_id: string },
+		NoteHistoryRestoreResult
+	>;
+	git_repo_info: CommandDef<void, GitRepoInfo>;
+	git_status: CommandDef<void, GitStatusEntry[]>;
+	git_settings_get: CommandDef<void, GitSettings>;
+	git_settings_set: CommandDef<{ settings: GitSettings }, GitSettings>;
+	git_commit: CommandDef<{ message?: string | null }, GitCommit | null>;
+	git_file_log: CommandDef<
+		{ path: string; limit?: number | null },
+		GitCommit[]
+	>;
+	git_file_diff: CommandDef<{ path: string; commit: string }, GitFileDiff>;
+	git_file_restore: CommandDef<
+		{ path: string; commit: string },
+		NoteHistoryRestoreResult
+	>;
+	git_pull: CommandDef<{ remote?: string | null }, GitSyncResult>;
+	git_push: CommandDef<{ remote?: string | null }, GitSyncResult>;
+	sync_settings_get: CommandDef<void, SyncSettingsStatus>;
+	sync_settings_set: CommandDef<
+		{ settings: SyncSettings },
+		SyncSettingsStatus
+	>;
+	sync_run: CommandDef<{ confirm_deletes?: boolean | null }, SyncReport>;
+	crypto_status: CommandDef<void, CryptoStatus>;
+	crypto_unlock: CommandDef<{ passphrase: string }, CryptoStatus>;
+	crypto_lock: CommandDef<void, CryptoStatus>;
+	crypto_change_passphrase: CommandDef<
+		{ old_passphrase: string; new_passphrase: string },
+		number
+	>;
+	crypto_encrypt_path: CommandDef<{ path: string }, number>;
+	crypto_decrypt_path: CommandDef<{ path: string }, number>;
 	search: CommandDef<{ query: string }, SearchResult[]>;
 	search_advanced: CommandDef<
 		{ request: SearchAdvancedRequest },
 		SearchResult[]
 	>;
 	search_parse_and_run: CommandDef<
-		{ raw_query: string; limit?: number | null },
+		{
+			raw_query: string;
+			limit?: number | null;
+			today?: string | null;
+			week_start?: string | null;
+		},
 		SearchResult[]
 	>;
 	search_view_data: CommandDef<
@@ -633,18 +1297,80 @@ interface TauriCommands {
 			today: string;
 			limit?: number | null;
 			folders?: string[] | null;
+			completed_from?: string | null;
+			completed_to?: string | null;
+			project?: string | null;
+			tree?: boolean | null;
 		},
 		TaskItem[]
 	>;
-	task_set_checked: CommandDef<{ task_id: string; checked: boolean }, void>;
+	tasks_search: CommandDef<
+		{
+			query: string;
+			today: string;
+			bucket?: TaskBucket | null;
+			limit?: number | null;
+			folders?: string[] | null;
+			project?: string | null;
+		},
+		TaskItem[]
+	>;
+	tasks_projects: CommandDef<void, TaskProjectSummary[]>;
+	task_settings_get: CommandDef<void, TaskSettings>;
+	task_settings_set: CommandDef<{ settings: TaskSettings }, TaskSettings>;
+	tasks_run_query: CommandDef<
+		{
+			query: string;
+			today: string;
+			week_start?: string | null;
+			folders?: string[] | null;
+		},
+		TaskQueryResult
+	>;
+	task_view_load: CommandDef<
+		{ path: string; today: string; week_start?: string | null },
+		TaskViewLoadResult
+	>;
+	task_view_save_config: CommandDef<
+		{
+			path: string;
+			config: TaskViewConfig;
+			today: string;
+			week_start?: string | null;
+		},
+		TaskViewConfig
+	>;
+	task_set_checked: CommandDef<
+		{
+			task_id: string;
+			checked: boolean;
+			cascade?: boolean | null;
+			today?: string | null;
+		},
+		void
+	>;
 	task_set_dates: CommandDef<
 		{
 			task_id: string;
 			scheduled_date?: string | null;
 			due_date?: string | null;
+			start_date?: string | null;
+			today?: string | null;
+			week_start?: string | null;
+		},
+		void
+	>;
+	task_set_status: CommandDef<
+		{
+			task_id: string;
+			status: TaskStatus;
+			completed_date?: string | null;
+			today?: string | null;
+			week_start?: string | null;
 		},
 		void
 	>;
+	task_set_priority: CommandDef<{ task_id: string; priority: string }, void>;
 	task_dates_by_ordinal: CommandDef<
 		{ markdown: string; ordinal: number },
 		TaskDateInfo | null
@@ -655,6 +1381,8 @@ interface TauriCommands {
 			ordinal: number;
 			scheduled_date: string;
 			due_date: string;
+			today?: string | null;
+			week_start?: string | null;
 		},
 		string | null
 	>;
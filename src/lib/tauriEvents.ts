@@ -1,5 +1,6 @@
 import { listen } from "@tauri-apps/api/event";
 import { useEffect, useRef } from "react";
+import type { DatabaseRowsChangedEvent } from "./tauri";
 
 type TauriEventMap = {
 	"menu:new_note": undefined;
@@ -48,6 +49,7 @@ type TauriEventMap = {
 	};
 	"notes:external_changed": { rel_path: string; removed: boolean };
 	"space:fs_changed": { rel_path: string; removed: boolean };
+	"database:rows_changed": DatabaseRowsChangedEvent;
 	"settings:updated": {
 		ui?: {
 			theme?: string;
@@ -68,6 +70,9 @@ type TauriEventMap = {
 		dailyNotes?: {
 			folder?: string | null;
 		};
+		templates?: {
+			folder?: string | null;
+		};
 		tasks?: {
 			source?: { mode?: "space" | "folders"; folders?: string[] };
 		};
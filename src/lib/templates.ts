@@ -0,0 +1,44 @@
+import { getTemplatesFolder } from "./settings";
+import { type TemplateApplyResult, type TemplateInfo, invoke } from "./tauri";
+
+/** Local wall-clock time as `YYYY-MM-DDTHH:MM:SS`, what template dates use. */
+export function localDateTimeIso(now = new Date()): string {
+	const pad = (value: number) => String(value).padStart(2, "0");
+	return `${now.getFullYear()}-${pad(now.getMonth() + 1)}-${pad(now.getDate())}T${pad(now.getHours())}:${pad(now.getMinutes())}:${pad(now.getSeconds())}`;
+}
+
+export async function listTemplates(): Promise<TemplateInfo[]> {
+	const folder = await getTemplatesFolder();
+	return invoke("template_list", { folder });
+}
+
+export function createNoteFromTemplate(
+	templatePath: string,
+	options: {
+		title?: string;
+		folder?: string;
+		prompts?: Record<string, string>;
+	} = {},
+): Promise<TemplateApplyResult> {
+	return invoke("note_create_from_template", {
+		template_path: templatePath,
+		title: options.title ?? null,
+		folder: options.folder ?? null,
+		prompts: options.prompts ?? null,
+		now: localDateTimeIso(),
+	});
+}
+
+export function insertTemplateIntoNote(
+	templatePath: string,
+	targetPath: string,
+	options: { insertAt?: number; prompts?: Record<string, string> } = {},
+): Promise<TemplateApplyResult> {
+	return invoke("note_create_from_template", {
+		template_path: templatePath,
+		target_path: targetPath,
+		insert_at: options.insertAt ?? null,
+		prompts: options.prompts ?? null,
+		now: localDateTimeIso(),
+	});
+}